@@ -0,0 +1,96 @@
+//! Runs one sync without the app window, for CI and scripts.
+//!
+//! Usage: `headless <source> <destination> [options.json]`
+//!
+//! `options.json` holds `SyncOptions` fields that override the defaults
+//! below. Progress goes to stdout as newline-delimited JSON, one
+//! `{"event": ..., "payload": ...}` object per line, ending with either a
+//! `sync-result` or a `sync-error` line. Logs go to stderr. The exit code is 0
+//! only if no file failed.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use rsync_lib::sync_engine::{print_ndjson, ConflictResolution, SyncEngine, SyncOptions};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (source, destination, options_file) = match args.as_slice() {
+        [source, destination] => (source, destination, None),
+        [source, destination, options_file] => (source, destination, Some(options_file)),
+        _ => {
+            eprintln!("usage: headless <source> <destination> [options.json]");
+            return ExitCode::from(2);
+        }
+    };
+
+    let options = match load_options(source, destination, options_file) {
+        Ok(options) => options,
+        Err(message) => return fail(message),
+    };
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => return fail(e.to_string()),
+    };
+    let outcome = runtime.block_on(async {
+        let engine = SyncEngine::new(None)?;
+        engine
+            .sync_files(PathBuf::from(source), PathBuf::from(destination), options)
+            .await
+    });
+
+    match outcome {
+        Ok(result) => {
+            print_ndjson("sync-result", &result);
+            if result.files_failed == 0 {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => fail(e.to_string()),
+    }
+}
+
+/// Defaults for a plain copy, overridden by the fields in `options_file`.
+fn load_options(
+    source: &str,
+    destination: &str,
+    options_file: Option<&String>,
+) -> Result<SyncOptions, String> {
+    let mut options = serde_json::json!({
+        "source": source,
+        "destination": destination,
+        "mode": "copy",
+        "conflict_resolution": "overwrite",
+        "verify_integrity": false,
+        "preserve_metadata": true,
+        "delete_orphans": false,
+        "buffer_size": null,
+    });
+    if let Some(path) = options_file {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let overrides: serde_json::Value =
+            serde_json::from_str(&content).map_err(|e| format!("{}: {}", path, e))?;
+        let serde_json::Value::Object(overrides) = overrides else {
+            return Err(format!("{}: expected a JSON object of sync options", path));
+        };
+        if let Some(fields) = options.as_object_mut() {
+            fields.extend(overrides);
+        }
+    }
+
+    let mut options: SyncOptions = serde_json::from_value(options).map_err(|e| e.to_string())?;
+    if options.conflict_resolution == ConflictResolution::Ask {
+        return Err("conflict_resolution \"ask\" needs the app to answer; pick another".into());
+    }
+    options.progress_ndjson = true;
+    options.notify_on_complete = false;
+    Ok(options)
+}
+
+fn fail(message: String) -> ExitCode {
+    print_ndjson("sync-error", &serde_json::json!({ "message": message }));
+    ExitCode::FAILURE
+}
@@ -0,0 +1,190 @@
+//! Detects paths inside folders that a cloud storage client keeps in sync.
+//!
+//! Syncing into such a folder makes its client upload everything the sync
+//! writes, and on the way back down can make it download the same data again.
+//! Detection goes by where the clients keep their folders (iCloud Drive's
+//! `Mobile Documents`, the File Provider folders under `Library/CloudStorage`,
+//! Dropbox's `info.json`, the `OneDrive` environment variables on Windows) and
+//! by the marker files they leave in the folder root. It works without the
+//! client running.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudProvider {
+    ICloudDrive,
+    Dropbox,
+    OneDrive,
+    GoogleDrive,
+    /// Another File Provider client with a folder under `Library/CloudStorage`
+    Other,
+}
+
+/// The cloud-synced folder a path is in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudFolder {
+    pub provider: CloudProvider,
+    /// Root of the synced folder; everything under it gets uploaded
+    pub root: PathBuf,
+}
+
+/// Files the Dropbox client keeps in the root of a Dropbox folder.
+const DROPBOX_MARKERS: &[&str] = &[".dropbox", ".dropbox.cache"];
+
+/// Folders Google Drive for desktop keeps in the root of a synced drive.
+const GOOGLE_DRIVE_MARKERS: &[&str] = &[".tmp.drivedownload", ".shortcut-targets-by-id"];
+
+/// Returns the cloud-synced folder holding `path`, if any. `path` doesn't
+/// need to exist yet; a destination that will be created inside a synced
+/// folder is reported too. The innermost folder wins when they're nested.
+pub fn detect_cloud_provider(path: &Path) -> Option<CloudFolder> {
+    let path = resolve_existing(path);
+    let home = dirs::home_dir();
+
+    known_folders(home.as_deref())
+        .into_iter()
+        .filter_map(|(provider, root)| Some((provider, root.canonicalize().ok()?)))
+        .chain(marked_folder(&path))
+        .filter(|(_, root)| path.starts_with(root))
+        .max_by_key(|(_, root)| root.components().count())
+        .map(|(provider, root)| CloudFolder { provider, root })
+}
+
+/// `path` with its nearest existing ancestor canonicalized, so symlinked
+/// locations compare equal to the folders they point at.
+fn resolve_existing(path: &Path) -> PathBuf {
+    path.ancestors()
+        .find_map(|ancestor| {
+            let real = ancestor.canonicalize().ok()?;
+            let missing = path.strip_prefix(ancestor).ok()?;
+            Some(real.join(missing))
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// The synced folders the clients put in their usual places.
+fn known_folders(home: Option<&Path>) -> Vec<(CloudProvider, PathBuf)> {
+    let mut folders = Vec::new();
+    if let Some(home) = home {
+        folders.push((
+            CloudProvider::ICloudDrive,
+            home.join("Library/Mobile Documents"),
+        ));
+        // iCloud for Windows
+        folders.push((CloudProvider::ICloudDrive, home.join("iCloudDrive")));
+        folders.push((CloudProvider::Dropbox, home.join("Dropbox")));
+        folders.push((CloudProvider::OneDrive, home.join("OneDrive")));
+        folders.push((CloudProvider::GoogleDrive, home.join("Google Drive")));
+
+        if let Ok(info) = std::fs::read_to_string(home.join(".dropbox/info.json")) {
+            folders.extend(
+                dropbox_roots(&info)
+                    .into_iter()
+                    .map(|root| (CloudProvider::Dropbox, root)),
+            );
+        }
+        if let Ok(entries) = std::fs::read_dir(home.join("Library/CloudStorage")) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                folders.push((file_provider_client(&name), entry.path()));
+            }
+        }
+    }
+
+    // Set by the OneDrive client on Windows, one per signed-in account type
+    for variable in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+        if let Some(root) = std::env::var_os(variable).filter(|root| !root.is_empty()) {
+            folders.push((CloudProvider::OneDrive, PathBuf::from(root)));
+        }
+    }
+    #[cfg(target_os = "macos")]
+    folders.push((
+        CloudProvider::GoogleDrive,
+        PathBuf::from("/Volumes/GoogleDrive"),
+    ));
+
+    folders
+}
+
+/// The client behind a `Library/CloudStorage` folder, which File Provider
+/// names after the client and account, e.g. `GoogleDrive-me@example.com`.
+fn file_provider_client(name: &str) -> CloudProvider {
+    if name.starts_with("Dropbox") {
+        CloudProvider::Dropbox
+    } else if name.starts_with("OneDrive") {
+        CloudProvider::OneDrive
+    } else if name.starts_with("GoogleDrive") {
+        CloudProvider::GoogleDrive
+    } else if name.starts_with("iCloud") {
+        CloudProvider::ICloudDrive
+    } else {
+        CloudProvider::Other
+    }
+}
+
+/// Folder roots listed in Dropbox's `info.json`, one per linked account:
+/// `{"personal": {"path": ...}, "business": {"path": ...}}`.
+fn dropbox_roots(info: &str) -> Vec<PathBuf> {
+    let Ok(serde_json::Value::Object(accounts)) = serde_json::from_str(info) else {
+        return Vec::new();
+    };
+    accounts
+        .values()
+        .filter_map(|account| account.get("path")?.as_str())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// The innermost ancestor of `path` holding a client's marker files, for
+/// folders that were moved somewhere other than their usual place.
+fn marked_folder(path: &Path) -> Option<(CloudProvider, PathBuf)> {
+    path.ancestors().find_map(|ancestor| {
+        let has_marker = |markers: &[&str]| markers.iter().any(|m| ancestor.join(m).exists());
+        if has_marker(DROPBOX_MARKERS) {
+            Some((CloudProvider::Dropbox, ancestor.to_path_buf()))
+        } else if has_marker(GOOGLE_DRIVE_MARKERS) {
+            Some((CloudProvider::GoogleDrive, ancestor.to_path_buf()))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropbox_info_lists_every_account() {
+        let info = r#"{"personal": {"path": "/Users/me/Dropbox", "host": 1},
+                       "business": {"path": "/Users/me/Dropbox (Work)"}}"#;
+        let mut roots = dropbox_roots(info);
+        roots.sort();
+        assert_eq!(
+            roots,
+            vec![
+                PathBuf::from("/Users/me/Dropbox"),
+                PathBuf::from("/Users/me/Dropbox (Work)")
+            ]
+        );
+        assert!(dropbox_roots("not json").is_empty());
+    }
+
+    #[test]
+    fn marker_files_find_a_moved_folder() {
+        let root = std::env::temp_dir().join(format!("cloud-folder-{}", uuid::Uuid::new_v4()));
+        let synced = root.join("Elsewhere");
+        std::fs::create_dir_all(synced.join("Photos")).unwrap();
+        std::fs::write(synced.join(".dropbox"), b"").unwrap();
+
+        let found = detect_cloud_provider(&synced.join("Photos/not-yet-created")).unwrap();
+        assert_eq!(found.provider, CloudProvider::Dropbox);
+        assert_eq!(found.root, synced.canonicalize().unwrap());
+        assert!(detect_cloud_provider(&root).is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,366 @@
+//! SMART health checks for the drive behind a path.
+//!
+//! Uses `smartctl` (smartmontools) when it is installed, reading its JSON
+//! output for the overall self-assessment and the attributes that predict
+//! failure: reallocated, pending and uncorrectable sectors on ATA drives, and
+//! media errors and wear on NVMe drives. On macOS without `smartctl`, falls back
+//! to the SMART status IOKit reports through `diskutil`. Drives whose SMART data
+//! can't be read (USB bridges that don't pass it through, network volumes,
+//! disk images) come back as `Unknown` rather than as an error.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// ATA attribute IDs that count failing sectors.
+const ATTR_REALLOCATED_SECTORS: u64 = 5;
+const ATTR_PENDING_SECTORS: u64 = 197;
+const ATTR_UNCORRECTABLE_SECTORS: u64 = 198;
+
+/// NVMe wear (`percentage_used`) from which a drive is reported as wearing out.
+const NVME_WEAR_WARNING_PERCENT: u64 = 90;
+
+/// `smartctl` exit status bits meaning the device couldn't be queried at all.
+const SMARTCTL_FATAL_BITS: i32 = 0b11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthVerdict {
+    Healthy,
+    /// The drive passes its self-assessment but reports bad sectors or wear
+    Warning,
+    /// The drive's own self-assessment failed
+    Failing,
+    /// No SMART data could be read
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartAttribute {
+    pub id: u64,
+    pub name: String,
+    pub raw_value: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveHealth {
+    pub verdict: HealthVerdict,
+    /// Whole-disk device the path is on, such as `/dev/sda` or `/dev/disk4`
+    pub device: Option<String>,
+    pub model: Option<String>,
+    /// Result of the drive's overall self-assessment
+    pub smart_passed: Option<bool>,
+    pub reallocated_sectors: Option<u64>,
+    pub pending_sectors: Option<u64>,
+    pub uncorrectable_sectors: Option<u64>,
+    /// NVMe media and data integrity errors
+    pub media_errors: Option<u64>,
+    /// NVMe estimate of the rated endurance used up, which can exceed 100
+    pub percentage_used: Option<u64>,
+    pub temperature_celsius: Option<u64>,
+    pub power_on_hours: Option<u64>,
+    /// Every ATA attribute reported, for display
+    pub attributes: Vec<SmartAttribute>,
+    /// Why the verdict is what it is, for the user
+    pub message: String,
+}
+
+impl DriveHealth {
+    /// Whether the drive reports anything worth warning about before a backup.
+    pub fn has_problems(&self) -> bool {
+        matches!(
+            self.verdict,
+            HealthVerdict::Warning | HealthVerdict::Failing
+        )
+    }
+
+    fn unknown(device: Option<String>, message: impl Into<String>) -> Self {
+        Self {
+            verdict: HealthVerdict::Unknown,
+            device,
+            model: None,
+            smart_passed: None,
+            reallocated_sectors: None,
+            pending_sectors: None,
+            uncorrectable_sectors: None,
+            media_errors: None,
+            percentage_used: None,
+            temperature_celsius: None,
+            power_on_hours: None,
+            attributes: Vec::new(),
+            message: message.into(),
+        }
+    }
+
+    /// Sets `verdict` and `message` from the collected values.
+    fn assess(&mut self) {
+        let mut problems = Vec::new();
+        let counts = [
+            (self.reallocated_sectors, "reallocated sectors"),
+            (self.pending_sectors, "sectors pending reallocation"),
+            (self.uncorrectable_sectors, "uncorrectable sectors"),
+            (self.media_errors, "media errors"),
+        ];
+        for (count, what) in counts {
+            if let Some(count) = count.filter(|&count| count > 0) {
+                problems.push(format!("{} {}", count, what));
+            }
+        }
+        if let Some(used) = self
+            .percentage_used
+            .filter(|&used| used >= NVME_WEAR_WARNING_PERCENT)
+        {
+            problems.push(format!("{}% of rated endurance used", used));
+        }
+
+        (self.verdict, self.message) = match self.smart_passed {
+            Some(false) => (
+                HealthVerdict::Failing,
+                "The drive's SMART self-assessment failed; back up its contents and replace it"
+                    .to_string(),
+            ),
+            _ if !problems.is_empty() => (
+                HealthVerdict::Warning,
+                format!("The drive reports {}", problems.join(", ")),
+            ),
+            Some(true) => (
+                HealthVerdict::Healthy,
+                "SMART reports no problems".to_string(),
+            ),
+            None if !self.attributes.is_empty() => (
+                HealthVerdict::Healthy,
+                "No failing attributes, but the drive gave no overall assessment".to_string(),
+            ),
+            None => (HealthVerdict::Unknown, self.message.clone()),
+        };
+    }
+}
+
+/// Checks the SMART health of the drive `path` is on, or would be created on
+/// if it doesn't exist yet. Never fails: anything that gets in the way of
+/// reading SMART data gives an `Unknown` verdict.
+pub fn check_drive_health(path: &Path) -> DriveHealth {
+    let existing = path
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(path);
+    let Some(device) = whole_disk_device(existing) else {
+        return DriveHealth::unknown(None, "Could not find the drive this path is on");
+    };
+
+    match Command::new("smartctl")
+        .args(["--json", "-H", "-A", "-i", &device])
+        .output()
+    {
+        Ok(output) => {
+            let json = String::from_utf8_lossy(&output.stdout);
+            let status = output.status.code().unwrap_or(SMARTCTL_FATAL_BITS);
+            parse_smartctl_json(&json, status, Some(device))
+        }
+        Err(_) => fallback_health(device),
+    }
+}
+
+/// Builds the report from `smartctl --json -H -A -i` output and its exit status.
+fn parse_smartctl_json(json: &str, exit_status: i32, device: Option<String>) -> DriveHealth {
+    let Ok(report) = serde_json::from_str::<serde_json::Value>(json) else {
+        return DriveHealth::unknown(device, "smartctl produced no readable output");
+    };
+    if exit_status & SMARTCTL_FATAL_BITS != 0 {
+        let reason = report["smartctl"]["messages"]
+            .as_array()
+            .and_then(|messages| messages.first())
+            .and_then(|message| message["string"].as_str())
+            .unwrap_or("the device could not be opened");
+        return DriveHealth::unknown(device, format!("SMART data unavailable: {}", reason));
+    }
+
+    let mut health = DriveHealth::unknown(device, "The drive reported no SMART data");
+    health.model = report["model_name"].as_str().map(str::to_string);
+    health.smart_passed = report["smart_status"]["passed"].as_bool();
+    health.temperature_celsius = report["temperature"]["current"].as_u64();
+    health.power_on_hours = report["power_on_time"]["hours"].as_u64();
+
+    if let Some(table) = report["ata_smart_attributes"]["table"].as_array() {
+        for entry in table {
+            let id = entry["id"].as_u64();
+            let raw_value = entry["raw"]["value"].as_u64();
+            let (Some(id), Some(raw_value)) = (id, raw_value) else {
+                continue;
+            };
+            match id {
+                ATTR_REALLOCATED_SECTORS => health.reallocated_sectors = Some(raw_value),
+                ATTR_PENDING_SECTORS => health.pending_sectors = Some(raw_value),
+                ATTR_UNCORRECTABLE_SECTORS => health.uncorrectable_sectors = Some(raw_value),
+                _ => {}
+            }
+            health.attributes.push(SmartAttribute {
+                id,
+                name: entry["name"].as_str().unwrap_or_default().to_string(),
+                raw_value,
+            });
+        }
+    }
+
+    let nvme = &report["nvme_smart_health_information_log"];
+    if nvme.is_object() {
+        health.media_errors = nvme["media_errors"].as_u64();
+        health.percentage_used = nvme["percentage_used"].as_u64();
+        if nvme["critical_warning"]
+            .as_u64()
+            .is_some_and(|warning| warning != 0)
+        {
+            health.smart_passed = Some(false);
+        }
+    }
+
+    health.assess();
+    health
+}
+
+/// Without `smartctl`, macOS still exposes IOKit's SMART status in `diskutil info`.
+#[cfg(target_os = "macos")]
+fn fallback_health(device: String) -> DriveHealth {
+    let status = Command::new("diskutil")
+        .args(["info", &device])
+        .output()
+        .ok()
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| {
+                    let status = line.trim().strip_prefix("SMART Status:")?;
+                    Some(status.trim().to_string())
+                })
+        });
+
+    let mut health = DriveHealth::unknown(
+        Some(device),
+        "SMART data unavailable; the drive or its enclosure doesn't report it",
+    );
+    match status.as_deref() {
+        Some("Verified") => health.smart_passed = Some(true),
+        Some("Failing") => health.smart_passed = Some(false),
+        _ => return health,
+    }
+    health.assess();
+    health
+        .message
+        .push_str(" (install smartmontools for sector counts)");
+    health
+}
+
+#[cfg(not(target_os = "macos"))]
+fn fallback_health(device: String) -> DriveHealth {
+    DriveHealth::unknown(
+        Some(device),
+        "smartctl is not installed; install smartmontools to check drive health",
+    )
+}
+
+/// The whole-disk device node holding `path`, from its volume's `Part of Whole`.
+#[cfg(target_os = "macos")]
+fn whole_disk_device(path: &Path) -> Option<String> {
+    let volume = crate::file_ops::get_volume_info(path).ok()?;
+    let output = Command::new("diskutil")
+        .args(["info", &volume.mount_point])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let disk = line.trim().strip_prefix("Part of Whole:")?;
+            Some(format!("/dev/{}", disk.trim()))
+        })
+}
+
+/// The whole-disk device node holding `path`: the source of its longest
+/// matching mount in `/proc/mounts`, with a partition mapped to its disk
+/// through sysfs.
+#[cfg(target_os = "linux")]
+fn whole_disk_device(path: &Path) -> Option<String> {
+    let canonical = path.canonicalize().ok()?;
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    let source = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let source = fields.next()?;
+            // Spaces in mount points are written as \040
+            let mount_point = fields.next()?.replace("\\040", " ");
+            Some((source, mount_point))
+        })
+        .filter(|(source, mount_point)| {
+            source.starts_with("/dev/") && canonical.starts_with(mount_point)
+        })
+        .max_by_key(|(_, mount_point)| mount_point.len())
+        .map(|(source, _)| source)?;
+
+    let device = Path::new(source).canonicalize().ok()?;
+    let name = device.file_name()?.to_string_lossy().into_owned();
+    let sys_block = Path::new("/sys/class/block").join(&name);
+    if sys_block.join("partition").exists() {
+        let disk = sys_block.canonicalize().ok()?;
+        return Some(format!(
+            "/dev/{}",
+            disk.parent()?.file_name()?.to_string_lossy()
+        ));
+    }
+    Some(device.to_string_lossy().into_owned())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn whole_disk_device(_path: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ata_drive_with_pending_sectors_warns() {
+        let json = r#"{
+            "model_name": "WDC WD40EFRX",
+            "smart_status": {"passed": true},
+            "temperature": {"current": 34},
+            "power_on_time": {"hours": 21000},
+            "ata_smart_attributes": {"table": [
+                {"id": 5, "name": "Reallocated_Sector_Ct", "raw": {"value": 0}},
+                {"id": 197, "name": "Current_Pending_Sector", "raw": {"value": 8}},
+                {"id": 198, "name": "Offline_Uncorrectable", "raw": {"value": 0}}
+            ]}
+        }"#;
+        let health = parse_smartctl_json(json, 0, Some("/dev/sda".into()));
+        assert_eq!(health.verdict, HealthVerdict::Warning);
+        assert_eq!(health.pending_sectors, Some(8));
+        assert_eq!(health.reallocated_sectors, Some(0));
+        assert_eq!(health.attributes.len(), 3);
+        assert_eq!(health.power_on_hours, Some(21000));
+    }
+
+    #[test]
+    fn test_failed_self_assessment_and_unreadable_bridge() {
+        let failing = r#"{"smart_status": {"passed": false}}"#;
+        assert_eq!(
+            parse_smartctl_json(failing, 8, None).verdict,
+            HealthVerdict::Failing
+        );
+
+        let nvme = r#"{"smart_status": {"passed": true},
+            "nvme_smart_health_information_log":
+                {"critical_warning": 0, "media_errors": 0, "percentage_used": 3}}"#;
+        assert_eq!(
+            parse_smartctl_json(nvme, 0, None).verdict,
+            HealthVerdict::Healthy
+        );
+
+        let bridge = r#"{"smartctl": {"messages": [
+            {"string": "Unknown USB bridge [0x1234:0x5678]"}]}}"#;
+        let health = parse_smartctl_json(bridge, 2, Some("/dev/sdb".into()));
+        assert_eq!(health.verdict, HealthVerdict::Unknown);
+        assert!(health.message.contains("Unknown USB bridge"));
+    }
+}
@@ -19,6 +19,9 @@ pub enum SyncError {
     #[error("Transfer not found: {0}")]
     TransferNotFound(String),
 
+    #[error("Sync profile not found: {0}")]
+    ProfileNotFound(String),
+
     #[error("Transfer already exists: {0}")]
     TransferAlreadyExists(String),
 
@@ -145,6 +148,75 @@ pub enum SyncError {
         actual_size: u64,
     },
 
+    /// Path is in a macOS privacy-protected location and Full Disk Access is not granted
+    #[error("Full Disk Access required: {path:?} (grant access in System Settings > Privacy & Security > Full Disk Access)")]
+    FullDiskAccessRequired { path: PathBuf },
+
+    /// Staged directory could not be swapped into place; the previous tree was kept
+    #[error("Publish failed: {path:?} ({reason})")]
+    PublishFailed { path: PathBuf, reason: String },
+
+    /// The user skipped this file while it was being copied; it stays pending for a resume
+    #[error("Skipped by user: {path:?}")]
+    SkippedByUser { path: PathBuf },
+
+    /// A moved file was copied, but its source could not be removed afterwards
+    #[error("Source not removed after move: {path:?} ({reason})")]
+    SourceRemovalFailed {
+        path: PathBuf,
+        bytes_copied: u64,
+        reason: String,
+    },
+
+    /// A remote source (such as an rsync daemon) could not be listed or fetched from
+    #[error("Remote source error: {url} ({reason})")]
+    RemoteSource { url: String, reason: String },
+
+    /// Transfer would write more data than the configured write budget allows
+    #[error("Write limit exceeded: transfer needs {required_bytes} bytes but the limit is {limit_bytes} bytes{}", whole_source_note(.whole_source))]
+    WriteLimitExceeded {
+        required_bytes: u64,
+        limit_bytes: u64,
+        /// Estimated from the whole source, as a streaming sync has no file
+        /// list to diff against the destination
+        whole_source: bool,
+    },
+
+    /// Transfer would push the destination above the configured fill level
+    #[error("Destination fill limit exceeded: {path:?} would be {projected_percent:.1}% full (limit: {limit_percent:.1}%){}", whole_source_note(.whole_source))]
+    DestinationFillLimitExceeded {
+        path: PathBuf,
+        projected_percent: f64,
+        limit_percent: f64,
+        /// Estimated from the whole source, as for `WriteLimitExceeded`
+        whole_source: bool,
+    },
+
+    /// Destination is the source itself or lies inside it, so the sync would copy its own output
+    #[error(
+        "Source and destination overlap: {destination:?} is the same as or inside {source_path:?}"
+    )]
+    SourceDestOverlap {
+        source_path: PathBuf,
+        destination: PathBuf,
+    },
+
+    /// Spanning destinations were rejected before the transfer started
+    #[error("Invalid spanning destinations: {0}")]
+    InvalidSpanning(String),
+
+    /// Spanning mode found a file that fits on none of the destination drives
+    #[error("Spanning destinations full: {path:?} (required: {required_bytes} bytes, largest free: {largest_free_bytes} bytes)")]
+    SpanningCapacityExceeded {
+        path: PathBuf,
+        required_bytes: u64,
+        largest_free_bytes: u64,
+    },
+
+    /// The transfer doesn't keep the record of written files a rollback needs
+    #[error("Rollback not possible: {0}")]
+    RollbackUnavailable(String),
+
     /// Transfer was interrupted (power loss, crash, etc.)
     #[error("Transfer interrupted: {transfer_id} (can be resumed)")]
     TransferInterrupted {
@@ -163,5 +235,14 @@ impl serde::Serialize for SyncError {
     }
 }
 
+/// Explains a limit estimated from the whole source in the error message.
+fn whole_source_note(whole_source: &bool) -> &'static str {
+    if *whole_source {
+        " (streaming sync: estimated from the whole source, not only the files that changed)"
+    } else {
+        ""
+    }
+}
+
 /// Result type alias for sync operations.
 pub type SyncResult<T> = Result<T, SyncError>;
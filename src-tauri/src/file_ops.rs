@@ -4,10 +4,15 @@
 //! for all edge cases including disk full, drive disconnection, and corruption.
 
 use chrono::{DateTime, TimeZone, Utc};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use xxhash_rust::xxh3::xxh3_64;
 
@@ -23,6 +28,19 @@ pub const COPY_BUFFER_SIZE: usize = 8 * 1024 * 1024;
 /// while still providing good hashing throughput.
 pub const HASH_BUFFER_SIZE: usize = 1024 * 1024;
 
+/// Bytes hashed between checkpoints of a resumable verification: 64 MiB.
+/// Each checkpoint is persisted with the transfer state, so an interruption
+/// costs at most this much re-hashing.
+pub const HASH_CHECKPOINT_INTERVAL: u64 = 64 * 1024 * 1024;
+
+/// Bytes written between durability points of a copy that reports them: 32 MiB.
+/// Each point costs a `sync_data`, and a crash loses at most this much progress.
+pub const DURABLE_SYNC_INTERVAL: u64 = 32 * 1024 * 1024;
+
+/// Buffers a `pipelined_copy` keeps in flight: one being read, one queued
+/// and one being written, so a slow read and a slow write can overlap.
+pub const READ_AHEAD_BUFFERS: usize = 3;
+
 /// Time window for bandwidth throttling measurement in milliseconds.
 /// Using 100ms provides responsive throttling while avoiding excessive sleep calls.
 const THROTTLE_WINDOW_MS: u64 = 100;
@@ -40,6 +58,9 @@ const TEMP_FILE_EXTENSION: &str = ".rsync-tmp";
 /// Extension for partial files that failed mid-transfer.
 const PARTIAL_FILE_EXTENSION: &str = ".rsync-partial";
 
+/// Extension for a locked destination moved aside during a forced overwrite.
+const DISPLACED_FILE_EXTENSION: &str = ".rsync-displaced";
+
 // ============================================================================
 // Helper functions for atomic operations and error classification
 // ============================================================================
@@ -78,6 +99,51 @@ pub fn cleanup_temp_files(dest: &Path) {
     }
 }
 
+/// Expands `{date}`, `{time}`, `{datetime}`, `{source_name}` and `{hostname}`
+/// in a destination path, so each run of a scheduled backup writes to a fresh
+/// folder such as `/backups/2024-06-01/Documents`. Times are local and use `-`
+/// rather than `:`, which Finder shows as `/` and FAT volumes reject. Unknown
+/// tokens are left as written.
+pub fn expand_destination_template(
+    template: &str,
+    source: &Path,
+    now: DateTime<chrono::Local>,
+) -> String {
+    if !template.contains('{') {
+        return template.to_string();
+    }
+    let source_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "source".to_string());
+
+    template
+        .replace("{datetime}", &now.format("%Y-%m-%d_%H-%M-%S").to_string())
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H-%M-%S").to_string())
+        .replace("{source_name}", &source_name)
+        .replace("{hostname}", &hostname())
+}
+
+/// This machine's host name, without a domain, for `{hostname}`.
+fn hostname() -> String {
+    #[cfg(unix)]
+    let name = {
+        let mut buffer = [0u8; 256];
+        let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+        (result == 0).then(|| {
+            let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+            String::from_utf8_lossy(&buffer[..end]).into_owned()
+        })
+    };
+    #[cfg(not(unix))]
+    let name = std::env::var("COMPUTERNAME").ok();
+
+    name.and_then(|name| name.split('.').next().map(str::to_string))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
 /// Check available disk space at a path.
 /// Returns (available_bytes, total_bytes).
 #[cfg(unix)]
@@ -128,6 +194,28 @@ pub fn is_external_drive(_path: &Path) -> bool {
     false
 }
 
+/// Returns true if both paths live on the same device (`st_dev`).
+/// A destination that doesn't exist yet is checked through its nearest existing ancestor.
+#[cfg(unix)]
+pub fn is_same_device(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let device = |path: &Path| {
+        path.ancestors()
+            .find_map(|p| fs::metadata(p).ok())
+            .map(|m| m.dev())
+    };
+    match (device(a), device(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_same_device(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
 /// Sync the parent directory to ensure durability after atomic rename.
 /// This is critical for data integrity - the rename is only durable once
 /// the parent directory's metadata is flushed to disk.
@@ -192,6 +280,193 @@ pub fn sync_parent_directory(path: &Path) -> SyncResult<()> {
     Ok(())
 }
 
+/// Returns true if the error means the file is busy or in use (EBUSY / ETXTBSY).
+pub fn is_locked_error(error: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        if let Some(raw_error) = error.raw_os_error() {
+            return raw_error == libc::EBUSY || raw_error == libc::ETXTBSY;
+        }
+    }
+    #[cfg(windows)]
+    {
+        // ERROR_SHARING_VIOLATION (32) / ERROR_LOCK_VIOLATION (33)
+        if let Some(raw_error) = error.raw_os_error() {
+            return raw_error == 32 || raw_error == 33;
+        }
+    }
+    false
+}
+
+/// Replaces a locked destination with a fully written temp file.
+/// The destination is first renamed aside, then the temp file is renamed into
+/// place. If that second rename fails, the original destination is restored.
+fn replace_locked_destination(temp_path: &Path, dest: &Path) -> std::io::Result<()> {
+    let mut displaced_name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    displaced_name.push_str(DISPLACED_FILE_EXTENSION);
+    let displaced_path = dest.with_file_name(displaced_name);
+
+    fs::rename(dest, &displaced_path)?;
+
+    match fs::rename(temp_path, dest) {
+        Ok(_) => {
+            // The old inode may still be open elsewhere; unlinking it is safe
+            let _ = fs::remove_file(&displaced_path);
+            Ok(())
+        }
+        Err(e) => {
+            if let Err(restore_err) = fs::rename(&displaced_path, dest) {
+                log::warn!(
+                    "Failed to restore displaced destination '{}': {}",
+                    dest.display(),
+                    restore_err
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Returns true if a rename failed because source and target are on different
+/// filesystems (EXDEV), e.g. the destination directory is a bind mount or a
+/// symlink into another volume.
+pub fn is_cross_device_error(error: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        if let Some(raw_error) = error.raw_os_error() {
+            return raw_error == libc::EXDEV;
+        }
+    }
+    #[cfg(windows)]
+    {
+        // ERROR_NOT_SAME_DEVICE (17)
+        if let Some(raw_error) = error.raw_os_error() {
+            return raw_error == 17;
+        }
+    }
+    false
+}
+
+/// Resolves symlinks in the destination (or its parent, if the destination
+/// doesn't exist yet) to find the path on the filesystem that actually holds it.
+fn resolve_real_destination(dest: &Path) -> std::io::Result<PathBuf> {
+    if let Ok(real) = fs::canonicalize(dest) {
+        return Ok(real);
+    }
+    let parent = dest.parent().unwrap_or_else(|| Path::new("."));
+    let name = dest.file_name().ok_or_else(|| {
+        std::io::Error::new(ErrorKind::InvalidInput, "destination has no file name")
+    })?;
+    Ok(fs::canonicalize(parent)?.join(name))
+}
+
+/// Moves a fully written temp file onto a destination that lives on another
+/// filesystem. The data is first re-staged next to the real destination and
+/// fsynced, so the final step is still a same-filesystem atomic rename.
+/// Returns the resolved destination, whose parent is the directory to fsync.
+///
+/// If the destination itself is a mount point (a bind-mounted file), no
+/// rename can replace it, and its contents are overwritten in place instead.
+fn replace_across_devices(temp_path: &Path, dest: &Path) -> std::io::Result<PathBuf> {
+    let real_dest = resolve_real_destination(dest)?;
+    let temp_metadata = fs::metadata(temp_path)?;
+    let mtime = filetime::FileTime::from_last_modification_time(&temp_metadata);
+
+    let staged_path = get_temp_path(&real_dest);
+    if staged_path != temp_path {
+        fs::copy(temp_path, &staged_path)?;
+        let _ = filetime::set_file_mtime(&staged_path, mtime);
+        File::open(&staged_path)?.sync_all()?;
+
+        match fs::rename(&staged_path, &real_dest) {
+            Ok(_) => {
+                let _ = fs::remove_file(temp_path);
+                return Ok(real_dest);
+            }
+            Err(e) if is_cross_device_error(&e) => {
+                let _ = fs::remove_file(&staged_path);
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&staged_path);
+                return Err(e);
+            }
+        }
+    }
+
+    log::warn!(
+        "Destination '{}' is a mount point; overwriting in place (not atomic)",
+        real_dest.display()
+    );
+    fs::copy(temp_path, &real_dest)?;
+    let _ = filetime::set_file_mtime(&real_dest, mtime);
+    fs::OpenOptions::new()
+        .write(true)
+        .open(&real_dest)?
+        .sync_all()?;
+    let _ = fs::remove_file(temp_path);
+    Ok(real_dest)
+}
+
+/// Returns the form of `path` to hand to file APIs. On Windows, absolute paths
+/// get the `\\?\` extended-length prefix (`\\?\UNC\` for network shares),
+/// which lifts the 260-character MAX_PATH limit to about 32,767 characters.
+/// Windows doesn't normalize prefixed paths, so `.` and `..` are resolved here
+/// and separators rewritten as `\`. Relative, drive-relative and already
+/// prefixed paths are returned unchanged.
+#[cfg(windows)]
+pub fn extended_length_path(path: &Path) -> PathBuf {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    let mut components = path.components();
+    let prefix = match components.next() {
+        Some(Component::Prefix(prefix)) if path.has_root() => prefix,
+        _ => return path.to_path_buf(),
+    };
+
+    let mut extended = OsString::from(r"\\?\");
+    match prefix.kind() {
+        Prefix::Disk(letter) => extended.push(format!("{}:", letter as char)),
+        Prefix::UNC(server, share) => {
+            extended.push(r"UNC\");
+            extended.push(server);
+            extended.push(r"\");
+            extended.push(share);
+        }
+        // Verbatim (`\\?\`) and device (`\\.\`) paths are used as given
+        _ => return path.to_path_buf(),
+    }
+
+    let mut names = Vec::new();
+    for component in components {
+        match component {
+            Component::Normal(name) => names.push(name),
+            Component::ParentDir => {
+                names.pop();
+            }
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+
+    if names.is_empty() {
+        extended.push(r"\");
+    }
+    for name in names {
+        extended.push(r"\");
+        extended.push(name);
+    }
+    PathBuf::from(extended)
+}
+
+/// Paths are only length-limited on Windows; elsewhere this is the identity.
+#[cfg(not(windows))]
+pub fn extended_length_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
 /// Classify an IO error into a more specific SyncError for better user messaging.
 pub fn classify_io_error(error: std::io::Error, path: &Path) -> SyncError {
     match error.kind() {
@@ -259,6 +534,47 @@ pub fn classify_io_error(error: std::io::Error, path: &Path) -> SyncError {
     }
 }
 
+/// Non-regular file types that can't be copied by reading their contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SpecialFileType {
+    /// Regular file, directory, or symlink
+    #[default]
+    None,
+    /// Named pipe - opening it for reading blocks until a writer appears
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+}
+
+impl SpecialFileType {
+    pub fn from_file_type(file_type: &fs::FileType) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_fifo() {
+                return SpecialFileType::Fifo;
+            }
+            if file_type.is_socket() {
+                return SpecialFileType::Socket;
+            }
+            if file_type.is_block_device() {
+                return SpecialFileType::BlockDevice;
+            }
+            if file_type.is_char_device() {
+                return SpecialFileType::CharDevice;
+            }
+        }
+        let _ = file_type;
+        SpecialFileType::None
+    }
+
+    pub fn is_special(&self) -> bool {
+        *self != SpecialFileType::None
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
     pub path: PathBuf,
@@ -266,6 +582,14 @@ pub struct FileInfo {
     pub modified: DateTime<Utc>,
     pub is_dir: bool,
     pub is_symlink: bool,
+    #[serde(default)]
+    pub file_type: SpecialFileType,
+    /// A File Provider (iCloud Drive) placeholder whose contents aren't on disk
+    #[serde(default)]
+    pub is_dataless: bool,
+    /// `chflags` flags (`st_flags`), on macOS; 0 elsewhere
+    #[serde(default)]
+    pub flags: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -285,6 +609,61 @@ pub struct DirectorySummary {
     pub file_count: usize,
     pub dir_count: usize,
     pub scan_id: String,
+    /// How the file sizes are spread
+    pub size_histogram: SizeHistogram,
+    /// Most of the files are small enough that per-file overhead, not drive
+    /// speed, decides how long copying them takes
+    pub small_file_heavy: bool,
+}
+
+/// Files at or above this count can make a transfer small-file heavy.
+const SMALL_FILE_HEAVY_MIN_FILES: usize = 10_000;
+
+/// Share of files under 64 KiB above which a transfer is small-file heavy.
+const SMALL_FILE_HEAVY_SHARE: f64 = 0.5;
+
+/// File counts by size, collected while scanning.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SizeHistogram {
+    pub under_4k: usize,
+    pub under_64k: usize,
+    pub under_1m: usize,
+    pub under_16m: usize,
+    pub under_256m: usize,
+    pub larger: usize,
+}
+
+impl SizeHistogram {
+    pub fn record(&mut self, size: u64) {
+        const KIB: u64 = 1024;
+        const MIB: u64 = 1024 * KIB;
+        let bucket = match size {
+            s if s < 4 * KIB => &mut self.under_4k,
+            s if s < 64 * KIB => &mut self.under_64k,
+            s if s < MIB => &mut self.under_1m,
+            s if s < 16 * MIB => &mut self.under_16m,
+            s if s < 256 * MIB => &mut self.under_256m,
+            _ => &mut self.larger,
+        };
+        *bucket += 1;
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.under_4k
+            + self.under_64k
+            + self.under_1m
+            + self.under_16m
+            + self.under_256m
+            + self.larger
+    }
+
+    /// Many files, most of them under 64 KiB: the copy is bound by per-file
+    /// syscalls and metadata updates however fast the drives are.
+    pub fn is_small_file_heavy(&self) -> bool {
+        let files = self.file_count();
+        files >= SMALL_FILE_HEAVY_MIN_FILES
+            && (self.under_4k + self.under_64k) as f64 / files as f64 > SMALL_FILE_HEAVY_SHARE
+    }
 }
 
 /// A chunk of files from a streaming directory scan
@@ -311,6 +690,24 @@ pub struct DeltaInfo {
     pub source_newer: bool,
     pub source_older: bool,
     pub size_differs: bool,
+    pub source_larger: bool,
+    /// Size and mtime match but the extended attributes don't; only set by
+    /// `detect_delta_with_xattrs`
+    pub xattrs_differ: bool,
+}
+
+/// Which modification time copied files get.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimestampMode {
+    /// Keep the source's mtime (when `preserve_metadata` is set)
+    #[default]
+    PreserveSource,
+    /// Stamp every copied file with the time it was copied, e.g. to wake file watchers
+    CurrentTime,
+    /// Like `CurrentTime`, but a destination whose contents already match the
+    /// source is left alone instead of being rewritten and touched
+    PreserveSourceButTouchOnChange,
 }
 
 #[derive(Debug, Clone)]
@@ -321,6 +718,9 @@ pub struct CopyOptions {
     pub resume_offset: u64,
     /// Bandwidth limit in bytes per second. 0 = unlimited.
     pub bandwidth_limit: u64,
+    /// Live bandwidth limit, re-read every block in place of `bandwidth_limit`
+    /// so it can be changed while a file is being copied.
+    pub shared_bandwidth_limit: Option<Arc<AtomicU64>>,
     /// Pre-computed source hash for end-to-end verification.
     /// If provided, this hash is used instead of re-hashing the source after copy.
     /// This prevents race conditions where source changes during/after copy.
@@ -328,6 +728,35 @@ pub struct CopyOptions {
     /// Source modification time captured before copy started.
     /// Used to detect if source was modified during copy.
     pub source_mtime_before_copy: Option<std::time::SystemTime>,
+    /// If the final rename fails because the destination is locked/busy,
+    /// move the destination aside and retry once (restoring it on failure).
+    pub force_overwrite_locked: bool,
+    /// Copy extended ACLs (beyond the POSIX permission bits) to the destination.
+    pub preserve_acl: bool,
+    /// Copy extended attributes, such as Finder tags, to the destination.
+    pub preserve_xattr: bool,
+    pub timestamp_mode: TimestampMode,
+    /// When set, each block is flushed and fsynced before the progress callback
+    /// runs, so the offset it reports is what is actually on disk.
+    pub flush_requested: Option<Arc<AtomicBool>>,
+    /// When set, the copy flushes and `sync_data`s every `DURABLE_SYNC_INTERVAL`
+    /// bytes and stores the offset here, so the caller knows how much of the
+    /// destination is safe to resume from after a crash.
+    pub durable_offset: Option<Arc<AtomicU64>>,
+    /// Read the source on a dedicated thread that stays up to
+    /// `READ_AHEAD_BUFFERS` blocks ahead of the writer, so read and write
+    /// latency overlap instead of adding up.
+    pub pipelined_copy: bool,
+    /// Taken for the verification hashes, so they run no more often at once
+    /// than the limiter allows however many copies are running.
+    pub hash_limiter: Option<Arc<HashLimiter>>,
+    /// Tag the temp file of an atomic copy with `spotlight::EXCLUDE_XATTR`
+    /// while it's written; the tag is removed after the rename
+    pub exclude_temp_from_indexing: bool,
+    /// With `verify_integrity`, hash the bytes as they're written instead of
+    /// reading the destination back afterwards. Resumed copies still read back,
+    /// since the bytes before the resume offset weren't written by this copy.
+    pub verify_during_copy: bool,
 }
 
 impl Default for CopyOptions {
@@ -338,9 +767,85 @@ impl Default for CopyOptions {
             verify_integrity: false,
             resume_offset: 0,
             bandwidth_limit: BANDWIDTH_UNLIMITED,
+            shared_bandwidth_limit: None,
             pre_copy_source_hash: None,
             source_mtime_before_copy: None,
+            force_overwrite_locked: false,
+            preserve_acl: false,
+            preserve_xattr: false,
+            timestamp_mode: TimestampMode::PreserveSource,
+            flush_requested: None,
+            durable_offset: None,
+            pipelined_copy: false,
+            hash_limiter: None,
+            exclude_temp_from_indexing: false,
+            verify_during_copy: false,
+        }
+    }
+}
+
+impl CopyOptions {
+    /// The bandwidth limit in effect right now.
+    pub fn current_bandwidth_limit(&self) -> u64 {
+        self.shared_bandwidth_limit
+            .as_ref()
+            .map_or(self.bandwidth_limit, |limit| limit.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Default)]
+struct HashSlots {
+    /// Most hashes at once; 0 for no limit
+    limit: usize,
+    active: usize,
+}
+
+/// Caps how many files are hashed at once, independently of how many are
+/// being copied. The limit can be changed while hashes run; permits already
+/// handed out are kept until dropped.
+#[derive(Debug, Default)]
+pub struct HashLimiter {
+    slots: parking_lot::Mutex<HashSlots>,
+    released: parking_lot::Condvar,
+}
+
+/// A slot in a `HashLimiter`, given back when dropped.
+pub struct HashPermit<'a> {
+    limiter: &'a HashLimiter,
+}
+
+impl HashLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            slots: parking_lot::Mutex::new(HashSlots { limit, active: 0 }),
+            released: parking_lot::Condvar::new(),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.slots.lock().limit
+    }
+
+    pub fn set_limit(&self, limit: usize) {
+        self.slots.lock().limit = limit;
+        self.released.notify_all();
+    }
+
+    /// Blocks until a hash may start.
+    pub fn acquire(&self) -> HashPermit<'_> {
+        let mut slots = self.slots.lock();
+        while slots.limit > 0 && slots.active >= slots.limit {
+            self.released.wait(&mut slots);
         }
+        slots.active += 1;
+        HashPermit { limiter: self }
+    }
+}
+
+impl Drop for HashPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.slots.lock().active -= 1;
+        self.limiter.released.notify_one();
     }
 }
 
@@ -363,10 +868,212 @@ pub fn compute_file_hash(path: &Path) -> SyncResult<u64> {
     Ok(hasher.digest())
 }
 
+/// Progress of an interrupted `verify_copy_resumable`: both files matched up
+/// to `offset`, and `digest` is the chained hash of that prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashCheckpoint {
+    pub offset: u64,
+    pub digest: u64,
+}
+
+/// Checks that `dest` holds the same bytes as `source`, hashing both in
+/// `HASH_CHECKPOINT_INTERVAL` segments. Each segment is hashed with the
+/// previous segment's digest as seed, so a prefix's digest is all it takes to
+/// carry on from it. `on_checkpoint` gets a checkpoint after every matching
+/// segment and returns false to stop; passing the last one back as
+/// `resume_from` picks an interrupted verification up where it left off.
+///
+/// The chained digest is not comparable with `compute_file_hash`.
+pub fn verify_copy_resumable<F>(
+    source: &Path,
+    dest: &Path,
+    resume_from: Option<HashCheckpoint>,
+    mut on_checkpoint: F,
+) -> SyncResult<()>
+where
+    F: FnMut(HashCheckpoint) -> bool,
+{
+    let mut src_file = File::open(source)?;
+    let mut dest_file = File::open(dest)?;
+    let len = src_file.metadata()?.len();
+    if dest_file.metadata()?.len() != len {
+        return Err(SyncError::HashMismatch(dest.display().to_string()));
+    }
+
+    // A checkpoint past the end can't be from these contents; start over
+    let mut checkpoint = resume_from
+        .filter(|checkpoint| checkpoint.offset <= len)
+        .unwrap_or(HashCheckpoint {
+            offset: 0,
+            digest: 0,
+        });
+    src_file.seek(SeekFrom::Start(checkpoint.offset))?;
+    dest_file.seek(SeekFrom::Start(checkpoint.offset))?;
+
+    let mut src_reader = BufReader::with_capacity(HASH_BUFFER_SIZE, src_file);
+    let mut dest_reader = BufReader::with_capacity(HASH_BUFFER_SIZE, dest_file);
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+    while checkpoint.offset < len {
+        let segment = HASH_CHECKPOINT_INTERVAL.min(len - checkpoint.offset);
+        let src_digest = hash_segment(&mut src_reader, segment, checkpoint.digest, &mut buffer)?;
+        let dest_digest = hash_segment(&mut dest_reader, segment, checkpoint.digest, &mut buffer)?;
+        if src_digest != dest_digest {
+            return Err(SyncError::HashMismatch(dest.display().to_string()));
+        }
+
+        checkpoint = HashCheckpoint {
+            offset: checkpoint.offset + segment,
+            digest: src_digest,
+        };
+        if !on_checkpoint(checkpoint) {
+            return Err(SyncError::TransferCancelled(
+                "Verification cancelled".into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Hashes the next `len` bytes of `reader` with xxh3 seeded by `seed`.
+fn hash_segment<R: Read>(
+    reader: &mut R,
+    len: u64,
+    seed: u64,
+    buffer: &mut [u8],
+) -> SyncResult<u64> {
+    let mut hasher = xxhash_rust::xxh3::Xxh3::with_seed(seed);
+    let mut remaining = len;
+
+    while remaining > 0 {
+        let want = remaining.min(buffer.len() as u64) as usize;
+        let bytes_read = reader.read(&mut buffer[..want])?;
+        if bytes_read == 0 {
+            // The file shrank while it was being hashed
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+        hasher.update(&buffer[..bytes_read]);
+        remaining -= bytes_read as u64;
+    }
+
+    Ok(hasher.digest())
+}
+
 pub fn compute_hash(data: &[u8]) -> u64 {
     xxh3_64(data)
 }
 
+/// Hash algorithm and file name for a checksum manifest written after a sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestFormat {
+    /// `SHA256SUMS`, checked with `shasum -a 256 -c` or `sha256sum -c`
+    Sha256,
+    /// `MD5SUMS`, checked with `md5sum -c`
+    Md5,
+}
+
+impl ManifestFormat {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ManifestFormat::Sha256 => "SHA256SUMS",
+            ManifestFormat::Md5 => "MD5SUMS",
+        }
+    }
+
+    fn hash_file(self, path: &Path) -> SyncResult<String> {
+        match self {
+            ManifestFormat::Sha256 => hash_file_hex::<sha2::Sha256>(path),
+            ManifestFormat::Md5 => hash_file_hex::<md5::Md5>(path),
+        }
+    }
+}
+
+fn hash_file_hex<D: sha2::Digest>(path: &Path) -> SyncResult<String> {
+    let file = File::open(path).map_err(|e| classify_io_error(e, path))?;
+    let mut reader = BufReader::with_capacity(HASH_BUFFER_SIZE, file);
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    let mut hasher = D::new();
+
+    loop {
+        let bytes_read = reader
+            .read(&mut buffer)
+            .map_err(|e| classify_io_error(e, path))?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Formats one manifest line the way `sha256sum`/`shasum` print them: hash,
+/// two spaces, path with `/` separators. Names containing a backslash or a
+/// newline are escaped and the line prefixed with `\`, as coreutils does.
+fn manifest_line(hash: &str, relative_path: &Path) -> String {
+    let name = relative_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if name.contains('\\') || name.contains('\n') || name.contains('\r') {
+        let escaped = name
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        format!("\\{}  {}\n", hash, escaped)
+    } else {
+        format!("{}  {}\n", hash, name)
+    }
+}
+
+/// Hashes every regular file under `root` and writes a manifest at its top
+/// level, so the tree can be verified with `shasum -c` from inside `root`.
+///
+/// Integrity verification during the copy uses xxh3, which no standard tool
+/// reads, so the files are hashed again here with the manifest's algorithm.
+/// Temp and partial files, the engine's directories and the manifest itself
+/// are left out. Returns the manifest path.
+pub fn write_checksum_manifest(root: &Path, format: ManifestFormat) -> SyncResult<PathBuf> {
+    let manifest_path = root.join(format.file_name());
+
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| e.depth() != 1 || !is_engine_dir(Path::new(e.file_name())))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| !is_partial_file_name(&e.file_name().to_string_lossy()))
+        .filter_map(|e| e.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .filter(|relative| relative.as_os_str() != format.file_name())
+        .collect();
+    files.sort();
+
+    let mut contents = String::new();
+    for relative in &files {
+        let hash = format.hash_file(&root.join(relative))?;
+        contents.push_str(&manifest_line(&hash, relative));
+    }
+
+    // Write beside the final name and rename, so a reader never sees half a manifest
+    let temp_path = root.join(format!("{}{}", format.file_name(), TEMP_FILE_EXTENSION));
+    fs::write(&temp_path, contents).map_err(|e| classify_io_error(e, &temp_path))?;
+    fs::rename(&temp_path, &manifest_path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        classify_io_error(e, &manifest_path)
+    })?;
+
+    Ok(manifest_path)
+}
+
 pub fn get_file_info(path: &Path, base_path: &Path) -> SyncResult<FileInfo> {
     let metadata = fs::symlink_metadata(path)?;
     let relative_path = path
@@ -382,9 +1089,150 @@ pub fn get_file_info(path: &Path, base_path: &Path) -> SyncResult<FileInfo> {
         modified,
         is_dir: metadata.is_dir(),
         is_symlink: metadata.is_symlink(),
+        file_type: SpecialFileType::from_file_type(&metadata.file_type()),
+        is_dataless: is_dataless(&metadata),
+        flags: file_flags(&metadata),
     })
 }
 
+/// `SF_DATALESS` from `<sys/stat.h>`, set on File Provider placeholders.
+#[cfg(target_os = "macos")]
+const SF_DATALESS: u32 = 0x4000_0000;
+
+/// Returns true for an evicted iCloud Drive / File Provider file. Reading one
+/// makes the system download its contents first.
+#[cfg(target_os = "macos")]
+pub fn is_dataless(metadata: &fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    metadata.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn is_dataless(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// The `chflags` flags `preserve_metadata` copies: hidden, nodump, the user
+/// and system immutable and append-only flags, and archived. Flags the
+/// system keeps itself, like `UF_COMPRESSED` and `SF_DATALESS`, are not copied.
+#[cfg(target_os = "macos")]
+const PRESERVED_FILE_FLAGS: u32 = libc::UF_HIDDEN
+    | libc::UF_NODUMP
+    | libc::UF_IMMUTABLE
+    | libc::UF_APPEND
+    | libc::SF_ARCHIVED
+    | libc::SF_IMMUTABLE
+    | libc::SF_APPEND;
+
+#[cfg(target_os = "macos")]
+pub fn file_flags(metadata: &fs::Metadata) -> u32 {
+    use std::os::macos::fs::MetadataExt;
+    metadata.st_flags()
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn file_flags(_metadata: &fs::Metadata) -> u32 {
+    0
+}
+
+/// Names `chflags` uses for the preserved flags in `flags`, for warnings.
+#[cfg(target_os = "macos")]
+fn flag_names(flags: u32) -> String {
+    [
+        (libc::UF_HIDDEN, "hidden"),
+        (libc::UF_NODUMP, "nodump"),
+        (libc::UF_IMMUTABLE, "uchg"),
+        (libc::UF_APPEND, "uappnd"),
+        (libc::SF_ARCHIVED, "arch"),
+        (libc::SF_IMMUTABLE, "schg"),
+        (libc::SF_APPEND, "sappnd"),
+    ]
+    .iter()
+    .filter(|(flag, _)| flags & flag != 0)
+    .map(|(_, name)| *name)
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+#[cfg(target_os = "macos")]
+fn set_file_flags(path: &Path, flags: u32) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    if unsafe { libc::chflags(c_path.as_ptr(), flags) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Gives `dest` the preserved flags of `source_flags`, keeping the flags the
+/// system set on it itself. Run last, since an immutable file takes no
+/// further changes. Only root may change system flags; without it the user
+/// flags are still set and the error names the system flags that weren't.
+#[cfg(target_os = "macos")]
+pub fn apply_file_flags(dest: &Path, source_flags: u32) -> Result<(), String> {
+    let metadata = fs::symlink_metadata(dest).map_err(|e| e.to_string())?;
+    // chflags follows links; the link's target isn't ours to change
+    if metadata.is_symlink() {
+        return Ok(());
+    }
+    let current = file_flags(&metadata);
+    let wanted = source_flags & PRESERVED_FILE_FLAGS;
+    if current & PRESERVED_FILE_FLAGS == wanted {
+        return Ok(());
+    }
+
+    let kept = current & !PRESERVED_FILE_FLAGS;
+    let error = match set_file_flags(dest, kept | wanted) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    let changes = (current ^ wanted) & PRESERVED_FILE_FLAGS;
+    let system_changes = changes & libc::SF_SETTABLE;
+    if error.raw_os_error() == Some(libc::EPERM) && system_changes != 0 {
+        let current_system = current & PRESERVED_FILE_FLAGS & libc::SF_SETTABLE;
+        let user_only = kept | (wanted & libc::UF_SETTABLE) | current_system;
+        if set_file_flags(dest, user_only).is_ok() {
+            return Err(format!(
+                "could not change {}: {}",
+                flag_names(system_changes),
+                error
+            ));
+        }
+    }
+    Err(format!(
+        "could not change {}: {}",
+        flag_names(changes),
+        error
+    ))
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn apply_file_flags(_dest: &Path, _source_flags: u32) -> Result<(), String> {
+    Ok(())
+}
+
+/// Clears `uchg` and `uappnd` on a destination about to be replaced, when the
+/// source has them too, so a lock an earlier sync copied over doesn't make
+/// this one fail. A destination locked on its own stays locked and its copy
+/// fails, as before.
+#[cfg(target_os = "macos")]
+pub fn unlock_copied_flags(dest: &Path, source_flags: u32) -> std::io::Result<()> {
+    let locks = libc::UF_IMMUTABLE | libc::UF_APPEND;
+    let metadata = fs::symlink_metadata(dest)?;
+    let current = file_flags(&metadata);
+    if metadata.is_symlink() || current & locks == 0 || source_flags & locks != current & locks {
+        return Ok(());
+    }
+    set_file_flags(dest, current & !locks)
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn unlock_copied_flags(_dest: &Path, _source_flags: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
 pub fn metadata_to_datetime(metadata: &std::fs::Metadata) -> SyncResult<DateTime<Utc>> {
     let modified = metadata.modified()?;
     let duration = modified
@@ -417,17 +1265,18 @@ pub fn scan_directory_with_options(path: &Path, follow_symlinks: bool) -> SyncRe
     let mut dir_count: usize = 0;
     let mut files = Vec::new();
 
-    for entry in walkdir::WalkDir::new(path)
+    let walk_root = extended_length_path(path);
+    for entry in walkdir::WalkDir::new(&walk_root)
         .follow_links(follow_symlinks)
         .into_iter()
         .filter_map(|e| e.ok())
     {
         let entry_path = entry.path();
-        if entry_path == path {
+        if entry_path == walk_root {
             continue;
         }
 
-        if let Ok(info) = get_file_info(entry_path, path) {
+        if let Ok(info) = get_file_info(entry_path, &walk_root) {
             if info.is_dir {
                 dir_count += 1;
             } else {
@@ -447,16 +1296,1257 @@ pub fn scan_directory_with_options(path: &Path, follow_symlinks: bool) -> SyncRe
     })
 }
 
+/// Per-directory ignore file read from the root of a sync source, one pattern per line.
+pub const IGNORE_FILENAME: &str = ".rsyncignore";
+
+/// Where an exclude pattern came from, from lowest to highest precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExcludeLayer {
+    /// App-wide patterns from the settings
+    Global,
+    /// The sync's own `exclude_patterns`
+    Profile,
+    /// The source's `.rsyncignore`
+    Directory,
+}
+
+/// One exclude pattern with its layer. A leading `!` negates the pattern,
+/// re-including paths that an earlier rule excluded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludeRule {
+    pub pattern: String,
+    pub negated: bool,
+    pub layer: ExcludeLayer,
+}
+
+impl ExcludeRule {
+    pub fn parse(pattern: &str, layer: ExcludeLayer) -> Self {
+        match pattern.strip_prefix('!') {
+            Some(rest) => Self {
+                pattern: rest.to_string(),
+                negated: true,
+                layer,
+            },
+            None => Self {
+                pattern: pattern.to_string(),
+                negated: false,
+                layer,
+            },
+        }
+    }
+}
+
+/// Merges the exclude layers in precedence order: global patterns first, then
+/// the sync's own patterns, then the source's `.rsyncignore`, so later layers
+/// override earlier ones. Blank lines and `#` comments in the ignore file are skipped.
+pub fn layered_exclude_rules(
+    global: &[String],
+    profile: &[String],
+    source_root: Option<&Path>,
+) -> Vec<ExcludeRule> {
+    let mut rules: Vec<ExcludeRule> = global
+        .iter()
+        .map(|p| ExcludeRule::parse(p, ExcludeLayer::Global))
+        .chain(
+            profile
+                .iter()
+                .map(|p| ExcludeRule::parse(p, ExcludeLayer::Profile)),
+        )
+        .collect();
+
+    if let Some(root) = source_root {
+        if let Ok(content) = fs::read_to_string(root.join(IGNORE_FILENAME)) {
+            rules.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| ExcludeRule::parse(line, ExcludeLayer::Directory)),
+            );
+        }
+    }
+    rules
+}
+
+/// Compiled exclude rules. The last rule matching a path decides it, so a
+/// negated rule re-includes what came before it and later layers win.
+#[derive(Debug, Clone)]
+pub struct ExcludeMatcher {
+    set: GlobSet,
+    /// Rule for each glob in `set`, by index
+    rules: Vec<ExcludeRule>,
+    /// Relative subdirectories a sync is restricted to; empty for the whole tree
+    subtrees: Vec<PathBuf>,
+}
+
+impl ExcludeMatcher {
+    /// Compiles the rules, dropping invalid globs. Returns None if no rule is usable.
+    pub fn compile(rules: Vec<ExcludeRule>) -> Option<Self> {
+        let mut builder = GlobSetBuilder::new();
+        let mut valid = Vec::new();
+
+        for rule in rules {
+            // Build glob with case-insensitive matching for macOS/Windows compatibility
+            let glob_result = Glob::new(&rule.pattern).or_else(|_| {
+                // Try with **/ prefix for directory matching
+                Glob::new(&format!("**/{}", rule.pattern))
+            });
+
+            if let Ok(glob) = glob_result {
+                builder.add(glob);
+                valid.push(rule);
+            } else {
+                eprintln!("Warning: Invalid glob pattern ignored: {}", rule.pattern);
+            }
+        }
+
+        if valid.is_empty() {
+            return None;
+        }
+        let set = builder.build().ok()?;
+        Some(Self {
+            set,
+            rules: valid,
+            subtrees: Vec::new(),
+        })
+    }
+
+    /// Restricts `matcher` (or no rules at all) to the given relative
+    /// subdirectories: everything outside them counts as excluded, apart from
+    /// the directories leading down to them.
+    pub fn restricted_to(matcher: Option<Self>, subtrees: Vec<PathBuf>) -> Self {
+        let mut matcher = matcher.unwrap_or_else(|| Self {
+            set: GlobSet::empty(),
+            rules: Vec::new(),
+            subtrees: Vec::new(),
+        });
+        matcher.subtrees = subtrees;
+        matcher
+    }
+
+    /// Whether a relative path lies outside every subdirectory the matcher is
+    /// restricted to, and isn't on the way to one of them either.
+    pub fn outside_subtrees(&self, path: &Path) -> bool {
+        !self.subtrees.is_empty()
+            && !self
+                .subtrees
+                .iter()
+                .any(|subtree| path.starts_with(subtree) || subtree.starts_with(path))
+    }
+
+    /// The merged rules in precedence order.
+    pub fn rules(&self) -> &[ExcludeRule] {
+        &self.rules
+    }
+
+    /// The rule that decides a relative path: the last one matching the full
+    /// path, the file/folder name (for patterns like ".DS_Store") or any path
+    /// component (for directory patterns like "node_modules").
+    pub fn deciding_rule(&self, path: &Path) -> Option<&ExcludeRule> {
+        self.deciding_index(path).map(|index| &self.rules[index])
+    }
+
+    /// Index into `rules()` of the rule that decides `path`.
+    pub fn deciding_index(&self, path: &Path) -> Option<usize> {
+        let components = path.components().filter_map(|component| match component {
+            std::path::Component::Normal(name) => Some(Path::new(name)),
+            _ => None,
+        });
+        std::iter::once(path)
+            .chain(path.file_name().map(Path::new))
+            .chain(components)
+            .flat_map(|candidate| self.set.matches(candidate))
+            .max()
+    }
+}
+
+/// Compiles plain exclusion patterns (leading `!` negates) into a single layer.
+/// Returns None if there are no patterns or all patterns are invalid.
+pub fn build_exclude_matcher(patterns: &[String]) -> Option<ExcludeMatcher> {
+    ExcludeMatcher::compile(
+        patterns
+            .iter()
+            .map(|p| ExcludeRule::parse(p, ExcludeLayer::Profile))
+            .collect(),
+    )
+}
+
+/// The merged exclude rules for a source, and which of them decides a given path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludeExplanation {
+    /// Usable rules in precedence order; later rules override earlier ones
+    pub rules: Vec<ExcludeRule>,
+    /// Relative path that was checked, if any
+    pub path: Option<PathBuf>,
+    /// Last rule matching `path`, whose layer is the one that decided it
+    pub deciding_rule: Option<ExcludeRule>,
+    pub excluded: bool,
+}
+
+/// Merges the exclude layers for `source_root` and, given a path relative to
+/// it, reports which rule excludes or re-includes that path.
+pub fn explain_exclusion(
+    source_root: &Path,
+    global: &[String],
+    profile: &[String],
+    path: Option<&Path>,
+) -> ExcludeExplanation {
+    let matcher =
+        ExcludeMatcher::compile(layered_exclude_rules(global, profile, Some(source_root)));
+    let deciding_rule = path
+        .zip(matcher.as_ref())
+        .and_then(|(path, m)| m.deciding_rule(path))
+        .cloned();
+    ExcludeExplanation {
+        rules: matcher.map(|m| m.rules).unwrap_or_default(),
+        path: path.map(Path::to_path_buf),
+        excluded: deciding_rule.as_ref().is_some_and(|rule| !rule.negated),
+        deciding_rule,
+    }
+}
+
+/// Checks if a relative file path should be excluded based on the compiled rules.
+pub fn is_excluded(path: &Path, matcher: Option<&ExcludeMatcher>) -> bool {
+    matcher.is_some_and(|m| {
+        m.outside_subtrees(path) || m.deciding_rule(path).is_some_and(|rule| !rule.negated)
+    })
+}
+
+/// Most sample paths classified by `analyze_patterns`.
+const PATTERN_ANALYSIS_MAX_SAMPLES: usize = 25;
+
+/// Paths classified alongside the ones derived from the patterns themselves.
+const REPRESENTATIVE_PATHS: &[&str] = &[
+    "notes.txt",
+    "docs/readme.md",
+    "src/lib/main.rs",
+    "logs/app.log",
+    ".config/settings.json",
+];
+
+/// Which list a pattern in `analyze_patterns` belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternList {
+    Include,
+    Exclude,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternIssueKind {
+    /// Not a valid glob; it is ignored
+    Invalid,
+    /// Everything it matches is already matched by `related`
+    Redundant,
+    /// A later rule (`related`) undoes it for everything it matches
+    Shadowed,
+    /// An include whose every match is excluded by `related`
+    Contradictory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternIssue {
+    pub kind: PatternIssueKind,
+    pub list: PatternList,
+    pub pattern: String,
+    pub related: Option<String>,
+    pub message: String,
+}
+
+/// How one path would be treated by the analyzed patterns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternSample {
+    pub path: PathBuf,
+    pub included: bool,
+    /// Pattern that decided the outcome, if any did
+    pub decided_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternAnalysis {
+    pub issues: Vec<PatternIssue>,
+    pub samples: Vec<PatternSample>,
+}
+
+/// One pattern of `analyze_patterns`, compiled on its own, with the sample
+/// paths it matches.
+struct AnalyzedPattern {
+    rule: ExcludeRule,
+    matcher: ExcludeMatcher,
+    samples: Vec<PathBuf>,
+}
+
+impl AnalyzedPattern {
+    fn compile(pattern: &str) -> Option<Self> {
+        let rule = ExcludeRule::parse(pattern, ExcludeLayer::Profile);
+        let matcher = ExcludeMatcher::compile(vec![rule.clone()])?;
+        let samples = glob_samples(&rule.pattern)
+            .into_iter()
+            .filter(|path| matcher.deciding_rule(path).is_some())
+            .collect();
+        Some(Self {
+            rule,
+            matcher,
+            samples,
+        })
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        self.matcher.deciding_rule(path).is_some()
+    }
+
+    /// True if `other` matches every sample of this pattern.
+    fn covered_by(&self, other: &AnalyzedPattern) -> bool {
+        !self.samples.is_empty() && self.samples.iter().all(|path| other.matches(path))
+    }
+
+    fn display(&self) -> String {
+        if self.rule.negated {
+            format!("!{}", self.rule.pattern)
+        } else {
+            self.rule.pattern.clone()
+        }
+    }
+}
+
+/// Concrete paths a glob matches: wildcards filled in, character classes and
+/// alternations resolved to their first choice, and `**` tried at a couple of
+/// depths, plus each of those nested under a directory.
+fn glob_samples(pattern: &str) -> Vec<PathBuf> {
+    fn instantiate(pattern: &str, depth: usize) -> String {
+        let mut out = String::new();
+        let mut chars = pattern.trim_matches('/').chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        out.push_str(&"sub/".repeat(depth));
+                    } else {
+                        out.push_str(&vec!["sub"; depth.max(1)].join("/"));
+                    }
+                }
+                '*' | '?' => out.push('x'),
+                '[' => {
+                    let class: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                    match class.strip_prefix(['!', '^']) {
+                        // Any character outside a small negated class will do
+                        Some(_) => out.push('_'),
+                        None => out.push(class.chars().next().unwrap_or('x')),
+                    }
+                }
+                '{' => {
+                    let alternatives: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    out.push_str(&instantiate(
+                        alternatives.split(',').next().unwrap_or(""),
+                        depth,
+                    ));
+                }
+                '\\' => out.extend(chars.next()),
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    let mut samples = Vec::new();
+    for depth in [0, 1, 2] {
+        let path = instantiate(pattern, depth);
+        if path.is_empty() {
+            continue;
+        }
+        for candidate in [PathBuf::from(&path), Path::new("dir").join(&path)] {
+            if !samples.contains(&candidate) {
+                samples.push(candidate);
+            }
+        }
+    }
+    samples
+}
+
+/// Of two patterns in `list` matching the same paths only the later is
+/// redundant; a pattern strictly narrower than another is redundant wherever it is.
+fn redundant_with(list: &[AnalyzedPattern], index: usize) -> Option<&AnalyzedPattern> {
+    let pattern = &list[index];
+    list.iter().enumerate().find_map(|(other_index, other)| {
+        let redundant = other_index != index
+            && other.rule.negated == pattern.rule.negated
+            && pattern.covered_by(other)
+            && (!other.covered_by(pattern) || other_index < index);
+        redundant.then_some(other)
+    })
+}
+
+impl PatternIssue {
+    fn new(
+        kind: PatternIssueKind,
+        list: PatternList,
+        pattern: &AnalyzedPattern,
+        related: Option<&AnalyzedPattern>,
+        message: String,
+    ) -> Self {
+        Self {
+            kind,
+            list,
+            pattern: pattern.display(),
+            related: related.map(AnalyzedPattern::display),
+            message,
+        }
+    }
+}
+
+/// Reports include and exclude patterns that are invalid, redundant, shadowed
+/// by a later rule or contradict each other, and classifies a few sample paths.
+/// A path is synced when it matches an include (or there are none) and the last
+/// exclude rule matching it isn't an exclusion; `!` in an exclude re-includes.
+/// Patterns are compared on concrete paths derived from them, so an issue means
+/// "on every path we tried", not a proof over all paths.
+pub fn analyze_patterns(includes: &[String], excludes: &[String]) -> PatternAnalysis {
+    let mut analysis = PatternAnalysis::default();
+
+    let mut compile_list = |patterns: &[String], list: PatternList| -> Vec<AnalyzedPattern> {
+        let mut compiled = Vec::new();
+        for pattern in patterns.iter().filter(|p| !p.trim().is_empty()) {
+            match AnalyzedPattern::compile(pattern) {
+                Some(analyzed) => compiled.push(analyzed),
+                None => analysis.issues.push(PatternIssue {
+                    kind: PatternIssueKind::Invalid,
+                    list,
+                    pattern: pattern.clone(),
+                    related: None,
+                    message: format!("\"{}\" is not a valid glob and is ignored", pattern),
+                }),
+            }
+        }
+        compiled
+    };
+    let includes = compile_list(includes, PatternList::Include);
+    let excludes = compile_list(excludes, PatternList::Exclude);
+    // Every exclude compiled on its own, so the combined rules line up with `excludes`
+    let exclude_matcher = ExcludeMatcher::compile(
+        excludes
+            .iter()
+            .map(|analyzed| analyzed.rule.clone())
+            .collect(),
+    );
+    let excluded_by = |path: &Path| -> Option<usize> {
+        let index = exclude_matcher.as_ref()?.deciding_index(path)?;
+        (!excludes[index].rule.negated).then_some(index)
+    };
+
+    for (index, include) in includes.iter().enumerate() {
+        if let Some(other) = redundant_with(&includes, index) {
+            let message = format!(
+                "Everything \"{}\" includes is already included by \"{}\"",
+                include.display(),
+                other.display()
+            );
+            analysis.issues.push(PatternIssue::new(
+                PatternIssueKind::Redundant,
+                PatternList::Include,
+                include,
+                Some(other),
+                message,
+            ));
+        }
+
+        // Contradictory only if every sample is excluded
+        let excluders: Option<Vec<usize>> = include
+            .samples
+            .iter()
+            .map(|path| excluded_by(path))
+            .collect();
+        if let Some(&first) = excluders.as_ref().and_then(|indices| indices.first()) {
+            let exclude = &excludes[first];
+            let message = format!(
+                "\"{}\" is included but everything it matches is excluded by \"{}\"",
+                include.display(),
+                exclude.display()
+            );
+            analysis.issues.push(PatternIssue::new(
+                PatternIssueKind::Contradictory,
+                PatternList::Include,
+                include,
+                Some(exclude),
+                message,
+            ));
+        }
+    }
+
+    for (index, exclude) in excludes.iter().enumerate() {
+        let undone_by = excludes[index + 1..]
+            .iter()
+            .find(|later| later.rule.negated != exclude.rule.negated && exclude.covered_by(later));
+        let reincludes_nothing = exclude.rule.negated
+            && !exclude.samples.is_empty()
+            && !excludes[..index].iter().any(|earlier| {
+                !earlier.rule.negated && exclude.samples.iter().any(|path| earlier.matches(path))
+            });
+
+        let (kind, related, message) = if let Some(later) = undone_by {
+            let verb = if exclude.rule.negated {
+                "excluded again"
+            } else {
+                "re-included entirely"
+            };
+            let message = format!(
+                "\"{}\" is {} by the later \"{}\"",
+                exclude.display(),
+                verb,
+                later.display()
+            );
+            (PatternIssueKind::Shadowed, Some(later), message)
+        } else if let Some(other) = redundant_with(&excludes, index) {
+            let message = format!(
+                "Everything \"{}\" matches is already matched by \"{}\"",
+                exclude.display(),
+                other.display()
+            );
+            (PatternIssueKind::Redundant, Some(other), message)
+        } else if reincludes_nothing {
+            let message = format!(
+                "\"{}\" has no effect: no earlier pattern excludes what it re-includes",
+                exclude.display()
+            );
+            (PatternIssueKind::Redundant, None, message)
+        } else {
+            continue;
+        };
+        analysis.issues.push(PatternIssue::new(
+            kind,
+            PatternList::Exclude,
+            exclude,
+            related,
+            message,
+        ));
+    }
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let derived = includes
+        .iter()
+        .chain(&excludes)
+        .flat_map(|analyzed| &analyzed.samples)
+        .cloned();
+    for path in derived.chain(REPRESENTATIVE_PATHS.iter().map(PathBuf::from)) {
+        if paths.len() >= PATTERN_ANALYSIS_MAX_SAMPLES {
+            break;
+        }
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    analysis.samples = paths
+        .into_iter()
+        .map(|path| {
+            let matched_include = includes.iter().find(|include| include.matches(&path));
+            let exclude_rule = exclude_matcher
+                .as_ref()
+                .and_then(|m| m.deciding_rule(&path));
+            let (included, decided_by) = match (exclude_rule, matched_include) {
+                (Some(rule), _) if !rule.negated => (false, Some(rule.pattern.clone())),
+                (_, None) if !includes.is_empty() => (false, None),
+                (Some(rule), _) => (true, Some(format!("!{}", rule.pattern))),
+                (None, include) => (true, include.map(AnalyzedPattern::display)),
+            };
+            PatternSample {
+                path,
+                included,
+                decided_by,
+            }
+        })
+        .collect();
+
+    analysis
+}
+
+/// Symlinks under `root`, not followed, whose targets resolve inside
+/// `target_root`, relative to `root`. Empty if `target_root` doesn't exist.
+pub fn symlinks_resolving_into(root: &Path, target_root: &Path) -> Vec<PathBuf> {
+    let Ok(target_real) = target_root.canonicalize() else {
+        return Vec::new();
+    };
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path_is_symlink())
+        .filter(|entry| {
+            entry
+                .path()
+                .canonicalize()
+                .is_ok_and(|real| real.starts_with(&target_real))
+        })
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+/// Returns true if any component of a relative path is a dotfile/dotfolder.
+pub fn is_hidden_path(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        std::path::Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+        _ => false,
+    })
+}
+
+/// How long a scan used by `preview_exclusions` stays fresh.
+/// Long enough to cover a burst of pattern edits, short enough to pick up new files.
+const EXCLUDE_PREVIEW_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Most excluded paths returned by `preview_exclusions`; totals still cover everything.
+const EXCLUDE_PREVIEW_MAX_PATHS: usize = 1_000;
+
+/// A scan kept for exclude previews: (root, scanned at, files).
+type PreviewScan = (PathBuf, Instant, Arc<Vec<FileInfo>>);
+
+/// Last scan used for exclude previews.
+static EXCLUDE_PREVIEW_CACHE: std::sync::Mutex<Option<PreviewScan>> = std::sync::Mutex::new(None);
+
+/// What a set of exclude patterns would leave out of a sync.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludePreview {
+    /// Excluded files, relative to the scanned root (capped; see `truncated`).
+    pub excluded_paths: Vec<PathBuf>,
+    pub excluded_files: usize,
+    pub excluded_bytes: u64,
+    pub total_files: usize,
+    pub total_bytes: u64,
+    /// True when more files were excluded than fit in `excluded_paths`.
+    pub truncated: bool,
+}
+
+/// Runs exclude patterns against a directory without syncing anything.
+/// The scan is cached briefly so the preview can be refreshed on every pattern edit.
+pub fn preview_exclusions(root: &Path, patterns: &[String]) -> SyncResult<ExcludePreview> {
+    let files = cached_preview_scan(root)?;
+    let matcher = build_exclude_matcher(patterns);
+    let mut preview = ExcludePreview::default();
+
+    for file in files.iter().filter(|f| !f.is_dir) {
+        preview.total_files += 1;
+        preview.total_bytes += file.size;
+
+        if is_excluded(&file.path, matcher.as_ref()) {
+            preview.excluded_files += 1;
+            preview.excluded_bytes += file.size;
+            if preview.excluded_paths.len() < EXCLUDE_PREVIEW_MAX_PATHS {
+                preview.excluded_paths.push(file.path.clone());
+            } else {
+                preview.truncated = true;
+            }
+        }
+    }
+
+    Ok(preview)
+}
+
+/// Returns the unfiltered file list for `root`, rescanning when the cache is stale.
+fn cached_preview_scan(root: &Path) -> SyncResult<Arc<Vec<FileInfo>>> {
+    if let Ok(cache) = EXCLUDE_PREVIEW_CACHE.lock() {
+        if let Some((cached_root, scanned_at, files)) = cache.as_ref() {
+            if cached_root == root && scanned_at.elapsed() < EXCLUDE_PREVIEW_CACHE_TTL {
+                return Ok(Arc::clone(files));
+            }
+        }
+    }
+
+    // Scan without holding the lock; previews of other roots shouldn't wait on this one
+    let files = Arc::new(scan_directory(root)?.files);
+    if let Ok(mut cache) = EXCLUDE_PREVIEW_CACHE.lock() {
+        *cache = Some((root.to_path_buf(), Instant::now(), Arc::clone(&files)));
+    }
+    Ok(files)
+}
+
+/// Extended attribute holding a copied file's xxh3 hash, as 16 hex digits.
+pub const HASH_XATTR: &str = "user.rsync.xxh3";
+
+/// Tags `path` with its content hash in `HASH_XATTR`, so it can later be
+/// checked for bit rot without the source. Fails with `ENOTSUP` on
+/// filesystems without extended attributes.
+#[cfg(unix)]
+pub fn write_hash_xattr(path: &Path, hash: u64) -> std::io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+    let c_name = CString::new(HASH_XATTR).expect("static xattr name");
+    let value = format!("{:016x}", hash);
+
+    #[cfg(target_os = "macos")]
+    let result = unsafe {
+        libc::setxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+            0,
+            0,
+        )
+    };
+    #[cfg(not(target_os = "macos"))]
+    let result = unsafe {
+        libc::setxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+            0,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn write_hash_xattr(_path: &Path, _hash: u64) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        ErrorKind::Unsupported,
+        "extended attributes are not supported",
+    ))
+}
+
+/// Whether an xattr write failed because the filesystem has no xattr support.
+pub fn is_xattr_unsupported(error: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    if matches!(error.raw_os_error(), Some(code) if code == libc::ENOTSUP || code == libc::EOPNOTSUPP)
+    {
+        return true;
+    }
+    error.kind() == ErrorKind::Unsupported
+}
+
+/// Whether an xattr is copied and compared by `preserve_xattr`. The stored
+/// hash belongs to the destination; on Linux, namespaces other than `user.`
+/// need privileges to write and hold permissions rather than metadata.
+fn is_portable_xattr(name: &[u8]) -> bool {
+    if name == HASH_XATTR.as_bytes() {
+        return false;
+    }
+    cfg!(target_os = "macos") || name.starts_with(b"user.")
+}
+
+/// A file's extended attributes that `preserve_xattr` carries over, by name.
+#[cfg(unix)]
+pub fn read_xattrs(path: &Path) -> std::io::Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+
+    // Sized with a first call; retried if attributes were added in between
+    let names = loop {
+        #[cfg(target_os = "macos")]
+        let size = unsafe { libc::listxattr(c_path.as_ptr(), std::ptr::null_mut(), 0, 0) };
+        #[cfg(not(target_os = "macos"))]
+        let size = unsafe { libc::listxattr(c_path.as_ptr(), std::ptr::null_mut(), 0) };
+        if size < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut names = vec![0u8; size as usize];
+        let buffer = names.as_mut_ptr() as *mut libc::c_char;
+        #[cfg(target_os = "macos")]
+        let listed = unsafe { libc::listxattr(c_path.as_ptr(), buffer, names.len(), 0) };
+        #[cfg(not(target_os = "macos"))]
+        let listed = unsafe { libc::listxattr(c_path.as_ptr(), buffer, names.len()) };
+        if listed >= 0 {
+            names.truncate(listed as usize);
+            break names;
+        }
+        let error = std::io::Error::last_os_error();
+        if error.raw_os_error() != Some(libc::ERANGE) {
+            return Err(error);
+        }
+    };
+
+    let mut xattrs = BTreeMap::new();
+    for name in names.split(|&b| b == 0).filter(|name| !name.is_empty()) {
+        if !is_portable_xattr(name) {
+            continue;
+        }
+        let c_name =
+            CString::new(name).map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+        let no_buffer = std::ptr::null_mut();
+        #[cfg(target_os = "macos")]
+        let size = unsafe { libc::getxattr(c_path.as_ptr(), c_name.as_ptr(), no_buffer, 0, 0, 0) };
+        #[cfg(not(target_os = "macos"))]
+        let size = unsafe { libc::getxattr(c_path.as_ptr(), c_name.as_ptr(), no_buffer, 0) };
+        if size < 0 {
+            // Removed since it was listed
+            continue;
+        }
+        let mut value = vec![0u8; size as usize];
+        #[cfg(target_os = "macos")]
+        let read = unsafe {
+            libc::getxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                value.as_mut_ptr() as *mut libc::c_void,
+                value.len(),
+                0,
+                0,
+            )
+        };
+        #[cfg(not(target_os = "macos"))]
+        let read = unsafe {
+            libc::getxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                value.as_mut_ptr() as *mut libc::c_void,
+                value.len(),
+            )
+        };
+        if read < 0 {
+            continue;
+        }
+        value.truncate(read as usize);
+        xattrs.insert(name.to_vec(), value);
+    }
+    Ok(xattrs)
+}
+
+#[cfg(not(unix))]
+pub fn read_xattrs(_path: &Path) -> std::io::Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+    Err(std::io::Error::new(
+        ErrorKind::Unsupported,
+        "extended attributes are not supported",
+    ))
+}
+
+/// Makes `dest`'s extended attributes the same as `source`'s, removing the
+/// ones the source doesn't have. Every attribute is tried; the first failure
+/// is returned.
+#[cfg(unix)]
+pub fn copy_xattrs(source: &Path, dest: &Path) -> std::io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let source_xattrs = read_xattrs(source)?;
+    let dest_xattrs = read_xattrs(dest)?;
+    let c_path = CString::new(dest.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+    let mut first_error = None;
+
+    for name in dest_xattrs
+        .keys()
+        .filter(|name| !source_xattrs.contains_key(*name))
+    {
+        let c_name = CString::new(name.as_slice()).expect("listed xattr names have no NUL");
+        #[cfg(target_os = "macos")]
+        let result = unsafe { libc::removexattr(c_path.as_ptr(), c_name.as_ptr(), 0) };
+        #[cfg(not(target_os = "macos"))]
+        let result = unsafe { libc::removexattr(c_path.as_ptr(), c_name.as_ptr()) };
+        if result != 0 {
+            first_error.get_or_insert_with(std::io::Error::last_os_error);
+        }
+    }
+    for (name, value) in &source_xattrs {
+        if dest_xattrs.get(name) == Some(value) {
+            continue;
+        }
+        let c_name = CString::new(name.as_slice()).expect("listed xattr names have no NUL");
+        #[cfg(target_os = "macos")]
+        let result = unsafe {
+            libc::setxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                value.as_ptr() as *const libc::c_void,
+                value.len(),
+                0,
+                0,
+            )
+        };
+        #[cfg(not(target_os = "macos"))]
+        let result = unsafe {
+            libc::setxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                value.as_ptr() as *const libc::c_void,
+                value.len(),
+                0,
+            )
+        };
+        if result != 0 {
+            first_error.get_or_insert_with(std::io::Error::last_os_error);
+        }
+    }
+
+    first_error.map_or(Ok(()), Err)
+}
+
+#[cfg(not(unix))]
+pub fn copy_xattrs(_source: &Path, _dest: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Reads the hash stored by `write_hash_xattr`, or None if the file has none.
+#[cfg(unix)]
+pub fn read_hash_xattr(path: &Path) -> std::io::Result<Option<u64>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+    let c_name = CString::new(HASH_XATTR).expect("static xattr name");
+    let mut value = [0u8; 16];
+
+    #[cfg(target_os = "macos")]
+    let size = unsafe {
+        libc::getxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            value.as_mut_ptr() as *mut libc::c_void,
+            value.len(),
+            0,
+            0,
+        )
+    };
+    #[cfg(not(target_os = "macos"))]
+    let size = unsafe {
+        libc::getxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            value.as_mut_ptr() as *mut libc::c_void,
+            value.len(),
+        )
+    };
+    if size < 0 {
+        let error = std::io::Error::last_os_error();
+        #[cfg(target_os = "macos")]
+        let missing = error.raw_os_error() == Some(libc::ENOATTR);
+        #[cfg(not(target_os = "macos"))]
+        let missing = error.raw_os_error() == Some(libc::ENODATA);
+        return if missing { Ok(None) } else { Err(error) };
+    }
+
+    std::str::from_utf8(&value[..size as usize])
+        .ok()
+        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        .map(Some)
+        .ok_or_else(|| {
+            std::io::Error::new(ErrorKind::InvalidData, format!("malformed {}", HASH_XATTR))
+        })
+}
+
+#[cfg(not(unix))]
+pub fn read_hash_xattr(_path: &Path) -> std::io::Result<Option<u64>> {
+    Ok(None)
+}
+
+/// Result of re-hashing a tree against the hashes in `HASH_XATTR`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashAudit {
+    pub files_checked: usize,
+    /// Files with no stored hash (copied without `store_hash_xattr`)
+    pub files_without_hash: usize,
+    /// Files whose contents no longer match their stored hash, relative to the root
+    pub mismatched: Vec<PathBuf>,
+    /// Files that couldn't be read or whose stored hash couldn't be parsed
+    pub errors: Vec<String>,
+}
+
+/// Re-hashes every file under `root` that carries a stored hash and reports
+/// the ones that changed since they were copied.
+pub fn audit_hash_xattrs(root: &Path) -> SyncResult<HashAudit> {
+    let mut audit = HashAudit::default();
+    for file in scan_directory(root)?
+        .files
+        .iter()
+        .filter(|f| !f.is_dir && !f.is_symlink)
+    {
+        let path = root.join(&file.path);
+        match read_hash_xattr(&path) {
+            Ok(Some(stored)) => {
+                audit.files_checked += 1;
+                match compute_file_hash(&path) {
+                    Ok(hash) if hash == stored => {}
+                    Ok(_) => audit.mismatched.push(file.path.clone()),
+                    Err(e) => audit.errors.push(format!("{}: {}", file.path.display(), e)),
+                }
+            }
+            Ok(None) => audit.files_without_hash += 1,
+            Err(e) => audit.errors.push(format!("{}: {}", file.path.display(), e)),
+        }
+    }
+    Ok(audit)
+}
+
+/// How long a listing of the files other processes have open for writing is
+/// reused before `OpenWriterIndex` takes a fresh one.
+const OPEN_WRITERS_REFRESH: Duration = Duration::from_secs(15);
+
+/// Which files under a sync source other processes have open for writing, so
+/// `skip_open_files` can leave them out instead of copying a torn file. The
+/// open-file listing is refreshed periodically; write locks are probed per file.
+pub struct OpenWriterIndex {
+    root: PathBuf,
+    /// `root` as the kernel reports open paths, with symlinks resolved
+    canonical_root: PathBuf,
+    snapshot: parking_lot::Mutex<(Instant, HashMap<PathBuf, String>)>,
+}
+
+impl OpenWriterIndex {
+    pub fn new(root: &Path) -> Self {
+        let canonical_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let writers = list_open_writers(&canonical_root);
+        Self {
+            root: root.to_path_buf(),
+            canonical_root,
+            snapshot: parking_lot::Mutex::new((Instant::now(), writers)),
+        }
+    }
+
+    /// Describes the process writing to `path`, if another process has it open
+    /// for writing or holds a write lock on it.
+    pub fn writer_of(&self, path: &Path) -> Option<String> {
+        let canonical = match path.strip_prefix(&self.root) {
+            Ok(relative) => self.canonical_root.join(relative),
+            Err(_) => path.to_path_buf(),
+        };
+        {
+            let mut snapshot = self.snapshot.lock();
+            if snapshot.0.elapsed() >= OPEN_WRITERS_REFRESH {
+                *snapshot = (Instant::now(), list_open_writers(&self.canonical_root));
+            }
+            if let Some(writer) = snapshot.1.get(&canonical) {
+                return Some(writer.clone());
+            }
+        }
+        write_lock_holder(path)
+    }
+}
+
+/// Files under `root` that other processes have open for writing, read from
+/// `/proc/<pid>/fd` and the access mode in `/proc/<pid>/fdinfo`.
+#[cfg(target_os = "linux")]
+fn list_open_writers(root: &Path) -> HashMap<PathBuf, String> {
+    let own_pid = std::process::id().to_string();
+    let mut writers = HashMap::new();
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return writers;
+    };
+
+    for process in processes.flatten() {
+        let pid = process.file_name().to_string_lossy().to_string();
+        if pid == own_pid || !pid.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            if !target.starts_with(root) {
+                continue;
+            }
+            let flags = fs::read_to_string(process.path().join("fdinfo").join(fd.file_name()))
+                .ok()
+                .and_then(|info| {
+                    info.lines()
+                        .find_map(|line| line.strip_prefix("flags:"))
+                        .and_then(|flags| u32::from_str_radix(flags.trim(), 8).ok())
+                });
+            let writable =
+                flags.is_some_and(|flags| flags & libc::O_ACCMODE as u32 != libc::O_RDONLY as u32);
+            if writable {
+                let command = fs::read_to_string(process.path().join("comm")).unwrap_or_default();
+                writers.insert(target, format!("{} (pid {})", command.trim(), pid));
+            }
+        }
+    }
+    writers
+}
+
+/// Files under `root` that other processes have open for writing, from `lsof`.
+#[cfg(target_os = "macos")]
+fn list_open_writers(root: &Path) -> HashMap<PathBuf, String> {
+    use std::process::Command;
+
+    let mut writers = HashMap::new();
+    // -F pcan: one field per line (pid, command, access mode, name)
+    let Ok(output) = Command::new("lsof")
+        .args(["-n", "-P", "-w", "-F", "pcan"])
+        .output()
+    else {
+        return writers;
+    };
+    let own_pid = std::process::id().to_string();
+    let (mut pid, mut command, mut access) = (String::new(), String::new(), ' ');
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some(field) = line.chars().next() else {
+            continue;
+        };
+        let value = &line[field.len_utf8()..];
+        match field {
+            'p' => pid = value.to_string(),
+            'c' => command = value.to_string(),
+            'a' => access = value.chars().next().unwrap_or(' '),
+            'n' if pid != own_pid && matches!(access, 'w' | 'u') => {
+                let path = PathBuf::from(value);
+                if path.starts_with(root) {
+                    writers.insert(path, format!("{} (pid {})", command, pid));
+                }
+            }
+            _ => {}
+        }
+    }
+    writers
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn list_open_writers(_root: &Path) -> HashMap<PathBuf, String> {
+    HashMap::new()
+}
+
+/// Probes `path` for a write lock held by another process (`fcntl(F_GETLK)`),
+/// as databases take while writing.
+#[cfg(unix)]
+fn write_lock_holder(path: &Path) -> Option<String> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = File::open(path).ok()?;
+    // SAFETY: flock is plain data and F_GETLK only fills it in
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = libc::F_RDLCK as _;
+    lock.l_whence = libc::SEEK_SET as _;
+    let result = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETLK, &mut lock) };
+    (result == 0 && lock.l_type == libc::F_WRLCK as libc::c_short)
+        .then(|| format!("a process holding a write lock (pid {})", lock.l_pid))
+}
+
+#[cfg(not(unix))]
+fn write_lock_holder(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Largest file FAT32 can store (4 GiB minus one byte).
+pub const FAT32_MAX_FILE_SIZE: u64 = u32::MAX as u64;
+
+/// Characters FAT and exFAT reject in file names (besides control characters).
+const FAT_ILLEGAL_CHARS: &[char] = &['"', '*', ':', '<', '>', '?', '\\', '|'];
+
+/// FAT-family filesystems, which restrict names and, for FAT32, file sizes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FatVariant {
+    Fat32,
+    ExFat,
+}
+
+impl FatVariant {
+    /// Recognizes a FAT filesystem from a `VolumeInfo::fs_type` string
+    /// ("msdos" on macOS, "vfat" on Linux).
+    pub fn from_fs_type(fs_type: &str) -> Option<Self> {
+        match fs_type.to_ascii_lowercase().as_str() {
+            "msdos" | "vfat" | "fat" | "fat16" | "fat32" => Some(Self::Fat32),
+            "exfat" => Some(Self::ExFat),
+            _ => None,
+        }
+    }
+}
+
+/// Returns why `file` can't be written to a FAT-family filesystem, or None if it can.
+pub fn fat_incompatibility(file: &FileInfo, variant: FatVariant) -> Option<String> {
+    if variant == FatVariant::Fat32 && !file.is_dir && file.size > FAT32_MAX_FILE_SIZE {
+        return Some("larger than the 4 GB FAT32 file size limit".to_string());
+    }
+
+    for component in file.path.components() {
+        let std::path::Component::Normal(name) = component else {
+            continue;
+        };
+        let name = name.to_string_lossy();
+        if let Some(c) = name
+            .chars()
+            .find(|c| FAT_ILLEGAL_CHARS.contains(c) || c.is_control())
+        {
+            return Some(format!(
+                "name contains a character FAT does not allow ({:?})",
+                c
+            ));
+        }
+        if name.ends_with(' ') || name.ends_with('.') {
+            return Some("name ends with a space or dot, which FAT strips".to_string());
+        }
+    }
+    None
+}
+
+/// Filters applied while scanning so previews match what a sync would copy.
+#[derive(Debug, Clone, Default)]
+pub struct ScanFilter {
+    /// Compiled exclusion patterns (same semantics as `SyncOptions::exclude_patterns`)
+    pub exclude_matcher: Option<ExcludeMatcher>,
+    /// Skip regular files smaller than this many bytes
+    pub min_size: Option<u64>,
+    /// Skip dotfiles and anything inside dot-directories
+    pub skip_hidden: bool,
+}
+
+impl ScanFilter {
+    pub fn from_patterns(
+        exclude_patterns: &[String],
+        min_size: Option<u64>,
+        skip_hidden: bool,
+    ) -> Self {
+        Self {
+            exclude_matcher: build_exclude_matcher(exclude_patterns),
+            min_size,
+            skip_hidden,
+        }
+    }
+
+    /// Returns true if the file passes all filters.
+    pub fn matches(&self, info: &FileInfo) -> bool {
+        if is_excluded(&info.path, self.exclude_matcher.as_ref()) {
+            return false;
+        }
+        if self.skip_hidden && is_hidden_path(&info.path) {
+            return false;
+        }
+        if let Some(min_size) = self.min_size {
+            if !info.is_dir && info.size < min_size {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 /// Streaming directory scan - returns an iterator over file chunks
 /// This allows processing files as they're discovered without loading all into memory
 pub struct DirectoryScanner {
     walker: walkdir::IntoIter,
     base_path: PathBuf,
     chunk_size: usize,
+    filter: ScanFilter,
+    scan_errors: Vec<String>,
+    follow_symlinks: bool,
+    skip_unreadable: bool,
+    skipped_unreadable: Vec<PathBuf>,
 }
 
 impl DirectoryScanner {
-    pub fn new(path: &Path, follow_symlinks: bool, chunk_size: usize) -> SyncResult<Self> {
+    pub fn new(
+        path: &Path,
+        follow_symlinks: bool,
+        chunk_size: usize,
+        filter: ScanFilter,
+    ) -> SyncResult<Self> {
         if !path.exists() {
             return Err(SyncError::SourceNotFound(path.display().to_string()));
         }
@@ -468,8 +2558,8 @@ impl DirectoryScanner {
             )));
         }
 
-        let base_path = path.to_path_buf();
-        let walker = walkdir::WalkDir::new(path)
+        let base_path = extended_length_path(path);
+        let walker = walkdir::WalkDir::new(&base_path)
             .follow_links(follow_symlinks)
             .into_iter();
 
@@ -477,26 +2567,100 @@ impl DirectoryScanner {
             walker,
             base_path,
             chunk_size,
+            filter,
+            scan_errors: Vec::new(),
+            follow_symlinks,
+            skip_unreadable: false,
+            skipped_unreadable: Vec::new(),
         })
     }
 
+    /// Visits each directory's entries in file-name order, so chunks come out
+    /// sorted by relative path. Call before the first `next_chunk`.
+    pub fn sorted_by_name(mut self) -> Self {
+        self.walker = walkdir::WalkDir::new(&self.base_path)
+            .follow_links(self.follow_symlinks)
+            .sort_by_file_name()
+            .into_iter();
+        self
+    }
+
+    /// Entries that could not be read so far. A scan with errors is incomplete,
+    /// which matters before deleting anything based on it.
+    pub fn scan_errors(&self) -> &[String] {
+        &self.scan_errors
+    }
+
+    /// Skips permission-denied entries instead of counting them as scan errors.
+    pub fn skipping_unreadable(mut self, skip: bool) -> Self {
+        self.skip_unreadable = skip;
+        self
+    }
+
+    /// Permission-denied entries skipped so far, relative to the scanned root.
+    /// Their destinations are off limits to orphan deletion.
+    pub fn skipped_unreadable(&self) -> &[PathBuf] {
+        &self.skipped_unreadable
+    }
+
+    /// Records `path` as a skipped unreadable entry, if it's under the root.
+    fn skip_unreadable_entry(&mut self, path: &Path) {
+        if let Ok(relative) = path.strip_prefix(&self.base_path) {
+            eprintln!("[Scan] Skipped unreadable entry: {}", relative.display());
+            self.skipped_unreadable.push(relative.to_path_buf());
+        }
+    }
+
     /// Get the next chunk of files
     pub fn next_chunk(&mut self) -> Option<Vec<FileInfo>> {
         let mut files = Vec::with_capacity(self.chunk_size);
         
         while let Some(entry_result) = self.walker.next() {
-            if let Ok(entry) = entry_result {
-                // Skip the root directory itself
-                if entry.path() == self.base_path {
+            let entry = match entry_result {
+                Ok(entry) => entry,
+                Err(e) => {
+                    let denied = e
+                        .io_error()
+                        .is_some_and(|io| io.kind() == std::io::ErrorKind::PermissionDenied);
+                    match e.path() {
+                        Some(path) if self.skip_unreadable && denied => {
+                            let path = path.to_path_buf();
+                            self.skip_unreadable_entry(&path);
+                        }
+                        _ => self.scan_errors.push(format!("Scan error: {}", e)),
+                    }
                     continue;
                 }
-                
-                if let Ok(info) = get_file_info(entry.path(), &self.base_path) {
+            };
+
+            // Skip the root directory itself
+            if entry.path() == self.base_path {
+                continue;
+            }
+
+            match get_file_info(entry.path(), &self.base_path) {
+                Ok(info) => {
+                    if !self.filter.matches(&info) {
+                        continue;
+                    }
                     files.push(info);
                     if files.len() >= self.chunk_size {
                         break;
                     }
                 }
+                Err(SyncError::Io(io))
+                    if self.skip_unreadable
+                        && io.kind() == std::io::ErrorKind::PermissionDenied =>
+                {
+                    self.skip_unreadable_entry(entry.path());
+                }
+                Err(e) => {
+                    self.scan_errors.push(format!(
+                        "Failed to get info for '{}': {}",
+                        entry.path().display(),
+                        e
+                    ));
+                }
             }
         }
 
@@ -508,12 +2672,264 @@ impl DirectoryScanner {
     }
 }
 
+impl Iterator for DirectoryScanner {
+    type Item = Vec<FileInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk()
+    }
+}
+
+/// How `compare_directories` decides whether two files with the same path match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompareMode {
+    Size,
+    /// Size and modification time, the same signals a sync's delta check uses
+    #[default]
+    SizeAndMtime,
+    /// Full content hash; only files of equal size are read
+    Hash,
+}
+
+/// Where a path stands when two directories are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffKind {
+    OnlyLeft,
+    OnlyRight,
+    Different,
+    Identical,
+}
+
+/// One path from a directory comparison, with both sides' metadata where present.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryDiffEntry {
+    pub path: PathBuf,
+    pub kind: DiffKind,
+    pub is_dir: bool,
+    pub left_size: Option<u64>,
+    pub right_size: Option<u64>,
+    pub left_modified: Option<DateTime<Utc>>,
+    pub right_modified: Option<DateTime<Utc>>,
+}
+
+impl DirectoryDiffEntry {
+    fn new(
+        path: PathBuf,
+        kind: DiffKind,
+        left: Option<&FileInfo>,
+        right: Option<&FileInfo>,
+    ) -> Self {
+        Self {
+            path,
+            kind,
+            is_dir: left.or(right).is_some_and(|f| f.is_dir),
+            left_size: left.map(|f| f.size),
+            right_size: right.map(|f| f.size),
+            left_modified: left.map(|f| f.modified),
+            right_modified: right.map(|f| f.modified),
+        }
+    }
+}
+
+/// A batch of comparison results streamed to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryDiffChunk {
+    pub compare_id: String,
+    pub entries: Vec<DirectoryDiffEntry>,
+    pub chunk_index: usize,
+    pub is_final: bool,
+}
+
+/// Totals for a finished directory comparison. Directories only count
+/// towards `only_left`/`only_right`; ones present on both sides are not listed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryDiffSummary {
+    pub only_left: usize,
+    pub only_right: usize,
+    pub different: usize,
+    pub identical: usize,
+    /// Entries that could not be read on either side
+    pub errors: Vec<String>,
+}
+
+impl DirectoryDiffSummary {
+    fn count(&mut self, kind: DiffKind) {
+        match kind {
+            DiffKind::OnlyLeft => self.only_left += 1,
+            DiffKind::OnlyRight => self.only_right += 1,
+            DiffKind::Different => self.different += 1,
+            DiffKind::Identical => self.identical += 1,
+        }
+    }
+}
+
+/// Compares two directory trees without changing either. The left tree is
+/// walked in chunks and each entry is looked up on the right; the right tree
+/// is then walked for entries missing on the left. Neither tree is held in
+/// memory, so `on_chunk` receives at most `chunk_size` entries at a time and
+/// can return false to stop early.
+pub fn compare_directories<F>(
+    left: &Path,
+    right: &Path,
+    mode: CompareMode,
+    chunk_size: usize,
+    mut on_chunk: F,
+) -> SyncResult<DirectoryDiffSummary>
+where
+    F: FnMut(Vec<DirectoryDiffEntry>) -> bool,
+{
+    let mut summary = DirectoryDiffSummary::default();
+
+    let mut left_scanner = DirectoryScanner::new(left, false, chunk_size, ScanFilter::default())?;
+    // Fail before reporting anything if the right side can't be walked either
+    let mut right_scanner = DirectoryScanner::new(right, false, chunk_size, ScanFilter::default())?;
+
+    for chunk in left_scanner.by_ref() {
+        let mut entries = Vec::with_capacity(chunk.len());
+        for left_info in chunk {
+            let right_path = right.join(&left_info.path);
+            let right_info = match fs::symlink_metadata(&right_path) {
+                Err(e) if e.kind() == ErrorKind::NotFound => None,
+                Err(e) => {
+                    summary
+                        .errors
+                        .push(format!("{}: {}", right_path.display(), e));
+                    continue;
+                }
+                Ok(_) => match get_file_info(&right_path, right) {
+                    Ok(info) => Some(info),
+                    Err(e) => {
+                        summary
+                            .errors
+                            .push(format!("{}: {}", right_path.display(), e));
+                        continue;
+                    }
+                },
+            };
+
+            let kind = match &right_info {
+                None => DiffKind::OnlyLeft,
+                // Directories on both sides only matter through their contents
+                Some(right_info) if left_info.is_dir && right_info.is_dir => continue,
+                Some(right_info) => {
+                    match entries_match(left, right, &left_info, right_info, mode) {
+                        Ok(true) => DiffKind::Identical,
+                        Ok(false) => DiffKind::Different,
+                        Err(e) => {
+                            summary
+                                .errors
+                                .push(format!("{}: {}", left_info.path.display(), e));
+                            continue;
+                        }
+                    }
+                }
+            };
+            summary.count(kind);
+            entries.push(DirectoryDiffEntry::new(
+                left_info.path.clone(),
+                kind,
+                Some(&left_info),
+                right_info.as_ref(),
+            ));
+        }
+        if !entries.is_empty() && !on_chunk(entries) {
+            return Ok(summary);
+        }
+    }
+    summary
+        .errors
+        .extend(left_scanner.scan_errors().iter().cloned());
+
+    for chunk in right_scanner.by_ref() {
+        let entries: Vec<DirectoryDiffEntry> = chunk
+            .iter()
+            .filter(|right_info| {
+                matches!(
+                    fs::symlink_metadata(left.join(&right_info.path)),
+                    Err(ref e) if e.kind() == ErrorKind::NotFound
+                )
+            })
+            .map(|right_info| {
+                summary.count(DiffKind::OnlyRight);
+                DirectoryDiffEntry::new(
+                    right_info.path.clone(),
+                    DiffKind::OnlyRight,
+                    None,
+                    Some(right_info),
+                )
+            })
+            .collect();
+        if !entries.is_empty() && !on_chunk(entries) {
+            return Ok(summary);
+        }
+    }
+    summary
+        .errors
+        .extend(right_scanner.scan_errors().iter().cloned());
+
+    Ok(summary)
+}
+
+/// Decides whether two entries at the same relative path match under `mode`.
+fn entries_match(
+    left_root: &Path,
+    right_root: &Path,
+    left: &FileInfo,
+    right: &FileInfo,
+    mode: CompareMode,
+) -> SyncResult<bool> {
+    if left.is_dir != right.is_dir
+        || left.is_symlink != right.is_symlink
+        || left.file_type != right.file_type
+    {
+        return Ok(false);
+    }
+    if left.is_symlink {
+        let left_target = fs::read_link(left_root.join(&left.path))?;
+        let right_target = fs::read_link(right_root.join(&right.path))?;
+        return Ok(left_target == right_target);
+    }
+    if left.size != right.size {
+        return Ok(false);
+    }
+
+    match mode {
+        CompareMode::Size => Ok(true),
+        CompareMode::SizeAndMtime => Ok(left.modified == right.modified),
+        CompareMode::Hash => Ok(compute_file_hash(&left_root.join(&left.path))?
+            == compute_file_hash(&right_root.join(&right.path))?),
+    }
+}
+
 /// Quick scan that only returns summary (file count, total size) without file list
 /// This is extremely fast even for multi-TB directories
 pub fn quick_scan_directory(path: &Path) -> SyncResult<DirectorySummary> {
     quick_scan_directory_with_options(path, false, None)
 }
 
+/// Total size of the files under `dest` at the same relative paths as the
+/// files under `source`: what syncing all of `source` would overwrite.
+/// Walks the source without keeping the file list.
+pub fn counterpart_bytes(source: &Path, dest: &Path, follow_symlinks: bool) -> u64 {
+    let walk_root = extended_length_path(source);
+    walkdir::WalkDir::new(&walk_root)
+        .follow_links(follow_symlinks)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| !entry.file_type().is_dir())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(&walk_root).ok()?;
+            let metadata = fs::symlink_metadata(extended_length_path(&dest.join(relative))).ok()?;
+            metadata.is_file().then_some(metadata.len())
+        })
+        .sum()
+}
+
 pub fn quick_scan_directory_with_options(
     path: &Path, 
     follow_symlinks: bool,
@@ -533,14 +2949,16 @@ pub fn quick_scan_directory_with_options(
     let mut total_size: u64 = 0;
     let mut file_count: usize = 0;
     let mut dir_count: usize = 0;
+    let mut size_histogram = SizeHistogram::default();
 
-    for entry in walkdir::WalkDir::new(path)
+    let walk_root = extended_length_path(path);
+    for entry in walkdir::WalkDir::new(&walk_root)
         .follow_links(follow_symlinks)
         .into_iter()
         .filter_map(|e| e.ok())
     {
         let entry_path = entry.path();
-        if entry_path == path {
+        if entry_path == walk_root {
             continue;
         }
 
@@ -550,6 +2968,7 @@ pub fn quick_scan_directory_with_options(
             } else {
                 file_count += 1;
                 total_size += metadata.len();
+                size_histogram.record(metadata.len());
             }
         }
     }
@@ -560,50 +2979,438 @@ pub fn quick_scan_directory_with_options(
         file_count,
         dir_count,
         scan_id: scan_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        small_file_heavy: size_histogram.is_small_file_heavy(),
+        size_histogram,
     })
 }
 
+/// Returns true if the tree under `path` has more than `limit` entries.
+/// Stops walking as soon as the limit is passed and never stats entries, so
+/// it stays cheap even on trees far larger than the limit.
+pub fn exceeds_entry_count(path: &Path, follow_symlinks: bool, limit: usize) -> bool {
+    walkdir::WalkDir::new(extended_length_path(path))
+        .min_depth(1)
+        .follow_links(follow_symlinks)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .nth(limit)
+        .is_some()
+}
+
 pub fn detect_delta(source: &FileInfo, dest_path: &Path) -> SyncResult<DeltaStatus> {
     let info = detect_delta_detailed(source, dest_path)?;
     Ok(info.status)
 }
 
-/// Detect delta with detailed timestamp comparison info
-pub fn detect_delta_detailed(source: &FileInfo, dest_path: &Path) -> SyncResult<DeltaInfo> {
-    let dest_file = dest_path.join(&source.path);
+/// Detect delta with detailed timestamp comparison info
+pub fn detect_delta_detailed(source: &FileInfo, dest_path: &Path) -> SyncResult<DeltaInfo> {
+    detect_delta_within(source, dest_path, chrono::Duration::zero())
+}
+
+/// Like `detect_delta_detailed`, but mtimes up to `modify_window` apart count
+/// as equal, like rsync's `--modify-window`, for destinations whose clock or
+/// mtime resolution differs from the source's.
+pub fn detect_delta_within(
+    source: &FileInfo,
+    dest_path: &Path,
+    modify_window: chrono::Duration,
+) -> SyncResult<DeltaInfo> {
+    let dest_file = dest_path.join(&source.path);
+
+    if !dest_file.exists() {
+        return Ok(DeltaInfo {
+            status: DeltaStatus::New,
+            source_newer: true,
+            source_older: false,
+            size_differs: false,
+            source_larger: true,
+            xattrs_differ: false,
+        });
+    }
+
+    let dest_metadata = fs::metadata(&dest_file)?;
+    let dest_modified = metadata_to_datetime(&dest_metadata)?;
+    let dest_size = dest_metadata.len();
+
+    // Saturates at the ends of the representable range instead of panicking
+    let source_newer = dest_modified
+        .checked_add_signed(modify_window)
+        .is_some_and(|latest| source.modified > latest);
+    let source_older = dest_modified
+        .checked_sub_signed(modify_window)
+        .is_some_and(|earliest| source.modified < earliest);
+    let size_differs = source.size != dest_size;
+    let source_larger = source.size > dest_size;
+
+    if size_differs || source_newer {
+        return Ok(DeltaInfo {
+            status: DeltaStatus::Modified,
+            source_newer,
+            source_older,
+            size_differs,
+            source_larger,
+            xattrs_differ: false,
+        });
+    }
+
+    Ok(DeltaInfo {
+        status: DeltaStatus::Unchanged,
+        source_newer,
+        source_older,
+        size_differs,
+        source_larger,
+        xattrs_differ: false,
+    })
+}
+
+/// Like `detect_delta_detailed`, but a file whose size and mtime match is
+/// still `Modified` if its extended attributes, Finder tags among them, differ
+/// from the destination's. The xattrs are only read for files that otherwise
+/// look unchanged.
+pub fn detect_delta_with_xattrs(
+    source: &FileInfo,
+    source_file: &Path,
+    dest_path: &Path,
+    modify_window: chrono::Duration,
+) -> SyncResult<DeltaInfo> {
+    let mut delta = detect_delta_within(source, dest_path, modify_window)?;
+    if delta.status == DeltaStatus::Unchanged {
+        let source_xattrs = read_xattrs(source_file);
+        let dest_xattrs = read_xattrs(&dest_path.join(&source.path));
+        // Unreadable xattrs (e.g. a filesystem without them) never force a copy
+        if let (Ok(source_xattrs), Ok(dest_xattrs)) = (source_xattrs, dest_xattrs) {
+            if source_xattrs != dest_xattrs {
+                delta.status = DeltaStatus::Modified;
+                delta.xattrs_differ = true;
+            }
+        }
+    }
+    Ok(delta)
+}
+
+/// Copies the source's creation date (`st_birthtime`) onto `dest` using
+/// `setattrlist(ATTR_CMN_CRTIME)`. Finder shows this as "Date Created" and
+/// photo libraries sort by it. Best effort, like the mtime copy.
+#[cfg(target_os = "macos")]
+fn preserve_creation_time(dest: &Path, src_metadata: &fs::Metadata) {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Some(since_epoch) = src_metadata
+        .created()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+    else {
+        return;
+    };
+    let Ok(c_path) = CString::new(dest.as_os_str().as_bytes()) else {
+        return;
+    };
+
+    let mut attrs: libc::attrlist = unsafe { std::mem::zeroed() };
+    attrs.bitmapcount = libc::ATTR_BIT_MAP_COUNT;
+    attrs.commonattr = libc::ATTR_CMN_CRTIME;
+
+    let mut crtime = libc::timespec {
+        tv_sec: since_epoch.as_secs() as libc::time_t,
+        tv_nsec: since_epoch.subsec_nanos() as libc::c_long,
+    };
+
+    let result = unsafe {
+        libc::setattrlist(
+            c_path.as_ptr(),
+            &mut attrs as *mut libc::attrlist as *mut libc::c_void,
+            &mut crtime as *mut libc::timespec as *mut libc::c_void,
+            std::mem::size_of::<libc::timespec>(),
+            0,
+        )
+    };
+    if result != 0 {
+        log::warn!(
+            "Failed to preserve creation time on {:?}: {}",
+            dest,
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// Creation time can't be set on this platform; no-op.
+#[cfg(not(target_os = "macos"))]
+fn preserve_creation_time(_dest: &Path, _src_metadata: &fs::Metadata) {}
+
+/// Copies the source's access ACL onto `dest`.
+///
+/// Linux stores POSIX ACLs as `system.posix_acl_*` extended attributes, so
+/// they're copied as xattrs (this avoids a libacl dependency). Fails with
+/// `ENOTSUP` when the destination filesystem has no ACL support.
+#[cfg(target_os = "linux")]
+fn copy_acl(source: &Path, dest: &Path) -> std::io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    const ACL_XATTRS: [&str; 2] = ["system.posix_acl_access", "system.posix_acl_default"];
+
+    let to_cstring = |path: &Path| {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))
+    };
+    let c_source = to_cstring(source)?;
+    let c_dest = to_cstring(dest)?;
+
+    for name in ACL_XATTRS {
+        let c_name = CString::new(name).expect("static xattr name");
+
+        let size =
+            unsafe { libc::getxattr(c_source.as_ptr(), c_name.as_ptr(), std::ptr::null_mut(), 0) };
+        if size < 0 {
+            let error = std::io::Error::last_os_error();
+            // No ACL of this kind (or none supported on the source): nothing to copy
+            if matches!(
+                error.raw_os_error(),
+                Some(libc::ENODATA) | Some(libc::ENOTSUP)
+            ) {
+                continue;
+            }
+            return Err(error);
+        }
+
+        let mut value = vec![0u8; size as usize];
+        let size = unsafe {
+            libc::getxattr(
+                c_source.as_ptr(),
+                c_name.as_ptr(),
+                value.as_mut_ptr() as *mut libc::c_void,
+                value.len(),
+            )
+        };
+        if size < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let result = unsafe {
+            libc::setxattr(
+                c_dest.as_ptr(),
+                c_name.as_ptr(),
+                value.as_ptr() as *const libc::c_void,
+                size as usize,
+                0,
+            )
+        };
+        if result != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Copies the source's extended ACL onto `dest` via `acl_get_file`/`acl_set_file`.
+#[cfg(target_os = "macos")]
+fn copy_acl(source: &Path, dest: &Path) -> std::io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    /// `ACL_TYPE_EXTENDED` from <sys/acl.h>; macOS only supports extended ACLs
+    const ACL_TYPE_EXTENDED: libc::c_int = 0x0000_0100;
+
+    extern "C" {
+        fn acl_get_file(path_p: *const libc::c_char, acl_type: libc::c_int) -> *mut libc::c_void;
+        fn acl_set_file(
+            path_p: *const libc::c_char,
+            acl_type: libc::c_int,
+            acl: *mut libc::c_void,
+        ) -> libc::c_int;
+        fn acl_free(obj_p: *mut libc::c_void) -> libc::c_int;
+    }
+
+    let to_cstring = |path: &Path| {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))
+    };
+    let c_source = to_cstring(source)?;
+    let c_dest = to_cstring(dest)?;
+
+    let acl = unsafe { acl_get_file(c_source.as_ptr(), ACL_TYPE_EXTENDED) };
+    if acl.is_null() {
+        let error = std::io::Error::last_os_error();
+        // ENOENT here means the file simply has no ACL
+        if error.raw_os_error() == Some(libc::ENOENT) {
+            return Ok(());
+        }
+        return Err(error);
+    }
+
+    let result = unsafe { acl_set_file(c_dest.as_ptr(), ACL_TYPE_EXTENDED, acl) };
+    let error = std::io::Error::last_os_error();
+    unsafe { acl_free(acl) };
+
+    if result != 0 {
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// ACLs aren't supported on this platform; no-op.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn copy_acl(_source: &Path, _dest: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Sets a copied file's mtime according to `options.timestamp_mode`.
+fn apply_timestamps(
+    dest: &Path,
+    src_metadata: &fs::Metadata,
+    options: &CopyOptions,
+) -> SyncResult<()> {
+    match options.timestamp_mode {
+        TimestampMode::PreserveSource => {
+            if options.preserve_metadata {
+                let _ = filetime::set_file_mtime(
+                    dest,
+                    filetime::FileTime::from_system_time(src_metadata.modified()?),
+                );
+                // After mtime: macOS pulls the creation date back if mtime is set earlier
+                preserve_creation_time(dest, src_metadata);
+            }
+        }
+        // Set explicitly: a resumed copy may not have written since it was reopened
+        TimestampMode::CurrentTime | TimestampMode::PreserveSourceButTouchOnChange => {
+            let _ = filetime::set_file_mtime(dest, filetime::FileTime::now());
+        }
+    }
+    Ok(())
+}
+
+/// Where `copy_file_with_progress` gets the source's blocks from.
+enum BlockReader {
+    Inline {
+        reader: BufReader<File>,
+        buffer: Vec<u8>,
+    },
+    /// Blocks filled by a reader thread. An empty block marks the end of the
+    /// file; the channel closing without one means the thread died.
+    ReadAhead {
+        filled: Receiver<std::io::Result<Vec<u8>>>,
+        empty: Sender<Vec<u8>>,
+        current: Vec<u8>,
+    },
+}
+
+impl BlockReader {
+    fn new(file: File, options: &CopyOptions) -> std::io::Result<Self> {
+        if !options.pipelined_copy {
+            return Ok(Self::Inline {
+                reader: BufReader::with_capacity(options.buffer_size, file),
+                buffer: vec![0u8; options.buffer_size],
+            });
+        }
+
+        let (filled_tx, filled) = mpsc::sync_channel(READ_AHEAD_BUFFERS);
+        let (empty, empty_rx) = mpsc::channel::<Vec<u8>>();
+        for _ in 0..READ_AHEAD_BUFFERS {
+            let _ = empty.send(vec![0u8; options.buffer_size]);
+        }
+        let buffer_size = options.buffer_size;
+        let mut file = file;
+        // Exits once the file is read or the writer hangs up, whichever is first
+        std::thread::Builder::new()
+            .name("copy-read-ahead".into())
+            .spawn(move || {
+                while let Ok(mut block) = empty_rx.recv() {
+                    block.resize(buffer_size, 0);
+                    let read = match file.read(&mut block) {
+                        Ok(read) => read,
+                        Err(e) => {
+                            let _ = filled_tx.send(Err(e));
+                            return;
+                        }
+                    };
+                    block.truncate(read);
+                    if filled_tx.send(Ok(block)).is_err() || read == 0 {
+                        return;
+                    }
+                }
+            })?;
+
+        Ok(Self::ReadAhead {
+            filled,
+            empty,
+            current: Vec::new(),
+        })
+    }
+
+    /// The next block of the source, empty at the end of the file.
+    fn next_block(&mut self) -> std::io::Result<&[u8]> {
+        match self {
+            Self::Inline { reader, buffer } => {
+                let read = reader.read(buffer)?;
+                Ok(&buffer[..read])
+            }
+            Self::ReadAhead {
+                filled,
+                empty,
+                current,
+            } => {
+                let written = std::mem::take(current);
+                if written.capacity() > 0 {
+                    let _ = empty.send(written);
+                }
+                *current = filled.recv().map_err(|_| {
+                    std::io::Error::other("read-ahead thread stopped before the end of the file")
+                })??;
+                Ok(current)
+            }
+        }
+    }
+}
+
+/// Holds a copy to a bandwidth limit, sleeping once a window's share of the
+/// limit has gone through.
+pub struct Throttle {
+    window_start: Instant,
+    window_bytes: u64,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self {
+            window_start: Instant::now(),
+            window_bytes: 0,
+        }
+    }
+}
+
+impl Throttle {
+    /// Counts `bytes` just written. The limit is passed every time so a
+    /// change made mid-copy takes effect right away.
+    pub fn pace(&mut self, bytes: u64, bandwidth_limit: u64) {
+        if bandwidth_limit == BANDWIDTH_UNLIMITED {
+            return;
+        }
+        let throttle_window = Duration::from_millis(THROTTLE_WINDOW_MS);
+        // Calculate bytes allowed per throttle window
+        let bytes_per_window =
+            (bandwidth_limit as f64 * (THROTTLE_WINDOW_MS as f64 / 1000.0)) as u64;
+        self.window_bytes += bytes;
 
-    if !dest_file.exists() {
-        return Ok(DeltaInfo {
-            status: DeltaStatus::New,
-            source_newer: true,
-            source_older: false,
-            size_differs: false,
-        });
-    }
+        // Check if we've exceeded the rate limit for this window
+        if self.window_bytes >= bytes_per_window {
+            let elapsed = self.window_start.elapsed();
 
-    let dest_metadata = fs::metadata(&dest_file)?;
-    let dest_modified = metadata_to_datetime(&dest_metadata)?;
-    let dest_size = dest_metadata.len();
+            if elapsed < throttle_window {
+                // Calculate how long to sleep to maintain the target rate
+                let sleep_duration = throttle_window.saturating_sub(elapsed);
 
-    let source_newer = source.modified > dest_modified;
-    let source_older = source.modified < dest_modified;
-    let size_differs = source.size != dest_size;
+                // Only sleep if it's worth it (avoid micro-sleeps)
+                if sleep_duration.as_micros() >= MIN_SLEEP_MICROS as u128 {
+                    std::thread::sleep(sleep_duration);
+                }
+            }
 
-    if size_differs || source_newer {
-        return Ok(DeltaInfo {
-            status: DeltaStatus::Modified,
-            source_newer,
-            source_older,
-            size_differs,
-        });
+            // Reset the window
+            self.window_start = Instant::now();
+            self.window_bytes = 0;
+        }
     }
-
-    Ok(DeltaInfo {
-        status: DeltaStatus::Unchanged,
-        source_newer,
-        source_older,
-        size_differs,
-    })
 }
 
 pub fn copy_file_with_progress<F>(
@@ -615,7 +3422,10 @@ pub fn copy_file_with_progress<F>(
 where
     F: Fn(u64, Option<u64>) -> bool,
 {
-    let src_file = File::open(source)?;
+    let source = &extended_length_path(source);
+    let dest = &extended_length_path(dest);
+
+    let mut src_file = File::open(source)?;
     let src_metadata = src_file.metadata()?;
 
     if let Some(parent) = dest.parent() {
@@ -632,64 +3442,60 @@ where
         File::create(dest)?
     };
 
-    let mut reader = BufReader::with_capacity(options.buffer_size, src_file);
     let mut writer = BufWriter::with_capacity(options.buffer_size, &mut dest_file);
 
     if options.resume_offset > 0 {
-        reader.seek(SeekFrom::Start(options.resume_offset))?;
+        src_file.seek(SeekFrom::Start(options.resume_offset))?;
         writer.seek(SeekFrom::Start(options.resume_offset))?;
     }
 
-    let mut buffer = vec![0u8; options.buffer_size];
+    let mut blocks = BlockReader::new(src_file, options)?;
     let mut bytes_copied = options.resume_offset;
-
-    // Bandwidth throttling state
-    let throttle_enabled = options.bandwidth_limit > BANDWIDTH_UNLIMITED;
-    let mut window_start = Instant::now();
-    let mut window_bytes: u64 = 0;
-    let throttle_window = Duration::from_millis(THROTTLE_WINDOW_MS);
-    
-    // Calculate bytes allowed per throttle window
-    let bytes_per_window = if throttle_enabled {
-        (options.bandwidth_limit as f64 * (THROTTLE_WINDOW_MS as f64 / 1000.0)) as u64
-    } else {
-        0 // Not used when throttling is disabled
+    // A resume starts from an offset that was already durable
+    let mut durable = options.resume_offset;
+    let report_durable = |offset: u64| {
+        if let Some(durable_offset) = &options.durable_offset {
+            durable_offset.store(offset, Ordering::SeqCst);
+        }
     };
+    report_durable(durable);
+    let mut written_hasher =
+        (options.verify_integrity && options.verify_during_copy && options.resume_offset == 0)
+            .then(xxhash_rust::xxh3::Xxh3::new);
+
+    let mut throttle = Throttle::default();
 
     loop {
-        let bytes_read = reader.read(&mut buffer)?;
-        if bytes_read == 0 {
+        let block = blocks.next_block()?;
+        if block.is_empty() {
             break;
         }
+        let bytes_read = block.len();
 
-        writer.write_all(&buffer[..bytes_read])?;
+        writer.write_all(block)?;
         bytes_copied += bytes_read as u64;
+        if let Some(hasher) = written_hasher.as_mut() {
+            hasher.update(block);
+        }
         
-        // Apply bandwidth throttling if enabled
-        if throttle_enabled {
-            window_bytes += bytes_read as u64;
-            
-            // Check if we've exceeded the rate limit for this window
-            if window_bytes >= bytes_per_window {
-                let elapsed = window_start.elapsed();
-                
-                if elapsed < throttle_window {
-                    // Calculate how long to sleep to maintain the target rate
-                    let sleep_duration = throttle_window.saturating_sub(elapsed);
-                    
-                    // Only sleep if it's worth it (avoid micro-sleeps)
-                    if sleep_duration.as_micros() >= MIN_SLEEP_MICROS as u128 {
-                        std::thread::sleep(sleep_duration);
-                    }
-                }
-                
-                // Reset the window
-                window_start = Instant::now();
-                window_bytes = 0;
-            }
+        // The limit is read every block so a change made while this file is
+        // copying takes effect right away
+        throttle.pace(bytes_read as u64, options.current_bandwidth_limit());
+
+        let flush_now = options
+            .flush_requested
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::SeqCst));
+        let durable_due =
+            options.durable_offset.is_some() && bytes_copied - durable >= DURABLE_SYNC_INTERVAL;
+        if flush_now || durable_due {
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+            durable = bytes_copied;
+            report_durable(durable);
         }
 
-        let should_continue = progress_callback(bytes_copied, Some(compute_hash(&buffer[..bytes_read])));
+        let should_continue = progress_callback(bytes_copied, Some(compute_hash(block)));
         if !should_continue {
             return Err(SyncError::TransferCancelled("Transfer cancelled by user".into()));
         }
@@ -698,15 +3504,33 @@ where
     writer.flush()?;
     drop(writer);
 
+    // A resumed file may run on past the source's end
+    if options.resume_offset > 0 {
+        dest_file.set_len(bytes_copied)?;
+    }
     dest_file.sync_all()?;
+    report_durable(bytes_copied);
 
     if options.preserve_metadata {
         let permissions = src_metadata.permissions();
         let _ = fs::set_permissions(dest, permissions);
-        let _ = filetime::set_file_mtime(
-            dest,
-            filetime::FileTime::from_system_time(src_metadata.modified()?),
-        );
+    }
+    apply_timestamps(dest, &src_metadata, options)?;
+
+    // After set_permissions, which would otherwise rewrite the ACL mask
+    if options.preserve_acl {
+        if let Err(e) = copy_acl(source, dest) {
+            log::warn!("Failed to preserve ACL on {:?}: {}", dest, e);
+        }
+    }
+    if options.preserve_xattr {
+        if let Err(e) = copy_xattrs(source, dest) {
+            log::warn!(
+                "Failed to preserve extended attributes on {:?}: {}",
+                dest,
+                e
+            );
+        }
     }
 
     if options.verify_integrity {
@@ -723,6 +3547,10 @@ where
             }
         }
 
+        let _hash_permit = options
+            .hash_limiter
+            .as_ref()
+            .map(|limiter| limiter.acquire());
         // END-TO-END VERIFICATION: Use pre-computed source hash if available
         // This prevents the race condition where source changes after copy but before hash
         let src_hash = match options.pre_copy_source_hash {
@@ -733,7 +3561,10 @@ where
             }
         };
         
-        let dest_hash = compute_file_hash(dest)?;
+        let dest_hash = match written_hasher {
+            Some(hasher) => hasher.digest(),
+            None => compute_file_hash(dest)?,
+        };
         if src_hash != dest_hash {
             return Err(SyncError::HashMismatch(dest.display().to_string()));
         }
@@ -749,6 +3580,9 @@ where
 /// 2. Does not exist at all
 /// 
 /// No partial/corrupt files will be left behind.
+///
+/// A resume (`resume_offset > 0`) carries on in the temp file the
+/// interrupted copy left, which has to still be there.
 /// 
 /// # Arguments
 /// * `source` - Source file path
@@ -767,10 +3601,9 @@ pub fn copy_file_atomic<F>(
 where
     F: Fn(u64, Option<u64>) -> bool,
 {
-    // For resume operations, we can't use atomic copy (need to append to existing file)
-    if options.resume_offset > 0 {
-        return copy_file_with_progress(source, dest, options, progress_callback);
-    }
+    // The temp path is derived from dest, so both stay usable past MAX_PATH
+    let source = &extended_length_path(source);
+    let dest = &extended_length_path(dest);
     
     // Pre-check: verify we have enough disk space
     let src_metadata = fs::metadata(source)
@@ -793,9 +3626,24 @@ where
     
     // Use a temp file in the same directory (for atomic rename)
     let temp_path = get_temp_path(dest);
-    
-    // Clean up any leftover temp files from previous failed attempts
-    cleanup_temp_files(dest);
+    let resuming = options.resume_offset > 0;
+
+    if resuming {
+        // Seeking past the end of a missing or truncated temp file would
+        // leave a hole in the copy
+        let staged_len = fs::metadata(&temp_path)
+            .map_err(|e| classify_io_error(e, &temp_path))?
+            .len();
+        if staged_len < options.resume_offset {
+            return Err(SyncError::Io(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("{} is shorter than the resume offset", temp_path.display()),
+            )));
+        }
+    } else {
+        // Clean up any leftover temp files from previous failed attempts
+        cleanup_temp_files(dest);
+    }
     
     // Create parent directory if needed
     if let Some(parent) = dest.parent() {
@@ -803,6 +3651,19 @@ where
             .map_err(|e| classify_io_error(e, parent))?;
     }
     
+    // Tagged before the copy opens it; truncating keeps the attribute
+    if options.exclude_temp_from_indexing && !resuming {
+        let tagged =
+            File::create(&temp_path).and_then(|_| crate::spotlight::mark_excluded(&temp_path));
+        if let Err(e) = tagged {
+            log::warn!(
+                "Failed to exclude temp file {:?} from indexing: {}",
+                temp_path,
+                e
+            );
+        }
+    }
+
     // Copy to temp file
     let result = copy_file_with_progress(source, &temp_path, options, progress_callback);
     
@@ -810,14 +3671,45 @@ where
         Ok(bytes_copied) => {
             // Atomic rename: temp -> final destination
             // This is atomic on POSIX systems when on the same filesystem
-            match fs::rename(&temp_path, dest) {
+            let mut renamed_dest = dest.to_path_buf();
+            let rename_result = match fs::rename(&temp_path, dest) {
+                Err(e) if options.force_overwrite_locked && is_locked_error(&e) => {
+                    eprintln!(
+                        "[Copy] Destination locked, retrying after moving it aside: {}",
+                        dest.display()
+                    );
+                    replace_locked_destination(&temp_path, dest)
+                }
+                Err(e) if is_cross_device_error(&e) => {
+                    eprintln!(
+                        "[Copy] Destination is on another filesystem, re-staging temp file: {}",
+                        dest.display()
+                    );
+                    replace_across_devices(&temp_path, dest)
+                        .map(|real_dest| renamed_dest = real_dest)
+                }
+                other => other,
+            };
+
+            match rename_result {
                 Ok(_) => {
                     // Sync parent directory to ensure rename is durable on disk.
                     // We log but don't fail on sync errors - the file is already renamed,
                     // just not guaranteed durable on immediate power loss.
-                    if let Err(e) = sync_parent_directory(dest) {
+                    // After a cross-device fallback this is the real destination's
+                    // directory, i.e. the filesystem the rename actually happened on.
+                    if let Err(e) = sync_parent_directory(&renamed_dest) {
                         log::warn!("Parent directory sync failed after rename: {:?}", e);
                     }
+                    if options.exclude_temp_from_indexing {
+                        if let Err(e) = crate::spotlight::clear_excluded(&renamed_dest) {
+                            log::warn!(
+                                "Failed to clear indexing exclusion on {:?}: {}",
+                                renamed_dest,
+                                e
+                            );
+                        }
+                    }
                     Ok(bytes_copied)
                 }
                 Err(e) => {
@@ -840,9 +3732,142 @@ where
     }
 }
 
+/// Bytes at the end of a partial file compared with the source before
+/// `resume_from_partial` continues from it: 1 MiB.
+const PARTIAL_OVERLAP_BLOCK: u64 = 1024 * 1024;
+
+/// What `resume_from_partial` did.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialResume {
+    /// The leftover file the copy was finished in
+    pub partial_path: PathBuf,
+    /// Offset the copy continued from; 0 when the partial file didn't match
+    /// the source and was copied over from the start
+    pub resumed_from: u64,
+    pub bytes_copied: u64,
+}
+
+/// Finishes copying `source` to `dest` from the `.rsync-partial` file, or the
+/// `.rsync-tmp` file a crash mid-copy leaves, next to `dest`, without needing
+/// the transfer's saved state. The partial file's length is the resume offset
+/// once its last block matches the source; a partial file longer than the
+/// source or with a different last block is copied over from the start.
+/// The finished file is hashed against the whole source before it replaces
+/// `dest`, so a prefix that differs further back is caught too.
+pub fn resume_from_partial(
+    source: &Path,
+    dest: &Path,
+    options: &CopyOptions,
+) -> SyncResult<PartialResume> {
+    let source = &extended_length_path(source);
+    let dest = &extended_length_path(dest);
+
+    let partial_path = [get_partial_path(dest), get_temp_path(dest)]
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            std::io::Error::new(
+                ErrorKind::NotFound,
+                format!("No partial file to resume for {}", dest.display()),
+            )
+        })?;
+
+    let source_size = fs::metadata(source)
+        .map_err(|e| classify_io_error(e, source))?
+        .len();
+    let partial_size = fs::metadata(&partial_path)?.len();
+    let resumed_from = if partial_size <= source_size
+        && partial_tail_matches(source, &partial_path, partial_size)?
+    {
+        partial_size
+    } else {
+        eprintln!(
+            "[Resume] {} doesn't match the source, copying it again from the start",
+            partial_path.display()
+        );
+        0
+    };
+
+    let copy_options = CopyOptions {
+        resume_offset: resumed_from,
+        // Only a full comparison proves the kept prefix is the source's
+        verify_integrity: true,
+        pre_copy_source_hash: None,
+        source_mtime_before_copy: None,
+        ..options.clone()
+    };
+    let copied = match copy_file_with_progress(source, &partial_path, &copy_options, |_, _| true) {
+        Ok(copied) => copied,
+        Err(e) => {
+            // A partial file that failed verification can't be resumed from again
+            if matches!(e, SyncError::HashMismatch(_)) {
+                let _ = fs::remove_file(&partial_path);
+            }
+            return Err(e);
+        }
+    };
+
+    fs::rename(&partial_path, dest).map_err(|e| classify_io_error(e, dest))?;
+    if let Err(e) = sync_parent_directory(dest) {
+        log::warn!("Parent directory sync failed after rename: {:?}", e);
+    }
+
+    Ok(PartialResume {
+        partial_path,
+        resumed_from,
+        bytes_copied: copied - resumed_from,
+    })
+}
+
+/// True if the last `PARTIAL_OVERLAP_BLOCK` bytes of `partial` (of `len`
+/// bytes) are the same as the source's bytes at the same offset.
+fn partial_tail_matches(source: &Path, partial: &Path, len: u64) -> SyncResult<bool> {
+    let overlap = len.min(PARTIAL_OVERLAP_BLOCK);
+    let mut source_block = vec![0u8; overlap as usize];
+    let mut partial_block = vec![0u8; overlap as usize];
+    for (path, block) in [(source, &mut source_block), (partial, &mut partial_block)] {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(len - overlap))?;
+        file.read_exact(block)?;
+    }
+    Ok(source_block == partial_block)
+}
+
+/// Directory under the destination root that holds, per transfer, copies of
+/// the files a sync overwrote, so `rollback_transfer` can put them back.
+pub const ROLLBACK_DIR: &str = ".rsync-rollback";
+
+/// Whether a path relative to a destination root is inside the directory
+/// the engine keeps there, which never holds user files.
+pub fn is_engine_dir(relative: &Path) -> bool {
+    relative.starts_with(ROLLBACK_DIR)
+}
+
+/// Where the backup of `relative` is kept for transfer `transfer_id`.
+pub fn rollback_backup_path(dest_root: &Path, transfer_id: &str, relative: &Path) -> PathBuf {
+    dest_root
+        .join(ROLLBACK_DIR)
+        .join(transfer_id)
+        .join(relative)
+}
+
+/// Copies a destination file about to be overwritten to `backup`. It is a
+/// copy rather than a hard link because a resumed copy writes into the
+/// destination in place; on APFS `fs::copy` clones, so it costs no space.
+pub fn keep_rollback_backup(dest: &Path, backup: &Path) -> SyncResult<()> {
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(dest, backup)?;
+    Ok(())
+}
+
 /// Check and clean up any partial files from previous failed transfers.
 /// Call this before starting a new sync to ensure clean state.
-pub fn cleanup_partial_files(directory: &Path) -> SyncResult<usize> {
+/// Files listed in `keep` are left in place: they belong to unfinished
+/// transfers that can still resume from them.
+pub fn cleanup_partial_files(directory: &Path, keep: &HashSet<PathBuf>) -> SyncResult<usize> {
     let mut cleaned = 0;
     
     if !directory.exists() {
@@ -855,10 +3880,10 @@ pub fn cleanup_partial_files(directory: &Path) -> SyncResult<usize> {
         
         if path.is_dir() {
             // Recursively clean subdirectories
-            cleaned += cleanup_partial_files(&path)?;
+            cleaned += cleanup_partial_files(&path, keep)?;
         } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
             // Remove temp and partial files
-            if name.ends_with(TEMP_FILE_EXTENSION) || name.ends_with(PARTIAL_FILE_EXTENSION) {
+            if is_partial_file_name(name) && !keep.contains(&path) {
                 if fs::remove_file(&path).is_ok() {
                     cleaned += 1;
                 }
@@ -869,17 +3894,158 @@ pub fn cleanup_partial_files(directory: &Path) -> SyncResult<usize> {
     Ok(cleaned)
 }
 
-pub fn generate_conflict_name(path: &Path) -> PathBuf {
+/// A leftover temp or partial file found during a maintenance scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialFileInfo {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+    pub age_secs: u64,
+    /// Belongs to an unfinished transfer that can still resume from it, so
+    /// `clean_partial_files` leaves it in place
+    pub resumable: bool,
+}
+
+/// Totals from removing leftover temp and partial files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialCleanupReport {
+    pub files_removed: usize,
+    pub bytes_reclaimed: u64,
+    /// Resumable files left in place
+    pub files_kept: usize,
+    pub errors: Vec<String>,
+}
+
+/// Returns true if the file name marks a temp or partial file left by a sync.
+pub fn is_partial_file_name(name: &str) -> bool {
+    name.ends_with(TEMP_FILE_EXTENSION) || name.ends_with(PARTIAL_FILE_EXTENSION)
+}
+
+/// Lists all `.rsync-tmp` / `.rsync-partial` files under a tree without touching them.
+/// Files listed in `resumable` are flagged as such.
+pub fn find_partial_files(
+    root: &Path,
+    resumable: &HashSet<PathBuf>,
+) -> SyncResult<Vec<PartialFileInfo>> {
+    if !root.exists() {
+        return Err(SyncError::SourceNotFound(root.display().to_string()));
+    }
+
+    let now = std::time::SystemTime::now();
+    let mut found = Vec::new();
+
+    for entry in walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let is_partial = entry
+            .file_name()
+            .to_str()
+            .map(is_partial_file_name)
+            .unwrap_or(false);
+        if !is_partial {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        let age_secs = metadata
+            .modified()
+            .ok()
+            .and_then(|m| now.duration_since(m).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        found.push(PartialFileInfo {
+            path: entry.path().to_path_buf(),
+            size: metadata.len(),
+            modified: metadata_to_datetime(&metadata)?,
+            age_secs,
+            resumable: resumable.contains(entry.path()),
+        });
+    }
+
+    Ok(found)
+}
+
+/// Removes the temp and partial files under a tree, except those in `keep`
+/// that unfinished transfers can still resume from, and reports what was reclaimed.
+pub fn clean_partial_files(
+    root: &Path,
+    keep: &HashSet<PathBuf>,
+) -> SyncResult<PartialCleanupReport> {
+    let mut report = PartialCleanupReport::default();
+
+    for file in find_partial_files(root, keep)? {
+        if file.resumable {
+            report.files_kept += 1;
+            continue;
+        }
+        match fs::remove_file(&file.path) {
+            Ok(_) => {
+                report.files_removed += 1;
+                report.bytes_reclaimed += file.size;
+            }
+            Err(e) => {
+                report
+                    .errors
+                    .push(format!("{}: {}", file.path.display(), e));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// What `generate_conflict_name` adds to the file stem when no template is set.
+pub const DEFAULT_CONFLICT_NAME_TEMPLATE: &str = "_{timestamp}";
+
+/// Picks a free name next to `path` for the copy kept when both sides of a
+/// conflict are kept. `template` is added to the file stem, before the
+/// extension, and can use `{timestamp}`, `{date}`, `{time}`, `{hostname}` and
+/// `{conflicted_copy}`: ` ({hostname} {conflicted_copy} {date})` turns
+/// `Report.txt` into `Report (mac-mini conflicted copy 2024-06-01).txt`. A name
+/// that is already taken gets ` (2)`, ` (3)` and so on.
+pub fn generate_conflict_name(path: &Path, template: Option<&str>) -> PathBuf {
     let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("file");
     let ext = path.extension().and_then(|s| s.to_str());
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let now = chrono::Local::now();
+    let suffix = template
+        .unwrap_or(DEFAULT_CONFLICT_NAME_TEMPLATE)
+        .replace("{timestamp}", &now.format("%Y%m%d_%H%M%S").to_string())
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H-%M-%S").to_string())
+        .replace("{hostname}", &hostname())
+        .replace("{conflicted_copy}", "conflicted copy")
+        .replace(['/', '\\'], "-");
 
-    let new_name = match ext {
-        Some(e) => format!("{}_{}.{}", stem, timestamp, e),
-        None => format!("{}_{}", stem, timestamp),
+    let named = |counter: u32| {
+        let counter = if counter > 1 {
+            format!(" ({})", counter)
+        } else {
+            String::new()
+        };
+        let new_name = match ext {
+            Some(e) => format!("{}{}{}.{}", stem, suffix, counter, e),
+            None => format!("{}{}{}", stem, suffix, counter),
+        };
+        path.with_file_name(new_name)
     };
-
-    path.with_file_name(new_name)
+    let mut counter = 1;
+    let mut candidate = named(counter);
+    while candidate.symlink_metadata().is_ok() {
+        counter += 1;
+        candidate = named(counter);
+    }
+    candidate
 }
 
 /// Copy a symlink to the destination, preserving it as a symlink.
@@ -935,6 +4101,241 @@ pub fn copy_symlink(source: &Path, dest: &Path, follow_symlinks: bool) -> SyncRe
     Ok(0)
 }
 
+/// Copies the tree at `source` into `dest` with metadata, so that a size and
+/// mtime comparison of the two reports no differences. Symlinks are copied as
+/// links and FIFOs recreated; sockets and device files are left out.
+/// Returns the number of bytes copied.
+pub fn copy_tree(source: &Path, dest: &Path) -> SyncResult<u64> {
+    let options = CopyOptions::default();
+    let mut bytes_copied = 0;
+    fs::create_dir_all(dest).map_err(|e| classify_io_error(e, dest))?;
+
+    for entry in walkdir::WalkDir::new(source).min_depth(1) {
+        let entry = entry.map_err(|e| SyncError::Io(e.into()))?;
+        let relative = entry.path().strip_prefix(source).map_err(|_| {
+            SyncError::InvalidPath(format!("Cannot strip prefix from {:?}", entry.path()))
+        })?;
+        let target = dest.join(relative);
+        let file_type = entry.file_type();
+
+        if file_type.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| classify_io_error(e, &target))?;
+        } else if file_type.is_symlink() {
+            copy_symlink(entry.path(), &target, false)?;
+        } else if file_type.is_file() {
+            bytes_copied += copy_file_atomic(entry.path(), &target, &options, |_, _| true)?;
+        } else if SpecialFileType::from_file_type(&file_type) == SpecialFileType::Fifo {
+            create_fifo(entry.path(), &target)?;
+        }
+    }
+
+    Ok(bytes_copied)
+}
+
+/// Size of the temporary file written by `benchmark_volume`: 256 MiB.
+/// Large enough to get past most drive caches while finishing in seconds.
+pub const BENCHMARK_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Result of a destination throughput benchmark.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeBenchmark {
+    pub path: PathBuf,
+    pub test_size_bytes: u64,
+    pub write_bytes_per_sec: f64,
+    pub read_bytes_per_sec: f64,
+    pub drive_type: String,
+}
+
+/// Keeps a benchmark file out of the OS page cache so reads hit the device.
+/// Called when the file is opened for writing, once it is fsynced, and when it
+/// is opened for reading.
+#[cfg(target_os = "macos")]
+fn disable_page_cache(file: &File) {
+    use std::os::unix::io::AsRawFd;
+    unsafe {
+        libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1);
+    }
+}
+
+/// Only clean pages can be dropped, which is why this waits for the fsync.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn disable_page_cache(file: &File) {
+    use std::os::unix::io::AsRawFd;
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+    }
+}
+
+/// Windows bypasses the cache for the read phase when opening the file instead.
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd"
+)))]
+fn disable_page_cache(_file: &File) {}
+
+/// Opens the benchmark file for its read phase, on Windows without the system
+/// cache. Unbuffered reads have to be sector aligned.
+fn open_benchmark_for_read(path: &Path) -> std::io::Result<File> {
+    let mut options = fs::OpenOptions::new();
+    options.read(true);
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        options.custom_flags(0x20000000); // FILE_FLAG_NO_BUFFERING
+    }
+    options.open(path)
+}
+
+/// Alignment of the benchmark's read buffer, a multiple of any sector size.
+const BENCHMARK_ALIGNMENT: usize = 4096;
+
+/// Fills `buffer` with pseudo-random bytes, so filesystems that compress or
+/// deduplicate can't write the benchmark file faster than real data.
+fn fill_incompressible(buffer: &mut [u8]) {
+    // xorshift64, seeded randomly; never zero
+    let mut state = (uuid::Uuid::new_v4().as_u128() as u64) | 1;
+    for chunk in buffer.chunks_mut(8) {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        chunk.copy_from_slice(&state.to_le_bytes()[..chunk.len()]);
+    }
+}
+
+/// Measures sequential write and read throughput of the volume containing `dir`.
+///
+/// Writes a temporary file using the same buffered block loop as
+/// `copy_file_with_progress`, fsyncs it, reads it back, and removes it.
+pub fn benchmark_volume(dir: &Path, test_size: u64) -> SyncResult<VolumeBenchmark> {
+    if !dir.is_dir() {
+        return Err(SyncError::InvalidPath(format!(
+            "{} is not a directory",
+            dir.display()
+        )));
+    }
+
+    if let Ok((available, _)) = get_disk_space(dir) {
+        let required = test_size + 4096;
+        if available < required {
+            return Err(SyncError::DiskFull {
+                path: dir.to_path_buf(),
+                required_bytes: required,
+                available_bytes: available,
+            });
+        }
+    }
+
+    // Use the temp extension so an interrupted benchmark is swept by cleanup
+    let test_path = dir.join(format!(".rsync-benchmark{}", TEMP_FILE_EXTENSION));
+    let result = run_benchmark(&test_path, test_size);
+    let _ = fs::remove_file(&test_path);
+    let (write_bytes_per_sec, read_bytes_per_sec) = result?;
+
+    let drive_type = get_volume_info(dir)
+        .map(|info| info.drive_type)
+        .unwrap_or_else(|_| "Unknown".to_string());
+
+    Ok(VolumeBenchmark {
+        path: dir.to_path_buf(),
+        test_size_bytes: test_size,
+        write_bytes_per_sec,
+        read_bytes_per_sec,
+        drive_type,
+    })
+}
+
+/// Writes then reads `test_size` bytes at `test_path`, returning (write, read) bytes/sec.
+fn run_benchmark(test_path: &Path, test_size: u64) -> SyncResult<(f64, f64)> {
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    fill_incompressible(&mut buffer);
+
+    // Write phase: same blocked BufWriter loop as a real copy, including the final fsync
+    let write_start = Instant::now();
+    {
+        let mut file = File::create(test_path).map_err(|e| classify_io_error(e, test_path))?;
+        disable_page_cache(&file);
+        let mut writer = BufWriter::with_capacity(COPY_BUFFER_SIZE, &mut file);
+        let mut written: u64 = 0;
+        while written < test_size {
+            let chunk = (test_size - written).min(buffer.len() as u64) as usize;
+            writer
+                .write_all(&buffer[..chunk])
+                .map_err(|e| classify_io_error(e, test_path))?;
+            written += chunk as u64;
+        }
+        writer.flush()?;
+        drop(writer);
+        file.sync_all()?;
+        // Drops the written pages where that only works once they are clean
+        disable_page_cache(&file);
+    }
+    let write_secs = write_start.elapsed().as_secs_f64();
+
+    // Read phase
+    let read_start = Instant::now();
+    {
+        let mut file =
+            open_benchmark_for_read(test_path).map_err(|e| classify_io_error(e, test_path))?;
+        disable_page_cache(&file);
+        let mut storage = vec![0u8; COPY_BUFFER_SIZE + BENCHMARK_ALIGNMENT];
+        let start = storage.as_ptr().align_offset(BENCHMARK_ALIGNMENT);
+        let read_buffer = &mut storage[start..start + COPY_BUFFER_SIZE];
+        loop {
+            let bytes_read = file.read(read_buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+        }
+    }
+    let read_secs = read_start.elapsed().as_secs_f64();
+
+    let rate = |secs: f64| {
+        if secs > 0.0 {
+            test_size as f64 / secs
+        } else {
+            0.0
+        }
+    };
+    Ok((rate(write_secs), rate(read_secs)))
+}
+
+/// Recreate a FIFO (named pipe) at the destination with the source's permissions.
+#[cfg(unix)]
+pub fn create_fifo(source: &Path, dest: &Path) -> SyncResult<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::symlink_metadata(source)?.permissions().mode() & 0o7777;
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if dest.symlink_metadata().is_ok() {
+        fs::remove_file(dest).map_err(|e| classify_io_error(e, dest))?;
+    }
+
+    let c_path = CString::new(dest.as_os_str().as_bytes())
+        .map_err(|_| SyncError::InvalidPath(dest.display().to_string()))?;
+
+    let result = unsafe { libc::mkfifo(c_path.as_ptr(), mode as libc::mode_t) };
+    if result != 0 {
+        return Err(classify_io_error(std::io::Error::last_os_error(), dest));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn create_fifo(_source: &Path, dest: &Path) -> SyncResult<()> {
+    Err(SyncError::InvalidPath(format!(
+        "FIFOs are not supported on this platform: {}",
+        dest.display()
+    )))
+}
+
 /// Information about a volume/drive
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VolumeInfo {
@@ -1207,3 +4608,94 @@ pub fn get_volume_info(path: &Path) -> SyncResult<VolumeInfo> {
         available_space: 0,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_later_exclude_layers_override_earlier_ones() {
+        let root = temp_root("exclude-layers");
+        let global = vec!["*.log".to_string()];
+        let profile = vec!["!keep.log".to_string()];
+
+        let rules = || layered_exclude_rules(&global, &profile, Some(&root));
+        let matcher = ExcludeMatcher::compile(rules());
+        assert!(is_excluded(Path::new("debug.log"), matcher.as_ref()));
+        assert!(!is_excluded(Path::new("logs/keep.log"), matcher.as_ref()));
+
+        // The source's ignore file comes last and excludes it again
+        fs::write(root.join(IGNORE_FILENAME), "# kept out\nkeep.log\n").unwrap();
+        let matcher = ExcludeMatcher::compile(rules());
+        let rule = matcher
+            .as_ref()
+            .unwrap()
+            .deciding_rule(Path::new("logs/keep.log"))
+            .unwrap();
+        assert_eq!(rule.layer, ExcludeLayer::Directory);
+        assert!(is_excluded(Path::new("logs/keep.log"), matcher.as_ref()));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_analyze_patterns_finds_shadowed_and_contradictory_rules() {
+        let excludes = vec![
+            "*.tmp".to_string(),
+            "!*.tmp".to_string(),
+            "docs".to_string(),
+        ];
+        let analysis = analyze_patterns(&["docs/**".to_string()], &excludes);
+        let issue = |kind| analysis.issues.iter().find(|issue| issue.kind == kind);
+
+        let shadowed = issue(PatternIssueKind::Shadowed).unwrap();
+        assert_eq!(shadowed.pattern, "*.tmp");
+        assert_eq!(shadowed.related.as_deref(), Some("!*.tmp"));
+
+        let contradictory = issue(PatternIssueKind::Contradictory).unwrap();
+        assert_eq!(contradictory.pattern, "docs/**");
+        assert_eq!(contradictory.related.as_deref(), Some("docs"));
+    }
+
+    #[test]
+    fn test_clean_partial_files_keeps_resumable_ones() {
+        let root = temp_root("partial-clean");
+        let (resumable, stale) = (
+            get_temp_path(&root.join("big.bin")),
+            get_partial_path(&root.join("old.bin")),
+        );
+        fs::write(&resumable, b"resume me").unwrap();
+        fs::write(&stale, b"leftover").unwrap();
+        let keep = HashSet::from([resumable.clone()]);
+
+        let found = find_partial_files(&root, &keep).unwrap();
+        assert!(found
+            .iter()
+            .all(|file| file.resumable == (file.path == resumable)));
+        let report = clean_partial_files(&root, &keep).unwrap();
+        assert_eq!((report.files_removed, report.files_kept), (1, 1));
+        assert!(resumable.exists() && !stale.exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_conflict_name_template_collisions_get_a_counter() {
+        let root = temp_root("conflict-name");
+        let original = root.join("Report.txt");
+        let template = Some(" ({conflicted_copy})");
+
+        let first = generate_conflict_name(&original, template);
+        assert_eq!(first, root.join("Report (conflicted copy).txt"));
+        fs::write(&first, b"kept").unwrap();
+        assert_eq!(
+            generate_conflict_name(&original, template),
+            root.join("Report (conflicted copy) (2).txt")
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+}
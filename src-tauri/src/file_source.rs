@@ -0,0 +1,419 @@
+//! Sources a sync can read from.
+//!
+//! `FileSource` abstracts listing and fetching files so the engine's
+//! progress, state and UI layer can sit on top of either the local filesystem
+//! or an rsync daemon (`rsync://host/module/path`). The daemon source shells
+//! out to the system `rsync` binary for the wire protocol.
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use std::cell::RefCell;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::errors::{SyncError, SyncResult};
+use crate::file_ops::{
+    copy_file_with_progress, scan_directory_with_options, CopyOptions, FileInfo, SpecialFileType,
+};
+
+/// URL scheme handled by `RsyncDaemonSource`.
+pub const RSYNC_URL_SCHEME: &str = "rsync://";
+
+/// A place files can be listed and fetched from.
+pub trait FileSource: Send + Sync {
+    /// Location shown in logs, errors and the transfer state.
+    fn describe(&self) -> String;
+
+    /// Lists every entry under the source, with paths relative to its root.
+    fn list_files(&self) -> SyncResult<Vec<FileInfo>>;
+
+    /// Writes the complete contents of `file` to `dest`, calling `progress`
+    /// with the bytes fetched so far. Returning false from `progress` aborts
+    /// the fetch. Returns the number of bytes fetched.
+    fn fetch_file(
+        &self,
+        file: &FileInfo,
+        dest: &Path,
+        progress: &mut dyn FnMut(u64) -> bool,
+    ) -> SyncResult<u64>;
+}
+
+// ============================================================================
+// Local filesystem
+// ============================================================================
+
+/// A directory on a locally mounted filesystem.
+pub struct LocalSource {
+    root: PathBuf,
+    follow_symlinks: bool,
+}
+
+impl LocalSource {
+    pub fn new(root: PathBuf, follow_symlinks: bool) -> Self {
+        Self {
+            root,
+            follow_symlinks,
+        }
+    }
+}
+
+impl FileSource for LocalSource {
+    fn describe(&self) -> String {
+        self.root.display().to_string()
+    }
+
+    fn list_files(&self) -> SyncResult<Vec<FileInfo>> {
+        Ok(scan_directory_with_options(&self.root, self.follow_symlinks)?.files)
+    }
+
+    fn fetch_file(
+        &self,
+        file: &FileInfo,
+        dest: &Path,
+        progress: &mut dyn FnMut(u64) -> bool,
+    ) -> SyncResult<u64> {
+        let progress = RefCell::new(progress);
+        copy_file_with_progress(
+            &self.root.join(&file.path),
+            dest,
+            &CopyOptions::default(),
+            |copied, _hash| (progress.borrow_mut())(copied),
+        )
+    }
+}
+
+// ============================================================================
+// rsync daemon
+// ============================================================================
+
+/// A running `rsync` and the thread collecting its stderr.
+struct RsyncProcess {
+    child: std::process::Child,
+    stderr: Option<std::thread::JoinHandle<String>>,
+}
+
+/// A module path on an rsync daemon, fetched through the system `rsync` binary.
+/// Set `RSYNC_PASSWORD` in the environment for modules that need auth.
+pub struct RsyncDaemonSource {
+    /// Normalized `rsync://[user@]host[:port]/module[/path]`, without a trailing slash
+    url: String,
+    preserve_metadata: bool,
+}
+
+impl RsyncDaemonSource {
+    /// Parses an `rsync://[user@]host[:port]/module[/path]` URL.
+    pub fn new(url: &str, preserve_metadata: bool) -> SyncResult<Self> {
+        let rest = url
+            .strip_prefix(RSYNC_URL_SCHEME)
+            .ok_or_else(|| SyncError::InvalidPath(format!("Not an rsync daemon URL: {}", url)))?;
+        let (host, module_path) = rest.split_once('/').unwrap_or((rest, ""));
+        let module_path = module_path.trim_matches('/');
+
+        if host.is_empty() || module_path.is_empty() {
+            return Err(SyncError::InvalidPath(format!(
+                "rsync URL needs a host and a module: {}",
+                url
+            )));
+        }
+        if module_path.split('/').any(|part| part == "..") {
+            return Err(SyncError::PermissionDenied(
+                "Path traversal sequences (..) are not allowed".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            url: format!("{}{}/{}", RSYNC_URL_SCHEME, host, module_path),
+            preserve_metadata,
+        })
+    }
+
+    fn remote_error(&self, reason: impl Into<String>) -> SyncError {
+        SyncError::RemoteSource {
+            url: self.url.clone(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Runs `rsync` with `args`, maps a missing binary to a readable error.
+    /// Stderr is drained on its own thread so a chatty rsync never blocks on
+    /// a full pipe while stdout is being read.
+    fn spawn(&self, args: &[&str]) -> SyncResult<RsyncProcess> {
+        let mut child = Command::new("rsync")
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    self.remote_error("the rsync binary was not found on this system")
+                } else {
+                    self.remote_error(format!("failed to start rsync: {}", e))
+                }
+            })?;
+        let stderr = child.stderr.take().map(|mut pipe| {
+            std::thread::spawn(move || {
+                let mut stderr = String::new();
+                let _ = pipe.read_to_string(&mut stderr);
+                stderr
+            })
+        });
+        Ok(RsyncProcess { child, stderr })
+    }
+
+    /// Waits for `process` and turns a non-zero exit into an error carrying stderr.
+    fn finish(&self, process: RsyncProcess) -> SyncResult<()> {
+        let RsyncProcess { mut child, stderr } = process;
+        let status = child.wait()?;
+        let stderr = stderr
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        if status.success() {
+            return Ok(());
+        }
+        let code = status
+            .code()
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "signal".to_string());
+        Err(self.remote_error(format!("rsync exited with {}: {}", code, stderr.trim())))
+    }
+}
+
+impl FileSource for RsyncDaemonSource {
+    fn describe(&self) -> String {
+        self.url.clone()
+    }
+
+    fn list_files(&self) -> SyncResult<Vec<FileInfo>> {
+        // Trailing slash lists the contents relative to the module path
+        let target = format!("{}/", self.url);
+        let mut process = self.spawn(&["--list-only", "--recursive", &target])?;
+
+        let mut output = String::new();
+        if let Some(mut stdout) = process.child.stdout.take() {
+            stdout.read_to_string(&mut output)?;
+        }
+        self.finish(process)?;
+
+        Ok(output.lines().filter_map(parse_list_line).collect())
+    }
+
+    fn fetch_file(
+        &self,
+        file: &FileInfo,
+        dest: &Path,
+        progress: &mut dyn FnMut(u64) -> bool,
+    ) -> SyncResult<u64> {
+        let relative: Vec<String> = file
+            .path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+        let remote = format!("{}/{}", self.url, relative.join("/"));
+        let local = dest.to_string_lossy().to_string();
+
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut args = vec!["--links", "--progress"];
+        if self.preserve_metadata {
+            args.extend(["--times", "--perms"]);
+        }
+        args.extend([remote.as_str(), local.as_str()]);
+        let mut process = self.spawn(&args)?;
+
+        // --progress redraws one line with \r; each redraw starts with the byte count
+        if let Some(stdout) = process.child.stdout.take() {
+            let mut stdout = BufReader::new(stdout);
+            let mut line = Vec::new();
+            let mut byte = [0u8; 1];
+            while stdout.read(&mut byte)? == 1 {
+                if byte[0] != b'\r' && byte[0] != b'\n' {
+                    line.push(byte[0]);
+                    continue;
+                }
+                if let Some(bytes) = parse_progress_bytes(&String::from_utf8_lossy(&line)) {
+                    if !progress(bytes) {
+                        let _ = process.child.kill();
+                        let _ = process.child.wait();
+                        let _ = std::fs::remove_file(dest);
+                        return Err(SyncError::TransferCancelled(
+                            "Transfer cancelled by user".into(),
+                        ));
+                    }
+                }
+                line.clear();
+            }
+        }
+        self.finish(process)?;
+
+        // What actually landed, rather than the last progress line or the
+        // size listed before the fetch
+        Ok(std::fs::metadata(dest)?.len())
+    }
+}
+
+/// Parses one line of `rsync --list-only` output, e.g.
+/// `-rw-r--r--      1,234 2024/01/31 12:00:00 dir/file name.txt`.
+/// Returns None for the root entry and lines that aren't entries.
+fn parse_list_line(line: &str) -> Option<FileInfo> {
+    let mut rest = line.trim_start();
+    let mut fields = Vec::with_capacity(4);
+    for _ in 0..4 {
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    let (perms, size, date, time) = (fields[0], fields[1], fields[2], fields[3]);
+    let kind = perms.chars().next()?;
+
+    let mut name = rest;
+    if kind == 'l' {
+        name = name
+            .split_once(" -> ")
+            .map(|(link, _)| link)
+            .unwrap_or(name);
+    }
+    let name = unescape_name(name);
+    // Only plain components, so no entry can name a path outside the
+    // destination; this also drops the root entry `.`
+    let path = PathBuf::from(name);
+    if path.as_os_str().is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let size: u64 = size.replace(',', "").parse().ok()?;
+    let naive =
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y/%m/%d %H:%M:%S").ok()?;
+    // The client prints times in its own local timezone
+    let modified: DateTime<Utc> = Local
+        .from_local_datetime(&naive)
+        .earliest()?
+        .with_timezone(&Utc);
+
+    let file_type = match kind {
+        'p' => SpecialFileType::Fifo,
+        's' => SpecialFileType::Socket,
+        'b' => SpecialFileType::BlockDevice,
+        'c' => SpecialFileType::CharDevice,
+        _ => SpecialFileType::None,
+    };
+
+    Some(FileInfo {
+        path,
+        size,
+        modified,
+        is_dir: kind == 'd',
+        is_symlink: kind == 'l',
+        file_type,
+        is_dataless: false,
+        flags: 0,
+    })
+}
+
+/// Decodes rsync's `\#ooo` octal escapes for unprintable name bytes.
+fn unescape_name(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'#') && i + 5 <= bytes.len() {
+            if let Some(value) = std::str::from_utf8(&bytes[i + 2..i + 5])
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok())
+            {
+                out.push(value);
+                i += 5;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Extracts the bytes-so-far count from an `rsync --progress` line.
+fn parse_progress_bytes(line: &str) -> Option<u64> {
+    let first = line.split_whitespace().next()?;
+    if !first.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    // Only progress lines have a percentage in the next column
+    if !line.contains('%') {
+        return None;
+    }
+    first.replace(',', "").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rsync_url_parsing() {
+        let source =
+            RsyncDaemonSource::new("rsync://backup@nas.local:873/photos/2024/", true).unwrap();
+        assert_eq!(
+            source.describe(),
+            "rsync://backup@nas.local:873/photos/2024"
+        );
+
+        assert!(RsyncDaemonSource::new("rsync://nas.local", true).is_err());
+        assert!(RsyncDaemonSource::new("rsync://nas.local/", true).is_err());
+        assert!(RsyncDaemonSource::new("/Volumes/NAS", true).is_err());
+        assert!(RsyncDaemonSource::new("rsync://nas.local/photos/../etc", true).is_err());
+    }
+
+    #[test]
+    fn test_parse_list_line() {
+        assert!(parse_list_line("drwxr-xr-x          4,096 2024/01/31 12:00:00 .").is_none());
+        assert!(
+            parse_list_line("-rw-r--r--              1 2024/01/31 12:00:00 ../escape").is_none()
+        );
+        assert!(
+            parse_list_line("-rw-r--r--              1 2024/01/31 12:00:00 /etc/passwd").is_none()
+        );
+        assert!(
+            parse_list_line("-rw-r--r--              1 2024/01/31 12:00:00 a/../../b").is_none()
+        );
+
+        let file = parse_list_line("-rw-r--r--      1,234,567 2024/01/31 12:00:00 dir/my file.txt")
+            .unwrap();
+        assert_eq!(file.path, PathBuf::from("dir/my file.txt"));
+        assert_eq!(file.size, 1_234_567);
+        assert!(!file.is_dir && !file.is_symlink);
+
+        let dir = parse_list_line("drwxr-xr-x           4096 2024/01/31 12:00:00 dir").unwrap();
+        assert!(dir.is_dir);
+
+        let link =
+            parse_list_line("lrwxrwxrwx              9 2024/01/31 12:00:00 latest -> dir/a.txt")
+                .unwrap();
+        assert!(link.is_symlink);
+        assert_eq!(link.path, PathBuf::from("latest"));
+
+        let escaped =
+            parse_list_line("-rw-r--r--              1 2024/01/31 12:00:00 tab\\#011name").unwrap();
+        assert_eq!(escaped.path, PathBuf::from("tab\tname"));
+    }
+
+    #[test]
+    fn test_parse_progress_bytes() {
+        assert_eq!(
+            parse_progress_bytes("     32,768   0%    0.00kB/s    0:00:00"),
+            Some(32_768)
+        );
+        assert_eq!(
+            parse_progress_bytes("  1,048,576 100%   12.34MB/s    0:00:01 (xfr#1, to-chk=0/1)"),
+            Some(1_048_576)
+        );
+        assert_eq!(parse_progress_bytes("file.txt"), None);
+        assert_eq!(parse_progress_bytes("2024 photos"), None);
+    }
+}
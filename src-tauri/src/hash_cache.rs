@@ -0,0 +1,212 @@
+//! Persistent cache of file content hashes.
+//!
+//! Integrity verification hashes every source file it copies. Entries are keyed
+//! by absolute path and only trusted while the file's size and mtime still
+//! match, so repeated verified syncs only hash what actually changed. The cache
+//! lives in the transfer state directory and is merged into the on-disk copy
+//! under an exclusive file lock, so concurrent transfers (or app instances)
+//! never overwrite each other's entries.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::errors::SyncResult;
+use crate::file_ops::{compute_file_hash, metadata_to_datetime};
+
+/// Subdirectory of the state directory holding the cache, so the cache file is
+/// never mistaken for a transfer state.
+const CACHE_DIR: &str = "hash-cache";
+
+const CACHE_FILENAME: &str = "hashes.json";
+
+const LOCK_FILENAME: &str = "hashes.lock";
+
+/// Entries kept on disk; the least recently used are dropped beyond this.
+const MAX_ENTRIES: usize = 500_000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct CachedHash {
+    size: u64,
+    modified: DateTime<Utc>,
+    hash: u64,
+    last_used: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    entries: HashMap<PathBuf, CachedHash>,
+    /// Entries added or refreshed since the last flush
+    changed: HashMap<PathBuf, CachedHash>,
+}
+
+pub struct HashCache {
+    dir: PathBuf,
+    inner: Mutex<CacheEntries>,
+}
+
+impl HashCache {
+    /// Loads the cache from `state_dir`. A missing or unreadable cache starts empty.
+    pub fn open(state_dir: &Path) -> Self {
+        let dir = state_dir.join(CACHE_DIR);
+        let entries = Self::read_entries(&dir.join(CACHE_FILENAME));
+        Self {
+            dir,
+            inner: Mutex::new(CacheEntries {
+                entries,
+                changed: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Returns the xxh3 hash of `path`, from the cache if the file's size and
+    /// mtime are unchanged since it was last hashed.
+    pub fn hash_file(&self, path: &Path) -> SyncResult<u64> {
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        // The cache is JSON keyed by path, which can't hold non-UTF-8 names
+        if key.to_str().is_none() {
+            return compute_file_hash(path);
+        }
+        let before = fs::metadata(path)?;
+        let size = before.len();
+        let modified = metadata_to_datetime(&before)?;
+
+        {
+            let mut inner = self.inner.lock();
+            let hit = inner
+                .entries
+                .get(&key)
+                .filter(|cached| cached.size == size && cached.modified == modified)
+                .copied();
+            if let Some(mut cached) = hit {
+                cached.last_used = Utc::now();
+                inner.entries.insert(key.clone(), cached);
+                inner.changed.insert(key, cached);
+                return Ok(cached.hash);
+            }
+        }
+
+        let hash = compute_file_hash(path)?;
+
+        // Don't cache a hash of a file that changed while it was being read
+        let after = fs::metadata(path)?;
+        if after.len() == size && metadata_to_datetime(&after)? == modified {
+            let cached = CachedHash {
+                size,
+                modified,
+                hash,
+                last_used: Utc::now(),
+            };
+            let mut inner = self.inner.lock();
+            inner.entries.insert(key.clone(), cached);
+            inner.changed.insert(key, cached);
+        }
+
+        Ok(hash)
+    }
+
+    /// The cached hash of `path`, only if its size and mtime still match.
+    /// Never reads the file.
+    pub fn cached_hash(&self, path: &Path) -> Option<u64> {
+        let key = path.canonicalize().ok()?;
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata_to_datetime(&metadata).ok()?;
+        self.inner
+            .lock()
+            .entries
+            .get(&key)
+            .filter(|cached| cached.size == metadata.len() && cached.modified == modified)
+            .map(|cached| cached.hash)
+    }
+
+    /// Writes new and refreshed entries to disk. The on-disk cache is re-read
+    /// under the lock and merged, so entries flushed by other transfers since
+    /// this cache was loaded are kept.
+    pub fn flush(&self) -> SyncResult<()> {
+        let changed = std::mem::take(&mut self.inner.lock().changed);
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.dir.join(LOCK_FILENAME))?;
+        lock_file.lock()?;
+
+        let cache_path = self.dir.join(CACHE_FILENAME);
+        let mut entries = Self::read_entries(&cache_path);
+        entries.extend(changed);
+        if entries.len() > MAX_ENTRIES {
+            let mut by_use: Vec<_> = entries.into_iter().collect();
+            by_use.sort_by_key(|(_, cached)| std::cmp::Reverse(cached.last_used));
+            by_use.truncate(MAX_ENTRIES);
+            entries = by_use.into_iter().collect();
+        }
+
+        let temp_path = self.dir.join(format!("{}.tmp", CACHE_FILENAME));
+        let written = Self::write_entries(&temp_path, &entries)
+            .and_then(|_| fs::rename(&temp_path, &cache_path).map_err(Into::into));
+        // Dropping the handle releases the lock even if the write failed
+        drop(lock_file);
+        written?;
+
+        // Pick up other transfers' entries without clobbering newer local ones
+        let mut inner = self.inner.lock();
+        for (path, cached) in entries {
+            inner.entries.entry(path).or_insert(cached);
+        }
+        Ok(())
+    }
+
+    fn read_entries(path: &Path) -> HashMap<PathBuf, CachedHash> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_entries(path: &Path, entries: &HashMap<PathBuf, CachedHash>) -> SyncResult<()> {
+        let file = File::create(path)?;
+        let mut writer = std::io::BufWriter::new(&file);
+        serde_json::to_writer(&mut writer, entries)?;
+        writer.flush()?;
+        drop(writer);
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_invalidated_when_file_changes() {
+        let dir = std::env::temp_dir().join(format!("hash-cache-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("data.bin");
+        fs::write(&file, b"first").unwrap();
+
+        let cache = HashCache::open(&dir);
+        let first = cache.hash_file(&file).unwrap();
+        cache.flush().unwrap();
+
+        // A fresh instance reads the flushed entry back
+        let reopened = HashCache::open(&dir);
+        assert_eq!(reopened.hash_file(&file).unwrap(), first);
+
+        fs::write(&file, b"second, longer").unwrap();
+        let second = reopened.hash_file(&file).unwrap();
+        assert_ne!(second, first);
+        assert_eq!(second, compute_file_hash(&file).unwrap());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
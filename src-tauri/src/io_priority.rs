@@ -0,0 +1,120 @@
+//! Lowered disk I/O priority for copies that shouldn't slow the machine down.
+//!
+//! On macOS the calling thread's disk policy is set to `IOPOL_THROTTLE`, the
+//! tier Time Machine backs up at; on Linux the thread joins the idle I/O
+//! scheduling class, which only gets disk time nothing else wants. Both are
+//! per thread and undone when the guard is dropped, since copies run on pooled
+//! threads that go on to run other work.
+
+/// Keeps the current thread at low I/O priority until dropped.
+pub struct LowIoPriority {
+    /// Policy to put back, or `None` if it couldn't be lowered
+    previous: Option<i32>,
+}
+
+impl LowIoPriority {
+    /// Lowers the current thread's I/O priority. Where that isn't supported
+    /// or allowed the thread keeps its priority and the guard does nothing.
+    pub fn enter() -> Self {
+        Self {
+            previous: platform::lower(),
+        }
+    }
+
+    /// Whether the priority was actually lowered.
+    pub fn is_lowered(&self) -> bool {
+        self.previous.is_some()
+    }
+}
+
+impl Drop for LowIoPriority {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous {
+            platform::restore(previous);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    const IOPOL_TYPE_DISK: i32 = 0;
+    const IOPOL_SCOPE_THREAD: i32 = 1;
+    const IOPOL_THROTTLE: i32 = 3;
+
+    extern "C" {
+        fn getiopolicy_np(iotype: i32, scope: i32) -> i32;
+        fn setiopolicy_np(iotype: i32, scope: i32, policy: i32) -> i32;
+    }
+
+    pub fn lower() -> Option<i32> {
+        let previous = unsafe { getiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_THREAD) };
+        if previous < 0 {
+            return None;
+        }
+        let result = unsafe { setiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_THREAD, IOPOL_THROTTLE) };
+        (result == 0).then_some(previous)
+    }
+
+    pub fn restore(previous: i32) {
+        unsafe { setiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_THREAD, previous) };
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    /// With a `who` of 0, the calling thread
+    const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+    const IOPRIO_CLASS_IDLE: libc::c_long = 3;
+    const IOPRIO_CLASS_SHIFT: u32 = 13;
+
+    pub fn lower() -> Option<i32> {
+        let previous = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, 0) };
+        if previous < 0 {
+            return None;
+        }
+        let idle = IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT;
+        let result = unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, idle) };
+        (result == 0).then_some(previous as i32)
+    }
+
+    pub fn restore(previous: i32) {
+        unsafe {
+            libc::syscall(
+                libc::SYS_ioprio_set,
+                IOPRIO_WHO_PROCESS,
+                0,
+                previous as libc::c_long,
+            )
+        };
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+mod platform {
+    pub fn lower() -> Option<i32> {
+        None
+    }
+
+    pub fn restore(_previous: i32) {}
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    fn current_ioprio() -> libc::c_long {
+        unsafe { libc::syscall(libc::SYS_ioprio_get, 1 as libc::c_long, 0) }
+    }
+
+    #[test]
+    fn test_priority_is_restored_on_drop() {
+        let before = current_ioprio();
+        {
+            let guard = LowIoPriority::enter();
+            if guard.is_lowered() {
+                assert_eq!(current_ioprio() >> 13, 3);
+            }
+        }
+        assert_eq!(current_ioprio(), before);
+    }
+}
@@ -3,9 +3,10 @@
 //! Provides functionality to enable/disable automatic app startup
 //! when the user logs in to macOS.
 
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::errors::SyncError;
 
@@ -204,6 +205,80 @@ pub fn is_auto_start_enabled() -> bool {
     }
 }
 
+/// Whether the Launch Agent exists and starts this copy of the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoStartStatus {
+    Disabled,
+    Enabled,
+    /// The Launch Agent starts an app at another path, such as the copy in
+    /// Downloads the app was moved out of, so it needs registering again
+    Stale,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoStartCheck {
+    pub status: AutoStartStatus,
+    /// The app the Launch Agent starts, if one is registered
+    pub registered_path: Option<String>,
+    pub current_path: String,
+}
+
+/// Checks that the Launch Agent, if any, starts the app at its current
+/// location. An agent whose plist can't be read or parsed counts as stale,
+/// since re-registering replaces it either way.
+pub fn validate_auto_start() -> Result<AutoStartCheck, SyncError> {
+    let plist_path = get_plist_path()?;
+    let current_path = get_app_path()?;
+    if !plist_path.exists() {
+        return Ok(AutoStartCheck {
+            status: AutoStartStatus::Disabled,
+            registered_path: None,
+            current_path,
+        });
+    }
+
+    let registered_path = fs::read_to_string(&plist_path)
+        .ok()
+        .and_then(|content| registered_app_path(&content));
+    let status = match &registered_path {
+        Some(path) if path == &current_path && Path::new(path).exists() => AutoStartStatus::Enabled,
+        _ => AutoStartStatus::Stale,
+    };
+    if status == AutoStartStatus::Stale {
+        eprintln!(
+            "[LaunchAgent] Launch Agent starts {:?}, but the app is at {}",
+            registered_path, current_path
+        );
+    }
+
+    Ok(AutoStartCheck {
+        status,
+        registered_path,
+        current_path,
+    })
+}
+
+/// The app path in a plist's `ProgramArguments`: the last argument, which is
+/// the `.app` bundle after `open -a` or the executable itself in development.
+fn registered_app_path(plist: &str) -> Option<String> {
+    let after_key = plist.split_once("<key>ProgramArguments</key>")?.1;
+    let array = after_key.split_once("<array>")?.1.split_once("</array>")?.0;
+    let last = array
+        .split("<string>")
+        .skip(1)
+        .filter_map(|item| item.split_once("</string>").map(|(value, _)| value))
+        .last()?;
+    Some(
+        last.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +298,18 @@ mod tests {
         assert!(content.contains("/path/to/rsync"));
         assert!(!content.contains("/usr/bin/open"));
     }
+
+    #[test]
+    fn test_registered_app_path() {
+        let bundle = generate_plist_content("/Users/me/Downloads/RSync.app");
+        assert_eq!(
+            registered_app_path(&bundle).as_deref(),
+            Some("/Users/me/Downloads/RSync.app")
+        );
+
+        let dev = generate_plist_content("/path/to/rsync");
+        assert_eq!(registered_app_path(&dev).as_deref(), Some("/path/to/rsync"));
+
+        assert_eq!(registered_app_path("<plist><dict></dict></plist>"), None);
+    }
 }
@@ -1,30 +1,50 @@
 //! RSync Tauri backend library.
 
+pub mod cloud_folder;
+pub mod drive_health;
 pub mod errors;
 pub mod file_ops;
+pub mod file_source;
+pub mod hash_cache;
+pub mod io_priority;
 pub mod launch_agent;
+pub mod notifications;
 pub mod permissions;
 pub mod power;
+pub mod spotlight;
 pub mod sync_engine;
+pub mod sync_profile;
+pub mod tar_pack;
 pub mod transfer_state;
 pub mod tray;
 pub mod volume_watcher;
 
 use parking_lot::RwLock;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{Emitter, Manager, State};
 
+use drive_health::DriveHealth;
 use errors::SyncError;
-use file_ops::{DirectoryInfo, DirectorySummary, FileChunk, VolumeInfo};
-use sync_engine::{SyncEngine, SyncOptions, SyncResult_};
-use transfer_state::TransferState;
+use file_ops::{
+    CompareMode, DirectoryDiffChunk, DirectoryDiffSummary, DirectoryInfo, DirectorySummary,
+    ExcludeExplanation, ExcludePreview, FileChunk, HashAudit, PartialCleanupReport,
+    PartialFileInfo, PartialResume, PatternAnalysis, VolumeBenchmark, VolumeInfo,
+};
+use launch_agent::AutoStartCheck;
+use sync_engine::{
+    CheckpointInfo, OrphanPreview, ResolvedConflict, RollbackReport, SyncEngine, SyncOptions,
+    SyncRehearsal, SyncResult_,
+};
+use sync_profile::SyncProfile;
+use transfer_state::{ReportFormat, ResumeEstimate, TransferEvent, TransferState};
 use tray::{TrayState, TrayStatus};
 use volume_watcher::VolumeWatcher;
 
 /// Sensitive system directories that should never be accessed for sync operations.
 /// This list covers macOS system directories that could cause security issues.
-const BLOCKED_PATHS: &[&str] = &[
+pub(crate) const BLOCKED_PATHS: &[&str] = &[
     "/System",
     "/Library",
     "/private/var",
@@ -53,28 +73,65 @@ fn validate_path(path: &Path) -> Result<PathBuf, SyncError> {
         ))
     })?;
 
-    let canonical_str = canonical.to_string_lossy();
+    reject_blocked_path(&canonical)?;
+
+    // Additional check: ensure no path component is ".."
+    for component in canonical.components() {
+        if let std::path::Component::ParentDir = component {
+            return Err(SyncError::PermissionDenied(
+                "Path traversal sequences (..) are not allowed".to_string(),
+            ));
+        }
+    }
+
+    Ok(canonical)
+}
+
+/// Refuses a path inside one of the `BLOCKED_PATHS`.
+fn reject_blocked_path(path: &Path) -> Result<(), SyncError> {
+    let path_str = path.to_string_lossy();
 
     // Check for blocked system directories
     for blocked in BLOCKED_PATHS {
-        if canonical_str.starts_with(blocked) {
+        if path_str.starts_with(blocked) {
             return Err(SyncError::PermissionDenied(format!(
                 "Access to system directory '{}' is not allowed for security reasons",
                 blocked
             )));
         }
     }
+    Ok(())
+}
 
-    // Additional check: ensure no path component is ".."
-    for component in canonical.components() {
-        if let std::path::Component::ParentDir = component {
-            return Err(SyncError::PermissionDenied(
-                "Path traversal sequences (..) are not allowed".to_string(),
-            ));
-        }
+/// Validates a destination path that may not exist yet.
+/// If the destination is missing, its nearest existing ancestor is validated
+/// instead and the missing components are re-joined onto the canonical
+/// ancestor, so a dated folder that a template names can be created.
+fn validate_destination_path(dest_path: &Path) -> Result<PathBuf, String> {
+    if dest_path.exists() {
+        return validate_path(dest_path).map_err(|e| e.to_string());
     }
 
-    Ok(canonical)
+    // Check that the nearest existing ancestor is valid
+    let ancestor = dest_path
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.exists())
+        .ok_or_else(|| "Destination path has no parent directory".to_string())?;
+    let validated_ancestor = validate_path(ancestor).map_err(|e| e.to_string())?;
+
+    let missing = dest_path
+        .strip_prefix(ancestor)
+        .map_err(|e| e.to_string())?;
+    if missing
+        .components()
+        .any(|c| !matches!(c, std::path::Component::Normal(_)))
+    {
+        return Err("Path traversal sequences (..) are not allowed".to_string());
+    }
+    let validated = validated_ancestor.join(missing);
+    reject_blocked_path(&validated).map_err(|e| e.to_string())?;
+    Ok(validated)
 }
 
 pub struct AppState {
@@ -94,10 +151,11 @@ impl AppState {
 
     pub fn init_sync_engine(&self, app_handle: tauri::AppHandle) -> Result<(), SyncError> {
         let engine = Arc::new(SyncEngine::new(Some(app_handle.clone()))?);
-        *self.sync_engine.write() = Some(engine);
         
-        // Initialize volume watcher
+        // Initialize volume watcher and share its mount notifications with the engine
         let watcher = VolumeWatcher::new(Some(app_handle));
+        engine.attach_volume_watcher(&watcher);
+        *self.sync_engine.write() = Some(engine);
         *self.volume_watcher.write() = Some(Arc::new(tokio::sync::RwLock::new(watcher)));
         
         Ok(())
@@ -141,33 +199,40 @@ impl Default for AppState {
     }
 }
 
+/// `destination` may contain tokens such as `{date}` and `{source_name}`,
+/// expanded by `file_ops::expand_destination_template`.
 #[tauri::command]
 async fn sync_files(
     state: State<'_, Arc<AppState>>,
     source: String,
     destination: String,
-    options: SyncOptions,
+    mut options: SyncOptions,
 ) -> Result<SyncResult_, String> {
     let source_path = PathBuf::from(&source);
+    // Tokens such as `{date}` are expanded first, so every check sees the real path
+    let destination =
+        file_ops::expand_destination_template(&destination, &source_path, chrono::Local::now());
     let dest_path = PathBuf::from(&destination);
 
+    // Surface missing Full Disk Access up front so the UI can prompt for it
+    permissions::require_full_disk_access(&source_path).map_err(|e| e.to_string())?;
+    permissions::require_full_disk_access(&dest_path).map_err(|e| e.to_string())?;
+
     // Validate source path for security
     let source_path = validate_path(&source_path).map_err(|e| e.to_string())?;
     
-    // For destination, we need to handle the case where it doesn't exist yet
-    // Validate the parent directory instead if destination doesn't exist
-    let dest_path = if dest_path.exists() {
-        validate_path(&dest_path).map_err(|e| e.to_string())?
-    } else {
-        // Check if parent exists and is valid
-        let parent = dest_path.parent().ok_or_else(|| {
-            "Destination path has no parent directory".to_string()
-        })?;
-        let validated_parent = validate_path(parent).map_err(|e| e.to_string())?;
-        validated_parent.join(dest_path.file_name().ok_or_else(|| {
-            "Destination path has no file name".to_string()
-        })?)
-    };
+    // Destination may not exist yet, so validate via its parent if needed
+    let dest_path = validate_destination_path(&dest_path)?;
+
+    // Spanning drives get the same checks as the main destination
+    options.spanning_destinations = options
+        .spanning_destinations
+        .iter()
+        .map(|root| {
+            permissions::require_full_disk_access(root).map_err(|e| e.to_string())?;
+            validate_destination_path(root)
+        })
+        .collect::<Result<_, String>>()?;
 
     if !source_path.exists() {
         return Err(format!("Source path does not exist: {}", source));
@@ -187,6 +252,112 @@ async fn sync_files(
         .map_err(|e: SyncError| e.to_string())
 }
 
+/// Runs the sync against a temporary copy of the destination and returns what
+/// it changed there. The real destination is never written to.
+#[tauri::command]
+async fn rehearse_sync(
+    state: State<'_, Arc<AppState>>,
+    source: String,
+    destination: String,
+    options: SyncOptions,
+) -> Result<SyncRehearsal, String> {
+    // Expanded like `sync_files` does, so the preview is of the same destination
+    let destination = file_ops::expand_destination_template(
+        &destination,
+        Path::new(&source),
+        chrono::Local::now(),
+    );
+    let source_path = validate_path(&PathBuf::from(&source)).map_err(|e| e.to_string())?;
+    let dest_path = validate_destination_path(&PathBuf::from(&destination))?;
+    permissions::require_full_disk_access(&source_path).map_err(|e| e.to_string())?;
+    permissions::require_full_disk_access(&dest_path).map_err(|e| e.to_string())?;
+
+    if !source_path.exists() {
+        return Err(format!("Source path does not exist: {}", source));
+    }
+
+    let engine = {
+        let engine_guard = state.sync_engine.read();
+        engine_guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| "Sync engine not initialized".to_string())?
+    };
+
+    engine
+        .rehearse_sync(source_path, dest_path, options)
+        .await
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Lists what enabling `delete_orphans` would remove from the destination,
+/// so it can be reviewed before the destructive run. Nothing is deleted.
+#[tauri::command]
+async fn preview_orphans(
+    state: State<'_, Arc<AppState>>,
+    source: String,
+    destination: String,
+    options: SyncOptions,
+) -> Result<OrphanPreview, String> {
+    // Expanded like `sync_files` does, so the preview is of the same destination
+    let destination = file_ops::expand_destination_template(
+        &destination,
+        Path::new(&source),
+        chrono::Local::now(),
+    );
+    let source_path = validate_path(&PathBuf::from(&source)).map_err(|e| e.to_string())?;
+    let dest_path = validate_destination_path(&PathBuf::from(&destination))?;
+    permissions::require_full_disk_access(&source_path).map_err(|e| e.to_string())?;
+    permissions::require_full_disk_access(&dest_path).map_err(|e| e.to_string())?;
+
+    if !source_path.exists() {
+        return Err(format!("Source path does not exist: {}", source));
+    }
+
+    let engine = {
+        let engine_guard = state.sync_engine.read();
+        engine_guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| "Sync engine not initialized".to_string())?
+    };
+
+    engine
+        .preview_orphans(source_path, dest_path, options)
+        .await
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Pulls from an rsync daemon (`rsync://[user@]host[:port]/module/path`) into
+/// a local destination. Uses the system `rsync` binary for the transfer itself.
+#[tauri::command]
+async fn sync_from_rsync_daemon(
+    state: State<'_, Arc<AppState>>,
+    url: String,
+    destination: String,
+    options: SyncOptions,
+) -> Result<SyncResult_, String> {
+    let source = file_source::RsyncDaemonSource::new(&url, options.preserve_metadata)
+        .map_err(|e| e.to_string())?;
+
+    let dest_path = PathBuf::from(&destination);
+    permissions::require_full_disk_access(&dest_path).map_err(|e| e.to_string())?;
+    let dest_path = validate_destination_path(&dest_path)?;
+
+    let engine = {
+        let engine_guard = state.sync_engine.read();
+        engine_guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| "Sync engine not initialized".to_string())?
+    };
+
+    engine
+        .sync_from_source(Arc::new(source), dest_path, options)
+        .await
+        .map_err(|e: SyncError| e.to_string())
+}
+
 #[tauri::command]
 fn get_transfer_state(
     state: State<'_, Arc<AppState>>,
@@ -202,6 +373,105 @@ fn get_transfer_state(
         .map_err(|e: SyncError| e.to_string())
 }
 
+/// Recent significant events of a transfer (files completed or failed,
+/// conflicts, pauses, drive disconnects) for the activity log. Pass the `seq`
+/// of the last event already shown as `since` to get only newer ones.
+#[tauri::command]
+fn get_transfer_events(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+    since: Option<u64>,
+) -> Result<Vec<TransferEvent>, String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .get_transfer_events(&transfer_id, since)
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Writes a transfer's per-file outcome (path, size, status, error, hash) to
+/// `path` as CSV or JSON, for auditing. Returns the number of files listed.
+#[tauri::command]
+async fn export_transfer_report(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+    path: String,
+    format: ReportFormat,
+) -> Result<usize, String> {
+    // The report file is usually new, so it is validated via its parent
+    let path = validate_destination_path(&PathBuf::from(&path))?;
+
+    let engine = {
+        let engine_guard = state.sync_engine.read();
+        engine_guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| "Sync engine not initialized".to_string())?
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        engine.export_transfer_report(&transfer_id, &path, format)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e: SyncError| e.to_string())
+}
+
+/// Changes a running transfer's bandwidth limit in bytes per second, including
+/// for files already being copied. 0 removes the limit.
+#[tauri::command]
+fn set_bandwidth_limit(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+    bytes_per_sec: u64,
+) -> Result<(), String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .set_bandwidth_limit(&transfer_id, bytes_per_sec)
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Changes how many files a running transfer hashes for verification at
+/// once, independently of how many it copies. 0 removes the limit.
+#[tauri::command]
+fn set_max_concurrent_hashes(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+    max_hashes: usize,
+) -> Result<(), String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .set_max_concurrent_hashes(&transfer_id, max_hashes)
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// How many files a running transfer hashes for verification at once (0 = no limit).
+#[tauri::command]
+fn get_max_concurrent_hashes(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+) -> Result<usize, String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .max_concurrent_hashes(&transfer_id)
+        .map_err(|e: SyncError| e.to_string())
+}
+
 #[tauri::command]
 fn pause_transfer(state: State<'_, Arc<AppState>>, transfer_id: String) -> Result<(), String> {
     let engine_guard = state.sync_engine.read();
@@ -238,6 +508,98 @@ fn cancel_transfer(state: State<'_, Arc<AppState>>, transfer_id: String) -> Resu
         .map_err(|e: SyncError| e.to_string())
 }
 
+/// Cancels a transfer and undoes its changes at the destination: created
+/// files are removed and overwritten files restored where a backup was kept.
+#[tauri::command]
+async fn rollback_transfer(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+) -> Result<RollbackReport, String> {
+    let engine = {
+        let engine_guard = state.sync_engine.read();
+        engine_guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| "Sync engine not initialized".to_string())?
+    };
+
+    engine
+        .rollback_transfer(&transfer_id)
+        .await
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Deletes the rollback backups a transfer kept, once its changes are to stay.
+#[tauri::command]
+fn discard_rollback_backups(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+) -> Result<(), String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .discard_rollback_backups(&transfer_id)
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Skips the file currently being copied and lets the rest of the transfer
+/// continue. Returns the path of the skipped file.
+#[tauri::command]
+fn skip_current_file(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+) -> Result<String, String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .skip_current_file(&transfer_id)
+        .map(|path| path.display().to_string())
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Sets how many transfers may run at once; further syncs wait as Pending.
+#[tauri::command]
+fn set_max_concurrent_transfers(state: State<'_, Arc<AppState>>, max: usize) -> Result<(), String> {
+    if max == 0 {
+        return Err("At least one transfer must be allowed to run".to_string());
+    }
+
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine.set_max_concurrent_transfers(max);
+    Ok(())
+}
+
+/// Pauses a transfer and returns once every in-flight file has been flushed
+/// to disk and its resume offset persisted, so the app can be safely killed.
+#[tauri::command]
+async fn checkpoint_transfer(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+) -> Result<CheckpointInfo, String> {
+    let engine = {
+        let engine_guard = state.sync_engine.read();
+        engine_guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| "Sync engine not initialized".to_string())?
+    };
+
+    engine
+        .checkpoint_transfer(&transfer_id)
+        .await
+        .map_err(|e: SyncError| e.to_string())
+}
+
 // ============================================================================
 // Interrupted/Resumable Transfer Commands
 // ============================================================================
@@ -255,6 +617,23 @@ fn get_interrupted_transfers(state: State<'_, Arc<AppState>>) -> Result<Vec<Tran
     Ok(engine.get_interrupted_transfers())
 }
 
+/// Returns the files and bytes left in an interrupted transfer, so the UI can
+/// show what resuming would involve before the user picks resume or discard.
+#[tauri::command]
+fn get_resume_estimate(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+) -> Result<ResumeEstimate, String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .get_resume_estimate(&transfer_id)
+        .map_err(|e: SyncError| e.to_string())
+}
+
 /// Resumes an interrupted transfer from where it left off.
 /// This restarts the sync operation using the persisted state.
 #[tauri::command]
@@ -277,6 +656,33 @@ async fn resume_interrupted_transfer(
         .map_err(|e: SyncError| e.to_string())
 }
 
+/// Resumes an interrupted transfer onto a different destination.
+/// Use when the original destination drive is no longer available.
+#[tauri::command]
+async fn resume_interrupted_transfer_to(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+    new_destination: String,
+) -> Result<bool, String> {
+    let dest_path = PathBuf::from(&new_destination);
+
+    let dest_path = validate_destination_path(&dest_path)?;
+
+    let engine = {
+        let engine_guard = state.sync_engine.read();
+        engine_guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| "Sync engine not initialized".to_string())?
+    };
+
+    engine
+        .resume_interrupted_transfer_to(&transfer_id, dest_path)
+        .await
+        .map(|_| true)
+        .map_err(|e: SyncError| e.to_string())
+}
+
 /// Discards an interrupted transfer, removing its state from disk.
 /// Use when the user decides not to resume a transfer.
 #[tauri::command]
@@ -353,6 +759,9 @@ async fn scan_directory_stream(
     app: tauri::AppHandle,
     path: String,
     scan_id: String,
+    exclude_patterns: Option<Vec<String>>,
+    min_size: Option<u64>,
+    skip_hidden: Option<bool>,
 ) -> Result<(), String> {
     let path_buf = PathBuf::from(&path);
     
@@ -367,20 +776,31 @@ async fn scan_directory_stream(
         /// Larger chunks = fewer IPC calls but UI updates less frequently.
         const CHUNK_SIZE: usize = 1000;
         
-        let mut scanner = match file_ops::DirectoryScanner::new(&path_buf, false, CHUNK_SIZE) {
-            Ok(s) => s,
-            Err(e) => {
-                // Emit error event
-                let _ = app.emit("scan_error", serde_json::json!({
-                    "scan_id": scan_id,
-                    "error": e.to_string()
-                }));
-                return;
-            }
-        };
-        
+        // Apply the same exclusions the sync will use so the preview matches
+        let filter = file_ops::ScanFilter::from_patterns(
+            &exclude_patterns.unwrap_or_default(),
+            min_size,
+            skip_hidden.unwrap_or(false),
+        );
+
+        let mut scanner =
+            match file_ops::DirectoryScanner::new(&path_buf, false, CHUNK_SIZE, filter) {
+                Ok(s) => s,
+                Err(e) => {
+                    // Emit error event
+                    let _ = app.emit(
+                        "scan_error",
+                        serde_json::json!({
+                            "scan_id": scan_id,
+                            "error": e.to_string()
+                        }),
+                    );
+                    return;
+                }
+            };
+
         let mut chunk_index = 0;
-        
+
         loop {
             match scanner.next_chunk() {
                 Some(files) => {
@@ -416,6 +836,118 @@ async fn scan_directory_stream(
     Ok(())
 }
 
+/// Compares two folders without syncing either. Entries stream to the
+/// frontend as `directory_diff_chunk` events while both trees are walked;
+/// the totals are returned once the comparison finishes.
+#[tauri::command]
+async fn compare_directories(
+    app: tauri::AppHandle,
+    left: String,
+    right: String,
+    compare_mode: Option<CompareMode>,
+    compare_id: String,
+) -> Result<DirectoryDiffSummary, String> {
+    let left = validate_path(&PathBuf::from(&left)).map_err(|e| e.to_string())?;
+    let right = validate_path(&PathBuf::from(&right)).map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        /// Entries per chunk, matching the streaming scan
+        const CHUNK_SIZE: usize = 1000;
+
+        let mut chunk_index = 0;
+        let summary = file_ops::compare_directories(
+            &left,
+            &right,
+            compare_mode.unwrap_or_default(),
+            CHUNK_SIZE,
+            |entries| {
+                let chunk = DirectoryDiffChunk {
+                    compare_id: compare_id.clone(),
+                    entries,
+                    chunk_index,
+                    is_final: false,
+                };
+                chunk_index += 1;
+                // Stop walking if the app has gone away
+                app.emit("directory_diff_chunk", &chunk).is_ok()
+            },
+        );
+
+        let final_chunk = DirectoryDiffChunk {
+            compare_id,
+            entries: vec![],
+            chunk_index,
+            is_final: true,
+        };
+        let _ = app.emit("directory_diff_chunk", &final_chunk);
+        summary
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e: SyncError| e.to_string())
+}
+
+/// Temp and partial files unfinished transfers can still resume from.
+fn resumable_partial_paths(state: &AppState) -> Result<HashSet<PathBuf>, String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+    Ok(engine.resumable_partial_paths())
+}
+
+/// Lists leftover `.rsync-tmp` / `.rsync-partial` files under a directory.
+/// Non-destructive: use this to show the user what can be reclaimed.
+/// Files a transfer can still resume from are flagged `resumable`.
+#[tauri::command]
+async fn find_partial_files(
+    state: State<'_, Arc<AppState>>,
+    path: String,
+) -> Result<Vec<PartialFileInfo>, String> {
+    let path_buf = validate_path(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+    let resumable = resumable_partial_paths(&state)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        file_ops::find_partial_files(&path_buf, &resumable)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e: SyncError| e.to_string())
+}
+
+/// Removes leftover temp/partial files under a directory, keeping those an
+/// unfinished transfer can still resume from.
+/// Returns how many files and bytes were reclaimed.
+#[tauri::command]
+async fn clean_partial_files(
+    state: State<'_, Arc<AppState>>,
+    path: String,
+) -> Result<PartialCleanupReport, String> {
+    let path_buf = validate_path(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+    let keep = resumable_partial_paths(&state)?;
+
+    tauri::async_runtime::spawn_blocking(move || file_ops::clean_partial_files(&path_buf, &keep))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Finishes copying a file from the partial file an interrupted copy left next
+/// to `dest`, for when the transfer's saved state is gone. The partial file's
+/// prefix is checked against the source before the copy continues.
+#[tauri::command]
+async fn resume_from_partial(source: String, dest: String) -> Result<PartialResume, String> {
+    let source_path = validate_path(&PathBuf::from(&source)).map_err(|e| e.to_string())?;
+    let dest_path = validate_destination_path(&PathBuf::from(&dest))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        file_ops::resume_from_partial(&source_path, &dest_path, &file_ops::CopyOptions::default())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e: SyncError| e.to_string())
+}
+
 #[tauri::command]
 fn get_active_transfers(state: State<'_, Arc<AppState>>) -> Result<Vec<TransferState>, String> {
     let engine_guard = state.sync_engine.read();
@@ -474,13 +1006,17 @@ pub enum FrontendConflictResolution {
 
 /// Resolves a file conflict by applying the user's chosen resolution.
 /// This command is called from the frontend when the user makes a decision
-/// in the conflict resolution dialog.
+/// in the conflict resolution dialog. `source_path` and `dest_path` name the
+/// conflicting files for `KeepBoth`; without them they're looked up in the
+/// transfer by `conflict_id`.
 #[tauri::command]
 async fn resolve_conflict(
     state: State<'_, Arc<AppState>>,
     conflict_id: String,
     resolution: FrontendConflictResolution,
     transfer_id: Option<String>,
+    source_path: Option<PathBuf>,
+    dest_path: Option<PathBuf>,
 ) -> Result<(), String> {
     // Get the sync engine
     let engine = {
@@ -493,11 +1029,39 @@ async fn resolve_conflict(
 
     // Delegate to the sync engine's conflict resolution handler
     engine
-        .resolve_conflict(&conflict_id, resolution, transfer_id.as_deref())
+        .resolve_conflict(
+            &conflict_id,
+            resolution,
+            transfer_id.as_deref(),
+            source_path.zip(dest_path),
+        )
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Lists the conflict resolutions made this session, oldest first.
+#[tauri::command]
+fn get_resolved_conflicts(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<ResolvedConflict>, String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+    Ok(engine.get_resolved_conflicts())
+}
+
+/// Forgets the conflict resolutions made so far, e.g. between runs.
+#[tauri::command]
+fn clear_resolved_conflicts(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+    engine.clear_resolved_conflicts();
+    Ok(())
+}
+
 #[tauri::command]
 async fn get_volume_info(path: String) -> Result<VolumeInfo, String> {
     tauri::async_runtime::spawn_blocking(move || file_ops::get_volume_info(Path::new(&path)))
@@ -506,6 +1070,20 @@ async fn get_volume_info(path: String) -> Result<VolumeInfo, String> {
         .map_err(|e| e.to_string())
 }
 
+/// Benchmarks sequential write/read throughput of the volume at `path`.
+/// Writes and removes a temporary file; helps recommend buffer sizes and concurrency.
+#[tauri::command]
+async fn benchmark_volume(path: String) -> Result<VolumeBenchmark, String> {
+    let path_buf = validate_path(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        file_ops::benchmark_volume(&path_buf, file_ops::BENCHMARK_FILE_SIZE)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e: SyncError| e.to_string())
+}
+
 // ============================================================================
 // Volume Watcher Commands
 // ============================================================================
@@ -526,6 +1104,36 @@ async fn get_mounted_volumes(
     }
 }
 
+/// Rescans mounted volumes now, e.g. to show up-to-date free space right
+/// after a transfer rather than at the watcher's next poll.
+#[tauri::command]
+async fn refresh_volumes(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<volume_watcher::VolumeInfo>, String> {
+    let watcher_opt = state.volume_watcher.read().clone();
+
+    if let Some(watcher) = watcher_opt {
+        Ok(watcher.read().await.refresh_volumes())
+    } else {
+        Ok(volume_watcher::VolumeWatcher::new(None).refresh_volumes())
+    }
+}
+
+/// Updates the space figures of the one volume holding `mount_point`.
+#[tauri::command]
+async fn refresh_volume(
+    state: State<'_, Arc<AppState>>,
+    mount_point: PathBuf,
+) -> Result<volume_watcher::VolumeInfo, String> {
+    let watcher_opt = state.volume_watcher.read().clone();
+
+    let volume = match watcher_opt {
+        Some(watcher) => watcher.read().await.refresh_volume(&mount_point),
+        None => volume_watcher::get_volume_for_path(&mount_point),
+    };
+    volume.ok_or_else(|| format!("No mounted volume holds {}", mount_point.display()))
+}
+
 /// Checks if a path is on a removable/external volume.
 /// Useful for warning users before sync operations to external drives.
 #[tauri::command]
@@ -571,6 +1179,60 @@ async fn is_volume_accessible(
     }
 }
 
+/// Lists the transfers using the volume a path is on, so the UI can warn
+/// before the drive is ejected. Accepts a mount point or any path on the volume.
+#[tauri::command]
+async fn get_transfers_on_volume(
+    state: State<'_, Arc<AppState>>,
+    mount_point: String,
+) -> Result<Vec<String>, String> {
+    let path_buf = PathBuf::from(&mount_point);
+
+    // Clone the Arc outside the guard scope to avoid holding non-Send guard across await
+    let watcher_opt = state.volume_watcher.read().clone();
+
+    if let Some(watcher) = watcher_opt {
+        Ok(watcher.read().await.transfers_on_volume(&path_buf))
+    } else {
+        // No watcher means nothing has been registered
+        Ok(Vec::new())
+    }
+}
+
+/// Pauses the running transfers that use the volume a path is on, leaving
+/// transfers to other drives alone. Returns the IDs of the paused transfers.
+#[tauri::command]
+fn pause_transfers_on_volume(
+    state: State<'_, Arc<AppState>>,
+    mount_point: String,
+) -> Result<Vec<String>, String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .pause_transfers_on_volume(Path::new(&mount_point))
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Resumes the transfers `pause_transfers_on_volume` paused on a volume.
+/// Returns the IDs of the resumed transfers.
+#[tauri::command]
+fn resume_transfers_on_volume(
+    state: State<'_, Arc<AppState>>,
+    mount_point: String,
+) -> Result<Vec<String>, String> {
+    let engine_guard = state.sync_engine.read();
+    let engine = engine_guard
+        .as_ref()
+        .ok_or_else(|| "Sync engine not initialized".to_string())?;
+
+    engine
+        .resume_transfers_on_volume(Path::new(&mount_point))
+        .map_err(|e: SyncError| e.to_string())
+}
+
 /// Validates a glob pattern and returns an error message if invalid.
 /// Returns Ok(()) if the pattern is valid.
 #[tauri::command]
@@ -594,6 +1256,100 @@ fn validate_glob_pattern(pattern: String) -> Result<(), String> {
     }
 }
 
+/// Previews which files the given exclude patterns would skip under a directory.
+/// Cheap to call repeatedly: the directory scan is cached for a short while.
+#[tauri::command]
+async fn test_exclude_patterns(
+    path: String,
+    patterns: Vec<String>,
+) -> Result<ExcludePreview, String> {
+    let path_buf = validate_path(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || file_ops::preview_exclusions(&path_buf, &patterns))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Shows the merged global, per-sync and `.rsyncignore` exclude rules for a
+/// source and, when `path` is given, which layer excluded or re-included it.
+#[tauri::command]
+fn explain_excludes(
+    source: String,
+    path: Option<String>,
+    global_patterns: Vec<String>,
+    patterns: Vec<String>,
+) -> Result<ExcludeExplanation, String> {
+    let source_path = validate_path(&PathBuf::from(&source)).map_err(|e| e.to_string())?;
+    let relative = path.map(|p| {
+        let p = PathBuf::from(p);
+        p.strip_prefix(&source_path)
+            .map(Path::to_path_buf)
+            .unwrap_or(p)
+    });
+
+    Ok(file_ops::explain_exclusion(
+        &source_path,
+        &global_patterns,
+        &patterns,
+        relative.as_deref(),
+    ))
+}
+
+/// Checks include and exclude patterns for rules that are invalid, redundant,
+/// shadowed or contradictory, for the pattern editor. Nothing is scanned.
+#[tauri::command]
+fn analyze_patterns(includes: Vec<String>, excludes: Vec<String>) -> PatternAnalysis {
+    file_ops::analyze_patterns(&includes, &excludes)
+}
+
+/// Re-hashes files copied with `store_hash_xattr` and lists the ones whose
+/// contents no longer match the stored hash.
+#[tauri::command]
+async fn audit_hash_xattrs(path: String) -> Result<HashAudit, String> {
+    let path_buf = validate_path(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || file_ops::audit_hash_xattrs(&path_buf))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e: SyncError| e.to_string())
+}
+
+/// Reports the SMART health of the drive a path is on, so a backup drive can
+/// be checked before it is trusted. `Unknown` when the drive doesn't report it.
+#[tauri::command]
+async fn check_drive_health(path: String) -> Result<DriveHealth, String> {
+    let path_buf = validate_path(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || drive_health::check_drive_health(&path_buf))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Reports the cloud-synced folder (iCloud Drive, Dropbox, OneDrive, Google
+/// Drive) a path is in, so the UI can warn before a sync makes the client
+/// upload everything. `None` when the path isn't in one.
+#[tauri::command]
+async fn detect_cloud_provider(path: String) -> Result<Option<cloud_folder::CloudFolder>, String> {
+    let path_buf = validate_destination_path(&PathBuf::from(&path))?;
+
+    tauri::async_runtime::spawn_blocking(move || cloud_folder::detect_cloud_provider(&path_buf))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Measures how far a destination's clock is from the local one, with the
+/// `mtime_tolerance_seconds` a sync to it would need to not re-copy files.
+#[tauri::command]
+async fn measure_clock_skew(path: String) -> Result<volume_watcher::ClockSkew, String> {
+    let path_buf = validate_path(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || volume_watcher::measure_clock_skew(&path_buf))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e: SyncError| e.to_string())
+}
+
 /// Checks if a path exists on the filesystem.
 /// Used by the schedule runner to validate paths before starting transfers.
 #[tauri::command]
@@ -601,6 +1357,36 @@ fn path_exists(path: String) -> bool {
     Path::new(&path).exists()
 }
 
+/// Shows a file or folder selected in its enclosing folder
+/// (Finder on macOS, the system file manager elsewhere).
+#[tauri::command]
+fn reveal_in_finder(path: String) -> Result<(), String> {
+    let path_buf = validate_path(&PathBuf::from(&path)).map_err(|e| e.to_string())?;
+
+    tauri_plugin_opener::reveal_item_in_dir(&path_buf).map_err(|e| e.to_string())
+}
+
+/// Reveals a transfer's destination folder, e.g. from the "sync finished" view.
+#[tauri::command]
+fn reveal_transfer_destination(
+    state: State<'_, Arc<AppState>>,
+    transfer_id: String,
+) -> Result<(), String> {
+    let dest_path = {
+        let engine_guard = state.sync_engine.read();
+        let engine = engine_guard
+            .as_ref()
+            .ok_or_else(|| "Sync engine not initialized".to_string())?;
+        engine
+            .get_transfer_state(&transfer_id)
+            .map_err(|e: SyncError| e.to_string())?
+            .dest_path
+    };
+    let path_buf = validate_path(&dest_path).map_err(|e| e.to_string())?;
+
+    tauri_plugin_opener::reveal_item_in_dir(&path_buf).map_err(|e| e.to_string())
+}
+
 /// Seconds to wait before shutdown, giving user time to cancel.
 const SHUTDOWN_DELAY_SECONDS: u32 = 5;
 
@@ -698,9 +1484,69 @@ fn is_auto_start_enabled() -> bool {
     launch_agent::is_auto_start_enabled()
 }
 
+/// Checks that the Launch Agent starts the app where it is now, reporting
+/// `stale` after the app was moved so the UI can offer to re-register it.
+#[tauri::command]
+fn validate_auto_start() -> Result<AutoStartCheck, String> {
+    launch_agent::validate_auto_start().map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Sync Profile Commands
+// ============================================================================
+
+/// Saves a named sync profile (source, destination and options).
+#[tauri::command]
+fn save_profile(
+    name: String,
+    source: String,
+    destination: String,
+    options: SyncOptions,
+) -> Result<SyncProfile, String> {
+    sync_profile::save_profile(
+        &name,
+        PathBuf::from(source),
+        PathBuf::from(destination),
+        options,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Loads a saved sync profile by name.
+#[tauri::command]
+fn load_profile(name: String) -> Result<SyncProfile, String> {
+    sync_profile::load_profile(&name).map_err(|e| e.to_string())
+}
+
+/// Lists all saved sync profiles.
+#[tauri::command]
+fn list_profiles() -> Result<Vec<SyncProfile>, String> {
+    sync_profile::list_profiles().map_err(|e| e.to_string())
+}
+
+/// Deletes a saved sync profile.
+#[tauri::command]
+fn delete_profile(name: String) -> Result<(), String> {
+    sync_profile::delete_profile(&name).map_err(|e| e.to_string())
+}
+
+/// Exports all saved profiles as a JSON document.
+#[tauri::command]
+fn export_profiles() -> Result<String, String> {
+    sync_profile::export_profiles().map_err(|e| e.to_string())
+}
+
+/// Imports profiles from a JSON document produced by `export_profiles`.
+/// Rejects the whole import if any profile points at a blocked system path.
+#[tauri::command]
+fn import_profiles(json: String) -> Result<usize, String> {
+    sync_profile::import_profiles(&json).map_err(|e| e.to_string())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let app_state = Arc::new(AppState::new());
+    let exit_state = app_state.clone();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
@@ -757,32 +1603,65 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             sync_files,
+            rehearse_sync,
+            preview_orphans,
+            sync_from_rsync_daemon,
             get_transfer_state,
+            get_transfer_events,
+            export_transfer_report,
             pause_transfer,
+            set_bandwidth_limit,
+            set_max_concurrent_hashes,
+            get_max_concurrent_hashes,
             resume_transfer,
             cancel_transfer,
+            rollback_transfer,
+            discard_rollback_backups,
+            skip_current_file,
+            set_max_concurrent_transfers,
             check_fda,
             open_fda_settings,
             get_directory_info,
             quick_scan_directory,
             scan_directory_stream,
+            compare_directories,
+            find_partial_files,
+            clean_partial_files,
+            resume_from_partial,
             get_active_transfers,
             is_path_accessible,
             is_path_writable,
             path_exists,
+            reveal_in_finder,
+            reveal_transfer_destination,
             hash_file,
             prevent_sleep,
             allow_sleep,
             is_preventing_sleep,
             get_volume_info,
+            benchmark_volume,
             get_mounted_volumes,
+            refresh_volumes,
+            refresh_volume,
             is_on_removable_volume,
             get_path_volume_info,
             validate_sync_volumes,
             is_volume_accessible,
+            get_transfers_on_volume,
+            pause_transfers_on_volume,
+            resume_transfers_on_volume,
             resolve_conflict,
+            get_resolved_conflicts,
+            clear_resolved_conflicts,
             initiate_shutdown,
             validate_glob_pattern,
+            test_exclude_patterns,
+            explain_excludes,
+            analyze_patterns,
+            audit_hash_xattrs,
+            check_drive_health,
+            detect_cloud_provider,
+            measure_clock_skew,
             set_minimize_to_tray,
             update_tray_status,
             show_main_window,
@@ -790,11 +1669,33 @@ pub fn run() {
             enable_auto_start,
             disable_auto_start,
             is_auto_start_enabled,
+            validate_auto_start,
+            save_profile,
+            load_profile,
+            list_profiles,
+            delete_profile,
+            export_profiles,
+            import_profiles,
+            checkpoint_transfer,
             get_interrupted_transfers,
+            get_resume_estimate,
             resume_interrupted_transfer,
+            resume_interrupted_transfer_to,
             discard_transfer,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(move |_app_handle, event| {
+            // Quitting mid-copy would leave transfers marked Running with their
+            // last block unflushed; park and persist them before the process ends
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                let engine = exit_state.sync_engine.read().as_ref().cloned();
+                if let Some(engine) = engine {
+                    if let Err(e) = tauri::async_runtime::block_on(engine.shutdown()) {
+                        eprintln!("[App] Warning: Failed to save transfer state on exit: {}", e);
+                    }
+                }
+            }
+        });
 }
 
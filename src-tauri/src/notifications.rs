@@ -0,0 +1,143 @@
+//! Native desktop notifications for finished transfers.
+//!
+//! Used when the app is minimized to the tray so the user still learns when a
+//! long-running sync completes or fails. On macOS this shells out to
+//! `osascript`; other platforms are a no-op.
+
+use std::time::Duration;
+
+use crate::errors::{SyncError, SyncResult};
+use crate::sync_engine::SyncResult_;
+
+/// Title shown on all transfer notifications.
+const NOTIFICATION_TITLE: &str = "RSync";
+
+/// Formats a duration as a short human-readable string (e.g. "1h 2m", "3m 4s", "5s").
+pub fn format_duration(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let seconds = total_secs % 60;
+
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Builds the notification subtitle and body for a finished sync.
+/// Returns `None` for user cancellations, which don't need a notification.
+pub fn describe_sync_outcome(
+    outcome: &SyncResult<SyncResult_>,
+    elapsed: Duration,
+) -> Option<(String, String)> {
+    match outcome {
+        Ok(result) if result.cancelled => None,
+        Ok(result) if result.files_failed == 0 => Some((
+            "Sync complete".to_string(),
+            format!(
+                "Copied {} of {} files in {}",
+                result.files_copied,
+                result.files_total,
+                format_duration(elapsed)
+            ),
+        )),
+        Ok(result) => Some((
+            "Sync finished with errors".to_string(),
+            format!(
+                "Copied {} of {} files, {} failed ({})",
+                result.files_copied,
+                result.files_total,
+                result.files_failed,
+                format_duration(elapsed)
+            ),
+        )),
+        Err(SyncError::TransferCancelled(_)) => None,
+        Err(e) => Some((
+            "Sync failed".to_string(),
+            format!("{} (after {})", e, format_duration(elapsed)),
+        )),
+    }
+}
+
+/// Posts a notification describing how a sync finished.
+/// Runs on a blocking thread so a slow `osascript` never stalls the engine.
+pub fn notify_sync_finished(outcome: &SyncResult<SyncResult_>, elapsed: Duration) {
+    if let Some((subtitle, body)) = describe_sync_outcome(outcome, elapsed) {
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = post_notification(NOTIFICATION_TITLE, &subtitle, &body) {
+                eprintln!("[Notify] Failed to post notification: {}", e);
+            }
+        });
+    }
+}
+
+/// Escapes a string for use inside an AppleScript string literal.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn escape_applescript(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(target_os = "macos")]
+fn post_notification(title: &str, subtitle: &str, body: &str) -> SyncResult<()> {
+    use std::process::Command;
+
+    let script = format!(
+        r#"display notification "{}" with title "{}" subtitle "{}""#,
+        escape_applescript(body),
+        escape_applescript(title),
+        escape_applescript(subtitle)
+    );
+
+    let output = Command::new("osascript").args(["-e", &script]).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(SyncError::Internal(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn post_notification(_title: &str, _subtitle: &str, _body: &str) -> SyncResult<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "5s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_duration(Duration::from_secs(3720)), "1h 2m");
+    }
+
+    #[test]
+    fn test_escape_applescript() {
+        assert_eq!(
+            escape_applescript(r#"say "hi" \ bye"#),
+            r#"say \"hi\" \\ bye"#
+        );
+    }
+
+    #[test]
+    fn test_cancelled_sync_is_silent() {
+        let outcome: SyncResult<SyncResult_> = Err(SyncError::TransferCancelled(
+            "Transfer cancelled by user".into(),
+        ));
+        assert!(describe_sync_outcome(&outcome, Duration::from_secs(1)).is_none());
+
+        let partial: SyncResult<SyncResult_> = Ok(SyncResult_ {
+            files_copied: 3,
+            cancelled: true,
+            ..SyncResult_::default()
+        });
+        assert!(describe_sync_outcome(&partial, Duration::from_secs(1)).is_none());
+    }
+}
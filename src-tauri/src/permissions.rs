@@ -1,6 +1,30 @@
 //! macOS permissions handling for Full Disk Access.
 
-use crate::errors::SyncResult;
+use std::path::{Path, PathBuf};
+
+use crate::errors::{SyncError, SyncResult};
+
+/// Locations under the user's home directory that macOS privacy protection (TCC)
+/// blocks unless the app has Full Disk Access or a per-folder grant.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const FDA_PROTECTED_HOME_PATHS: &[&str] = &[
+    "Library/Mail",
+    "Library/Messages",
+    "Library/Safari",
+    "Library/Cookies",
+    "Library/Calendars",
+    "Library/Reminders",
+    "Library/HomeKit",
+    "Library/Containers",
+    "Library/Group Containers",
+    "Library/Application Support/com.apple.TCC",
+    "Library/Application Support/AddressBook",
+    "Library/Application Support/CallHistoryDB",
+    "Library/Metadata/CoreSpotlight",
+    "Documents",
+    "Desktop",
+    "Downloads",
+];
 
 /// Check if the application has Full Disk Access on macOS.
 /// 
@@ -100,6 +124,39 @@ pub fn open_full_disk_access_settings() -> SyncResult<()> {
     Ok(())
 }
 
+/// Returns true if `path` is in a location macOS guards with privacy protection.
+#[cfg(target_os = "macos")]
+pub fn is_fda_protected_path(path: &Path) -> bool {
+    if path.starts_with("/Library/Application Support/com.apple.TCC") {
+        return true;
+    }
+    match dirs::home_dir() {
+        Some(home) => FDA_PROTECTED_HOME_PATHS
+            .iter()
+            .any(|protected| path.starts_with(home.join(protected))),
+        None => false,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn is_fda_protected_path(_path: &Path) -> bool {
+    false
+}
+
+/// Pre-sync check for privacy-protected locations.
+/// Fails with `FullDiskAccessRequired` when `path` is protected, can't be read,
+/// and Full Disk Access is not granted, so the UI can prompt before the sync
+/// starts rather than hitting permission errors mid-transfer. Protected paths
+/// that are readable (e.g. via a per-folder grant) pass.
+pub fn require_full_disk_access(path: &Path) -> SyncResult<()> {
+    if is_fda_protected_path(path) && !check_path_accessible(path) && !check_full_disk_access() {
+        return Err(SyncError::FullDiskAccessRequired {
+            path: PathBuf::from(path),
+        });
+    }
+    Ok(())
+}
+
 pub fn check_path_accessible(path: &std::path::Path) -> bool {
     if !path.exists() {
         if let Some(parent) = path.parent() {
@@ -0,0 +1,172 @@
+//! Keeps Spotlight from working on files a sync is still writing (macOS).
+//!
+//! Two levels, both opt-in. Temp files can be tagged with the backup exclusion
+//! attribute while they are written and untagged once they are renamed into
+//! place. The destination volume's indexing can also be switched off with
+//! `mdutil` for the length of a transfer and switched back on afterwards,
+//! which saves Spotlight from importing every new file of a large first sync.
+//! Elsewhere both do nothing.
+
+use std::path::{Path, PathBuf};
+
+/// Attribute `tmutil addexclusion` sets to keep a file out of backups. Temp
+/// files carry it so nothing treats them as user data while they're written.
+pub const EXCLUDE_XATTR: &str = "com.apple.metadata:com_apple_backup_excludeItem";
+
+/// The attribute's value: a binary plist holding the string `com.apple.backupd`.
+#[cfg(target_os = "macos")]
+const EXCLUDE_XATTR_VALUE: &[u8] = b"bplist00_\x10\x11com.apple.backupd\x08\
+    \x00\x00\x00\x00\x00\x00\x01\x01\
+    \x00\x00\x00\x00\x00\x00\x00\x01\
+    \x00\x00\x00\x00\x00\x00\x00\x00\
+    \x00\x00\x00\x00\x00\x00\x00\x1c";
+
+/// Tags a temp file with `EXCLUDE_XATTR` while it is being written.
+#[cfg(target_os = "macos")]
+pub fn mark_excluded(path: &Path) -> std::io::Result<()> {
+    let (c_path, c_name) = xattr_names(path)?;
+    let result = unsafe {
+        libc::setxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            EXCLUDE_XATTR_VALUE.as_ptr() as *const libc::c_void,
+            EXCLUDE_XATTR_VALUE.len(),
+            0,
+            0,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Removes the tag `mark_excluded` set, once the file has its final name.
+/// A file without the tag is left as it is.
+#[cfg(target_os = "macos")]
+pub fn clear_excluded(path: &Path) -> std::io::Result<()> {
+    let (c_path, c_name) = xattr_names(path)?;
+    if unsafe { libc::removexattr(c_path.as_ptr(), c_name.as_ptr(), 0) } != 0 {
+        let error = std::io::Error::last_os_error();
+        if error.raw_os_error() != Some(libc::ENOATTR) {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn xattr_names(path: &Path) -> std::io::Result<(std::ffi::CString, std::ffi::CString)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let c_name = std::ffi::CString::new(EXCLUDE_XATTR).expect("static xattr name");
+    Ok((c_path, c_name))
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn mark_excluded(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn clear_excluded(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Keeps Spotlight indexing off on a destination volume until dropped.
+pub struct IndexingPause {
+    /// Volume to switch indexing back on for, or `None` if it was already off
+    volume: Option<PathBuf>,
+}
+
+impl IndexingPause {
+    /// Switches off indexing on the volume holding `destination`. The boot
+    /// volume is left alone, since that would stop Spotlight for the whole
+    /// machine. `mdutil -i` needs administrator rights; the error says why
+    /// indexing couldn't be paused, for the sync's warnings.
+    pub fn pause(destination: &Path) -> Result<Self, String> {
+        if !cfg!(target_os = "macos") {
+            return Ok(Self { volume: None });
+        }
+        let volume = crate::volume_watcher::get_volume_for_path(destination)
+            .map(|volume| volume.mount_point)
+            .ok_or_else(|| format!("No mounted volume holds {}", destination.display()))?;
+        if volume == Path::new("/") {
+            return Err("Spotlight indexing is only paused on external volumes".to_string());
+        }
+        if !platform::indexing_enabled(&volume) {
+            return Ok(Self { volume: None });
+        }
+        platform::set_indexing(&volume, false).map_err(|e| {
+            format!(
+                "Could not pause Spotlight indexing on {}: {}",
+                volume.display(),
+                e
+            )
+        })?;
+        eprintln!("[Spotlight] Paused indexing on {}", volume.display());
+        Ok(Self {
+            volume: Some(volume),
+        })
+    }
+}
+
+impl Drop for IndexingPause {
+    fn drop(&mut self) {
+        if let Some(volume) = self.volume.take() {
+            match platform::set_indexing(&volume, true) {
+                Ok(()) => eprintln!("[Spotlight] Resumed indexing on {}", volume.display()),
+                Err(e) => eprintln!(
+                    "[Spotlight] Failed to resume indexing on {}: {}; run `sudo mdutil -i on {}`",
+                    volume.display(),
+                    e,
+                    volume.display()
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::Path;
+    use std::process::Command;
+
+    /// `mdutil -s` prints "Indexing enabled." for an indexed volume.
+    pub fn indexing_enabled(volume: &Path) -> bool {
+        Command::new("mdutil")
+            .arg("-s")
+            .arg(volume)
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains("Indexing enabled"))
+            .unwrap_or(false)
+    }
+
+    pub fn set_indexing(volume: &Path, enabled: bool) -> Result<(), String> {
+        let output = Command::new("mdutil")
+            .args(["-i", if enabled { "on" } else { "off" }])
+            .arg(volume)
+            .output()
+            .map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use std::path::Path;
+
+    pub fn indexing_enabled(_volume: &Path) -> bool {
+        false
+    }
+
+    pub fn set_indexing(_volume: &Path, _enabled: bool) -> Result<(), String> {
+        Err("Spotlight is only available on macOS".to_string())
+    }
+}
@@ -3,30 +3,91 @@
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
-use tokio::sync::{Notify, Semaphore};
+use tokio::sync::{broadcast, Notify, Semaphore};
 use walkdir::WalkDir;
 
+use crate::drive_health::check_drive_health;
 use crate::errors::{SyncError, SyncResult};
 use crate::file_ops::{
-    copy_file_atomic, copy_file_with_progress, copy_symlink, cleanup_partial_files,
-    detect_delta_detailed, generate_conflict_name, scan_directory_with_options, 
-    CopyOptions, DeltaStatus, DirectoryInfo, FileInfo,
+    apply_file_flags, cleanup_partial_files, cleanup_temp_files, compare_directories,
+    copy_file_atomic, copy_symlink, copy_xattrs, counterpart_bytes, create_fifo,
+    detect_delta_with_xattrs, detect_delta_within, exceeds_entry_count, extended_length_path,
+    fat_incompatibility, generate_conflict_name, get_disk_space, get_partial_path, get_temp_path,
+    is_engine_dir, is_excluded, is_same_device, is_xattr_unsupported, keep_rollback_backup,
+    layered_exclude_rules, quick_scan_directory_with_options, rollback_backup_path,
+    scan_directory_with_options, symlinks_resolving_into, sync_parent_directory,
+    unlock_copied_flags, verify_copy_resumable, write_hash_xattr, CompareMode, CopyOptions,
+    DeltaInfo, DeltaStatus, DiffKind, DirectoryDiffEntry, DirectoryDiffSummary, DirectoryInfo,
+    DirectoryScanner, ExcludeMatcher, FatVariant, FileInfo, HashCheckpoint, HashLimiter,
+    ManifestFormat, OpenWriterIndex, ScanFilter, SpecialFileType, TimestampMode, COPY_BUFFER_SIZE,
+    READ_AHEAD_BUFFERS, ROLLBACK_DIR,
+};
+use crate::file_source::FileSource;
+use crate::io_priority::LowIoPriority;
+use crate::spotlight::IndexingPause;
+use crate::tar_pack::{
+    fits_header, transfer_batch, PACK_BATCH_BYTES, PACK_BATCH_FILES, PACK_FILE_THRESHOLD,
 };
 use crate::transfer_state::{
-    FileTransferState, TransferState, TransferStateManager, TransferStatus,
+    FileTransferState, OrphanDeletion, ReportFormat, ResumeEstimate, TransferEvent,
+    TransferEventKind, TransferState, TransferStateManager, TransferStatus,
+};
+use crate::volume_watcher::{
+    get_volume_for_path, measure_clock_skew, probe_network_destination, TransferVolumeRegistry,
+    VolumeInfo, VolumeWatcher,
 };
 
+/// An entry produced by either source walk in `scan_directory_with_error_tracking`.
+enum ScanEntry {
+    Entry(PathBuf, SyncResult<FileInfo>),
+    /// A directory or entry that couldn't be read
+    Failed {
+        path: Option<PathBuf>,
+        denied: bool,
+        message: String,
+    },
+}
+
+/// One entry of a directory listed by `parallel_walk`.
+enum ListedEntry {
+    Entry {
+        path: PathBuf,
+        info: SyncResult<FileInfo>,
+        is_dir: bool,
+    },
+    Excluded,
+    Failed {
+        path: Option<PathBuf>,
+        denied: bool,
+        message: String,
+    },
+}
+
+/// A directory's entries in read order, listed by `parallel_walk`.
+#[derive(Default)]
+struct DirListing {
+    entries: Vec<ListedEntry>,
+    subdirs: Vec<PathBuf>,
+    /// Set when the directory itself couldn't be read
+    error: Option<ScanEntry>,
+}
+
 /// Result of a directory scan operation, tracking any errors encountered
 #[derive(Debug)]
 pub struct ScanResult {
     pub info: DirectoryInfo,
     pub scan_complete: bool,
     pub scan_errors: Vec<String>,
+    /// Permission-denied entries skipped under `skip_unreadable`, relative to
+    /// the scanned root. Not scan errors, but their destinations are off
+    /// limits to orphan deletion
+    pub skipped_unreadable: Vec<PathBuf>,
 }
 
 impl ScanResult {
@@ -35,6 +96,7 @@ impl ScanResult {
             info,
             scan_complete: true,
             scan_errors: Vec::new(),
+            skipped_unreadable: Vec::new(),
         }
     }
 
@@ -43,6 +105,7 @@ impl ScanResult {
             info,
             scan_complete: errors.is_empty(),
             scan_errors: errors,
+            skipped_unreadable: Vec::new(),
         }
     }
 
@@ -56,6 +119,43 @@ impl ScanResult {
 pub enum SyncMode {
     Copy,
     Move,
+    /// Copies and verifies every file first, then removes the sources in a
+    /// second pass only if the whole copy phase succeeded
+    #[serde(rename = "move_verified")]
+    MoveVerified,
+}
+
+/// Order regular files are handed to the copy workers in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyOrder {
+    /// Directory walk order
+    #[default]
+    AsScanned,
+    /// The file count climbs fastest early on
+    SmallestFirst,
+    /// The files most likely to fail or run out of space go first
+    LargestFirst,
+    /// By relative path
+    Alphabetical,
+}
+
+impl CopyOrder {
+    /// Sorts `files` in place; ties keep path order, so the result is stable.
+    fn sort<F: std::borrow::Borrow<FileInfo>>(self, files: &mut [F]) {
+        match self {
+            CopyOrder::AsScanned => {}
+            CopyOrder::SmallestFirst => files.sort_by(|a, b| {
+                let (a, b) = (a.borrow(), b.borrow());
+                a.size.cmp(&b.size).then_with(|| a.path.cmp(&b.path))
+            }),
+            CopyOrder::LargestFirst => files.sort_by(|a, b| {
+                let (a, b) = (a.borrow(), b.borrow());
+                b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path))
+            }),
+            CopyOrder::Alphabetical => files.sort_by(|a, b| a.borrow().path.cmp(&b.borrow().path)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -65,6 +165,96 @@ pub enum ConflictResolution {
     Skip,
     Rename,
     Ask,
+    /// Keep whichever side was modified last, falling back to the larger
+    /// file when both have the same mtime
+    Newest,
+    /// Keep whichever side is larger, falling back to the newer file when
+    /// both have the same size
+    Largest,
+}
+
+impl ConflictResolution {
+    /// Whether a modified file's destination gets replaced by the source.
+    /// `Rename` writes the source next to it instead.
+    pub fn replaces_destination(self, delta: &DeltaInfo) -> bool {
+        match self {
+            ConflictResolution::Skip | ConflictResolution::Ask => false,
+            ConflictResolution::Overwrite | ConflictResolution::Rename => true,
+            ConflictResolution::Newest if delta.source_newer || delta.source_older => {
+                delta.source_newer
+            }
+            ConflictResolution::Largest if delta.size_differs => delta.source_larger,
+            ConflictResolution::Newest | ConflictResolution::Largest => {
+                delta.source_newer || delta.source_larger
+            }
+        }
+    }
+}
+
+/// Compiled `SyncOptions::conflict_rules`, built once per run like the exclude matcher.
+#[derive(Debug, Clone)]
+pub struct ConflictRuleMatcher {
+    set: GlobSet,
+    /// Resolution for each glob in `set`, by index
+    resolutions: Vec<ConflictResolution>,
+}
+
+impl ConflictRuleMatcher {
+    /// Compiles the rules, dropping invalid globs. Returns None if no rule is usable.
+    pub fn compile(rules: &[(String, ConflictResolution)]) -> Option<Self> {
+        let mut builder = GlobSetBuilder::new();
+        let mut resolutions = Vec::new();
+
+        for (pattern, resolution) in rules {
+            match Glob::new(pattern) {
+                Ok(glob) => {
+                    builder.add(glob);
+                    resolutions.push(*resolution);
+                }
+                Err(_) => eprintln!(
+                    "Warning: Invalid conflict rule pattern ignored: {}",
+                    pattern
+                ),
+            }
+        }
+
+        if resolutions.is_empty() {
+            return None;
+        }
+        let set = builder.build().ok()?;
+        Some(Self { set, resolutions })
+    }
+
+    /// Resolution of the first rule matching the relative path or its file name.
+    pub fn resolve(&self, path: &Path) -> Option<ConflictResolution> {
+        let mut first = self.set.matches(path).into_iter().min();
+        if let Some(name) = path.file_name() {
+            first = first.into_iter().chain(self.set.matches(name)).min();
+        }
+        first.map(|index| self.resolutions[index])
+    }
+}
+
+/// What to do when an individual file fails to transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorPolicy {
+    /// Record the failure and keep going with the remaining files
+    #[default]
+    Continue,
+    /// Stop the whole transfer on the first failed file
+    AbortTransfer,
+}
+
+/// What to do when a moved file was copied but its source could not be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MoveFailurePolicy {
+    /// List the file in `SyncResult_::move_failures` so it can be removed by hand
+    #[default]
+    Record,
+    /// Count the file as a plain copy and only log a warning
+    Warn,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,7 +263,32 @@ pub struct SyncOptions {
     pub destination: PathBuf,
     pub mode: SyncMode,
     pub conflict_resolution: ConflictResolution,
+    /// Name for the copy kept beside a conflicting destination file, added
+    /// to its stem; see `generate_conflict_name` for the tokens. `None`
+    /// appends `_{timestamp}`
+    #[serde(default)]
+    pub conflict_name_template: Option<String>,
+    /// Before overwriting a destination file, keep a copy of it under
+    /// `.rsync-rollback/<transfer id>` in the destination root, so
+    /// `rollback_transfer` can restore it. Discard with `discard_rollback_backups`
+    #[serde(default)]
+    pub keep_rollback_backups: bool,
     pub verify_integrity: bool,
+    /// Without `verify_integrity`, verify this percentage of the copied files,
+    /// picked at random, for some confidence at a fraction of the cost
+    #[serde(default)]
+    pub verify_sample_percent: f64,
+    /// Along with the sample, always verify this many of the largest files
+    #[serde(default)]
+    pub verify_largest_files: usize,
+    /// Verify by hashing the bytes as they're written rather than reading each
+    /// copied file back, halving the I/O of `verify_integrity`. This checks
+    /// what was sent to the destination, not what its media stored, so leave
+    /// it off for drives you don't trust
+    #[serde(default)]
+    pub verify_during_copy: bool,
+    /// Keep the source's mtimes and permissions, and on macOS its file flags
+    /// (hidden, locked, nodump, archived)
     pub preserve_metadata: bool,
     pub delete_orphans: bool,
     pub buffer_size: Option<usize>,
@@ -81,30 +296,347 @@ pub struct SyncOptions {
     pub dry_run: bool,
     #[serde(default)]
     pub follow_symlinks: bool,
-    /// Maximum number of files to copy in parallel (1-8)
+    /// Maximum number of files to copy in parallel (1-8). Clamped to 1 when
+    /// source and destination share a spinning disk, unless `force_parallel` is set
     #[serde(default = "default_max_concurrent_files")]
     pub max_concurrent_files: usize,
+    /// Most `verify_integrity` hashes at once across all parallel copies, so
+    /// many files can copy in parallel while their hashing is serialized.
+    /// Defaults to the copy concurrency
+    #[serde(default)]
+    pub max_concurrent_hashes: Option<usize>,
+    /// Cap on copy buffer memory across all files copied in parallel. Buffers
+    /// are shrunk first, then fewer files are copied at once, to stay within it
+    #[serde(default)]
+    pub max_memory_bytes: Option<u64>,
     /// Only copy if source is newer than destination
     #[serde(default)]
     pub overwrite_newer: bool,
+    /// Keep `max_concurrent_files` even when source and destination share a hard disk
+    #[serde(default)]
+    pub force_parallel: bool,
+    /// Start with one file at a time and copy more in parallel while that
+    /// raises throughput, up to the limit `max_concurrent_files` would allow
+    #[serde(default)]
+    pub adaptive_concurrency: bool,
+    /// Read each file ahead on its own thread while the previous block is
+    /// written, for spinning disks and network sources where either side stalls
+    #[serde(default)]
+    pub pipelined_copy: bool,
+    /// Copy at background I/O priority so the machine stays responsive during
+    /// a big backup; the disk still runs at full speed when nothing else needs it
+    #[serde(default)]
+    pub low_priority: bool,
+    /// After a verified copy, store the file's hash in the `user.rsync.xxh3`
+    /// extended attribute on the destination for later bit-rot audits
+    #[serde(default)]
+    pub store_hash_xattr: bool,
+    /// Skip source files another process has open for writing or write-locked,
+    /// since copying them mid-write gives a torn copy (macOS and Linux)
+    #[serde(default)]
+    pub skip_open_files: bool,
+    /// Download and copy iCloud Drive placeholders whose contents aren't on
+    /// disk. Off by default, which leaves them out and lists them in
+    /// `dataless_skipped`, so a backup can't pull down a whole cloud library
+    #[serde(default)]
+    pub materialize_dataless: bool,
+    /// Stat directory entries on several threads while scanning the source.
+    /// Enabled automatically for network and removable sources, and never
+    /// used with `follow_symlinks`
+    #[serde(default)]
+    pub parallel_scan: bool,
+    /// Threads used by a parallel scan (default `DEFAULT_SCAN_THREADS`)
+    #[serde(default)]
+    pub scan_threads: Option<usize>,
+    /// Higher-priority transfers start first when more are queued than can run
+    #[serde(default)]
+    pub priority: i32,
+    /// Copy regular files one at a time in relative-path order, so the copy
+    /// order is reproducible and can be audited from the log
+    #[serde(default)]
+    pub deterministic_order: bool,
+    /// Order files are copied in. `deterministic_order` overrides it with
+    /// path order; streaming syncs sort each scanned chunk on its own
+    #[serde(default)]
+    pub copy_order: CopyOrder,
     /// Only copy if source is older than destination
     #[serde(default)]
     pub overwrite_older: bool,
     /// Skip files that already exist at destination
     #[serde(default)]
     pub skip_existing: bool,
-    /// Glob patterns for files/directories to exclude
+    /// Re-copy a non-empty source over a 0-byte destination file whatever the
+    /// mtimes and conflict settings say, since that is what a run interrupted
+    /// between creating and writing the file leaves behind
+    #[serde(default)]
+    pub recopy_zero_byte_dest: bool,
+    /// Glob patterns for files/directories to exclude; a leading `!` re-includes
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
+    /// App-wide exclude patterns, overridden by `exclude_patterns` and then by
+    /// the source's `.rsyncignore`
+    #[serde(default)]
+    pub global_exclude_patterns: Vec<String>,
     /// Bandwidth limit in bytes per second (0 = unlimited)
     #[serde(default)]
     pub bandwidth_limit: u64,
+    /// Refuse to start if the transfer would write more than this many bytes
+    #[serde(default)]
+    pub max_write_bytes: Option<u64>,
+    /// Refuse to start if the destination would end up fuller than this percentage
+    #[serde(default)]
+    pub max_dest_fill_percent: Option<f64>,
+    /// Check the destination drive's SMART health before copying, warning
+    /// (without refusing) if it reports bad sectors or a failed self-assessment
+    #[serde(default)]
+    pub check_drive_health: bool,
+    /// Before starting, write a test file on each destination that is a
+    /// network mount, failing with `DestinationNotWritable` if the share is
+    /// read-only or refuses the write
+    #[serde(default)]
+    pub probe_network_destination: bool,
+    /// Treat mtimes up to this many seconds apart as equal, like rsync's
+    /// `--modify-window`, so a destination with a skewed clock or coarse
+    /// timestamps doesn't have every file copied again. Capped at a day
+    #[serde(default)]
+    pub mtime_tolerance_seconds: Option<u64>,
+    /// Before starting, measure each destination's clock skew with a probe
+    /// file and widen `mtime_tolerance_seconds` to cover it, with a warning
+    #[serde(default)]
+    pub detect_clock_skew: bool,
+    /// Tag temp files so they're left out of backups and indexing until
+    /// they're renamed into place (macOS)
+    #[serde(default)]
+    pub exclude_temp_from_indexing: bool,
+    /// Switch off Spotlight indexing on external destination volumes for the
+    /// length of the sync (macOS). Needs administrator rights; when indexing
+    /// can't be paused the sync runs anyway with a warning
+    #[serde(default)]
+    pub pause_spotlight: bool,
+    /// Pause the transfer when destination free space drops below this many bytes
+    #[serde(default)]
+    pub low_disk_space_threshold: Option<u64>,
+    /// Emit `transfer-stalled` when no file makes progress for this many
+    /// seconds while the transfer isn't paused
+    #[serde(default)]
+    pub stall_timeout_secs: Option<u64>,
+    /// Fail a stalled transfer with `SyncError::Timeout` instead of only
+    /// reporting it
+    #[serde(default)]
+    pub fail_on_stall: bool,
+    /// Retry the final rename by moving a locked/busy destination aside
+    #[serde(default)]
+    pub force_overwrite_locked: bool,
+    /// Copy extended ACLs along with the permission bits
+    #[serde(default)]
+    pub preserve_acl: bool,
+    /// Copy extended attributes such as Finder tags, and re-sync files whose
+    /// attributes changed even when their contents didn't
+    #[serde(default)]
+    pub preserve_xattr: bool,
+    /// Whether copied files keep the source's mtime or get the current time
+    #[serde(default)]
+    pub timestamp_mode: TimestampMode,
+    /// Post a native notification when the transfer completes or fails
+    #[serde(default)]
+    pub notify_on_complete: bool,
+    /// With no app handle to emit events to, print progress to stdout as
+    /// newline-delimited JSON instead, for scripts driving a headless engine
+    #[serde(default)]
+    pub progress_ndjson: bool,
+    /// Whether a failed file aborts the transfer or is recorded and skipped
+    #[serde(default)]
+    pub on_error: ErrorPolicy,
+    /// In move mode, how a source that couldn't be removed after copying is reported
+    #[serde(default)]
+    pub on_move_failure: MoveFailurePolicy,
+    /// Recreate FIFOs at the destination instead of skipping special files
+    #[serde(default)]
+    pub preserve_special: bool,
+    /// Skip source entries the scan isn't permitted to read instead of counting
+    /// them as scan errors, so orphan deletion still runs. Nothing at or under a
+    /// skipped path is deleted from the destination. Other I/O errors, and any
+    /// error in a streaming scan, still block orphan deletion
+    #[serde(default)]
+    pub skip_unreadable: bool,
+    /// On drive disconnect, wait this long for the drive to return and resume
+    /// instead of failing immediately
+    #[serde(default)]
+    pub reconnect_timeout_secs: Option<u64>,
+    /// Copy new files under `PACK_FILE_THRESHOLD` in tar batches instead of
+    /// one at a time, for trees of many tiny files on slow or network drives.
+    /// Each unpacked file is checked against its source's hash
+    #[serde(default)]
+    pub pack_small_files: bool,
+    /// Mirror the tree only: each new file is created empty, with the source's
+    /// mtime, instead of being copied. Existing destination files are left
+    /// alone, and moves keep their sources
+    #[serde(default)]
+    pub structure_only: bool,
+    /// Only consider source files modified since the last successful sync of
+    /// this source/destination pair (everything on the first run)
+    #[serde(default)]
+    pub incremental_since_marker: bool,
+    /// Process the source tree chunk by chunk instead of scanning it all up
+    /// front. Turned on automatically above `STREAMING_ENTRY_THRESHOLD` entries,
+    /// with a warning in the result. A streaming transfer can't be rolled back.
+    #[serde(default)]
+    pub streaming: bool,
+    /// Copy into a staging directory next to the destination and swap it into
+    /// place only once the whole tree has been copied. Readers never see a
+    /// half-copied destination, and a failed copy leaves the old tree untouched.
+    #[serde(default)]
+    pub atomic_publish: bool,
+    /// Glob → resolution overrides for `conflict_resolution`; the first rule
+    /// matching a file's relative path wins
+    #[serde(default)]
+    pub conflict_rules: Vec<(String, ConflictResolution)>,
+    /// Every source directory is created at the destination up front. With this
+    /// set, directories left empty after the copy (because all of their files
+    /// were excluded or skipped) are removed again
+    #[serde(default)]
+    pub prune_empty_dirs: bool,
+    /// With `prune_empty_dirs`, still keep destination directories whose source
+    /// directory is itself empty, so empty source directories are always mirrored
+    #[serde(default)]
+    pub ensure_empty_dirs: bool,
+    /// After a sync with no failed files, write a checksum manifest
+    /// (`SHA256SUMS` or `MD5SUMS`) at the top of the destination
+    #[serde(default)]
+    pub write_manifest: Option<ManifestFormat>,
+    /// Spanning mode: further destination roots, each on its own drive, used
+    /// after `destination` fills. Files are packed by size across the roots and
+    /// each file's root is recorded in the transfer state. Directories,
+    /// symlinks and special files all go to `destination`
+    #[serde(default)]
+    pub spanning_destinations: Vec<PathBuf>,
+    /// Only sync these subdirectories of the source, each to the same relative
+    /// path in the destination. Only their subtrees are walked, and orphan
+    /// deletion stays inside them too
+    #[serde(default)]
+    pub subdirs: Option<Vec<PathBuf>>,
+    /// `conflict_rules` compiled by `run_sync`
+    #[serde(skip)]
+    pub(crate) conflict_matcher: Option<Arc<ConflictRuleMatcher>>,
+    /// Set by a resume to the interrupted transfer the run carries on,
+    /// instead of starting a new one
+    #[serde(skip)]
+    pub(crate) resume_of: Option<String>,
+    /// Debug builds only: fake each file copy with sleeps and progress events
+    /// instead of real I/O, for UI development and demos
+    #[cfg(debug_assertions)]
+    #[serde(default)]
+    pub simulate: bool,
+}
+
+impl SyncOptions {
+    /// Conflict resolution for one file, from the first matching conflict rule
+    /// or the global `conflict_resolution`.
+    pub fn conflict_resolution_for(&self, relative_path: &Path) -> ConflictResolution {
+        self.conflict_matcher
+            .as_ref()
+            .and_then(|matcher| matcher.resolve(relative_path))
+            .unwrap_or(self.conflict_resolution)
+    }
+
+    /// How far apart mtimes may be and still count as equal, at most
+    /// `MAX_MTIME_TOLERANCE_SECS`.
+    pub fn modify_window(&self) -> chrono::Duration {
+        let seconds = self
+            .mtime_tolerance_seconds
+            .unwrap_or(0)
+            .min(MAX_MTIME_TOLERANCE_SECS);
+        chrono::Duration::seconds(seconds as i64)
+    }
+}
+
+/// Prints one `{"event": ..., "payload": ...}` line to stdout, the format of
+/// `SyncOptions::progress_ndjson`.
+pub fn print_ndjson<T: Serialize>(event: &str, payload: &T) {
+    use std::io::Write;
+    let line = serde_json::json!({ "event": event, "payload": payload });
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
 }
 
 fn default_max_concurrent_files() -> usize {
     4
 }
 
+/// Largest `mtime_tolerance_seconds` honoured. A day covers any real clock
+/// skew or timestamp granularity; beyond it files that did change would be
+/// skipped as unchanged.
+const MAX_MTIME_TOLERANCE_SECS: u64 = 86_400;
+
+/// Trees with more entries than this are synced through the streaming path.
+const STREAMING_ENTRY_THRESHOLD: usize = 1_000_000;
+
+/// Files taken from the scanner per streaming chunk. Bounds both the in-memory
+/// file list and the per-file transfer state, which is rewritten on every block.
+const STREAMING_CHUNK_SIZE: usize = 2_000;
+
+/// How often the source/destination paths are re-checked while waiting for a
+/// disconnected drive, in addition to volume watcher mount notifications.
+const RECONNECT_POLL_INTERVAL_SECS: u64 = 2;
+
+/// Throughput reported by simulated copies (debug builds only).
+#[cfg(debug_assertions)]
+const SIMULATED_BYTES_PER_SEC: u64 = 50 * 1024 * 1024;
+
+/// Granularity of simulated progress updates (debug builds only).
+#[cfg(debug_assertions)]
+const SIMULATED_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// How long `checkpoint_transfer` waits for in-flight files to flush.
+/// One block is at most `COPY_BUFFER_SIZE`, so this only trips on a stalled drive.
+const CHECKPOINT_TIMEOUT_SECS: u64 = 30;
+
+/// How long `rollback_transfer` waits for the in-flight copies of a cancelled
+/// transfer to stop before undoing what they wrote.
+const ROLLBACK_TIMEOUT_SECS: u64 = 30;
+
+/// How long `shutdown` waits for in-flight files to flush before persisting
+/// state anyway. Shorter than a checkpoint, since it holds up quitting the app.
+const SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+
+/// Most orphans listed by `preview_orphans`; the counts still cover everything.
+const ORPHAN_PREVIEW_MAX_PATHS: usize = 10_000;
+
+/// Orphans deleted between saves of the deletion progress.
+const ORPHAN_CHECKPOINT_INTERVAL: usize = 256;
+
+/// Buffers a file copy holds at once: the read buffer, the writer's and the
+/// block in flight between them, each `buffer_size` bytes.
+const COPY_BUFFERS_PER_FILE: u64 = 3;
+
+/// Smallest buffer `max_memory_bytes` shrinks to before copying fewer files
+/// at once; below this, per-call overhead dominates the copy.
+const MIN_BUDGET_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Threads a parallel source scan uses unless `SyncOptions::scan_threads` is set.
+/// Scans are bound by stat latency, not CPU, so this is well above the core count.
+const DEFAULT_SCAN_THREADS: usize = 16;
+
+/// Space left free on every spanning destination for directories and metadata.
+const SPANNING_RESERVE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Per-file allowance on top of its size when packing spanning destinations,
+/// matching the headroom `copy_file_atomic` requires.
+const SPANNING_FILE_OVERHEAD_BYTES: u64 = 4096;
+
+/// How often the destination free space is polled while a transfer runs.
+const DISK_SPACE_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Longest gap between stall checks; shorter stall timeouts are checked more often.
+const STALL_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Throughput window `adaptive_concurrency` measures each concurrency level over.
+const ADAPTIVE_WINDOW_SECS: u64 = 3;
+
+/// Gain in throughput over the previous level that justifies one more parallel copy.
+const ADAPTIVE_MIN_GAIN: f64 = 0.10;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncResult_ {
     pub files_total: usize,
@@ -115,6 +647,395 @@ pub struct SyncResult_ {
     pub bytes_copied: u64,
     pub duration_ms: u64,
     pub errors: Vec<String>,
+    /// FIFOs, sockets and device files that were not copied
+    pub special_files_skipped: usize,
+    /// Moved files that were copied but whose source could not be removed
+    pub move_failures: Vec<MoveFailure>,
+    /// Files skipped because the FAT/exFAT destination can't store them
+    pub incompatible_files: Vec<IncompatibleFile>,
+    /// Number of files actually copied in parallel; with `adaptive_concurrency`,
+    /// the level the controller settled on
+    pub effective_concurrency: usize,
+    /// Why `effective_concurrency` is lower than `max_concurrent_files`, if it is
+    pub concurrency_limited_reason: Option<String>,
+    /// Checksum manifest written for `write_manifest`; with spanning, the one
+    /// on `destination` (every other drive gets its own)
+    pub manifest_path: Option<PathBuf>,
+    /// Source entries skipped by `skip_unreadable` because they couldn't be read
+    pub unreadable_skipped: Vec<PathBuf>,
+    /// Cloud placeholders left out because `materialize_dataless` was off
+    pub dataless_skipped: Vec<PathBuf>,
+    /// Results of `verify_sample_percent`; `None` when no sample was taken
+    pub verify_sample: Option<SampleVerification>,
+    /// Sources removed by the second pass of a `MoveVerified` sync, for auditing
+    pub sources_deleted: Vec<PathBuf>,
+    /// Files left out by `skip_open_files`, with the process writing to each
+    pub open_files_skipped: Vec<SkippedOpenFile>,
+    /// Problems that didn't fail any file, such as hashes that couldn't be stored
+    pub warnings: Vec<String>,
+    /// The transfer was cancelled; the counts cover what finished before that
+    pub cancelled: bool,
+    /// Files and bytes copied per lowercased extension; files without one are under ""
+    pub by_extension: HashMap<String, ExtensionTotals>,
+    /// Verified copies whose sources the second pass of a `MoveVerified` sync
+    /// removes; only filled when the copy phase had no failures
+    #[serde(skip)]
+    pub(crate) verified_for_move: Vec<PathBuf>,
+}
+
+/// Files and bytes copied with one extension, for `SyncResult_::by_extension`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ExtensionTotals {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl ExtensionTotals {
+    /// Adds a copied file to the totals of its extension.
+    fn record(
+        totals: &parking_lot::Mutex<HashMap<String, ExtensionTotals>>,
+        path: &Path,
+        bytes: u64,
+    ) {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let mut totals = totals.lock();
+        let entry = totals.entry(extension).or_default();
+        entry.files += 1;
+        entry.bytes += bytes;
+    }
+}
+
+/// A source file the destination filesystem can't store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncompatibleFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// A source file skipped because another process was writing to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedOpenFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// What the sampled verification of `verify_sample_percent` found.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SampleVerification {
+    /// Copied files that were verified, matching or not
+    pub files_sampled: usize,
+    /// Sampled copies that matched their source
+    pub files_verified: usize,
+    pub bytes_verified: u64,
+    /// Sampled copies that didn't match their source; these also count as failed
+    pub mismatched: Vec<PathBuf>,
+}
+
+impl SampleVerification {
+    /// Counts the outcome of copying a sampled file. Files that were skipped
+    /// or unchanged weren't verified and aren't counted.
+    fn record(&mut self, path: &Path, size: u64, outcome: &SyncResult<u64>, verified: bool) {
+        match outcome {
+            Ok(_) if verified => {
+                self.files_sampled += 1;
+                self.files_verified += 1;
+                self.bytes_verified += size;
+            }
+            Err(SyncError::IntegrityCheckFailed { .. } | SyncError::HashMismatch(_)) => {
+                self.files_sampled += 1;
+                self.mismatched.push(path.to_path_buf());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Picks the files `verify_sample_percent` verifies as they're queued.
+struct VerifySampler {
+    percent: f64,
+    largest: usize,
+    /// Varies the random pick from run to run
+    seed: u64,
+    /// Sizes of the `largest` biggest files offered so far, smallest on top
+    top_sizes: BinaryHeap<Reverse<u64>>,
+}
+
+impl VerifySampler {
+    /// `None` when nothing is sampled, including when `verify_integrity`
+    /// verifies every file anyway.
+    fn new(options: &SyncOptions) -> Option<Self> {
+        let sampling = options.verify_sample_percent > 0.0 || options.verify_largest_files > 0;
+        (sampling && !options.verify_integrity && !options.dry_run).then(|| Self {
+            percent: options.verify_sample_percent.min(100.0),
+            largest: options.verify_largest_files,
+            seed: uuid::Uuid::new_v4().as_u64_pair().0,
+            top_sizes: BinaryHeap::new(),
+        })
+    }
+
+    /// Takes the sizes of files about to be queued, before `select` is asked
+    /// about them. Offered in batches, every file among the overall largest
+    /// is still among the largest seen when it's selected.
+    fn offer(&mut self, sizes: impl IntoIterator<Item = u64>) {
+        for size in sizes {
+            if self.top_sizes.len() < self.largest {
+                self.top_sizes.push(Reverse(size));
+            } else if self
+                .top_sizes
+                .peek()
+                .is_some_and(|Reverse(smallest)| size > *smallest)
+            {
+                self.top_sizes.pop();
+                self.top_sizes.push(Reverse(size));
+            }
+        }
+    }
+
+    /// Whether `file` gets verified: one of the largest offered so far, or in
+    /// the random share, which hashes the path so the pick is spread evenly.
+    fn select(&self, file: &FileInfo) -> bool {
+        let among_largest = self
+            .top_sizes
+            .peek()
+            .is_some_and(|Reverse(smallest)| file.size >= *smallest);
+        let path = file.path.as_os_str().as_encoded_bytes();
+        let hash = xxhash_rust::xxh3::xxh3_64_with_seed(path, self.seed);
+        among_largest || (hash as f64 / u64::MAX as f64) * 100.0 < self.percent
+    }
+}
+
+/// What the per-file copy tasks of one run share: the counters and lists
+/// they fill in, read back into the result once every task has finished.
+/// Used by both the scanned and the streaming sync.
+#[derive(Clone)]
+struct FileTasks {
+    transfer_id: String,
+    source_root: PathBuf,
+    control: Arc<TransferControl>,
+    state_manager: Arc<TransferStateManager>,
+    app_handle: Option<AppHandle>,
+    open_writers: Option<Arc<OpenWriterIndex>>,
+    files_copied: Arc<AtomicUsize>,
+    files_failed: Arc<AtomicUsize>,
+    bytes_copied: Arc<AtomicUsize>,
+    errors: Arc<parking_lot::Mutex<Vec<String>>>,
+    /// First failure that stops the run: any under `ErrorPolicy::AbortTransfer`,
+    /// or a disconnect when the transfer waits for the drive
+    abort_error: Arc<parking_lot::Mutex<Option<SyncError>>>,
+    move_failures: Arc<parking_lot::Mutex<Vec<MoveFailure>>>,
+    verified_moves: Arc<parking_lot::Mutex<Vec<PathBuf>>>,
+    by_extension: Arc<parking_lot::Mutex<HashMap<String, ExtensionTotals>>>,
+    files_skipped_by_user: Arc<AtomicUsize>,
+    open_files_skipped: Arc<parking_lot::Mutex<Vec<SkippedOpenFile>>>,
+    sample: Arc<parking_lot::Mutex<SampleVerification>>,
+}
+
+impl FileTasks {
+    fn new(
+        transfer_id: &str,
+        source_root: &Path,
+        options: &SyncOptions,
+        control: &Arc<TransferControl>,
+        state_manager: &Arc<TransferStateManager>,
+        app_handle: Option<&AppHandle>,
+    ) -> Self {
+        Self {
+            transfer_id: transfer_id.to_string(),
+            source_root: source_root.to_path_buf(),
+            control: control.clone(),
+            state_manager: state_manager.clone(),
+            app_handle: app_handle.cloned(),
+            open_writers: options
+                .skip_open_files
+                .then(|| Arc::new(OpenWriterIndex::new(source_root))),
+            files_copied: Arc::new(AtomicUsize::new(0)),
+            files_failed: Arc::new(AtomicUsize::new(0)),
+            bytes_copied: Arc::new(AtomicUsize::new(0)),
+            errors: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            abort_error: Arc::new(parking_lot::Mutex::new(None)),
+            move_failures: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            verified_moves: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            by_extension: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            files_skipped_by_user: Arc::new(AtomicUsize::new(0)),
+            open_files_skipped: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            sample: Arc::new(parking_lot::Mutex::new(SampleVerification::default())),
+        }
+    }
+
+    /// Records a file copied outside the tasks, such as by a tar batch.
+    fn record_copied(&self, path: &Path, bytes: u64) {
+        self.files_copied.fetch_add(1, Ordering::Relaxed);
+        self.bytes_copied
+            .fetch_add(bytes as usize, Ordering::Relaxed);
+        ExtensionTotals::record(&self.by_extension, path, bytes);
+    }
+
+    /// Copies `file` into `dest_root` on its own task, holding `permit` until
+    /// it's done. A `sampled` file is verified whatever the options say.
+    fn spawn(
+        &self,
+        file: FileInfo,
+        dest_root: PathBuf,
+        options: &SyncOptions,
+        sampled: bool,
+        permit: tokio::sync::OwnedSemaphorePermit,
+    ) -> tokio::task::JoinHandle<()> {
+        let tasks = self.clone();
+        let mut options = options.clone();
+        options.verify_integrity |= sampled;
+
+        tokio::spawn(async move {
+            let _permit = permit; // Hold permit until task completes
+            let transfer_id = &tasks.transfer_id;
+            let state_manager = &tasks.state_manager;
+
+            // Checked right before the copy, since files open at scan time may be closed by now
+            let source_abs = tasks.source_root.join(&file.path);
+            if let Some(writer) = tasks
+                .open_writers
+                .as_ref()
+                .and_then(|index| index.writer_of(&source_abs))
+            {
+                if let Ok(state_arc) = state_manager.get_transfer(transfer_id) {
+                    let mut state = state_arc.write();
+                    state.skip_file(&source_abs);
+                    let _ = state_manager.save_state(&state);
+                }
+                eprintln!(
+                    "[Sync] Skipped {}: open for writing by {}",
+                    file.path.display(),
+                    writer
+                );
+                tasks.open_files_skipped.lock().push(SkippedOpenFile {
+                    path: file.path.clone(),
+                    reason: format!("open for writing by {}", writer),
+                });
+                tasks.control.note_progress();
+                return;
+            }
+
+            let outcome = SyncEngine::sync_file_static(
+                transfer_id,
+                &tasks.source_root,
+                &dest_root,
+                &file,
+                &options,
+                &tasks.control,
+                state_manager,
+                tasks.app_handle.as_ref(),
+            )
+            .await;
+            // Skipped and unchanged files count as progress too
+            tasks.control.note_progress();
+            if sampled {
+                let verified = outcome.is_ok()
+                    && SyncEngine::is_file_verified(state_manager, transfer_id, &source_abs);
+                tasks
+                    .sample
+                    .lock()
+                    .record(&file.path, file.size, &outcome, verified);
+            }
+
+            match outcome {
+                Ok(bytes) => {
+                    tasks.record_copied(&file.path, bytes);
+                    if options.mode == SyncMode::MoveVerified
+                        && SyncEngine::is_file_verified(state_manager, transfer_id, &source_abs)
+                    {
+                        tasks.verified_moves.lock().push(source_abs);
+                    }
+                }
+                Err(SyncError::SkippedByUser { .. }) => {
+                    tasks.files_skipped_by_user.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(SyncError::SourceRemovalFailed {
+                    path,
+                    bytes_copied,
+                    reason,
+                }) => {
+                    // The copy itself succeeded, only the source is left behind
+                    tasks.record_copied(&file.path, bytes_copied);
+                    match options.on_move_failure {
+                        MoveFailurePolicy::Record => {
+                            tasks.move_failures.lock().push(MoveFailure {
+                                path,
+                                error: reason,
+                            });
+                        }
+                        MoveFailurePolicy::Warn => {
+                            eprintln!(
+                                "[Sync] Warning: Copied but could not remove source {:?}: {}",
+                                path, reason
+                            );
+                        }
+                    }
+                }
+                Err(e) => {
+                    tasks.files_failed.fetch_add(1, Ordering::Relaxed);
+                    tasks
+                        .errors
+                        .lock()
+                        .push(format!("{}: {}", file.path.display(), e));
+                    if let Ok(state_arc) = state_manager.get_transfer(transfer_id) {
+                        let mut state = state_arc.write();
+                        state.fail_file(&source_abs, e.to_string());
+                        let _ = state_manager.save_state(&state);
+                    }
+                    // A disconnect with reconnect enabled stops the run under any
+                    // policy, so the transfer waits for the drive instead of
+                    // failing every remaining file
+                    let wait_for_drive = options.reconnect_timeout_secs.is_some()
+                        && matches!(e, SyncError::DriveDisconnected { .. });
+                    if options.on_error == ErrorPolicy::AbortTransfer || wait_for_drive {
+                        let mut first = tasks.abort_error.lock();
+                        if first.is_none() && !tasks.control.is_cancelled() {
+                            *first = Some(e);
+                            // Stop queued and in-flight files
+                            tasks.control.cancel();
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Adds what the finished tasks copied, failed and skipped to `result`.
+    fn collect_into(&self, result: &mut SyncResult_) {
+        result.files_copied += self.files_copied.load(Ordering::Relaxed);
+        result.files_failed += self.files_failed.load(Ordering::Relaxed);
+        result.bytes_copied += self.bytes_copied.load(Ordering::Relaxed) as u64;
+        result.errors.extend(self.errors.lock().drain(..));
+        result
+            .move_failures
+            .extend(self.move_failures.lock().drain(..));
+        result.files_skipped += self.files_skipped_by_user.load(Ordering::Relaxed);
+        result.open_files_skipped = std::mem::take(&mut *self.open_files_skipped.lock());
+        result.files_skipped += result.open_files_skipped.len();
+        result.by_extension = std::mem::take(&mut *self.by_extension.lock());
+    }
+
+    /// Sources of verified copies a `MoveVerified` sync may remove. Empty
+    /// unless every file made it across.
+    fn take_verified_moves(&self, result: &SyncResult_) -> Vec<PathBuf> {
+        if result.files_failed == 0
+            && self.files_skipped_by_user.load(Ordering::Relaxed) == 0
+            && result.incompatible_files.is_empty()
+        {
+            std::mem::take(&mut *self.verified_moves.lock())
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// A file copied by a move whose source is still in place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveFailure {
+    pub path: PathBuf,
+    pub error: String,
 }
 
 impl Default for SyncResult_ {
@@ -128,6 +1049,21 @@ impl Default for SyncResult_ {
             bytes_copied: 0,
             duration_ms: 0,
             errors: Vec::new(),
+            special_files_skipped: 0,
+            move_failures: Vec::new(),
+            incompatible_files: Vec::new(),
+            effective_concurrency: 0,
+            concurrency_limited_reason: None,
+            manifest_path: None,
+            unreadable_skipped: Vec::new(),
+            dataless_skipped: Vec::new(),
+            verify_sample: None,
+            sources_deleted: Vec::new(),
+            open_files_skipped: Vec::new(),
+            warnings: Vec::new(),
+            cancelled: false,
+            by_extension: HashMap::new(),
+            verified_for_move: Vec::new(),
         }
     }
 }
@@ -152,6 +1088,22 @@ pub struct TransferControl {
     pub cancelled: AtomicBool,
     /// Notifies waiting tasks when resume is called
     pub resume_notify: Notify,
+    /// Asks in-flight copies to fsync each block before reporting progress
+    pub flush_requested: Arc<AtomicBool>,
+    /// Bandwidth limit in bytes per second (0 = unlimited), read by in-flight
+    /// copies every block so it can be changed while the transfer runs
+    pub bandwidth_limit: Arc<AtomicU64>,
+    /// Number of file copies currently inside the blocking copy loop
+    pub active_copies: AtomicUsize,
+    /// Number of those copies parked at a flushed block boundary while paused
+    pub parked_copies: AtomicUsize,
+    /// Source files the user asked to skip while they are being copied
+    pub skip_requested: parking_lot::Mutex<HashSet<PathBuf>>,
+    /// When a copy last reported progress, watched by the stall watchdog
+    pub last_progress: parking_lot::Mutex<std::time::Instant>,
+    /// Caps the source and destination hashes of `verify_integrity` across
+    /// the transfer's copies; can be changed while the transfer runs
+    pub hash_limiter: Arc<HashLimiter>,
 }
 
 impl TransferControl {
@@ -160,13 +1112,34 @@ impl TransferControl {
             paused: AtomicBool::new(false),
             cancelled: AtomicBool::new(false),
             resume_notify: Notify::new(),
+            flush_requested: Arc::new(AtomicBool::new(false)),
+            bandwidth_limit: Arc::new(AtomicU64::new(0)),
+            active_copies: AtomicUsize::new(0),
+            parked_copies: AtomicUsize::new(0),
+            skip_requested: parking_lot::Mutex::new(HashSet::new()),
+            last_progress: parking_lot::Mutex::new(std::time::Instant::now()),
+            hash_limiter: Arc::new(HashLimiter::default()),
         }
     }
 
+    pub fn with_bandwidth_limit(bytes_per_sec: u64) -> Self {
+        let control = Self::new();
+        control.set_bandwidth_limit(bytes_per_sec);
+        control
+    }
+
     pub fn is_paused(&self) -> bool {
         self.paused.load(Ordering::SeqCst)
     }
 
+    pub fn bandwidth_limit(&self) -> u64 {
+        self.bandwidth_limit.load(Ordering::Relaxed)
+    }
+
+    pub fn set_bandwidth_limit(&self, bytes_per_sec: u64) {
+        self.bandwidth_limit.store(bytes_per_sec, Ordering::Relaxed);
+    }
+
     pub fn is_cancelled(&self) -> bool {
         self.cancelled.load(Ordering::SeqCst)
     }
@@ -177,15 +1150,40 @@ impl TransferControl {
 
     pub fn resume(&self) {
         self.paused.store(false, Ordering::SeqCst);
+        self.flush_requested.store(false, Ordering::SeqCst);
+        // Time spent paused doesn't count towards a stall
+        self.note_progress();
         self.resume_notify.notify_waiters();
     }
 
+    pub fn note_progress(&self) {
+        *self.last_progress.lock() = std::time::Instant::now();
+    }
+
+    pub fn since_last_progress(&self) -> std::time::Duration {
+        self.last_progress.lock().elapsed()
+    }
+
     pub fn cancel(&self) {
         self.cancelled.store(true, Ordering::SeqCst);
         // Also notify in case we're paused and waiting
         self.resume_notify.notify_waiters();
     }
 
+    /// Asks the in-flight copy of `source_path` to stop at its next block.
+    pub fn request_skip(&self, source_path: PathBuf) {
+        self.skip_requested.lock().insert(source_path);
+    }
+
+    pub fn is_skip_requested(&self, source_path: &Path) -> bool {
+        self.skip_requested.lock().contains(source_path)
+    }
+
+    /// Clears a skip request, returning whether one was pending.
+    pub fn take_skip(&self, source_path: &Path) -> bool {
+        self.skip_requested.lock().remove(source_path)
+    }
+
     /// Wait efficiently for resume signal, with timeout fallback
     pub async fn wait_for_resume(&self) {
         while self.is_paused() && !self.is_cancelled() {
@@ -200,41 +1198,378 @@ impl TransferControl {
     }
 }
 
-/// Resolved conflict information stored by the engine.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ResolvedConflict {
-    pub conflict_id: String,
-    pub resolution: ConflictResolutionAction,
-    pub resolved_at: std::time::SystemTime,
+/// Counts a copy in `TransferControl::active_copies` while held, so
+/// checkpoints, shutdown and rollback wait for it.
+struct ActiveCopy(Arc<TransferControl>);
+
+impl ActiveCopy {
+    fn enter(control: &Arc<TransferControl>) -> Self {
+        control.active_copies.fetch_add(1, Ordering::SeqCst);
+        Self(control.clone())
+    }
 }
 
-/// The action taken to resolve a conflict, mapped from frontend resolution types.
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum ConflictResolutionAction {
-    KeepSource,
-    KeepDest,
-    KeepBoth,
-    Skip,
+impl Drop for ActiveCopy {
+    fn drop(&mut self) {
+        self.0.active_copies.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
-pub struct SyncEngine {
-    app_handle: Option<AppHandle>,
-    state_manager: Arc<TransferStateManager>,
-    controls: RwLock<HashMap<String, Arc<TransferControl>>>,
-    /// Tracks resolved conflicts for the current session
-    resolved_conflicts: RwLock<HashMap<String, ResolvedConflict>>,
+/// Transfers allowed to run at once until `set_max_concurrent_transfers` is called.
+pub const DEFAULT_MAX_CONCURRENT_TRANSFERS: usize = 2;
+
+/// Admits whole transfers so that starting several syncs queues them instead of
+/// running them all against the same disks. This sits above the per-file
+/// semaphore each transfer uses for its own copies.
+pub struct TransferQueue {
+    inner: parking_lot::Mutex<TransferQueueInner>,
+    /// Wakes queued transfers when a slot frees or the limit changes
+    slot_freed: Notify,
 }
 
-impl SyncEngine {
-    pub fn new(app_handle: Option<AppHandle>) -> SyncResult<Self> {
-        Ok(Self {
+struct TransferQueueInner {
+    max_running: usize,
+    running: usize,
+    /// Queued transfers in arrival order: (transfer id, priority)
+    waiting: Vec<(String, i32)>,
+}
+
+impl TransferQueueInner {
+    /// Index of the transfer to admit next: highest priority, then earliest queued.
+    fn next_index(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (index, (_, priority)) in self.waiting.iter().enumerate() {
+            if best.is_none_or(|b| *priority > self.waiting[b].1) {
+                best = Some(index);
+            }
+        }
+        best
+    }
+}
+
+/// A running transfer's place in the queue, released when dropped.
+pub struct TransferSlot {
+    queue: Arc<TransferQueue>,
+}
+
+impl Drop for TransferSlot {
+    fn drop(&mut self) {
+        self.queue.inner.lock().running -= 1;
+        self.queue.slot_freed.notify_waiters();
+    }
+}
+
+impl TransferQueue {
+    pub fn new(max_running: usize) -> Self {
+        Self {
+            inner: parking_lot::Mutex::new(TransferQueueInner {
+                max_running: max_running.max(1),
+                running: 0,
+                waiting: Vec::new(),
+            }),
+            slot_freed: Notify::new(),
+        }
+    }
+
+    pub fn set_max_running(&self, max_running: usize) {
+        self.inner.lock().max_running = max_running.max(1);
+        self.slot_freed.notify_waiters();
+    }
+
+    /// Position of a queued transfer (0 = next to start), if it is waiting.
+    pub fn position(&self, transfer_id: &str) -> Option<usize> {
+        let inner = self.inner.lock();
+        let index = inner.waiting.iter().position(|(id, _)| id == transfer_id)?;
+        let priority = inner.waiting[index].1;
+        Some(
+            inner
+                .waiting
+                .iter()
+                .enumerate()
+                .filter(|(i, (_, p))| *p > priority || (*p == priority && *i < index))
+                .count(),
+        )
+    }
+
+    /// Waits until the transfer may run. Returns None if it was cancelled while queued.
+    pub async fn acquire(
+        self: &Arc<Self>,
+        transfer_id: &str,
+        priority: i32,
+        control: &TransferControl,
+    ) -> Option<TransferSlot> {
+        self.inner
+            .lock()
+            .waiting
+            .push((transfer_id.to_string(), priority));
+
+        loop {
+            // Register for the wakeup before checking, so a release in between isn't missed
+            let notified = self.slot_freed.notified();
+            {
+                let mut inner = self.inner.lock();
+                let index = inner.waiting.iter().position(|(id, _)| id == transfer_id)?;
+                if control.is_cancelled() {
+                    inner.waiting.remove(index);
+                    drop(inner);
+                    // The next transfer in line may have been waiting behind this one
+                    self.slot_freed.notify_waiters();
+                    return None;
+                }
+                if inner.running < inner.max_running && inner.next_index() == Some(index) {
+                    inner.waiting.remove(index);
+                    inner.running += 1;
+                    let more_free = inner.running < inner.max_running && !inner.waiting.is_empty();
+                    drop(inner);
+                    if more_free {
+                        self.slot_freed.notify_waiters();
+                    }
+                    return Some(TransferSlot {
+                        queue: Arc::clone(self),
+                    });
+                }
+            }
+            // Timeout as a fallback: cancel() notifies the control, not the queue
+            tokio::time::timeout(std::time::Duration::from_millis(500), notified)
+                .await
+                .ok();
+        }
+    }
+}
+
+/// Confirmation returned by `checkpoint_transfer` once the transfer is safe to kill.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointInfo {
+    pub transfer_id: String,
+    /// Files paused mid-copy at a flushed block boundary
+    pub files_in_flight: usize,
+    /// Bytes recorded in the persisted state, all of which are on disk
+    pub bytes_persisted: u64,
+}
+
+/// What `rollback_transfer` undid at the destination.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackReport {
+    pub transfer_id: String,
+    /// Files the transfer created, now removed
+    pub files_removed: usize,
+    /// Overwritten files put back from their rollback backups
+    pub files_restored: usize,
+    /// Overwritten files with no backup, left holding the transfer's copy
+    pub not_restorable: Vec<PathBuf>,
+    pub errors: Vec<String>,
+}
+
+/// What a sync did to a sandboxed copy of the destination, from `rehearse_sync`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncRehearsal {
+    /// Result of the sync run against the sandbox
+    pub result: SyncResult_,
+    /// Every path the sync created (`onlyRight`), deleted (`onlyLeft`) or
+    /// changed (`different`), relative to the destination
+    pub changes: Vec<DirectoryDiffEntry>,
+    pub summary: DirectoryDiffSummary,
+}
+
+/// What `delete_orphans` would remove, from `preview_orphans`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanPreview {
+    /// Orphans as absolute paths, contents before their directories (capped;
+    /// see `truncated`)
+    pub paths: Vec<PathBuf>,
+    pub file_count: usize,
+    pub dir_count: usize,
+    /// Combined size of the orphaned files
+    pub total_bytes: u64,
+    /// True when there were more orphans than fit in `paths`
+    pub truncated: bool,
+}
+
+/// Resolved conflict information stored by the engine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedConflict {
+    pub conflict_id: String,
+    pub resolution: ConflictResolutionAction,
+    pub resolved_at: std::time::SystemTime,
+}
+
+/// The action taken to resolve a conflict, mapped from frontend resolution types.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictResolutionAction {
+    KeepSource,
+    KeepDest,
+    KeepBoth,
+    Skip,
+}
+
+/// How a source file compares with its scanned `FileInfo` at copy time.
+enum SourceChange {
+    Unchanged,
+    Removed,
+    /// Size or mtime differ; carries the current metadata
+    Modified(FileInfo),
+}
+
+pub struct SyncEngine {
+    app_handle: Option<AppHandle>,
+    state_manager: Arc<TransferStateManager>,
+    controls: RwLock<HashMap<String, Arc<TransferControl>>>,
+    /// Tracks resolved conflicts for the current session
+    resolved_conflicts: RwLock<HashMap<String, ResolvedConflict>>,
+    /// Mount notifications from the volume watcher, used to resume transfers
+    /// waiting for a disconnected drive
+    mount_events: RwLock<Option<broadcast::Sender<VolumeInfo>>>,
+    /// Records which volumes running transfers use, so the UI can warn before an eject
+    volume_registry: RwLock<Option<TransferVolumeRegistry>>,
+    /// Limits how many transfers run at once; the rest wait as Pending
+    transfer_queue: Arc<TransferQueue>,
+    /// Transfers paused by `pause_transfers_on_volume`, which only resumes these
+    volume_paused: RwLock<HashSet<String>>,
+}
+
+impl SyncEngine {
+    pub fn new(app_handle: Option<AppHandle>) -> SyncResult<Self> {
+        Ok(Self::with_state_manager(
             app_handle,
-            state_manager: Arc::new(TransferStateManager::new()?),
+            TransferStateManager::new()?,
+        ))
+    }
+
+    /// An engine keeping its transfer state in `state_manager`.
+    pub fn with_state_manager(
+        app_handle: Option<AppHandle>,
+        state_manager: TransferStateManager,
+    ) -> Self {
+        Self {
+            app_handle,
+            state_manager: Arc::new(state_manager),
             controls: RwLock::new(HashMap::new()),
             resolved_conflicts: RwLock::new(HashMap::new()),
-        })
+            mount_events: RwLock::new(None),
+            volume_registry: RwLock::new(None),
+            transfer_queue: Arc::new(TransferQueue::new(DEFAULT_MAX_CONCURRENT_TRANSFERS)),
+            volume_paused: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Connects the engine to the volume watcher so transfers waiting for a
+    /// disconnected drive resume as soon as it is mounted again, and so the
+    /// watcher knows which volumes each transfer is using.
+    pub fn attach_volume_watcher(&self, watcher: &VolumeWatcher) {
+        *self.mount_events.write() = Some(watcher.mount_sender());
+        *self.volume_registry.write() = Some(watcher.transfer_registry());
+    }
+
+    /// Sets how many transfers may run at once. Queued transfers start right
+    /// away if the new limit leaves room for them.
+    pub fn set_max_concurrent_transfers(&self, max: usize) {
+        self.transfer_queue.set_max_running(max);
+    }
+
+    /// Creates the transfer a run records into, with its control. A resume
+    /// carries on the interrupted transfer instead, trying its failed files
+    /// again and keeping a bandwidth limit changed while it last ran.
+    fn begin_transfer(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        options: &SyncOptions,
+    ) -> SyncResult<(String, Arc<TransferControl>)> {
+        let Some(transfer_id) = options.resume_of.clone() else {
+            let transfer_id = self
+                .state_manager
+                .create_transfer(source_path.to_path_buf(), dest_path.to_path_buf())?;
+            let control = Arc::new(TransferControl::with_bandwidth_limit(
+                options.bandwidth_limit,
+            ));
+            {
+                let mut controls = self.controls.write();
+                controls
+                    .entry(transfer_id.clone())
+                    .or_insert_with(|| control.clone());
+            }
+            return Ok((transfer_id, control));
+        };
+
+        let bandwidth_limit = self
+            .get_control(&transfer_id)
+            .map_or(options.bandwidth_limit, |previous| {
+                previous.bandwidth_limit()
+            });
+        let control = Arc::new(TransferControl::with_bandwidth_limit(bandwidth_limit));
+        self.controls
+            .write()
+            .insert(transfer_id.clone(), control.clone());
+        let state_arc = self.state_manager.get_transfer(&transfer_id)?;
+        let mut state = state_arc.write();
+        state.retry_failed_files();
+        state.status = TransferStatus::Pending;
+        state.error = None;
+        self.state_manager.save_state(&state)?;
+        Ok((transfer_id, control))
+    }
+
+    /// Holds a new transfer in the Pending state until the queue admits it.
+    async fn wait_for_transfer_slot(
+        &self,
+        transfer_id: &str,
+        priority: i32,
+        control: &TransferControl,
+    ) -> SyncResult<TransferSlot> {
+        let queue = Arc::clone(&self.transfer_queue);
+        let acquire = queue.acquire(transfer_id, priority, control);
+        tokio::pin!(acquire);
+
+        // Only announce the transfer as queued if it actually has to wait
+        let slot = match futures::poll!(acquire.as_mut()) {
+            std::task::Poll::Ready(slot) => slot,
+            std::task::Poll::Pending => {
+                if let Some(ref handle) = self.app_handle {
+                    let _ = handle.emit(
+                        "transfer-queued",
+                        serde_json::json!({
+                            "transferId": transfer_id,
+                            "priority": priority,
+                            "position": queue.position(transfer_id),
+                        }),
+                    );
+                }
+                acquire.await
+            }
+        };
+
+        match slot {
+            Some(slot) => Ok(slot),
+            None => {
+                self.set_status(
+                    transfer_id,
+                    TransferStatus::Cancelled,
+                    Some("Transfer cancelled by user".to_string()),
+                )?;
+                Err(SyncError::TransferCancelled(
+                    "Transfer cancelled by user".into(),
+                ))
+            }
+        }
+    }
+
+    /// Registers a transfer's source and destination volumes with the watcher.
+    fn track_transfer_volumes(&self, transfer_id: &str, source: &Path, dest: &Path) {
+        if let Some(registry) = self.volume_registry.read().as_ref() {
+            registry.register_transfer(transfer_id, source, dest);
+        }
+    }
+
+    fn untrack_transfer_volumes(&self, transfer_id: &str) {
+        if let Some(registry) = self.volume_registry.read().as_ref() {
+            registry.unregister_transfer(transfer_id);
+        }
     }
 
     pub fn get_directory_info(&self, path: &Path) -> SyncResult<DirectoryInfo> {
@@ -256,29 +1591,135 @@ impl SyncEngine {
     pub async fn resume_interrupted_transfer(&self, transfer_id: &str) -> SyncResult<()> {
         // Get the persisted transfer state
         let state = self.state_manager.get_state(transfer_id)?;
-        
-        // Create default sync options from the persisted state
-        let options = SyncOptions {
+        let options = Self::resume_options(&state);
+
+        // Resume the sync using the existing transfer ID
+        self.resume_sync_with_state(transfer_id, options).await
+    }
+
+    /// Resumes an interrupted transfer onto a different destination path.
+    /// Used when the original destination drive is gone: every file is re-rooted
+    /// under `new_dest` and reset to pending, since the new destination is empty
+    /// and the old completion tracking no longer applies.
+    pub async fn resume_interrupted_transfer_to(
+        &self,
+        transfer_id: &str,
+        new_dest: PathBuf,
+    ) -> SyncResult<()> {
+        if !crate::permissions::check_write_access(&new_dest) {
+            return Err(SyncError::DestinationNotWritable(
+                new_dest.display().to_string(),
+            ));
+        }
+
+        let state_arc = self.state_manager.get_transfer(transfer_id)?;
+        let options = {
+            let mut state = state_arc.write();
+            // Anything with a live control is still running, waiting or queued
+            if self.controls.read().contains_key(transfer_id) {
+                return Err(SyncError::TransferAlreadyExists(transfer_id.to_string()));
+            }
+            Self::check_source_dest_overlap(&state.source_path, &new_dest)?;
+            state.reroot_destination(new_dest);
+            self.state_manager.save_state(&state)?;
+            Self::resume_options(&state)
+        };
+
+        self.resume_sync_with_state(transfer_id, options).await
+    }
+
+    /// Builds the sync options used when resuming from persisted state: the
+    /// ones the transfer was started with, on its current destinations.
+    fn resume_options(state: &TransferState) -> SyncOptions {
+        match &state.options {
+            Some(options) => SyncOptions {
+                source: state.source_path.clone(),
+                destination: state.dest_path.clone(),
+                spanning_destinations: state.spanning_destinations.clone(),
+                ..options.clone()
+            },
+            None => Self::fallback_resume_options(state),
+        }
+    }
+
+    /// Resume options for a state saved before its options were kept with it.
+    fn fallback_resume_options(state: &TransferState) -> SyncOptions {
+        SyncOptions {
             source: state.source_path.clone(),
             destination: state.dest_path.clone(),
             mode: SyncMode::Copy,
             conflict_resolution: ConflictResolution::Skip, // Skip existing to resume
-            verify_integrity: false,
+            conflict_name_template: state.conflict_name_template.clone(),
+            keep_rollback_backups: state.keep_rollback_backups,
+            verify_integrity: state.verify_integrity,
+            verify_sample_percent: 0.0,
+            verify_largest_files: 0,
+            verify_during_copy: false,
             preserve_metadata: true,
             delete_orphans: false,
             buffer_size: None,
             dry_run: false,
             follow_symlinks: false,
             max_concurrent_files: 4,
+            max_concurrent_hashes: None,
+            max_memory_bytes: None,
             overwrite_newer: false,
+            force_parallel: false,
+            adaptive_concurrency: false,
+            pipelined_copy: false,
+            low_priority: false,
+            parallel_scan: false,
+            scan_threads: None,
+            skip_open_files: false,
+            materialize_dataless: false,
+            store_hash_xattr: false,
+            priority: 0,
+            deterministic_order: state.deterministic_order,
+            copy_order: CopyOrder::AsScanned,
             overwrite_older: false,
             skip_existing: false, // We use the persisted file state to determine what to skip
-            exclude_patterns: Vec::new(),
+            recopy_zero_byte_dest: false,
+            exclude_patterns: state.exclude_patterns.clone(),
+            global_exclude_patterns: state.global_exclude_patterns.clone(),
             bandwidth_limit: 0,
-        };
-        
-        // Resume the sync using the existing transfer ID
-        self.resume_sync_with_state(transfer_id, options).await
+            max_write_bytes: None,
+            max_dest_fill_percent: None,
+            check_drive_health: false,
+            probe_network_destination: false,
+            mtime_tolerance_seconds: state.mtime_tolerance_seconds,
+            detect_clock_skew: false,
+            exclude_temp_from_indexing: false,
+            pause_spotlight: false,
+            low_disk_space_threshold: None,
+            stall_timeout_secs: None,
+            fail_on_stall: false,
+            force_overwrite_locked: false,
+            preserve_acl: false,
+            preserve_xattr: false,
+            timestamp_mode: TimestampMode::PreserveSource,
+            notify_on_complete: false,
+            progress_ndjson: false,
+            on_error: ErrorPolicy::Continue,
+            on_move_failure: MoveFailurePolicy::Record,
+            preserve_special: false,
+            skip_unreadable: false,
+            reconnect_timeout_secs: None,
+            pack_small_files: false,
+            structure_only: false,
+            incremental_since_marker: false,
+            streaming: false,
+            atomic_publish: false,
+            conflict_rules: Vec::new(),
+            prune_empty_dirs: false,
+            ensure_empty_dirs: false,
+            write_manifest: None,
+            spanning_destinations: state.spanning_destinations.clone(),
+            subdirs: state.subdirs.clone(),
+            conflict_matcher: None,
+            resume_of: None,
+            #[cfg(debug_assertions)]
+            simulate: false,
+        }
     }
 
     /// Discards an interrupted transfer, removing its state from disk.
@@ -297,9 +1738,80 @@ impl SyncEngine {
         self.state_manager.get_state(transfer_id)
     }
 
+    /// Recent events of a transfer numbered after `since`, oldest first.
+    pub fn get_transfer_events(
+        &self,
+        transfer_id: &str,
+        since: Option<u64>,
+    ) -> SyncResult<Vec<TransferEvent>> {
+        Ok(self
+            .state_manager
+            .get_transfer(transfer_id)?
+            .read()
+            .events_since(since))
+    }
+
+    /// Adds an event that isn't about one file to a transfer's activity log.
+    fn record_event(&self, transfer_id: &str, kind: TransferEventKind, message: Option<String>) {
+        if let Ok(state_arc) = self.state_manager.get_transfer(transfer_id) {
+            state_arc.write().record_event(kind, None, message);
+        }
+    }
+
+    /// Writes the per-file outcome of a transfer to `path`; see
+    /// `TransferStateManager::export_report`.
+    pub fn export_transfer_report(
+        &self,
+        transfer_id: &str,
+        path: &Path,
+        format: ReportFormat,
+    ) -> SyncResult<usize> {
+        self.state_manager.export_report(transfer_id, path, format)
+    }
+
+    /// Files and bytes a resume of `transfer_id` would still copy, from its
+    /// persisted state without re-scanning the source.
+    pub fn get_resume_estimate(&self, transfer_id: &str) -> SyncResult<ResumeEstimate> {
+        Ok(self.state_manager.get_state(transfer_id)?.resume_estimate())
+    }
+
+    /// Changes the bandwidth limit of a running transfer. Files already being
+    /// copied pick it up at their next block; 0 removes the limit.
+    pub fn set_bandwidth_limit(&self, transfer_id: &str, bytes_per_sec: u64) -> SyncResult<()> {
+        let control = self.get_control(transfer_id)?;
+        control.set_bandwidth_limit(bytes_per_sec);
+        eprintln!(
+            "[Throttle] Transfer {} limit set to {} bytes/sec",
+            transfer_id, bytes_per_sec
+        );
+        Ok(())
+    }
+
+    /// Changes how many verification hashes a running transfer does at once
+    /// (0 = no limit). Hashes already running finish first.
+    pub fn set_max_concurrent_hashes(
+        &self,
+        transfer_id: &str,
+        max_hashes: usize,
+    ) -> SyncResult<()> {
+        let control = self.get_control(transfer_id)?;
+        control.hash_limiter.set_limit(max_hashes);
+        eprintln!(
+            "[Verify] Transfer {} hashes at most {} files at once",
+            transfer_id, max_hashes
+        );
+        Ok(())
+    }
+
+    /// How many verification hashes a running transfer does at once (0 = no limit).
+    pub fn max_concurrent_hashes(&self, transfer_id: &str) -> SyncResult<usize> {
+        Ok(self.get_control(transfer_id)?.hash_limiter.limit())
+    }
+
     pub fn pause_transfer(&self, transfer_id: &str) -> SyncResult<()> {
         let control = self.get_control(transfer_id)?;
         control.pause();
+        self.record_event(transfer_id, TransferEventKind::Paused, None);
         self.set_status(transfer_id, TransferStatus::Paused, None)?;
         Ok(())
     }
@@ -307,10 +1819,63 @@ impl SyncEngine {
     pub fn resume_transfer(&self, transfer_id: &str) -> SyncResult<()> {
         let control = self.get_control(transfer_id)?;
         control.resume();
+        self.volume_paused.write().remove(transfer_id);
+        self.record_event(transfer_id, TransferEventKind::Resumed, None);
         self.set_status(transfer_id, TransferStatus::Running, None)?;
         Ok(())
     }
 
+    /// Pauses every running transfer whose source or destination is on the
+    /// volume `path` is on, e.g. so the drive can be ejected for a moment.
+    /// Transfers on other drives carry on. Returns the IDs paused.
+    pub fn pause_transfers_on_volume(&self, path: &Path) -> SyncResult<Vec<String>> {
+        let Some(registry) = self.volume_registry.read().clone() else {
+            return Ok(Vec::new());
+        };
+        let mut paused = Vec::new();
+        for transfer_id in registry.transfers_on_volume(path) {
+            let running = self
+                .state_manager
+                .get_state(&transfer_id)
+                .is_ok_and(|state| state.status == TransferStatus::Running);
+            if !running || self.get_control(&transfer_id).is_err() {
+                continue;
+            }
+            self.pause_transfer(&transfer_id)?;
+            self.volume_paused.write().insert(transfer_id.clone());
+            paused.push(transfer_id);
+        }
+        eprintln!(
+            "[Volume] Paused {} transfer(s) on {}",
+            paused.len(),
+            path.display()
+        );
+        Ok(paused)
+    }
+
+    /// Resumes the transfers on the volume `path` is on that
+    /// `pause_transfers_on_volume` paused. Ones the user or a low-disk check
+    /// paused stay paused. Returns the IDs resumed.
+    pub fn resume_transfers_on_volume(&self, path: &Path) -> SyncResult<Vec<String>> {
+        let Some(registry) = self.volume_registry.read().clone() else {
+            return Ok(Vec::new());
+        };
+        let mut resumed = Vec::new();
+        for transfer_id in registry.transfers_on_volume(path) {
+            if !self.volume_paused.read().contains(&transfer_id) {
+                continue;
+            }
+            self.resume_transfer(&transfer_id)?;
+            resumed.push(transfer_id);
+        }
+        eprintln!(
+            "[Volume] Resumed {} transfer(s) on {}",
+            resumed.len(),
+            path.display()
+        );
+        Ok(resumed)
+    }
+
     pub fn cancel_transfer(&self, transfer_id: &str) -> SyncResult<()> {
         let control = self.get_control(transfer_id)?;
         control.cancel();
@@ -322,148 +1887,457 @@ impl SyncEngine {
         Ok(())
     }
 
-    /// Resolves a file conflict with the user's chosen action.
-    /// 
-    /// This method is called from the frontend when a user makes a decision
-    /// in the conflict resolution dialog. It records the resolution and
-    /// optionally emits an event to notify other parts of the system.
-    /// 
-    /// # Arguments
-    /// * `conflict_id` - Unique identifier for the conflict (typically a file path hash)
-    /// * `resolution` - The resolution action chosen by the user
-    /// * `transfer_id` - Optional transfer ID if the conflict is associated with an active transfer
-    /// 
-    /// # Returns
-    /// * `Ok(())` if the conflict was successfully resolved
-    /// * `Err(SyncError)` if there was an error recording the resolution
-    pub async fn resolve_conflict(
-        &self,
-        conflict_id: &str,
-        resolution: crate::FrontendConflictResolution,
-        transfer_id: Option<&str>,
-    ) -> SyncResult<()> {
-        // Convert frontend resolution type to internal action type
-        let action = match resolution {
-            crate::FrontendConflictResolution::KeepSource => ConflictResolutionAction::KeepSource,
-            crate::FrontendConflictResolution::KeepDest => ConflictResolutionAction::KeepDest,
-            crate::FrontendConflictResolution::KeepBoth => ConflictResolutionAction::KeepBoth,
-            crate::FrontendConflictResolution::Skip => ConflictResolutionAction::Skip,
-        };
-
-        // Record the resolution
-        let resolved_conflict = ResolvedConflict {
-            conflict_id: conflict_id.to_string(),
-            resolution: action,
-            resolved_at: std::time::SystemTime::now(),
-        };
-
+    /// Cancels a transfer and undoes what it did to the destination: files it
+    /// created are removed, along with directories left empty, and files it
+    /// overwrote are put back from the backups `keep_rollback_backups` kept.
+    /// Overwritten files without a backup can't be restored and are listed
+    /// instead, as are orphans the transfer deleted. Rolled-back files are
+    /// reset to pending, so resuming the transfer runs it again.
+    /// A streaming transfer drops finished files from its state as it goes,
+    /// so it is refused rather than reported as rolled back.
+    pub async fn rollback_transfer(&self, transfer_id: &str) -> SyncResult<RollbackReport> {
+        let state_arc = self.state_manager.get_transfer(transfer_id)?;
+        if state_arc
+            .read()
+            .options
+            .as_ref()
+            .is_some_and(|options| options.streaming)
         {
-            let mut resolved = self.resolved_conflicts.write();
-            resolved.insert(conflict_id.to_string(), resolved_conflict.clone());
+            return Err(SyncError::RollbackUnavailable(format!(
+                "transfer {} ran as a streaming sync, which doesn't keep a record of the files it wrote",
+                transfer_id
+            )));
         }
 
-        // Emit conflict resolution event for any listeners
-        if let Some(handle) = self.app_handle.as_ref() {
-            let event_payload = serde_json::json!({
-                "conflictId": conflict_id,
-                "resolution": action,
-                "transferId": transfer_id,
-            });
-            
-            if let Err(e) = handle.emit("conflict-resolved", &event_payload) {
-                // Log but don't fail - the resolution was still recorded
-                eprintln!("Warning: Failed to emit conflict-resolved event: {}", e);
+        if let Ok(control) = self.get_control(transfer_id) {
+            control.cancel();
+            let deadline =
+                std::time::Instant::now() + std::time::Duration::from_secs(ROLLBACK_TIMEOUT_SECS);
+            while control.active_copies.load(Ordering::SeqCst) > 0 {
+                if std::time::Instant::now() >= deadline {
+                    return Err(SyncError::Timeout(format!(
+                        "In-flight files of transfer {} did not stop within {}s",
+                        transfer_id, ROLLBACK_TIMEOUT_SECS
+                    )));
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
             }
         }
 
-        // If this is associated with an active transfer, we could update the transfer state
-        // This allows the sync engine to continue processing if it was waiting for resolution
-        if let Some(tid) = transfer_id {
-            if let Ok(state_arc) = self.state_manager.get_transfer(tid) {
-                let mut state = state_arc.write();
-                // Mark that a conflict was resolved - this could be used to track resolution count
-                state.conflicts_resolved = state.conflicts_resolved.saturating_add(1);
-                let _ = self.state_manager.save_state(&state);
+        let (files, dest_roots) = {
+            let state = state_arc.read();
+            let files: Vec<FileTransferState> = state
+                .files
+                .values()
+                .filter(|f| !f.skipped)
+                .cloned()
+                .collect();
+            let mut dest_roots = vec![state.dest_path.clone()];
+            dest_roots.extend(state.spanning_destinations.iter().cloned());
+            (files, dest_roots)
+        };
+
+        let id = transfer_id.to_string();
+        let (report, rolled_back) =
+            tokio::task::spawn_blocking(move || Self::undo_files(&id, &files, &dest_roots))
+                .await
+                .map_err(|e| SyncError::Internal(e.to_string()))?;
+
+        {
+            let mut state = state_arc.write();
+            for source_path in &rolled_back {
+                state.roll_back_file(source_path);
             }
+            state.status = TransferStatus::Cancelled;
+            state.error = Some("Rolled back".to_string());
+            state.current_file = None;
+            state.updated_at = chrono::Utc::now();
+            self.state_manager.save_state(&state)?;
         }
+        eprintln!(
+            "[Rollback] Transfer {}: {} removed, {} restored, {} not restorable, {} errors",
+            transfer_id,
+            report.files_removed,
+            report.files_restored,
+            report.not_restorable.len(),
+            report.errors.len()
+        );
+        Ok(report)
+    }
 
-        Ok(())
+    /// Undoes the destination side of `files`, returning the report and the
+    /// source paths of the files that are back where they started.
+    fn undo_files(
+        transfer_id: &str,
+        files: &[FileTransferState],
+        dest_roots: &[PathBuf],
+    ) -> (RollbackReport, Vec<PathBuf>) {
+        let mut report = RollbackReport {
+            transfer_id: transfer_id.to_string(),
+            ..RollbackReport::default()
+        };
+        let mut rolled_back = Vec::new();
+        let mut emptied = Vec::new();
+
+        for file in files {
+            let written = file.written_to.as_ref().unwrap_or(&file.dest_path);
+            cleanup_temp_files(written);
+            let touched = file.status == TransferStatus::Completed || file.bytes_transferred > 0;
+
+            let outcome = if file.replaced && file.written_to.is_none() {
+                match &file.rollback_backup {
+                    Some(backup) => std::fs::rename(backup, written).map(|()| {
+                        report.files_restored += 1;
+                    }),
+                    None => {
+                        if touched {
+                            report.not_restorable.push(file.dest_path.clone());
+                            continue;
+                        }
+                        Ok(())
+                    }
+                }
+            } else if touched {
+                match std::fs::remove_file(written) {
+                    Ok(()) => {
+                        report.files_removed += 1;
+                        emptied.extend(written.parent().map(Path::to_path_buf));
+                        Ok(())
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    Err(e) => Err(e),
+                }
+            } else {
+                Ok(())
+            };
+            match outcome {
+                Ok(()) => rolled_back.push(file.source_path.clone()),
+                Err(e) => report.errors.push(format!("{}: {}", written.display(), e)),
+            }
+        }
+
+        // Deepest first, so a directory emptied by removing its subdirectory goes too
+        emptied.sort_by_key(|dir| Reverse(dir.components().count()));
+        for dir in emptied {
+            for ancestor in dir.ancestors() {
+                let inside_root = dest_roots
+                    .iter()
+                    .any(|root| ancestor.starts_with(root) && ancestor != root);
+                if !inside_root || std::fs::remove_dir(ancestor).is_err() {
+                    break;
+                }
+            }
+        }
+
+        if report.errors.is_empty() {
+            for root in dest_roots {
+                Self::remove_rollback_backups(root, transfer_id);
+            }
+        }
+        (report, rolled_back)
     }
 
-    /// Gets a resolved conflict by ID, if one exists.
-    pub fn get_resolved_conflict(&self, conflict_id: &str) -> Option<ResolvedConflict> {
-        let resolved = self.resolved_conflicts.read();
-        resolved.get(conflict_id).cloned()
+    /// Deletes the backups `keep_rollback_backups` kept for a transfer, once
+    /// its changes are to stay.
+    pub fn discard_rollback_backups(&self, transfer_id: &str) -> SyncResult<()> {
+        let state_arc = self.state_manager.get_transfer(transfer_id)?;
+        let mut state = state_arc.write();
+        for root in std::iter::once(&state.dest_path).chain(&state.spanning_destinations) {
+            Self::remove_rollback_backups(root, transfer_id);
+        }
+        for file in state.files.values_mut() {
+            file.rollback_backup = None;
+        }
+        self.state_manager.save_state(&state)
     }
 
-    /// Clears all resolved conflicts (typically called when starting a new sync).
-    pub fn clear_resolved_conflicts(&self) {
-        let mut resolved = self.resolved_conflicts.write();
-        resolved.clear();
+    /// Removes a transfer's backups under `dest_root`, and the rollback
+    /// directory with them once no other transfer has any there.
+    fn remove_rollback_backups(dest_root: &Path, transfer_id: &str) {
+        let backups = dest_root.join(ROLLBACK_DIR);
+        if let Err(e) = std::fs::remove_dir_all(backups.join(transfer_id)) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!(
+                    "[Rollback] Failed to remove backups in {}: {}",
+                    backups.display(),
+                    e
+                );
+            }
+        }
+        let _ = std::fs::remove_dir(&backups);
     }
 
-    /// Builds a compiled GlobSet from exclusion patterns for efficient matching.
-    /// Returns None if there are no patterns or all patterns are invalid.
-    fn build_exclude_matcher(patterns: &[String]) -> Option<GlobSet> {
-        if patterns.is_empty() {
-            return None;
+    /// Skips the file a transfer is currently copying without cancelling the
+    /// rest of the transfer. The file is left pending so a resume retries it.
+    /// Returns the skipped source path.
+    pub fn skip_current_file(&self, transfer_id: &str) -> SyncResult<PathBuf> {
+        let control = self.get_control(transfer_id)?;
+        let current_file = self
+            .state_manager
+            .get_transfer(transfer_id)?
+            .read()
+            .current_file
+            .clone()
+            .ok_or_else(|| SyncError::Internal("No file is currently being copied".into()))?;
+        control.request_skip(current_file.clone());
+        Ok(current_file)
+    }
+
+    /// Pauses a transfer and waits until every in-flight file has fsynced its
+    /// last written block and persisted that offset, so the process can be
+    /// killed without leaving the resume offset ahead of the data on disk.
+    pub async fn checkpoint_transfer(&self, transfer_id: &str) -> SyncResult<CheckpointInfo> {
+        let control = self.get_control(transfer_id)?;
+        control.flush_requested.store(true, Ordering::SeqCst);
+        control.pause();
+        self.set_status(transfer_id, TransferStatus::Paused, None)?;
+
+        let deadline =
+            std::time::Instant::now() + std::time::Duration::from_secs(CHECKPOINT_TIMEOUT_SECS);
+        loop {
+            let active = control.active_copies.load(Ordering::SeqCst);
+            if control.parked_copies.load(Ordering::SeqCst) >= active {
+                break;
+            }
+            if std::time::Instant::now() >= deadline {
+                return Err(SyncError::Timeout(format!(
+                    "In-flight files of transfer {} did not reach a flushed block within {}s",
+                    transfer_id, CHECKPOINT_TIMEOUT_SECS
+                )));
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
         }
 
-        let mut builder = GlobSetBuilder::new();
-        let mut valid_count = 0;
-
-        for pattern in patterns {
-            // Build glob with case-insensitive matching for macOS/Windows compatibility
-            let glob_result = Glob::new(pattern)
-                .or_else(|_| {
-                    // Try with **/ prefix for directory matching
-                    Glob::new(&format!("**/{}", pattern))
-                });
+        let state_arc = self.state_manager.get_transfer(transfer_id)?;
+        let state = state_arc.read();
+        self.state_manager.save_state(&state)?;
 
-            if let Ok(glob) = glob_result {
-                builder.add(glob);
-                valid_count += 1;
-            } else {
-                eprintln!("Warning: Invalid glob pattern ignored: {}", pattern);
+        Ok(CheckpointInfo {
+            transfer_id: transfer_id.to_string(),
+            files_in_flight: control.active_copies.load(Ordering::SeqCst),
+            bytes_persisted: state.bytes_transferred,
+        })
+    }
+
+    /// Gets the engine ready for the app to quit: pauses every running
+    /// transfer, waits for in-flight files to park at a flushed block (up to
+    /// `SHUTDOWN_TIMEOUT_SECS`), then persists all transfer state and the
+    /// hash cache. The paused transfers are offered for resume on next launch.
+    pub async fn shutdown(&self) -> SyncResult<()> {
+        let controls: Vec<(String, Arc<TransferControl>)> = self
+            .controls
+            .read()
+            .iter()
+            .map(|(transfer_id, control)| (transfer_id.clone(), control.clone()))
+            .collect();
+
+        for (transfer_id, control) in &controls {
+            control.flush_requested.store(true, Ordering::SeqCst);
+            control.pause();
+            // A transfer can finish between reading the controls and here
+            if let Ok(state_arc) = self.state_manager.get_transfer(transfer_id) {
+                let mut state = state_arc.write();
+                if !state.is_finished() {
+                    state.status = TransferStatus::Paused;
+                }
             }
         }
 
-        if valid_count == 0 {
-            return None;
+        let deadline =
+            std::time::Instant::now() + std::time::Duration::from_secs(SHUTDOWN_TIMEOUT_SECS);
+        while controls.iter().any(|(_, control)| {
+            control.parked_copies.load(Ordering::SeqCst)
+                < control.active_copies.load(Ordering::SeqCst)
+        }) {
+            if std::time::Instant::now() >= deadline {
+                // Their resume offsets may be ahead of the data on disk; the
+                // block-level check on resume re-copies the unflushed tail
+                eprintln!(
+                    "[Shutdown] In-flight files did not reach a flushed block within {}s",
+                    SHUTDOWN_TIMEOUT_SECS
+                );
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
         }
 
-        builder.build().ok()
+        let saved = self.state_manager.save_all();
+        if let Err(e) = self.state_manager.hash_cache().flush() {
+            eprintln!("[Shutdown] Warning: Failed to save hash cache: {}", e);
+        }
+        eprintln!(
+            "[Shutdown] Paused {} transfer(s) and saved state",
+            controls.len()
+        );
+        saved
     }
 
-    /// Checks if a file path should be excluded based on the compiled pattern set.
-    fn should_exclude(path: &Path, matcher: Option<&GlobSet>) -> bool {
-        let matcher = match matcher {
-            Some(m) => m,
-            None => return false,
+    /// Resolves a file conflict with the user's chosen action.
+    ///
+    /// This method is called from the frontend when a user makes a decision
+    /// in the conflict resolution dialog. It records the resolution and
+    /// optionally emits an event to notify other parts of the system.
+    ///
+    /// # Arguments
+    /// * `conflict_id` - Unique identifier for the conflict (typically a file path hash)
+    /// * `resolution` - The resolution action chosen by the user
+    /// * `transfer_id` - Optional transfer ID if the conflict is associated with an active transfer
+    /// * `paths` - Source and destination file of the conflict. For `KeepBoth`
+    ///   they're looked up in the transfer by `conflict_id` when not given
+    ///
+    /// `KeepBoth` copies the source next to the destination under a name from
+    /// `generate_conflict_name`, leaving the destination file as it is.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the conflict was successfully resolved
+    /// * `Err(SyncError)` if there was an error recording the resolution or
+    ///   making the kept copy
+    pub async fn resolve_conflict(
+        &self,
+        conflict_id: &str,
+        resolution: crate::FrontendConflictResolution,
+        transfer_id: Option<&str>,
+        paths: Option<(PathBuf, PathBuf)>,
+    ) -> SyncResult<()> {
+        // Convert frontend resolution type to internal action type
+        let action = match resolution {
+            crate::FrontendConflictResolution::KeepSource => ConflictResolutionAction::KeepSource,
+            crate::FrontendConflictResolution::KeepDest => ConflictResolutionAction::KeepDest,
+            crate::FrontendConflictResolution::KeepBoth => ConflictResolutionAction::KeepBoth,
+            crate::FrontendConflictResolution::Skip => ConflictResolutionAction::Skip,
+        };
+
+        let kept_copy = if action == ConflictResolutionAction::KeepBoth {
+            let paths =
+                paths.or_else(|| transfer_id.and_then(|tid| self.conflict_paths(tid, conflict_id)));
+            match paths {
+                Some((source, dest)) => Some(self.keep_both(&source, &dest, transfer_id).await?),
+                None => {
+                    eprintln!(
+                        "[Conflict] No files known for {}; recorded keep-both without copying",
+                        conflict_id
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Record the resolution
+        let resolved_conflict = ResolvedConflict {
+            conflict_id: conflict_id.to_string(),
+            resolution: action,
+            resolved_at: std::time::SystemTime::now(),
         };
 
-        // Check the full path
-        if matcher.is_match(path) {
-            return true;
+        {
+            let mut resolved = self.resolved_conflicts.write();
+            resolved.insert(conflict_id.to_string(), resolved_conflict.clone());
         }
 
-        // Also check just the file/folder name for patterns like ".DS_Store"
-        if let Some(file_name) = path.file_name() {
-            if matcher.is_match(file_name) {
-                return true;
+        // Emit conflict resolution event for any listeners
+        if let Some(handle) = self.app_handle.as_ref() {
+            let event_payload = serde_json::json!({
+                "conflictId": conflict_id,
+                "resolution": action,
+                "transferId": transfer_id,
+                "keptCopy": kept_copy,
+            });
+
+            if let Err(e) = handle.emit("conflict-resolved", &event_payload) {
+                // Log but don't fail - the resolution was still recorded
+                eprintln!("Warning: Failed to emit conflict-resolved event: {}", e);
             }
         }
 
-        // Check each path component for directory patterns like "node_modules"
-        for component in path.components() {
-            if let std::path::Component::Normal(name) = component {
-                if matcher.is_match(name) {
-                    return true;
-                }
+        // If this is associated with an active transfer, we could update the transfer state
+        // This allows the sync engine to continue processing if it was waiting for resolution
+        if let Some(tid) = transfer_id {
+            if let Ok(state_arc) = self.state_manager.get_transfer(tid) {
+                let mut state = state_arc.write();
+                // Mark that a conflict was resolved - this could be used to track resolution count
+                state.conflicts_resolved = state.conflicts_resolved.saturating_add(1);
+                let _ = self.state_manager.save_state(&state);
             }
         }
 
-        false
+        Ok(())
+    }
+
+    /// Source and destination of the file in `transfer_id` that `conflict_id`
+    /// names, as an absolute source path or one relative to the source.
+    fn conflict_paths(&self, transfer_id: &str, conflict_id: &str) -> Option<(PathBuf, PathBuf)> {
+        let state_arc = self.state_manager.get_transfer(transfer_id).ok()?;
+        let state = state_arc.read();
+        let conflict_path = Path::new(conflict_id);
+        state
+            .files
+            .values()
+            .find(|file| {
+                file.source_path == conflict_path
+                    || (conflict_path.is_relative() && file.source_path.ends_with(conflict_path))
+            })
+            .map(|file| (file.source_path.clone(), file.dest_path.clone()))
+    }
+
+    /// Copies `source` beside `dest` under a conflict name, using the transfer's
+    /// `conflict_name_template`, and returns where the copy went.
+    async fn keep_both(
+        &self,
+        source: &Path,
+        dest: &Path,
+        transfer_id: Option<&str>,
+    ) -> SyncResult<PathBuf> {
+        let state_arc = transfer_id.and_then(|tid| self.state_manager.get_transfer(tid).ok());
+        let template = state_arc
+            .as_ref()
+            .and_then(|state| state.read().conflict_name_template.clone());
+        let kept = generate_conflict_name(dest, template.as_deref());
+
+        let (copy_source, copy_dest) = (source.to_path_buf(), kept.clone());
+        tokio::task::spawn_blocking(move || {
+            copy_file_atomic(&copy_source, &copy_dest, &CopyOptions::default(), |_, _| {
+                true
+            })
+        })
+        .await
+        .map_err(|e| SyncError::Internal(format!("Keep-both copy task failed: {}", e)))??;
+
+        eprintln!(
+            "[Conflict] Kept both; copied {} to {}",
+            source.display(),
+            kept.display()
+        );
+        if let Some(state_arc) = state_arc {
+            let mut state = state_arc.write();
+            state.record_event(
+                TransferEventKind::Conflict,
+                Some(source),
+                Some(format!("Kept both; copied to {}", kept.display())),
+            );
+            self.state_manager.save_state(&state)?;
+        }
+        Ok(kept)
+    }
+
+    /// Gets a resolved conflict by ID, if one exists.
+    pub fn get_resolved_conflict(&self, conflict_id: &str) -> Option<ResolvedConflict> {
+        let resolved = self.resolved_conflicts.read();
+        resolved.get(conflict_id).cloned()
+    }
+
+    /// All conflicts resolved this session, oldest first.
+    pub fn get_resolved_conflicts(&self) -> Vec<ResolvedConflict> {
+        let mut resolved: Vec<_> = self.resolved_conflicts.read().values().cloned().collect();
+        resolved.sort_by_key(|conflict| conflict.resolved_at);
+        resolved
+    }
+
+    /// Clears all resolved conflicts (typically called when starting a new sync).
+    pub fn clear_resolved_conflicts(&self) {
+        let mut resolved = self.resolved_conflicts.write();
+        resolved.clear();
     }
 
     pub async fn sync_files(
@@ -472,114 +2346,696 @@ impl SyncEngine {
         dest_path: PathBuf,
         mut options: SyncOptions,
     ) -> SyncResult<SyncResult_> {
-        options.source = source_path.clone();
-        options.destination = dest_path.clone();
-
-        // Clean up any stale temp/partial files from previous failed syncs
-        // This ensures we don't have leftover corrupt files and start clean
-        if !options.dry_run && dest_path.exists() {
-            if let Err(e) = cleanup_partial_files(&dest_path) {
-                eprintln!("[Cleanup] Warning: Failed to clean partial files: {}", e);
-                // Non-fatal - continue with sync
+        for dest_root in std::iter::once(&dest_path).chain(&options.spanning_destinations) {
+            Self::check_source_dest_overlap(&source_path, dest_root)?;
+        }
+        Self::validate_subdirs(&source_path, &mut options)?;
+        if !options.spanning_destinations.is_empty() {
+            Self::validate_spanning(&dest_path, &options)?;
+        }
+        if options.probe_network_destination {
+            for dest_root in std::iter::once(&dest_path).chain(&options.spanning_destinations) {
+                probe_network_destination(dest_root)?;
             }
         }
-
-        let transfer_id = self
-            .state_manager
-            .create_transfer(source_path.clone(), dest_path.clone())?;
-        let control = Arc::new(TransferControl::new());
-        {
-            let mut controls = self.controls.write();
-            controls.entry(transfer_id.clone()).or_insert_with(|| control.clone());
+        let skew_warnings = if options.detect_clock_skew {
+            Self::widen_for_clock_skew(&dest_path, &mut options)
+        } else {
+            Vec::new()
+        };
+        if options.mode == SyncMode::MoveVerified {
+            // A source is only removed once its copy has been hashed
+            options.verify_integrity = true;
         }
-
+        let on_move_failure = options.on_move_failure;
+        // Resolutions from an earlier sync don't apply to this one
+        self.clear_resolved_conflicts();
+
+        let notify_on_complete = options.notify_on_complete;
+        let manifest_format = options.write_manifest.filter(|_| !options.dry_run);
+        // Every spanned drive gets its own manifest so each can be checked on its own
+        let manifest_roots: Vec<PathBuf> = std::iter::once(dest_path.clone())
+            .chain(options.spanning_destinations.iter().cloned())
+            .collect();
         let start = std::time::Instant::now();
-        let mut result = SyncResult_::default();
+        // Held until the sync returns, which switches indexing back on
+        let spotlight_pauses: Vec<Result<IndexingPause, String>> =
+            if options.pause_spotlight && !options.dry_run {
+                manifest_roots
+                    .iter()
+                    .map(|root| IndexingPause::pause(root))
+                    .collect()
+            } else {
+                Vec::new()
+            };
 
-        // Perform scan with error tracking for safe orphan deletion
-        let scan_result = self.scan_directory_with_error_tracking(&source_path, options.follow_symlinks)?;
-        // Check completeness before moving info out
-        let scan_complete = scan_result.is_complete();
-        let scan_errors = scan_result.scan_errors;
-        let source_info = scan_result.info;
-        
-        result.files_total = source_info.file_count;
-        result.bytes_total = source_info.total_size;
+        let mut outcome = if options.atomic_publish {
+            self.run_atomic_publish(source_path, dest_path, options)
+                .await
+        } else {
+            self.run_sync(source_path, dest_path, options).await
+        };
 
-        // Build exclusion pattern matcher (compiled once for efficiency)
-        let exclude_matcher = Self::build_exclude_matcher(&options.exclude_patterns);
+        if let Ok(result) = outcome.as_mut() {
+            for warning in spotlight_pauses
+                .iter()
+                .filter_map(|pause| pause.as_ref().err())
+            {
+                eprintln!("[Spotlight] {}", warning);
+                result.warnings.push(warning.clone());
+            }
+            result.warnings.extend(skew_warnings);
+        }
 
-        let state_arc = self.state_manager.get_transfer(&transfer_id)?;
-        {
-            let mut state = state_arc.write();
-            state.status = TransferStatus::Running;
-            state.total_bytes = source_info.total_size;
-            state.total_files = source_info.file_count;
-            state.current_file = None;
-            for file in &source_info.files {
-                if file.is_dir {
-                    continue;
-                }
-                // Skip excluded files from state tracking
-                if Self::should_exclude(&file.path, exclude_matcher.as_ref()) {
-                    continue;
-                }
-                let src = source_path.join(&file.path);
-                if !state.files.contains_key(&src) {
-                    let dst = dest_path.join(&file.path);
-                    let file_state = FileTransferState::new(src, dst, file.size, file.modified);
-                    state.add_file(file_state);
+        if let (Some(format), Ok(result)) = (manifest_format, outcome.as_mut()) {
+            if result.cancelled {
+                eprintln!("[Manifest] Skipped: transfer was cancelled");
+            } else if result.files_failed == 0 {
+                let written = tokio::task::spawn_blocking(move || {
+                    let mut primary = None;
+                    for (index, root) in manifest_roots.iter().enumerate() {
+                        // A spanned drive that received no files has nothing to list
+                        if index > 0 && !root.exists() {
+                            continue;
+                        }
+                        let path = crate::file_ops::write_checksum_manifest(root, format)?;
+                        primary.get_or_insert(path);
+                    }
+                    Ok::<_, SyncError>(primary)
+                })
+                .await
+                .map_err(|e| SyncError::Internal(format!("Manifest task failed: {}", e)))
+                .and_then(|manifest| manifest);
+                match written {
+                    Ok(path) => result.manifest_path = path,
+                    Err(e) => outcome = Err(e),
                 }
+            } else {
+                eprintln!(
+                    "[Manifest] Skipped: {} files failed to copy",
+                    result.files_failed
+                );
             }
-            self.state_manager.save_state(&state)?;
         }
 
-        self.emit_initial_progress(&transfer_id, &source_info);
+        // After publishing and the manifest, so a failure there deletes nothing
+        if let Ok(result) = outcome.as_mut() {
+            Self::remove_verified_sources(result, on_move_failure);
+        }
 
-        // Separate directories, symlinks, and regular files (applying exclusion filters)
-        let mut dirs: Vec<&FileInfo> = Vec::new();
-        let mut symlinks: Vec<&FileInfo> = Vec::new();
-        let mut regular_files: Vec<&FileInfo> = Vec::new();
-        let mut excluded_count: usize = 0;
-        let mut excluded_bytes: u64 = 0;
+        if let Err(e) = self.state_manager.hash_cache().flush() {
+            eprintln!("[HashCache] Warning: Failed to save hash cache: {}", e);
+        }
 
-        for file in &source_info.files {
-            // Check if file should be excluded
-            if Self::should_exclude(&file.path, exclude_matcher.as_ref()) {
-                excluded_count += 1;
-                if !file.is_dir {
-                    excluded_bytes += file.size;
+        if notify_on_complete {
+            crate::notifications::notify_sync_finished(&outcome, start.elapsed());
+        }
+
+        outcome
+    }
+
+    /// Lists the destination entries `delete_orphans` would remove, on every
+    /// spanning drive too, without deleting anything. Like the sync itself,
+    /// this refuses with `IncompleteScan` if the source couldn't be fully scanned.
+    pub async fn preview_orphans(
+        &self,
+        source_path: PathBuf,
+        dest_path: PathBuf,
+        mut options: SyncOptions,
+    ) -> SyncResult<OrphanPreview> {
+        Self::check_source_dest_overlap(&source_path, &dest_path)?;
+        Self::validate_subdirs(&source_path, &mut options)?;
+
+        let exclude_matcher = Self::exclude_matcher(&options, Some(&source_path));
+        let scan_result = self.scan_directory_with_error_tracking(
+            &source_path,
+            options.follow_symlinks,
+            options.skip_unreadable,
+            exclude_matcher.as_ref(),
+            Self::scan_threads(&options, &source_path),
+        )?;
+        let scan_complete = scan_result.is_complete();
+
+        let mut preview = OrphanPreview::default();
+        let roots: Vec<PathBuf> = std::iter::once(dest_path)
+            .chain(options.spanning_destinations.iter().cloned())
+            .collect();
+        let looping_links = Self::looping_symlinks(&options, &source_path, &roots);
+        let untouched: Vec<PathBuf> = scan_result
+            .skipped_unreadable
+            .iter()
+            .cloned()
+            .chain(looping_links.into_iter().map(|(link, _)| link))
+            .collect();
+        for root in roots.into_iter().filter(|root| root.exists()) {
+            let orphans = self.find_orphans(
+                &scan_result.info,
+                &root,
+                scan_complete,
+                &scan_result.scan_errors,
+                &untouched,
+                exclude_matcher.as_ref(),
+            )?;
+            for relative in orphans {
+                let path = root.join(relative);
+                match std::fs::symlink_metadata(&path) {
+                    Ok(metadata) if metadata.is_dir() => preview.dir_count += 1,
+                    Ok(metadata) => {
+                        preview.file_count += 1;
+                        preview.total_bytes += metadata.len();
+                    }
+                    Err(_) => continue,
+                }
+                if preview.paths.len() < ORPHAN_PREVIEW_MAX_PATHS {
+                    preview.paths.push(path);
+                } else {
+                    preview.truncated = true;
                 }
-                result.files_skipped += 1;
-                continue;
             }
+        }
 
-            if file.is_dir {
-                dirs.push(file);
-            } else if file.is_symlink && !options.follow_symlinks {
-                symlinks.push(file);
-            } else {
-                regular_files.push(file);
-            }
+        Ok(preview)
+    }
+
+    /// Rehearses a sync: the destination is copied into a temporary sandbox,
+    /// the sync runs for real against the sandbox (orphan deletion included),
+    /// and the sandbox is diffed against the untouched destination. Move mode
+    /// is rehearsed as a copy, since the source is the real one.
+    pub async fn rehearse_sync(
+        &self,
+        source_path: PathBuf,
+        dest_path: PathBuf,
+        mut options: SyncOptions,
+    ) -> SyncResult<SyncRehearsal> {
+        Self::check_source_dest_overlap(&source_path, &dest_path)?;
+        Self::validate_subdirs(&source_path, &mut options)?;
+        if !options.spanning_destinations.is_empty() {
+            return Err(SyncError::InvalidSpanning(
+                "rehearsals run against a single destination".into(),
+            ));
         }
+        options.mode = SyncMode::Copy;
+        options.dry_run = false;
+        options.notify_on_complete = false;
+        // A marker for the sandbox would never be used again
+        options.incremental_since_marker = false;
+
+        let sandbox =
+            std::env::temp_dir().join(format!("rsync-rehearsal-{}", uuid::Uuid::new_v4().simple()));
+        let sandbox_dest = sandbox.join("destination");
+        // An empty stand-in for a destination that doesn't exist yet
+        let before = if dest_path.exists() {
+            dest_path.clone()
+        } else {
+            let empty = sandbox.join("empty");
+            std::fs::create_dir_all(&empty)
+                .map_err(|e| crate::file_ops::classify_io_error(e, &empty))?;
+            empty
+        };
 
-        // Log exclusion stats if any files were excluded
-        if excluded_count > 0 {
+        let outcome = self
+            .run_rehearsal(source_path, before, sandbox_dest, options)
+            .await;
+        if let Err(e) = std::fs::remove_dir_all(&sandbox) {
             eprintln!(
-                "Excluded {} files/directories ({} bytes) based on patterns",
-                excluded_count, excluded_bytes
+                "[Rehearsal] Warning: Failed to remove sandbox {:?}: {}",
+                sandbox, e
             );
         }
+        outcome
+    }
 
-        // Create directories first (must be sequential)
-        for file in dirs {
-            if control.is_cancelled() {
-                self.set_status(
-                    &transfer_id,
-                    TransferStatus::Cancelled,
-                    Some("Transfer cancelled by user".to_string()),
-                )?;
-                return Err(SyncError::TransferCancelled("Transfer cancelled by user".into()));
+    async fn run_rehearsal(
+        &self,
+        source_path: PathBuf,
+        before: PathBuf,
+        sandbox_dest: PathBuf,
+        options: SyncOptions,
+    ) -> SyncResult<SyncRehearsal> {
+        let (copy_from, copy_to) = (before.clone(), sandbox_dest.clone());
+        tokio::task::spawn_blocking(move || {
+            let destination_size = quick_scan_directory_with_options(&copy_from, false, None)
+                .map(|summary| summary.total_size)
+                .unwrap_or(0);
+            let (available, _) = get_disk_space(&copy_to)?;
+            if available < destination_size {
+                return Err(SyncError::DiskFull {
+                    path: copy_to,
+                    required_bytes: destination_size,
+                    available_bytes: available,
+                });
+            }
+            crate::file_ops::copy_tree(&copy_from, &copy_to)
+        })
+        .await
+        .map_err(|e| SyncError::Internal(format!("Sandbox copy task failed: {}", e)))??;
+
+        let result = self
+            .sync_files(source_path, sandbox_dest.clone(), options)
+            .await?;
+
+        let (summary, changes) = tokio::task::spawn_blocking(move || {
+            let mut changes = Vec::new();
+            let summary = compare_directories(
+                &before,
+                &sandbox_dest,
+                CompareMode::SizeAndMtime,
+                STREAMING_CHUNK_SIZE,
+                |entries| {
+                    changes.extend(
+                        entries
+                            .into_iter()
+                            .filter(|entry| entry.kind != DiffKind::Identical),
+                    );
+                    true
+                },
+            )?;
+            Ok::<_, SyncError>((summary, changes))
+        })
+        .await
+        .map_err(|e| SyncError::Internal(format!("Sandbox diff task failed: {}", e)))??;
+
+        Ok(SyncRehearsal {
+            result,
+            changes,
+            summary,
+        })
+    }
+
+    /// Syncs into a sibling staging directory, then publishes it with two
+    /// same-filesystem renames: the current destination is moved aside and the
+    /// staged tree is moved into its place. If the copy fails the staging
+    /// directory is removed; if the swap fails the old destination is restored.
+    async fn run_atomic_publish(
+        &self,
+        source_path: PathBuf,
+        dest_path: PathBuf,
+        mut options: SyncOptions,
+    ) -> SyncResult<SyncResult_> {
+        let (parent, name) = match (dest_path.parent(), dest_path.file_name()) {
+            (Some(parent), Some(name)) => {
+                (parent.to_path_buf(), name.to_string_lossy().to_string())
+            }
+            _ => {
+                return Err(SyncError::InvalidPath(format!(
+                    "Atomic publish needs a destination with a parent directory: {}",
+                    dest_path.display()
+                )))
+            }
+        };
+        if dest_path.exists() && !dest_path.is_dir() {
+            return Err(SyncError::InvalidPath(format!(
+                "{} is not a directory",
+                dest_path.display()
+            )));
+        }
+
+        // Siblings of the destination, so both renames stay on one filesystem
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let staging = parent.join(format!(".{}.rsync-staging-{}", name, suffix));
+        let previous = parent.join(format!(".{}.rsync-previous-{}", name, suffix));
+
+        // The staging tree starts empty, so every file must be copied into it
+        options.atomic_publish = false;
+        options.incremental_since_marker = false;
+        options.skip_existing = false;
+        options.delete_orphans = false;
+
+        if !options.dry_run {
+            std::fs::create_dir_all(&staging)
+                .map_err(|e| crate::file_ops::classify_io_error(e, &staging))?;
+        }
+
+        let result = match self
+            .run_sync(source_path, staging.clone(), options.clone())
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&staging);
+                return Err(e);
+            }
+        };
+
+        if options.dry_run {
+            return Ok(result);
+        }
+        // A partial tree is never published
+        if result.cancelled {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Ok(result);
+        }
+        if result.files_failed > 0 {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(SyncError::PublishFailed {
+                path: dest_path,
+                reason: format!(
+                    "{} files failed to copy, nothing was published",
+                    result.files_failed
+                ),
+            });
+        }
+
+        let had_previous = dest_path.exists();
+        if had_previous {
+            if let Err(e) = std::fs::rename(&dest_path, &previous) {
+                let _ = std::fs::remove_dir_all(&staging);
+                return Err(SyncError::PublishFailed {
+                    path: dest_path,
+                    reason: format!("could not move the current tree aside: {}", e),
+                });
+            }
+        }
+
+        if let Err(e) = std::fs::rename(&staging, &dest_path) {
+            // Roll back so the destination keeps serving the previous tree
+            if had_previous {
+                if let Err(restore) = std::fs::rename(&previous, &dest_path) {
+                    eprintln!(
+                        "[Publish] Failed to restore previous tree from {:?}: {}",
+                        previous, restore
+                    );
+                }
+            }
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(SyncError::PublishFailed {
+                path: dest_path,
+                reason: format!("could not move the staged tree into place: {}", e),
+            });
+        }
+
+        if let Err(e) = sync_parent_directory(&dest_path) {
+            eprintln!("[Publish] Warning: Parent directory sync failed: {:?}", e);
+        }
+
+        if had_previous {
+            if let Err(e) = std::fs::remove_dir_all(&previous) {
+                eprintln!(
+                    "[Publish] Warning: Failed to remove previous tree {:?}: {}",
+                    previous, e
+                );
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn run_sync(
+        &self,
+        source_path: PathBuf,
+        dest_path: PathBuf,
+        mut options: SyncOptions,
+    ) -> SyncResult<SyncResult_> {
+        options.source = source_path.clone();
+        options.destination = dest_path.clone();
+
+        // A simulated run must never touch the destination: the dry-run guards
+        // cover directories, symlinks, orphans and markers, and
+        // sync_file_static fakes the file copies themselves
+        #[cfg(debug_assertions)]
+        if options.simulate {
+            options.dry_run = true;
+        }
+
+        options.conflict_matcher =
+            ConflictRuleMatcher::compile(&options.conflict_rules).map(Arc::new);
+
+        // Clean up any stale temp/partial files from previous failed syncs
+        // This ensures we don't have leftover corrupt files and start clean.
+        // Partials an unfinished transfer can still resume from are kept.
+        if !options.dry_run && dest_path.exists() {
+            let resumable = self.resumable_partial_paths();
+            if let Err(e) = cleanup_partial_files(&dest_path, &resumable) {
+                eprintln!("[Cleanup] Warning: Failed to clean partial files: {}", e);
+                // Non-fatal - continue with sync
+            }
+        }
+
+        // Holding tens of millions of FileInfos and file states would exhaust memory.
+        // Spanning needs the whole file list to pack the drives, so it never streams.
+        let mut switch_warning = None;
+        if !options.streaming
+            && options.spanning_destinations.is_empty()
+            && exceeds_entry_count(
+                &source_path,
+                options.follow_symlinks,
+                STREAMING_ENTRY_THRESHOLD,
+            )
+        {
+            let warning = format!(
+                "Source has more than {} entries, switched to streaming sync",
+                STREAMING_ENTRY_THRESHOLD
+            );
+            eprintln!("[Sync] {}", warning);
+            switch_warning = Some(warning);
+            options.streaming = true;
+        }
+        if options.streaming {
+            return self
+                .run_streaming_sync(source_path, dest_path, options, switch_warning)
+                .await;
+        }
+
+        let (transfer_id, control) = self.begin_transfer(&source_path, &dest_path, &options)?;
+        self.track_transfer_volumes(&transfer_id, &source_path, &dest_path);
+
+        // Held until this function returns, or given up to wait for a drive
+        let slot = self
+            .wait_for_transfer_slot(&transfer_id, options.priority, &control)
+            .await?;
+
+        let start = std::time::Instant::now();
+        let started_at = chrono::Utc::now();
+        let mut result = SyncResult_::default();
+
+        // Files older than the last successful run can be skipped without a delta check
+        let since_marker = if options.incremental_since_marker {
+            self.state_manager
+                .load_sync_marker(&source_path, &dest_path)
+        } else {
+            None
+        };
+        let unchanged_since_marker = |file: &FileInfo| {
+            !file.is_dir && since_marker.is_some_and(|marker| file.modified < marker)
+        };
+
+        // FAT/exFAT destinations reject some names and sizes; flag those up front
+        // instead of failing them mid-copy
+        let fat_variant = Self::destination_fat_variant(&dest_path);
+        let incompatible = |file: &FileInfo| fat_variant.and_then(|v| fat_incompatibility(file, v));
+
+        // Build exclusion pattern matcher (compiled once for efficiency)
+        let exclude_matcher = Self::exclude_matcher(&options, Some(&source_path));
+
+        // Perform scan with error tracking for safe orphan deletion.
+        // Excluded paths are pruned by the scan and never reach the lists below.
+        let scan_result = self.scan_directory_with_error_tracking(
+            &source_path,
+            options.follow_symlinks,
+            options.skip_unreadable,
+            exclude_matcher.as_ref(),
+            Self::scan_threads(&options, &source_path),
+        )?;
+        // Check completeness before moving info out
+        let scan_complete = scan_result.is_complete();
+        let scan_errors = scan_result.scan_errors;
+        let source_info = scan_result.info;
+        result.unreadable_skipped = scan_result.skipped_unreadable;
+
+        result.files_total = source_info.file_count;
+        result.bytes_total = source_info.total_size;
+
+        // With spanning, links on any of the drives count
+        let dest_roots: Vec<PathBuf> = std::iter::once(dest_path.clone())
+            .chain(options.spanning_destinations.iter().cloned())
+            .collect();
+        let looping_links = Self::looping_symlinks(&options, &source_path, &dest_roots);
+        let loops_back = |file: &FileInfo| {
+            looping_links
+                .iter()
+                .any(|(link, _)| file.path.starts_with(link))
+        };
+        let health_warnings: Vec<String> = dest_roots
+            .iter()
+            .filter_map(|root| self.drive_health_warning(&transfer_id, &options, root))
+            .collect();
+
+        let state_arc = self.state_manager.get_transfer(&transfer_id)?;
+        {
+            let mut state = state_arc.write();
+            state.status = TransferStatus::Running;
+            state.deterministic_order = options.deterministic_order;
+            state.subdirs = options.subdirs.clone();
+            state.conflict_name_template = options.conflict_name_template.clone();
+            state.keep_rollback_backups = options.keep_rollback_backups;
+            state.mtime_tolerance_seconds = options.mtime_tolerance_seconds;
+            state.exclude_patterns = options.exclude_patterns.clone();
+            state.global_exclude_patterns = options.global_exclude_patterns.clone();
+            state.verify_integrity = options.verify_integrity;
+            state.options = Some(options.clone());
+            state.total_bytes = source_info.total_size;
+            state.total_files = source_info.file_count;
+            state.current_file = None;
+            for (_, warning) in &looping_links {
+                state.record_warning(warning.clone());
+            }
+            for warning in health_warnings {
+                state.record_warning(warning);
+            }
+            for file in &source_info.files {
+                if file.is_dir
+                    || file.file_type.is_special()
+                    || unchanged_since_marker(file)
+                    || incompatible(file).is_some()
+                    || loops_back(file)
+                    || Self::leaves_dataless(file, &options)
+                {
+                    continue;
+                }
+                // Insert directly rather than via add_file: the totals above already include it
+                let src = source_path.join(&file.path);
+                let dst = dest_path.join(&file.path);
+                state
+                    .files
+                    .entry(src.clone())
+                    .or_insert_with(|| FileTransferState::new(src, dst, file.size, file.modified));
+            }
+            self.state_manager.save_state(&state)?;
+        }
+
+        self.emit_initial_progress(&transfer_id, &source_info);
+
+        // Files a resumed transfer finished before it was interrupted, unless
+        // their source changed since
+        let finished: HashMap<PathBuf, FileTransferState> = if options.resume_of.is_some() {
+            state_arc
+                .read()
+                .files
+                .iter()
+                .filter(|(_, file_state)| file_state.status == TransferStatus::Completed)
+                .map(|(path, file_state)| (path.clone(), file_state.clone()))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        let finished_copy = |file: &FileInfo| {
+            finished.get(&source_path.join(&file.path)).filter(|f| {
+                !file.is_dir && f.total_bytes == file.size && f.source_mtime == file.modified
+            })
+        };
+        let mut resumed_moves = Vec::new();
+
+        // Separate directories, symlinks, and regular files
+        let mut dirs: Vec<&FileInfo> = Vec::new();
+        let mut symlinks: Vec<&FileInfo> = Vec::new();
+        let mut regular_files: Vec<&FileInfo> = Vec::new();
+        let mut special_files: Vec<&FileInfo> = Vec::new();
+
+        for file in &source_info.files {
+            // Counted as the interrupted run left them, so the result covers the whole transfer
+            if let Some(file_state) = finished_copy(file) {
+                if file_state.skipped {
+                    result.files_skipped += 1;
+                } else {
+                    result.files_copied += 1;
+                    result.bytes_copied += file_state.total_bytes;
+                }
+                if options.mode == SyncMode::MoveVerified && file_state.verified {
+                    resumed_moves.push(file_state.source_path.clone());
+                }
+                continue;
+            }
+            if unchanged_since_marker(file) {
+                result.files_skipped += 1;
+                continue;
+            }
+            if loops_back(file) {
+                if !file.is_dir {
+                    result.files_skipped += 1;
+                }
+                continue;
+            }
+            if Self::leaves_dataless(file, &options) {
+                result.files_skipped += 1;
+                result.dataless_skipped.push(file.path.clone());
+                continue;
+            }
+
+            if let Some(reason) = incompatible(file) {
+                if !file.is_dir {
+                    result.files_skipped += 1;
+                }
+                result.incompatible_files.push(IncompatibleFile {
+                    path: file.path.clone(),
+                    reason,
+                });
+                continue;
+            }
+
+            if file.is_dir {
+                dirs.push(file);
+            } else if file.file_type.is_special() {
+                // Reading a FIFO blocks forever and sockets/devices can't be opened as files
+                special_files.push(file);
+            } else if file.is_symlink && !options.follow_symlinks {
+                symlinks.push(file);
+            } else {
+                regular_files.push(file);
+            }
+        }
+
+        self.emit_incompatible_files(&transfer_id, &result.incompatible_files);
+
+        if options.deterministic_order {
+            regular_files.sort_by(|a, b| a.path.cmp(&b.path));
+        } else {
+            options.copy_order.sort(&mut regular_files);
+        }
+
+        // Spanning: every root in fill order, and the root chosen for each file
+        let span_roots: Vec<PathBuf> = std::iter::once(dest_path.clone())
+            .chain(options.spanning_destinations.iter().cloned())
+            .collect();
+        let placement = if options.spanning_destinations.is_empty() {
+            HashMap::new()
+        } else {
+            let placement =
+                match Self::plan_spanning(&regular_files, &span_roots, options.modify_window()) {
+                    Ok(placement) => placement,
+                    Err(e) => {
+                        self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+                        return Err(e);
+                    }
+                };
+            let mut state = state_arc.write();
+            state.spanning_destinations = options.spanning_destinations.clone();
+            for (relative, &index) in &placement {
+                if let Some(file_state) = state.files.get_mut(&source_path.join(relative)) {
+                    file_state.dest_path = span_roots[index].join(relative);
+                }
+            }
+            self.state_manager.save_state(&state)?;
+            placement
+        };
+
+        // Pre-flight: enforce write budget and destination fill policy limits
+        if (options.max_write_bytes.is_some() || options.max_dest_fill_percent.is_some())
+            && !options.structure_only
+        {
+            let (bytes_to_copy, bytes_replaced) =
+                Self::estimate_bytes_to_copy(&regular_files, &dest_path, options.modify_window());
+            if let Err(e) =
+                Self::check_write_limits(&options, &dest_path, bytes_to_copy, bytes_replaced, false)
+            {
+                self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+                return Err(e);
+            }
+        }
+
+        // Create directories first (must be sequential)
+        for file in &dirs {
+            if control.is_cancelled() {
+                return self.finish_cancelled(&transfer_id, result, start);
             }
             if !options.dry_run {
                 self.create_directory(&dest_path, file)?;
@@ -589,12 +3045,7 @@ impl SyncEngine {
         // Copy symlinks (sequential, fast operation)
         for file in symlinks {
             if control.is_cancelled() {
-                self.set_status(
-                    &transfer_id,
-                    TransferStatus::Cancelled,
-                    Some("Transfer cancelled by user".to_string()),
-                )?;
-                return Err(SyncError::TransferCancelled("Transfer cancelled by user".into()));
+                return self.finish_cancelled(&transfer_id, result, start);
             }
             if !options.dry_run {
                 let source_abs = source_path.join(&file.path);
@@ -604,6 +3055,31 @@ impl SyncEngine {
                         result.files_copied += 1;
                     }
                     Err(e) => {
+                        // A disconnect with reconnect enabled stops the run so the
+                        // whole transfer can wait for the drive instead of failing
+                        // every remaining file
+                        if let (SyncError::DriveDisconnected { .. }, Some(timeout_secs)) =
+                            (&e, options.reconnect_timeout_secs)
+                        {
+                            return self
+                                .wait_for_reconnect_and_resume(
+                                    &transfer_id,
+                                    e,
+                                    timeout_secs,
+                                    &options,
+                                    slot,
+                                    start,
+                                )
+                                .await;
+                        }
+                        if options.on_error == ErrorPolicy::AbortTransfer {
+                            self.set_status(
+                                &transfer_id,
+                                TransferStatus::Failed,
+                                Some(e.to_string()),
+                            )?;
+                            return Err(e);
+                        }
                         result.files_failed += 1;
                         result.errors.push(format!("{}: {}", file.path.display(), e));
                     }
@@ -613,18 +3089,123 @@ impl SyncEngine {
             }
         }
 
+        // Special files: recreate FIFOs if requested, skip everything else
+        for file in special_files {
+            if options.preserve_special && file.file_type == SpecialFileType::Fifo {
+                if !options.dry_run {
+                    let source_abs = source_path.join(&file.path);
+                    let dest_abs = dest_path.join(&file.path);
+                    if let Err(e) = create_fifo(&source_abs, &dest_abs) {
+                        result.files_failed += 1;
+                        result
+                            .errors
+                            .push(format!("{}: {}", file.path.display(), e));
+                        continue;
+                    }
+                }
+                result.files_copied += 1;
+            } else {
+                result.special_files_skipped += 1;
+                result.files_skipped += 1;
+            }
+        }
+
         // Process regular files in parallel using semaphore
-        let max_concurrent = options.max_concurrent_files.clamp(1, 8);
-        let semaphore = Arc::new(Semaphore::new(max_concurrent));
-        let files_copied = Arc::new(AtomicUsize::new(0));
-        let files_failed = Arc::new(AtomicUsize::new(0));
-        let bytes_copied_atomic = Arc::new(AtomicUsize::new(0));
-        let errors = Arc::new(parking_lot::Mutex::new(Vec::<String>::new()));
+        let (max_concurrent, limited_reason) =
+            Self::effective_concurrency(&options, &source_path, &dest_path);
+        let (max_concurrent, limited_reason) =
+            Self::fit_memory_budget(&mut options, max_concurrent, limited_reason);
+        result.effective_concurrency = max_concurrent;
+        control
+            .hash_limiter
+            .set_limit(options.max_concurrent_hashes.unwrap_or(max_concurrent));
+        result.concurrency_limited_reason = limited_reason;
+        // Adaptive runs start at one permit and the controller adds the rest
+        let adaptive = options.adaptive_concurrency && max_concurrent > 1;
+        let semaphore = Arc::new(Semaphore::new(if adaptive { 1 } else { max_concurrent }));
+        let tasks = FileTasks::new(
+            &transfer_id,
+            &source_path,
+            &options,
+            &control,
+            &self.state_manager,
+            self.app_handle.as_ref(),
+        );
+        tasks.verified_moves.lock().extend(resumed_moves);
+
+        // Small new files go across in tar batches; whatever a batch couldn't
+        // copy is left to the per-file copy below
+        if Self::packs_small_files(&options) {
+            let (packable, rest): (Vec<&FileInfo>, Vec<&FileInfo>) =
+                regular_files.into_iter().partition(|file| {
+                    file.size < PACK_FILE_THRESHOLD
+                        && !file.is_symlink
+                        && fits_header(&file.path)
+                        && !dest_path.join(&file.path).exists()
+                });
+            let (packed, unpacked) = self
+                .copy_packed(
+                    &transfer_id,
+                    &source_path,
+                    &dest_path,
+                    packable,
+                    &options,
+                    &control,
+                )
+                .await;
+            for (path, bytes) in packed {
+                tasks.record_copied(&path, bytes);
+            }
+            regular_files = unpacked.into_iter().chain(rest).collect();
+        }
 
         // Clone shared resources for tasks
         let state_manager = self.state_manager.clone();
         let app_handle = self.app_handle.clone();
 
+        // Watch destination free space in the background and pause before it fills up.
+        // Spanning fills the first drive on purpose, so it isn't watched there.
+        let disk_monitor = options
+            .low_disk_space_threshold
+            .filter(|_| options.spanning_destinations.is_empty())
+            .map(|threshold| {
+                Self::spawn_disk_space_monitor(
+                    transfer_id.clone(),
+                    dest_path.clone(),
+                    threshold,
+                    control.clone(),
+                    state_manager.clone(),
+                    app_handle.clone(),
+                )
+            });
+        let stall_watchdog = options.stall_timeout_secs.map(|timeout_secs| {
+            Self::spawn_stall_watchdog(
+                transfer_id.clone(),
+                timeout_secs,
+                options.fail_on_stall,
+                control.clone(),
+                tasks.abort_error.clone(),
+                state_manager.clone(),
+                app_handle.clone(),
+            )
+        });
+        let chosen_concurrency = Arc::new(AtomicUsize::new(1));
+        let concurrency_controller = adaptive.then(|| {
+            Self::spawn_concurrency_controller(
+                transfer_id.clone(),
+                max_concurrent,
+                semaphore.clone(),
+                chosen_concurrency.clone(),
+                control.clone(),
+                state_manager.clone(),
+            )
+        });
+
+        let mut sampler = VerifySampler::new(&options);
+        if let Some(sampler) = sampler.as_mut() {
+            sampler.offer(regular_files.iter().map(|file| file.size));
+        }
+
         let mut handles = Vec::new();
 
         for file in regular_files {
@@ -645,50 +3226,14 @@ impl SyncEngine {
                     break;
                 }
             };
-            let transfer_id = transfer_id.clone();
-            let source_path = source_path.clone();
-            let dest_path = dest_path.clone();
-            let file = file.clone();
-            let options = options.clone();
-            let control = control.clone();
-            let files_copied = files_copied.clone();
-            let files_failed = files_failed.clone();
-            let bytes_copied_atomic = bytes_copied_atomic.clone();
-            let errors = errors.clone();
-            let state_manager = state_manager.clone();
-            let app_handle = app_handle.clone();
-
-            let handle = tokio::spawn(async move {
-                let _permit = permit; // Hold permit until task completes
-
-                match Self::sync_file_static(
-                    &transfer_id,
-                    &source_path,
-                    &dest_path,
-                    &file,
-                    &options,
-                    &control,
-                    &state_manager,
-                    app_handle.as_ref(),
-                ).await
-                {
-                    Ok(bytes) => {
-                        files_copied.fetch_add(1, Ordering::Relaxed);
-                        bytes_copied_atomic.fetch_add(bytes as usize, Ordering::Relaxed);
-                    }
-                    Err(e) => {
-                        files_failed.fetch_add(1, Ordering::Relaxed);
-                        errors.lock().push(format!("{}: {}", file.path.display(), e));
-                        let source_abs = source_path.join(&file.path);
-                        if let Ok(state_arc) = state_manager.get_transfer(&transfer_id) {
-                            let mut state = state_arc.write();
-                            state.fail_file(&source_abs, e.to_string());
-                            let _ = state_manager.save_state(&state);
-                        }
-                    }
-                }
-            });
-            handles.push(handle);
+            if options.deterministic_order {
+                eprintln!("[Sync] Copying {}", file.path.display());
+            }
+            let dest_root = placement
+                .get(&file.path)
+                .map_or_else(|| dest_path.clone(), |&index| span_roots[index].clone());
+            let sampled = sampler.as_ref().is_some_and(|sampler| sampler.select(file));
+            handles.push(tasks.spawn(file.clone(), dest_root, &options, sampled, permit));
         }
 
         // Wait for all file transfers to complete
@@ -696,360 +3241,2455 @@ impl SyncEngine {
             let _ = handle.await;
         }
 
-        // Check if cancelled while processing
-        if control.is_cancelled() {
-            self.set_status(
-                &transfer_id,
-                TransferStatus::Cancelled,
-                Some("Transfer cancelled by user".to_string()),
-            )?;
-            return Err(SyncError::TransferCancelled("Transfer cancelled by user".into()));
+        if let Some(monitor) = disk_monitor {
+            monitor.abort();
+        }
+        if let Some(watchdog) = stall_watchdog {
+            watchdog.abort();
+        }
+        if let Some(controller) = concurrency_controller {
+            controller.abort();
+            result.effective_concurrency = chosen_concurrency.load(Ordering::Relaxed);
         }
 
-        // Collect results
-        result.files_copied += files_copied.load(Ordering::Relaxed);
-        result.files_failed += files_failed.load(Ordering::Relaxed);
-        result.bytes_copied += bytes_copied_atomic.load(Ordering::Relaxed) as u64;
-        result.errors.extend(errors.lock().drain(..));
-
-        if options.delete_orphans && !options.dry_run {
-            match self.cleanup_orphans(&source_info, &dest_path, scan_complete, &scan_errors) {
-                Ok(_) => {}
-                Err(e) => {
-                    // Don't fail the whole sync, but add to errors
-                    result.errors.push(format!("Orphan cleanup skipped: {}", e));
-                }
+        // Abort-on-first-error: report the original failure rather than a cancellation
+        let abort = tasks.abort_error.lock().take();
+        if let Some(e) = abort {
+            if let (SyncError::DriveDisconnected { .. }, Some(timeout_secs)) =
+                (&e, options.reconnect_timeout_secs)
+            {
+                return self
+                    .wait_for_reconnect_and_resume(
+                        &transfer_id,
+                        e,
+                        timeout_secs,
+                        &options,
+                        slot,
+                        start,
+                    )
+                    .await;
+            }
+            self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+            return Err(e);
+        }
+
+        // Collect results
+        tasks.collect_into(&mut result);
+        // Reflects files that changed size between the scan and their copy
+        result.bytes_total = state_arc.read().total_bytes;
+        Self::warn_dataless_skipped(&mut state_arc.write(), &result.dataless_skipped);
+        if sampler.is_some() {
+            result.verify_sample = Some(std::mem::take(&mut *tasks.sample.lock()));
+        }
+        result.warnings = state_arc.read().warnings.clone();
+        // Stop before anything that assumes the whole tree was copied
+        if control.is_cancelled() {
+            return self.finish_cancelled(&transfer_id, result, start);
+        }
+        // A verified move deletes nothing unless every file made it across
+        result.verified_for_move = tasks.take_verified_moves(&result);
+
+        // With spanning, each drive is cleaned against the full source tree
+        if options.delete_orphans && !options.dry_run {
+            // Looping links are left in place, like unreadable sources
+            let untouched: Vec<PathBuf> = result
+                .unreadable_skipped
+                .iter()
+                .cloned()
+                .chain(looping_links.iter().map(|(link, _)| link.clone()))
+                .collect();
+            let mut pending = Vec::new();
+            for root in span_roots.iter().filter(|root| root.exists()) {
+                match self.find_orphans(
+                    &source_info,
+                    root,
+                    scan_complete,
+                    &scan_errors,
+                    &untouched,
+                    exclude_matcher.as_ref(),
+                ) {
+                    Ok(orphans) => pending.push(OrphanDeletion {
+                        dest_root: root.clone(),
+                        orphans,
+                        next: 0,
+                    }),
+                    Err(e) => {
+                        // Don't fail the whole sync, but add to errors
+                        result.errors.push(format!("Orphan cleanup skipped: {}", e));
+                    }
+                }
+            }
+            if let Err(e) = self.delete_orphans_resumably(&transfer_id, pending, &control) {
+                result.errors.push(format!("Orphan cleanup failed: {}", e));
+            }
+        }
+
+        if options.prune_empty_dirs && !options.dry_run {
+            for root in span_roots.iter().filter(|root| root.exists()) {
+                Self::prune_empty_directories(&source_path, root, options.ensure_empty_dirs);
+            }
+        }
+
+        // Last, since copying into a directory resets its mtime
+        if options.preserve_metadata && !options.dry_run {
+            let mtimes: Vec<_> = dirs
+                .iter()
+                .map(|dir| (dir.path.as_path(), dir.modified))
+                .collect();
+            let flags: Vec<_> = dirs
+                .iter()
+                .filter(|dir| dir.flags != 0)
+                .map(|dir| (dir.path.as_path(), dir.flags))
+                .collect();
+            for root in span_roots.iter().filter(|root| root.exists()) {
+                Self::restore_directory_mtimes(root, &mtimes);
+                result
+                    .warnings
+                    .extend(Self::restore_directory_flags(root, &flags));
+            }
+        }
+
+        self.set_status(&transfer_id, TransferStatus::Completed, None)?;
+
+        // Only a clean run may advance the marker; failed files have old mtimes
+        // and would otherwise be skipped forever
+        if result.files_failed == 0 && !options.dry_run {
+            if let Err(e) =
+                self.state_manager
+                    .save_sync_marker(&source_path, &dest_path, started_at)
+            {
+                eprintln!(
+                    "[Sync] Warning: Failed to save incremental sync marker: {}",
+                    e
+                );
             }
         }
 
-        self.set_status(&transfer_id, TransferStatus::Completed, None)?;
         result.duration_ms = start.elapsed().as_millis() as u64;
         Ok(result)
     }
 
-    /// Resume an interrupted sync from its persisted state.
-    /// This reuses the existing transfer ID and continues from where it left off.
-    async fn resume_sync_with_state(
+    /// Sync path for very large trees. Files are taken from a `DirectoryScanner`
+    /// in chunks of `STREAMING_CHUNK_SIZE`, and each chunk is copied and pruned
+    /// from the transfer state before the next one is scanned, so memory stays
+    /// bounded by the chunk size rather than the tree size.
+    async fn run_streaming_sync(
         &self,
-        transfer_id: &str,
+        source_path: PathBuf,
+        dest_path: PathBuf,
         mut options: SyncOptions,
-    ) -> SyncResult<()> {
-        let state_arc = self.state_manager.get_transfer(transfer_id)?;
-        let (source_path, dest_path) = {
-            let state = state_arc.read();
-            (state.source_path.clone(), state.dest_path.clone())
+        switch_warning: Option<String>,
+    ) -> SyncResult<SyncResult_> {
+        let (transfer_id, control) = self.begin_transfer(&source_path, &dest_path, &options)?;
+        self.track_transfer_volumes(&transfer_id, &source_path, &dest_path);
+
+        // Held until this function returns, or given up to wait for a drive
+        let slot = self
+            .wait_for_transfer_slot(&transfer_id, options.priority, &control)
+            .await?;
+
+        let start = std::time::Instant::now();
+        let started_at = chrono::Utc::now();
+        let mut result = SyncResult_::default();
+
+        let since_marker = if options.incremental_since_marker {
+            self.state_manager
+                .load_sync_marker(&source_path, &dest_path)
+        } else {
+            None
+        };
+        let unchanged_since_marker = |file: &FileInfo| {
+            !file.is_dir && since_marker.is_some_and(|marker| file.modified < marker)
         };
 
-        options.source = source_path.clone();
-        options.destination = dest_path.clone();
+        let fat_variant = Self::destination_fat_variant(&dest_path);
 
-        // Validate paths still exist
-        if !source_path.exists() {
-            return Err(SyncError::SourceNotFound(source_path.display().to_string()));
-        }
+        // Totals for progress reporting, without keeping the file list
+        let summary =
+            quick_scan_directory_with_options(&source_path, options.follow_symlinks, None)?;
+        result.files_total = summary.file_count;
+        result.bytes_total = summary.total_size;
 
-        // Create control for the resumed transfer
-        let control = Arc::new(TransferControl::new());
+        // Without a full file list there is nothing to diff against the
+        // destination, so limits are checked against the whole source size,
+        // less the destination files at the same paths for the fill level
+        if (options.max_write_bytes.is_some() || options.max_dest_fill_percent.is_some())
+            && !options.structure_only
         {
-            let mut controls = self.controls.write();
-            controls.insert(transfer_id.to_string(), control.clone());
+            let bytes_replaced = if options.max_dest_fill_percent.is_some() {
+                counterpart_bytes(&source_path, &dest_path, options.follow_symlinks)
+            } else {
+                0
+            };
+            if let Err(e) = Self::check_write_limits(
+                &options,
+                &dest_path,
+                summary.total_size,
+                bytes_replaced,
+                true,
+            ) {
+                self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+                return Err(e);
+            }
         }
 
-        // Update state to running
+        let looping_links =
+            Self::looping_symlinks(&options, &source_path, std::slice::from_ref(&dest_path));
+        let loops_back = |file: &FileInfo| {
+            looping_links
+                .iter()
+                .any(|(link, _)| file.path.starts_with(link))
+        };
+        let health_warning = self.drive_health_warning(&transfer_id, &options, &dest_path);
+
+        let state_arc = self.state_manager.get_transfer(&transfer_id)?;
         {
             let mut state = state_arc.write();
             state.status = TransferStatus::Running;
-            state.error = None;
+            state.deterministic_order = options.deterministic_order;
+            state.subdirs = options.subdirs.clone();
+            state.conflict_name_template = options.conflict_name_template.clone();
+            state.keep_rollback_backups = options.keep_rollback_backups;
+            state.mtime_tolerance_seconds = options.mtime_tolerance_seconds;
+            state.exclude_patterns = options.exclude_patterns.clone();
+            state.global_exclude_patterns = options.global_exclude_patterns.clone();
+            state.verify_integrity = options.verify_integrity;
+            state.options = Some(options.clone());
+            state.total_bytes = summary.total_size;
+            state.total_files = summary.file_count;
+            state.current_file = None;
+            if let Some(warning) = switch_warning {
+                state.record_warning(warning);
+            }
+            for (_, warning) in &looping_links {
+                state.record_warning(warning.clone());
+            }
+            if let Some(warning) = health_warning {
+                state.record_warning(warning);
+            }
             self.state_manager.save_state(&state)?;
         }
 
-        // Re-scan source to get current file list
-        let scan_result = self.scan_directory_with_error_tracking(&source_path, options.follow_symlinks)?;
-        let source_info = scan_result.info;
+        self.emit_initial_progress(
+            &transfer_id,
+            &DirectoryInfo {
+                path: source_path.clone(),
+                total_size: summary.total_size,
+                file_count: summary.file_count,
+                dir_count: summary.dir_count,
+                files: Vec::new(),
+            },
+        );
+
+        let exclude_matcher = Self::exclude_matcher(&options, Some(&source_path));
+        let filter = ScanFilter {
+            exclude_matcher: exclude_matcher.clone(),
+            ..Default::default()
+        };
+        let mut scanner = DirectoryScanner::new(
+            &source_path,
+            options.follow_symlinks,
+            STREAMING_CHUNK_SIZE,
+            filter,
+        )?
+        .skipping_unreadable(options.skip_unreadable);
+        if options.deterministic_order {
+            // A name-sorted depth-first walk yields chunks already in path order
+            scanner = scanner.sorted_by_name();
+        }
 
-        // Build exclusion pattern matcher
-        let exclude_matcher = Self::build_exclude_matcher(&options.exclude_patterns);
+        let (max_concurrent, limited_reason) =
+            Self::effective_concurrency(&options, &source_path, &dest_path);
+        let (max_concurrent, limited_reason) =
+            Self::fit_memory_budget(&mut options, max_concurrent, limited_reason);
+        result.effective_concurrency = max_concurrent;
+        control
+            .hash_limiter
+            .set_limit(options.max_concurrent_hashes.unwrap_or(max_concurrent));
+        result.concurrency_limited_reason = limited_reason;
+        // Adaptive runs start at one permit and the controller adds the rest
+        let adaptive = options.adaptive_concurrency && max_concurrent > 1;
+        let semaphore = Arc::new(Semaphore::new(if adaptive { 1 } else { max_concurrent }));
+        let tasks = FileTasks::new(
+            &transfer_id,
+            &source_path,
+            &options,
+            &control,
+            &self.state_manager,
+            self.app_handle.as_ref(),
+        );
+        // Stamped after the copy, once nothing else is written into them
+        let mut directory_mtimes: Vec<(PathBuf, chrono::DateTime<chrono::Utc>)> = Vec::new();
+        let mut directory_flags: Vec<(PathBuf, u32)> = Vec::new();
 
-        // Identify files that need to be transferred
-        let files_to_transfer: Vec<&FileInfo> = source_info
-            .files
-            .iter()
-            .filter(|file| {
-                if file.is_dir {
-                    return false;
+        let state_manager = self.state_manager.clone();
+        let app_handle = self.app_handle.clone();
+
+        let disk_monitor = options.low_disk_space_threshold.map(|threshold| {
+            Self::spawn_disk_space_monitor(
+                transfer_id.clone(),
+                dest_path.clone(),
+                threshold,
+                control.clone(),
+                state_manager.clone(),
+                app_handle.clone(),
+            )
+        });
+        let stall_watchdog = options.stall_timeout_secs.map(|timeout_secs| {
+            Self::spawn_stall_watchdog(
+                transfer_id.clone(),
+                timeout_secs,
+                options.fail_on_stall,
+                control.clone(),
+                tasks.abort_error.clone(),
+                state_manager.clone(),
+                app_handle.clone(),
+            )
+        });
+        let chosen_concurrency = Arc::new(AtomicUsize::new(1));
+        let concurrency_controller = adaptive.then(|| {
+            Self::spawn_concurrency_controller(
+                transfer_id.clone(),
+                max_concurrent,
+                semaphore.clone(),
+                chosen_concurrency.clone(),
+                control.clone(),
+                state_manager.clone(),
+            )
+        });
+
+        let mut sampler = VerifySampler::new(&options);
+
+        while let Some(chunk) = scanner.next_chunk() {
+            if control.is_cancelled() {
+                break;
+            }
+
+            let mut regular_files: Vec<FileInfo> = Vec::new();
+            let mut chunk_incompatible: Vec<IncompatibleFile> = Vec::new();
+            // Waited on once the state lock is released
+            let mut disconnected = None;
+            {
+                let mut state = state_arc.write();
+                for file in chunk {
+                    if unchanged_since_marker(&file) {
+                        result.files_skipped += 1;
+                        continue;
+                    }
+                    if loops_back(&file) {
+                        if !file.is_dir {
+                            result.files_skipped += 1;
+                        }
+                        continue;
+                    }
+                    if Self::leaves_dataless(&file, &options) {
+                        result.files_skipped += 1;
+                        result.dataless_skipped.push(file.path);
+                        continue;
+                    }
+
+                    if let Some(reason) = fat_variant.and_then(|v| fat_incompatibility(&file, v)) {
+                        if !file.is_dir {
+                            result.files_skipped += 1;
+                        }
+                        chunk_incompatible.push(IncompatibleFile {
+                            path: file.path,
+                            reason,
+                        });
+                        continue;
+                    }
+
+                    // Walkdir yields a directory before its contents, so parents
+                    // always exist by the time their files are copied
+                    if file.is_dir {
+                        if !options.dry_run {
+                            self.create_directory(&dest_path, &file)?;
+                        }
+                        directory_mtimes.push((file.path.clone(), file.modified));
+                        if file.flags != 0 {
+                            directory_flags.push((file.path.clone(), file.flags));
+                        }
+                    } else if file.file_type.is_special() {
+                        if options.preserve_special && file.file_type == SpecialFileType::Fifo {
+                            if !options.dry_run {
+                                if let Err(e) = create_fifo(
+                                    &source_path.join(&file.path),
+                                    &dest_path.join(&file.path),
+                                ) {
+                                    result.files_failed += 1;
+                                    result
+                                        .errors
+                                        .push(format!("{}: {}", file.path.display(), e));
+                                    continue;
+                                }
+                            }
+                            result.files_copied += 1;
+                        } else {
+                            result.special_files_skipped += 1;
+                            result.files_skipped += 1;
+                        }
+                    } else if file.is_symlink && !options.follow_symlinks {
+                        if options.dry_run {
+                            result.files_copied += 1;
+                            continue;
+                        }
+                        let source_abs = source_path.join(&file.path);
+                        let dest_abs = dest_path.join(&file.path);
+                        match copy_symlink(&source_abs, &dest_abs, false) {
+                            Ok(_) => result.files_copied += 1,
+                            Err(e) => {
+                                if options.reconnect_timeout_secs.is_some()
+                                    && matches!(e, SyncError::DriveDisconnected { .. })
+                                {
+                                    disconnected = Some(e);
+                                    break;
+                                }
+                                if options.on_error == ErrorPolicy::AbortTransfer {
+                                    drop(state);
+                                    self.set_status(
+                                        &transfer_id,
+                                        TransferStatus::Failed,
+                                        Some(e.to_string()),
+                                    )?;
+                                    return Err(e);
+                                }
+                                result.files_failed += 1;
+                                result
+                                    .errors
+                                    .push(format!("{}: {}", file.path.display(), e));
+                            }
+                        }
+                    } else {
+                        // Added directly so the totals from the quick scan stay put
+                        let src = source_path.join(&file.path);
+                        let dst = dest_path.join(&file.path);
+                        state.files.insert(
+                            src.clone(),
+                            FileTransferState::new(src, dst, file.size, file.modified),
+                        );
+                        regular_files.push(file);
+                    }
                 }
-                if Self::should_exclude(&file.path, exclude_matcher.as_ref()) {
-                    return false;
+                self.state_manager.save_state(&state)?;
+            }
+            if let (Some(e), Some(timeout_secs)) = (disconnected, options.reconnect_timeout_secs) {
+                return self
+                    .wait_for_reconnect_and_resume(
+                        &transfer_id,
+                        e,
+                        timeout_secs,
+                        &options,
+                        slot,
+                        start,
+                    )
+                    .await;
+            }
+
+            self.emit_incompatible_files(&transfer_id, &chunk_incompatible);
+            result.incompatible_files.extend(chunk_incompatible);
+
+            // A name-sorted walk already yields deterministic chunks in path order
+            if !options.deterministic_order {
+                options.copy_order.sort(&mut regular_files);
+            }
+
+            if let Some(sampler) = sampler.as_mut() {
+                sampler.offer(regular_files.iter().map(|file| file.size));
+            }
+
+            let mut handles = Vec::new();
+
+            for file in regular_files {
+                if control.is_cancelled() {
+                    break;
                 }
-                
-                // Check if file was already completed in previous run
-                let src = source_path.join(&file.path);
-                let state = state_arc.read();
-                if let Some(file_state) = state.files.get(&src) {
-                    // Skip if already completed
-                    if file_state.status == TransferStatus::Completed {
-                        return false;
+
+                control.wait_for_resume().await;
+
+                let permit = match semaphore.clone().acquire_owned().await {
+                    Ok(p) => p,
+                    Err(_) => {
+                        eprintln!("[Sync] Semaphore closed, stopping sync");
+                        break;
                     }
+                };
+                if options.deterministic_order {
+                    eprintln!("[Sync] Copying {}", file.path.display());
                 }
-                true
-            })
+                let sampled = sampler
+                    .as_ref()
+                    .is_some_and(|sampler| sampler.select(&file));
+                handles.push(tasks.spawn(file, dest_path.clone(), &options, sampled, permit));
+            }
+
+            for handle in handles {
+                let _ = handle.await;
+            }
+
+            // Drop finished files so the state only ever holds one chunk plus
+            // failures; the counters keep the totals
+            {
+                let mut state = state_arc.write();
+                state
+                    .files
+                    .retain(|_, f| f.status != TransferStatus::Completed);
+                self.state_manager.save_state(&state)?;
+            }
+        }
+
+        if let Some(monitor) = disk_monitor {
+            monitor.abort();
+        }
+        if let Some(watchdog) = stall_watchdog {
+            watchdog.abort();
+        }
+        if let Some(controller) = concurrency_controller {
+            controller.abort();
+            result.effective_concurrency = chosen_concurrency.load(Ordering::Relaxed);
+        }
+
+        let abort = tasks.abort_error.lock().take();
+        if let Some(e) = abort {
+            if let (SyncError::DriveDisconnected { .. }, Some(timeout_secs)) =
+                (&e, options.reconnect_timeout_secs)
+            {
+                return self
+                    .wait_for_reconnect_and_resume(
+                        &transfer_id,
+                        e,
+                        timeout_secs,
+                        &options,
+                        slot,
+                        start,
+                    )
+                    .await;
+            }
+            self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+            return Err(e);
+        }
+
+        tasks.collect_into(&mut result);
+        result.bytes_total = state_arc.read().total_bytes;
+        Self::warn_dataless_skipped(&mut state_arc.write(), &result.dataless_skipped);
+        if sampler.is_some() {
+            result.verify_sample = Some(std::mem::take(&mut *tasks.sample.lock()));
+        }
+        result.warnings = state_arc.read().warnings.clone();
+        // Stop before anything that assumes the whole tree was copied
+        if control.is_cancelled() {
+            return self.finish_cancelled(&transfer_id, result, start);
+        }
+        // A verified move deletes nothing unless every file made it across
+        result.verified_for_move = tasks.take_verified_moves(&result);
+
+        result.unreadable_skipped = scanner.skipped_unreadable().to_vec();
+        if options.delete_orphans && !options.dry_run {
+            // Looping links are left in place, like unreadable sources
+            let untouched: Vec<PathBuf> = result
+                .unreadable_skipped
+                .iter()
+                .cloned()
+                .chain(looping_links.iter().map(|(link, _)| link.clone()))
+                .collect();
+            match self.find_orphans_against_source(
+                &source_path,
+                &dest_path,
+                &untouched,
+                scanner.scan_errors(),
+                exclude_matcher.as_ref(),
+            ) {
+                Ok(orphans) => {
+                    let pending = vec![OrphanDeletion {
+                        dest_root: dest_path.clone(),
+                        orphans,
+                        next: 0,
+                    }];
+                    if let Err(e) = self.delete_orphans_resumably(&transfer_id, pending, &control) {
+                        result.errors.push(format!("Orphan cleanup failed: {}", e));
+                    }
+                }
+                Err(e) => result.errors.push(format!("Orphan cleanup skipped: {}", e)),
+            }
+        }
+
+        if options.prune_empty_dirs && !options.dry_run {
+            Self::prune_empty_directories(&source_path, &dest_path, options.ensure_empty_dirs);
+        }
+
+        // Last, since copying into a directory resets its mtime
+        if options.preserve_metadata && !options.dry_run {
+            let mtimes: Vec<_> = directory_mtimes
+                .iter()
+                .map(|(path, modified)| (path.as_path(), *modified))
+                .collect();
+            Self::restore_directory_mtimes(&dest_path, &mtimes);
+            let flags: Vec<_> = directory_flags
+                .iter()
+                .map(|(path, flags)| (path.as_path(), *flags))
+                .collect();
+            result
+                .warnings
+                .extend(Self::restore_directory_flags(&dest_path, &flags));
+        }
+
+        self.set_status(&transfer_id, TransferStatus::Completed, None)?;
+
+        if result.files_failed == 0 && !options.dry_run {
+            if let Err(e) =
+                self.state_manager
+                    .save_sync_marker(&source_path, &dest_path, started_at)
+            {
+                eprintln!(
+                    "[Sync] Warning: Failed to save incremental sync marker: {}",
+                    e
+                );
+            }
+        }
+
+        result.duration_ms = start.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+
+    /// Syncs from any `FileSource` into a local destination, for sources that
+    /// aren't a local path (such as an rsync daemon). Files are listed up front
+    /// and fetched one at a time into a temp file that is renamed into place,
+    /// with the same transfer queue, state, progress events, pause and cancel
+    /// as a local sync. Unchanged files (same size, not newer) are skipped.
+    pub async fn sync_from_source(
+        &self,
+        source: Arc<dyn FileSource>,
+        dest_path: PathBuf,
+        mut options: SyncOptions,
+    ) -> SyncResult<SyncResult_> {
+        if !options.spanning_destinations.is_empty() {
+            return Err(SyncError::InvalidSpanning(
+                "remote sources sync to a single destination".into(),
+            ));
+        }
+        let source_root = PathBuf::from(source.describe());
+        Self::validate_subdirs(&source_root, &mut options)?;
+        let transfer_id = self
+            .state_manager
+            .create_transfer(source_root.clone(), dest_path.clone())?;
+        let control = Arc::new(TransferControl::with_bandwidth_limit(
+            options.bandwidth_limit,
+        ));
+        {
+            let mut controls = self.controls.write();
+            controls
+                .entry(transfer_id.clone())
+                .or_insert_with(|| control.clone());
+        }
+        // Only the destination is on a local volume
+        self.track_transfer_volumes(&transfer_id, &source_root, &dest_path);
+
+        // Held until this function returns, whichever way it exits
+        let _slot = self
+            .wait_for_transfer_slot(&transfer_id, options.priority, &control)
+            .await?;
+
+        let start = std::time::Instant::now();
+        let mut result = SyncResult_::default();
+        if options.detect_clock_skew {
+            result.warnings = Self::widen_for_clock_skew(&dest_path, &mut options);
+        }
+
+        let listing_source = source.clone();
+        let files = match tokio::task::spawn_blocking(move || listing_source.list_files()).await {
+            Ok(Ok(files)) => files,
+            Ok(Err(e)) => {
+                self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+                return Err(e);
+            }
+            Err(e) => {
+                let e = SyncError::Internal(e.to_string());
+                self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+                return Err(e);
+            }
+        };
+
+        let exclude_matcher = Self::exclude_matcher(&options, None);
+        let files: Vec<FileInfo> = files
+            .into_iter()
+            .filter(|file| !is_excluded(&file.path, exclude_matcher.as_ref()))
             .collect();
 
-        if files_to_transfer.is_empty() {
-            // All files already transferred
-            let mut state = state_arc.write();
-            state.status = TransferStatus::Completed;
-            self.state_manager.save_state(&state)?;
-            return Ok(());
-        }
+        let state_arc = self.state_manager.get_transfer(&transfer_id)?;
+        {
+            let mut state = state_arc.write();
+            state.status = TransferStatus::Running;
+            state.current_file = None;
+            for file in files
+                .iter()
+                .filter(|f| !f.is_dir && !f.file_type.is_special())
+            {
+                let src = source_root.join(&file.path);
+                let dst = dest_path.join(&file.path);
+                state.add_file(FileTransferState::new(src, dst, file.size, file.modified));
+                result.files_total += 1;
+                result.bytes_total += file.size;
+            }
+            self.state_manager.save_state(&state)?;
+        }
+
+        for file in &files {
+            if control.is_cancelled() {
+                break;
+            }
+            control.wait_for_resume().await;
+
+            if file.is_dir {
+                if !options.dry_run {
+                    self.create_directory(&dest_path, file)?;
+                }
+                continue;
+            }
+            if file.file_type.is_special() {
+                result.special_files_skipped += 1;
+                result.files_skipped += 1;
+                continue;
+            }
+
+            let src = source_root.join(&file.path);
+            let delta = detect_delta_within(file, &dest_path, options.modify_window())?;
+            let conflict_resolution = options.conflict_resolution_for(&file.path);
+            let keep_existing = delta.status == DeltaStatus::Modified
+                && (options.skip_existing || !conflict_resolution.replaces_destination(&delta));
+            if delta.status == DeltaStatus::Unchanged || keep_existing {
+                let mut state = state_arc.write();
+                state.skip_file(&src);
+                self.state_manager.save_state(&state)?;
+                result.files_skipped += 1;
+                continue;
+            }
+
+            if options.dry_run {
+                result.files_copied += 1;
+                result.bytes_copied += file.size;
+                continue;
+            }
+
+            let dest_file = dest_path.join(&file.path);
+            let dest_file = if delta.status == DeltaStatus::Modified
+                && conflict_resolution == ConflictResolution::Rename
+            {
+                generate_conflict_name(&dest_file, options.conflict_name_template.as_deref())
+            } else {
+                dest_file
+            };
+            let temp_path = crate::file_ops::get_temp_path(&dest_file);
+
+            let fetch_source = source.clone();
+            let fetch_file = file.clone();
+            let fetch_dest = temp_path.clone();
+            let fetch_control = control.clone();
+            let fetch_src = src.clone();
+            let state_manager = self.state_manager.clone();
+            let app_handle = self.app_handle.clone();
+            let fetch_transfer_id = transfer_id.clone();
+            let bytes_before = result.bytes_copied;
+            let files_before = result.files_copied;
+            let files_total = result.files_total;
+            let bytes_total = result.bytes_total;
+            let progress_ndjson = options.progress_ndjson;
+
+            let fetched = match tokio::task::spawn_blocking(move || {
+                let file_start = std::time::Instant::now();
+                let mut progress = |copied: u64| {
+                    if fetch_control.is_cancelled() {
+                        return false;
+                    }
+                    if let Ok(state_arc) = state_manager.get_transfer(&fetch_transfer_id) {
+                        let mut state = state_arc.write();
+                        state.current_file = Some(fetch_src.clone());
+                        state.update_file_progress(&fetch_src, copied, None);
+                        let _ = state_manager.save_state(&state);
+                    }
+                    if app_handle.is_some() || progress_ndjson {
+                        let elapsed = file_start.elapsed().as_secs_f64();
+                        let speed = if elapsed > 0.0 {
+                            copied as f64 / elapsed
+                        } else {
+                            0.0
+                        };
+                        let overall = bytes_before + copied;
+                        let event = ProgressEvent {
+                            transfer_id: fetch_transfer_id.clone(),
+                            current_file: fetch_file.path.display().to_string(),
+                            current_file_progress: if fetch_file.size > 0 {
+                                copied as f64 / fetch_file.size as f64
+                            } else {
+                                1.0
+                            },
+                            overall_progress: if bytes_total > 0 {
+                                overall as f64 / bytes_total as f64
+                            } else {
+                                0.0
+                            },
+                            bytes_copied: overall,
+                            bytes_total,
+                            files_completed: files_before,
+                            files_total,
+                            speed_bytes_per_sec: speed,
+                            eta_seconds: (speed > 0.0)
+                                .then(|| bytes_total.saturating_sub(overall) as f64 / speed),
+                        };
+                        match app_handle.as_ref() {
+                            Some(handle) => {
+                                let _ = handle.emit("sync-progress", &event);
+                            }
+                            None => print_ndjson("sync-progress", &event),
+                        }
+                    }
+                    true
+                };
+                fetch_source.fetch_file(&fetch_file, &fetch_dest, &mut progress)
+            })
+            .await
+            {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    let _ = std::fs::remove_file(&temp_path);
+                    let e = SyncError::Internal(e.to_string());
+                    self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+                    return Err(e);
+                }
+            };
+
+            let outcome = fetched.and_then(|bytes| {
+                if delta.status == DeltaStatus::Modified {
+                    Self::record_replacement(
+                        &transfer_id,
+                        &self.state_manager,
+                        options.keep_rollback_backups,
+                        &src,
+                        &dest_path,
+                        &file.path,
+                        &dest_file,
+                    )?;
+                }
+                std::fs::rename(&temp_path, &dest_file)
+                    .map_err(|e| crate::file_ops::classify_io_error(e, &dest_file))?;
+                Ok(bytes)
+            });
+
+            match outcome {
+                Ok(bytes) => {
+                    let mut state = state_arc.write();
+                    state.complete_file(&src);
+                    self.state_manager.save_state(&state)?;
+                    result.files_copied += 1;
+                    result.bytes_copied += bytes;
+                }
+                Err(e) => {
+                    let _ = std::fs::remove_file(&temp_path);
+                    if control.is_cancelled() {
+                        break;
+                    }
+                    {
+                        let mut state = state_arc.write();
+                        state.fail_file(&src, e.to_string());
+                        self.state_manager.save_state(&state)?;
+                    }
+                    if options.on_error == ErrorPolicy::AbortTransfer {
+                        self.set_status(&transfer_id, TransferStatus::Failed, Some(e.to_string()))?;
+                        return Err(e);
+                    }
+                    result.files_failed += 1;
+                    result
+                        .errors
+                        .push(format!("{}: {}", file.path.display(), e));
+                }
+            }
+        }
+
+        if control.is_cancelled() {
+            return self.finish_cancelled(&transfer_id, result, start);
+        }
+
+        self.set_status(&transfer_id, TransferStatus::Completed, None)?;
+        {
+            let mut controls = self.controls.write();
+            controls.remove(&transfer_id);
+        }
+
+        result.duration_ms = start.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+
+    /// Holds a transfer whose drive disconnected in `WaitingForDevice` until the
+    /// source and destination are reachable again, then resumes it with the
+    /// options it ran with. Expires into `Failed` with the original error after
+    /// `timeout_secs`. Cancelling the transfer while waiting stops the wait.
+    /// The queue slot is given up while waiting and queued for again after.
+    async fn wait_for_reconnect_and_resume(
+        &self,
+        transfer_id: &str,
+        error: SyncError,
+        timeout_secs: u64,
+        options: &SyncOptions,
+        slot: TransferSlot,
+        start: std::time::Instant,
+    ) -> SyncResult<SyncResult_> {
+        drop(slot);
+        // The run's control was cancelled to stop in-flight files; give the
+        // waiting transfer a fresh one so the user can still cancel it
+        let bandwidth_limit = self
+            .get_control(transfer_id)
+            .map_or(options.bandwidth_limit, |previous| {
+                previous.bandwidth_limit()
+            });
+        let control = Arc::new(TransferControl::with_bandwidth_limit(bandwidth_limit));
+        self.controls
+            .write()
+            .insert(transfer_id.to_string(), control.clone());
+
+        self.record_event(
+            transfer_id,
+            TransferEventKind::VolumeDisconnected,
+            Some(error.to_string()),
+        );
+        self.set_status(
+            transfer_id,
+            TransferStatus::WaitingForDevice,
+            Some(error.to_string()),
+        )?;
+        if let Some(handle) = &self.app_handle {
+            let _ = handle.emit(
+                "transfer-waiting-for-device",
+                serde_json::json!({
+                    "transferId": transfer_id,
+                    "error": error.to_string(),
+                    "timeoutSecs": timeout_secs,
+                }),
+            );
+        }
+
+        let mut mounts = self.mount_events.read().as_ref().map(|tx| tx.subscribe());
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+        let poll = std::time::Duration::from_secs(RECONNECT_POLL_INTERVAL_SECS);
+
+        loop {
+            if control.is_cancelled() {
+                self.controls.write().remove(transfer_id);
+                self.set_status(
+                    transfer_id,
+                    TransferStatus::Cancelled,
+                    Some("Transfer cancelled by user".to_string()),
+                )?;
+                return Ok(Self::result_from_state(
+                    &self.state_manager.get_state(transfer_id)?,
+                    start,
+                ));
+            }
+            if options.source.exists() && options.destination.exists() {
+                break;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                self.controls.write().remove(transfer_id);
+                self.set_status(
+                    transfer_id,
+                    TransferStatus::Failed,
+                    Some(format!(
+                        "Drive not reconnected within {}s: {}",
+                        timeout_secs, error
+                    )),
+                )?;
+                return Err(error);
+            }
+
+            // Wake on the next mount notification or poll tick, whichever is first
+            let wait = async {
+                match mounts.as_mut() {
+                    Some(rx) => {
+                        let _ = rx.recv().await;
+                    }
+                    None => std::future::pending::<()>().await,
+                }
+            };
+            let _ = tokio::time::timeout(poll, wait).await;
+        }
+        self.record_event(
+            transfer_id,
+            TransferEventKind::Resumed,
+            Some("Drive reconnected".to_string()),
+        );
+
+        // A second disconnect fails the transfer rather than waiting again
+        let resume_options = SyncOptions {
+            reconnect_timeout_secs: None,
+            resume_of: Some(transfer_id.to_string()),
+            ..options.clone()
+        };
+        let mut result = Box::pin(self.run_sync(
+            options.source.clone(),
+            options.destination.clone(),
+            resume_options,
+        ))
+        .await?;
+        result.duration_ms = start.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+
+    /// What a resumed run accomplished, read back from its persisted state.
+    fn result_from_state(state: &TransferState, start: std::time::Instant) -> SyncResult_ {
+        SyncResult_ {
+            files_total: state.total_files,
+            files_copied: state.files_completed,
+            files_skipped: state.files_skipped,
+            files_failed: state.files_failed,
+            bytes_total: state.total_bytes,
+            bytes_copied: state.bytes_transferred,
+            duration_ms: start.elapsed().as_millis() as u64,
+            errors: state.error.iter().cloned().collect(),
+            warnings: state.warnings.clone(),
+            cancelled: state.status == TransferStatus::Cancelled,
+            ..SyncResult_::default()
+        }
+    }
+
+    /// Ends a cancelled run with what it finished before the cancel, so the
+    /// caller can report partial progress rather than a bare error.
+    fn finish_cancelled(
+        &self,
+        transfer_id: &str,
+        mut result: SyncResult_,
+        start: std::time::Instant,
+    ) -> SyncResult<SyncResult_> {
+        self.set_status(
+            transfer_id,
+            TransferStatus::Cancelled,
+            Some("Transfer cancelled by user".to_string()),
+        )?;
+        result.cancelled = true;
+        result.duration_ms = start.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+
+    /// Resume an interrupted sync from its persisted state. It runs as a sync
+    /// under the same transfer ID, which leaves alone the files it finished
+    /// and carries on the ones it was part way through.
+    async fn resume_sync_with_state(
+        &self,
+        transfer_id: &str,
+        mut options: SyncOptions,
+    ) -> SyncResult<()> {
+        let state_arc = self.state_manager.get_transfer(transfer_id)?;
+        let (source_path, dest_path) = {
+            let state = state_arc.read();
+            (state.source_path.clone(), state.dest_path.clone())
+        };
+
+        // Validate paths still exist
+        if !source_path.exists() {
+            return Err(SyncError::SourceNotFound(source_path.display().to_string()));
+        }
+
+        // Interrupted after the copy phase: only the orphan deletion is left
+        if !state_arc.read().orphan_deletion.is_empty() {
+            // Keeping a limit changed while the transfer was running
+            let bandwidth_limit = self
+                .get_control(transfer_id)
+                .map_or(options.bandwidth_limit, |previous| {
+                    previous.bandwidth_limit()
+                });
+            let control = Arc::new(TransferControl::with_bandwidth_limit(bandwidth_limit));
+            self.controls
+                .write()
+                .insert(transfer_id.to_string(), control.clone());
+
+            self.set_status(transfer_id, TransferStatus::DeletingOrphans, None)?;
+            let outcome = self.delete_recorded_orphans(transfer_id, &control);
+            match &outcome {
+                Ok(()) => self.set_status(transfer_id, TransferStatus::Completed, None)?,
+                Err(SyncError::TransferCancelled(reason)) => {
+                    self.set_status(transfer_id, TransferStatus::Cancelled, Some(reason.clone()))?
+                }
+                Err(e) => {
+                    self.set_status(transfer_id, TransferStatus::Failed, Some(e.to_string()))?
+                }
+            }
+            self.controls.write().remove(transfer_id);
+            return outcome;
+        }
+
+        options.resume_of = Some(transfer_id.to_string());
+        self.sync_files(source_path, dest_path, options)
+            .await
+            .map(|_| ())
+    }
+
+    /// Whether `pack_small_files` applies. Packed files are new files copied
+    /// as they are, so runs that move sources, preview, span drives or carry
+    /// ACLs and extended attributes copy them one at a time.
+    fn packs_small_files(options: &SyncOptions) -> bool {
+        options.pack_small_files
+            && options.mode == SyncMode::Copy
+            && !options.dry_run
+            && !options.structure_only
+            && !options.skip_open_files
+            && !options.preserve_acl
+            && !options.preserve_xattr
+            && !options.store_hash_xattr
+            && options.spanning_destinations.is_empty()
+    }
+
+    /// Whether `file` is a cloud placeholder this run leaves alone. Copying
+    /// one would download it first, so that waits for `materialize_dataless`.
+    fn leaves_dataless(file: &FileInfo, options: &SyncOptions) -> bool {
+        file.is_dataless && !file.is_dir && !options.materialize_dataless
+    }
+
+    /// Logs and records a warning about the placeholders in `dataless_skipped`.
+    fn warn_dataless_skipped(state: &mut TransferState, skipped: &[PathBuf]) {
+        if skipped.is_empty() {
+            return;
+        }
+        let warning = format!(
+            "{} cloud placeholder file(s) were not downloaded and were left out; \
+             enable materialize_dataless to copy them",
+            skipped.len()
+        );
+        eprintln!("[Sync] {}", warning);
+        state.record_warning(warning);
+    }
+
+    /// Copies `files` in tar batches of up to `PACK_BATCH_FILES` files and
+    /// `PACK_BATCH_BYTES`, saving the transfer state once per batch. Returns
+    /// the relative path and size of each file copied, and the files that
+    /// weren't, which the caller copies on their own.
+    async fn copy_packed<'a>(
+        &self,
+        transfer_id: &str,
+        source_path: &Path,
+        dest_path: &Path,
+        files: Vec<&'a FileInfo>,
+        options: &SyncOptions,
+        control: &Arc<TransferControl>,
+    ) -> (Vec<(PathBuf, u64)>, Vec<&'a FileInfo>) {
+        let preserve_mtime =
+            options.preserve_metadata && options.timestamp_mode == TimestampMode::PreserveSource;
+        let low_priority = options.low_priority;
+        let mut copied = Vec::new();
+        let mut left_over = Vec::new();
+        let mut queue = files.into_iter().peekable();
+
+        while queue.peek().is_some() {
+            control.wait_for_resume().await;
+            if control.is_cancelled() {
+                left_over.extend(queue);
+                break;
+            }
+            let mut batch: Vec<&FileInfo> = Vec::new();
+            let mut batch_bytes = 0;
+            while let Some(file) = queue.next_if(|file| {
+                batch.len() < PACK_BATCH_FILES
+                    && (batch.is_empty() || batch_bytes + file.size <= PACK_BATCH_BYTES)
+            }) {
+                batch_bytes += file.size;
+                batch.push(file);
+            }
+
+            // Until the batch's files are recorded, so a rollback sees them
+            let _active_copy = ActiveCopy::enter(control);
+            let relatives: Vec<PathBuf> = batch.iter().map(|file| file.path.clone()).collect();
+            let (from, to) = (source_path.to_path_buf(), dest_path.to_path_buf());
+            let bandwidth_limit = control.bandwidth_limit.clone();
+            let entries = tokio::task::spawn_blocking(move || {
+                let _io_priority = low_priority.then(LowIoPriority::enter);
+                transfer_batch(&from, &to, &relatives, preserve_mtime, &bandwidth_limit)
+            })
+            .await
+            .map_err(|e| SyncError::Internal(e.to_string()))
+            .and_then(|entries| entries);
+            let entries = match entries {
+                Ok(entries) => entries,
+                Err(e) => {
+                    eprintln!(
+                        "[Pack] Batch of {} files failed, copying them one by one: {}",
+                        batch.len(),
+                        e
+                    );
+                    left_over.extend(batch);
+                    continue;
+                }
+            };
+
+            let Ok(state_arc) = self.state_manager.get_transfer(transfer_id) else {
+                left_over.extend(batch);
+                continue;
+            };
+            let mut state = state_arc.write();
+            for (file, entry) in batch.into_iter().zip(entries) {
+                let source_abs = source_path.join(&file.path);
+                match entry.result {
+                    Ok(packed) => {
+                        // The file changed size since the scan
+                        if packed.size != file.size {
+                            state.rescan_file(&source_abs, packed.size, file.modified);
+                        }
+                        state.mark_verified(&source_abs);
+                        if options.preserve_metadata {
+                            Self::apply_flags(&mut state, &dest_path.join(&file.path), file.flags);
+                        }
+                        state.complete_file(&source_abs);
+                        copied.push((packed.path, packed.size));
+                    }
+                    Err(e) => {
+                        eprintln!("[Pack] {} copied on its own: {}", file.path.display(), e);
+                        left_over.push(file);
+                    }
+                }
+            }
+            let _ = self.state_manager.save_state(&state);
+            control.note_progress();
+
+            if self.app_handle.is_some() || options.progress_ndjson {
+                let event = ProgressEvent {
+                    transfer_id: transfer_id.to_string(),
+                    current_file: String::new(),
+                    current_file_progress: 1.0,
+                    overall_progress: if state.total_bytes > 0 {
+                        state.bytes_transferred as f64 / state.total_bytes as f64
+                    } else {
+                        0.0
+                    },
+                    bytes_copied: state.bytes_transferred,
+                    bytes_total: state.total_bytes,
+                    files_completed: state.files_completed,
+                    files_total: state.total_files,
+                    speed_bytes_per_sec: state.speed_bytes_per_sec,
+                    eta_seconds: None,
+                };
+                match self.app_handle.as_ref() {
+                    Some(handle) => {
+                        let _ = handle.emit("sync-progress", &event);
+                    }
+                    None => print_ndjson("sync-progress", &event),
+                }
+            }
+        }
+        (copied, left_over)
+    }
+
+    /// True if the file's destination was hashed and matched its source.
+    fn is_file_verified(
+        state_manager: &TransferStateManager,
+        transfer_id: &str,
+        source_path: &Path,
+    ) -> bool {
+        state_manager
+            .get_transfer(transfer_id)
+            .is_ok_and(|state_arc| {
+                state_arc
+                    .read()
+                    .files
+                    .get(source_path)
+                    .is_some_and(|file_state| file_state.verified)
+            })
+    }
+
+    /// Second pass of a `MoveVerified` sync: removes the sources of the
+    /// verified copies and records them in `sources_deleted`.
+    fn remove_verified_sources(result: &mut SyncResult_, on_move_failure: MoveFailurePolicy) {
+        for source in std::mem::take(&mut result.verified_for_move) {
+            match std::fs::remove_file(&source) {
+                Ok(()) => result.sources_deleted.push(source),
+                Err(e) => match on_move_failure {
+                    MoveFailurePolicy::Record => {
+                        result.move_failures.push(MoveFailure {
+                            path: source,
+                            error: e.to_string(),
+                        });
+                    }
+                    MoveFailurePolicy::Warn => {
+                        eprintln!(
+                            "[Sync] Warning: Copied but could not remove source {:?}: {}",
+                            source, e
+                        );
+                    }
+                },
+            }
+        }
+        if !result.sources_deleted.is_empty() {
+            eprintln!(
+                "[Move] Removed {} verified source files",
+                result.sources_deleted.len()
+            );
+        }
+    }
+
+    /// Verifies a resumed file's destination against its source, saving a
+    /// checkpoint after every segment so an interrupted verification can be
+    /// picked up again rather than started over.
+    fn verify_resumed_file(
+        source: &Path,
+        dest: &Path,
+        resume_from: Option<HashCheckpoint>,
+        state_arc: &Arc<RwLock<TransferState>>,
+        state_manager: &TransferStateManager,
+        control: &TransferControl,
+    ) -> SyncResult<()> {
+        verify_copy_resumable(source, dest, resume_from, |checkpoint| {
+            let mut state = state_arc.write();
+            state.checkpoint_verification(source, checkpoint);
+            let _ = state_manager.save_state(&state);
+            !control.is_cancelled()
+        })?;
+
+        let mut state = state_arc.write();
+        state.mark_verified(source);
+        state_manager.save_state(&state)
+    }
+
+    /// Scan a directory while tracking any errors encountered.
+    /// This allows us to determine if the scan was complete for safe orphan deletion.
+    /// Excluded entries are pruned during the walk, so an excluded directory
+    /// such as `node_modules` is never descended into.
+    fn scan_directory_with_error_tracking(
+        &self,
+        path: &Path,
+        follow_symlinks: bool,
+        skip_unreadable: bool,
+        exclude_matcher: Option<&ExcludeMatcher>,
+        scan_threads: usize,
+    ) -> SyncResult<ScanResult> {
+        if !path.exists() {
+            return Err(SyncError::SourceNotFound(path.display().to_string()));
+        }
+
+        if !path.is_dir() {
+            return Err(SyncError::InvalidPath(format!(
+                "{} is not a directory",
+                path.display()
+            )));
+        }
+
+        let mut total_size: u64 = 0;
+        let mut file_count: usize = 0;
+        let mut dir_count: usize = 0;
+        let mut files = Vec::new();
+        let mut scan_errors = Vec::new();
+        let mut skipped_unreadable = Vec::new();
+        let mut excluded_entries: usize = 0;
+
+        let walk_root = extended_length_path(path);
+        // Both walks yield entries in the same order, so the parallel one only
+        // changes how long the scan takes
+        let entries: Box<dyn Iterator<Item = ScanEntry>> = if scan_threads > 1 && !follow_symlinks {
+            let (entries, excluded) =
+                Self::parallel_walk(&walk_root, scan_threads, exclude_matcher);
+            excluded_entries = excluded;
+            Box::new(entries.into_iter())
+        } else {
+            let excluded = &mut excluded_entries;
+            let root = walk_root.as_path();
+            let walker = WalkDir::new(root)
+                .follow_links(follow_symlinks)
+                .into_iter()
+                .filter_entry(move |e| {
+                    let is_excluded_entry = e
+                        .path()
+                        .strip_prefix(root)
+                        .map(|rel| !rel.as_os_str().is_empty() && is_excluded(rel, exclude_matcher))
+                        .unwrap_or(false);
+                    if is_excluded_entry {
+                        *excluded += 1;
+                    }
+                    !is_excluded_entry
+                });
+            Box::new(walker.filter_map(move |entry| match entry {
+                Ok(e) if e.path() == root => None,
+                Ok(e) => {
+                    let info = crate::file_ops::get_file_info(e.path(), root);
+                    Some(ScanEntry::Entry(e.into_path(), info))
+                }
+                Err(e) => {
+                    Some(
+                        ScanEntry::Failed {
+                            path: e.path().map(Path::to_path_buf),
+                            denied: e.io_error().is_some_and(|io| {
+                                io.kind() == std::io::ErrorKind::PermissionDenied
+                            }),
+                            message: e.to_string(),
+                        },
+                    )
+                }
+            }))
+        };
+
+        for entry in entries {
+            match entry {
+                ScanEntry::Entry(entry_path, info) => match info {
+                    Ok(info) => {
+                        if info.is_dir {
+                            dir_count += 1;
+                        } else {
+                            file_count += 1;
+                            total_size += info.size;
+                        }
+                        files.push(info);
+                    }
+                    Err(SyncError::Io(io))
+                        if skip_unreadable && io.kind() == std::io::ErrorKind::PermissionDenied =>
+                    {
+                        skipped_unreadable.push(entry_path);
+                    }
+                    Err(e) => {
+                        scan_errors.push(format!(
+                            "Failed to get info for '{}': {}",
+                            entry_path.display(),
+                            e
+                        ));
+                    }
+                },
+                ScanEntry::Failed {
+                    path,
+                    denied,
+                    message,
+                } => match path {
+                    Some(denied_path) if skip_unreadable && denied => {
+                        skipped_unreadable.push(denied_path);
+                    }
+                    _ => scan_errors.push(format!("Scan error: {}", message)),
+                },
+            }
+        }
+
+        // Kept relative, like the scanned FileInfo paths
+        let skipped_unreadable: Vec<PathBuf> = skipped_unreadable
+            .into_iter()
+            .filter_map(|skipped| skipped.strip_prefix(&walk_root).ok().map(Path::to_path_buf))
+            .collect();
+        for skipped in &skipped_unreadable {
+            eprintln!("[Scan] Skipped unreadable entry: {}", skipped.display());
+        }
+
+        let info = DirectoryInfo {
+            path: path.to_path_buf(),
+            total_size,
+            file_count,
+            dir_count,
+            files,
+        };
+
+        if excluded_entries > 0 {
+            eprintln!(
+                "Excluded {} files/directories based on patterns (excluded directories not scanned)",
+                excluded_entries
+            );
+        }
+
+        let mut result = ScanResult::with_errors(info, scan_errors);
+        result.skipped_unreadable = skipped_unreadable;
+        Ok(result)
+    }
+
+    /// Threads for scanning `source`: the configured count when `parallel_scan`
+    /// is set or the source is on a network or removable volume, where stat
+    /// latency dominates, and 1 otherwise.
+    fn scan_threads(options: &SyncOptions, source: &Path) -> usize {
+        if options.follow_symlinks {
+            return 1;
+        }
+        let slow_volume = || {
+            crate::file_ops::get_volume_info(source)
+                .map(|info| info.drive_type == "Network" || info.is_removable)
+                .unwrap_or(false)
+        };
+        if options.parallel_scan || slow_volume() {
+            options.scan_threads.unwrap_or(DEFAULT_SCAN_THREADS).max(1)
+        } else {
+            1
+        }
+    }
+
+    /// Walks `walk_root` like `WalkDir` does without following symlinks, but
+    /// lists and stats each level's directories on `threads` threads. Returns
+    /// the entries in `WalkDir` order (each directory before its contents) and
+    /// how many entries were excluded.
+    fn parallel_walk(
+        walk_root: &Path,
+        threads: usize,
+        exclude_matcher: Option<&ExcludeMatcher>,
+    ) -> (Vec<ScanEntry>, usize) {
+        let mut listings: HashMap<PathBuf, DirListing> = HashMap::new();
+        let mut level = vec![walk_root.to_path_buf()];
+
+        while !level.is_empty() {
+            let next = AtomicUsize::new(0);
+            let results: Vec<parking_lot::Mutex<Option<DirListing>>> = level
+                .iter()
+                .map(|_| parking_lot::Mutex::new(None))
+                .collect();
+            std::thread::scope(|scope| {
+                for _ in 0..threads.min(level.len()) {
+                    scope.spawn(|| loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(dir) = level.get(index) else {
+                            break;
+                        };
+                        *results[index].lock() =
+                            Some(Self::list_directory(dir, walk_root, exclude_matcher));
+                    });
+                }
+            });
+
+            let mut next_level = Vec::new();
+            for (dir, listing) in level.into_iter().zip(results) {
+                let listing = listing.into_inner().unwrap_or_default();
+                next_level.extend(listing.subdirs.iter().cloned());
+                listings.insert(dir, listing);
+            }
+            level = next_level;
+        }
+
+        // Stitch the listings back together depth first
+        let mut entries = Vec::new();
+        let mut excluded = 0;
+        let mut stack = vec![listings
+            .remove(walk_root)
+            .unwrap_or_default()
+            .entries
+            .into_iter()];
+        while let Some(current) = stack.last_mut() {
+            let Some(listed) = current.next() else {
+                stack.pop();
+                continue;
+            };
+            match listed {
+                ListedEntry::Excluded => excluded += 1,
+                ListedEntry::Failed {
+                    path,
+                    denied,
+                    message,
+                } => {
+                    entries.push(ScanEntry::Failed {
+                        path,
+                        denied,
+                        message,
+                    });
+                }
+                ListedEntry::Entry { path, info, is_dir } => {
+                    let listing = if is_dir { listings.remove(&path) } else { None };
+                    entries.push(ScanEntry::Entry(path, info));
+                    if let Some(listing) = listing {
+                        if let Some(error) = listing.error {
+                            entries.push(error);
+                        }
+                        stack.push(listing.entries.into_iter());
+                    }
+                }
+            }
+        }
+        (entries, excluded)
+    }
+
+    /// Reads and stats one directory for `parallel_walk`.
+    fn list_directory(
+        dir: &Path,
+        walk_root: &Path,
+        exclude_matcher: Option<&ExcludeMatcher>,
+    ) -> DirListing {
+        let mut listing = DirListing::default();
+        let read_dir = match std::fs::read_dir(dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) => {
+                listing.error = Some(ScanEntry::Failed {
+                    path: Some(dir.to_path_buf()),
+                    denied: e.kind() == std::io::ErrorKind::PermissionDenied,
+                    message: format!("IO error for operation on {}: {}", dir.display(), e),
+                });
+                return listing;
+            }
+        };
+
+        for dir_entry in read_dir {
+            let dir_entry = match dir_entry {
+                Ok(dir_entry) => dir_entry,
+                Err(e) => {
+                    listing.entries.push(ListedEntry::Failed {
+                        path: Some(dir.to_path_buf()),
+                        denied: e.kind() == std::io::ErrorKind::PermissionDenied,
+                        message: format!("IO error for operation on {}: {}", dir.display(), e),
+                    });
+                    continue;
+                }
+            };
+            let path = dir_entry.path();
+            let excluded = path
+                .strip_prefix(walk_root)
+                .is_ok_and(|rel| is_excluded(rel, exclude_matcher));
+            if excluded {
+                listing.entries.push(ListedEntry::Excluded);
+                continue;
+            }
+            // Descend on the entry's own type, like WalkDir, so symlinks aren't followed
+            let is_dir = dir_entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_dir {
+                listing.subdirs.push(path.clone());
+            }
+            let info = crate::file_ops::get_file_info(&path, walk_root);
+            listing
+                .entries
+                .push(ListedEntry::Entry { path, info, is_dir });
+        }
+        listing
+    }
+
+    /// Clean up orphaned files in the destination that don't exist in the source.
+    /// SAFETY: This will refuse to delete files if the source scan was incomplete
+    /// to prevent accidental data loss.
+    fn find_orphans(
+        &self,
+        source_info: &DirectoryInfo,
+        dest_root: &Path,
+        scan_complete: bool,
+        scan_errors: &[String],
+        skipped_unreadable: &[PathBuf],
+        exclude_matcher: Option<&ExcludeMatcher>,
+    ) -> SyncResult<Vec<PathBuf>> {
+        // CRITICAL SAFETY CHECK: Do not delete orphans if the scan was incomplete
+        // This prevents data loss if we couldn't fully scan the source
+        if !scan_complete {
+            let error_count = scan_errors.len();
+            let error_preview: String = scan_errors
+                .iter()
+                .take(3)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("; ");
+
+            return Err(SyncError::IncompleteScan(format!(
+                "Orphan deletion skipped: source scan was incomplete ({} errors). \
+                 First errors: {}. \
+                 Re-run sync after resolving scan issues to safely delete orphans.",
+                error_count, error_preview
+            )));
+        }
+
+        let mut source_paths: HashSet<String> = HashSet::new();
+        for entry in &source_info.files {
+            source_paths.insert(entry.path.to_string_lossy().to_string());
+        }
+
+        // Excluded paths were pruned from the source scan, so they must be
+        // pruned here too or their destination copies would look like orphans.
+        // The same goes for unreadable source entries, whose contents are unknown.
+        let walker = WalkDir::new(dest_root)
+            .contents_first(true)
+            .into_iter()
+            .filter_entry(|e| {
+                e.path()
+                    .strip_prefix(dest_root)
+                    .map(|rel| {
+                        rel.as_os_str().is_empty()
+                            || (!is_excluded(rel, exclude_matcher)
+                                && !skipped_unreadable
+                                    .iter()
+                                    .any(|skipped| rel.starts_with(skipped)))
+                    })
+                    .unwrap_or(true)
+            });
+
+        let mut orphans = Vec::new();
+        for entry in walker.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path == dest_root {
+                continue;
+            }
+            let relative = match path.strip_prefix(dest_root) {
+                Ok(rel) => rel.to_string_lossy().to_string(),
+                Err(_) => continue,
+            };
+            if source_paths.contains(&relative) {
+                continue;
+            }
+            // Rollback backups are engine-owned, never orphans
+            if is_engine_dir(Path::new(&relative)) {
+                continue;
+            }
+            orphans.push(PathBuf::from(relative));
+        }
+
+        Ok(orphans)
+    }
+
+    /// Streaming counterpart of `find_orphans`: instead of a set of scanned
+    /// source paths, each destination entry is looked up in the source directly.
+    /// Refuses to list anything if the streaming scan hit errors.
+    fn find_orphans_against_source(
+        &self,
+        source_root: &Path,
+        dest_root: &Path,
+        untouched: &[PathBuf],
+        scan_errors: &[String],
+        exclude_matcher: Option<&ExcludeMatcher>,
+    ) -> SyncResult<Vec<PathBuf>> {
+        if !scan_errors.is_empty() {
+            return Err(SyncError::IncompleteScan(format!(
+                "Orphan deletion skipped: source scan was incomplete ({} errors). \
+                 First errors: {}. \
+                 Re-run sync after resolving scan issues to safely delete orphans.",
+                scan_errors.len(),
+                scan_errors
+                    .iter()
+                    .take(3)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            )));
+        }
+
+        let walker = WalkDir::new(dest_root)
+            .contents_first(true)
+            .into_iter()
+            .filter_entry(|e| {
+                e.path()
+                    .strip_prefix(dest_root)
+                    .map(|rel| {
+                        rel.as_os_str().is_empty()
+                            || (!is_excluded(rel, exclude_matcher)
+                                && !is_engine_dir(rel)
+                                && !untouched.iter().any(|path| rel.starts_with(path)))
+                    })
+                    .unwrap_or(true)
+            });
+
+        let mut orphans = Vec::new();
+        for entry in walker.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path == dest_root {
+                continue;
+            }
+            let relative = match path.strip_prefix(dest_root) {
+                Ok(rel) => rel,
+                Err(_) => continue,
+            };
+            if std::fs::symlink_metadata(source_root.join(relative)).is_ok() {
+                continue;
+            }
+            orphans.push(relative.to_path_buf());
+        }
+
+        Ok(orphans)
+    }
+
+    /// Records the orphans of a finished copy phase in the transfer state and
+    /// deletes them, so an interruption part way through can be resumed with
+    /// `delete_recorded_orphans` instead of copying or scanning again.
+    fn delete_orphans_resumably(
+        &self,
+        transfer_id: &str,
+        pending: Vec<OrphanDeletion>,
+        control: &TransferControl,
+    ) -> SyncResult<()> {
+        let pending: Vec<_> = pending
+            .into_iter()
+            .filter(|root| !root.orphans.is_empty())
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        {
+            let state_arc = self.state_manager.get_transfer(transfer_id)?;
+            let mut state = state_arc.write();
+            state.orphan_deletion = pending;
+            state.status = TransferStatus::DeletingOrphans;
+            self.state_manager.save_state(&state)?;
+        }
+        self.delete_recorded_orphans(transfer_id, control)
+    }
+
+    /// Works through the orphans recorded in the transfer state, saving how far
+    /// it got every `ORPHAN_CHECKPOINT_INTERVAL` entries. An entry that has
+    /// reappeared in the source since it was recorded is left alone.
+    fn delete_recorded_orphans(
+        &self,
+        transfer_id: &str,
+        control: &TransferControl,
+    ) -> SyncResult<()> {
+        let state_arc = self.state_manager.get_transfer(transfer_id)?;
+        let (source_root, pending) = {
+            let state = state_arc.read();
+            (state.source_path.clone(), state.orphan_deletion.clone())
+        };
+
+        for (root_index, root) in pending.iter().enumerate() {
+            for (index, relative) in root.orphans.iter().enumerate().skip(root.next) {
+                if control.is_cancelled() {
+                    return Err(SyncError::TransferCancelled(
+                        "Transfer cancelled by user".into(),
+                    ));
+                }
+                if std::fs::symlink_metadata(source_root.join(relative)).is_err() {
+                    let path = root.dest_root.join(relative);
+                    match std::fs::symlink_metadata(&path) {
+                        Ok(meta) if meta.is_dir() => {
+                            let _ = std::fs::remove_dir(&path);
+                        }
+                        Ok(_) => {
+                            let _ = std::fs::remove_file(&path);
+                        }
+                        Err(_) => {}
+                    }
+                }
+
+                let done = index + 1;
+                if done % ORPHAN_CHECKPOINT_INTERVAL == 0 || done == root.orphans.len() {
+                    let mut state = state_arc.write();
+                    if let Some(recorded) = state.orphan_deletion.get_mut(root_index) {
+                        recorded.next = done;
+                    }
+                    self.state_manager.save_state(&state)?;
+                }
+            }
+        }
+
+        let mut state = state_arc.write();
+        state.orphan_deletion.clear();
+        self.state_manager.save_state(&state)
+    }
+
+    /// Removes empty destination directories that were mirrored from the source,
+    /// deepest first so parents emptied by the pass are removed too. Directories
+    /// with no source counterpart are left to orphan cleanup. With
+    /// `keep_empty_source_dirs`, a directory that is empty in the source stays.
+    fn prune_empty_directories(source_root: &Path, dest_root: &Path, keep_empty_source_dirs: bool) {
+        let is_empty_dir = |path: &Path| {
+            std::fs::read_dir(path)
+                .map(|mut entries| entries.next().is_none())
+                .unwrap_or(false)
+        };
+
+        let walker = WalkDir::new(dest_root)
+            .contents_first(true)
+            .into_iter()
+            .filter_entry(|e| {
+                e.path()
+                    .strip_prefix(dest_root)
+                    .map(|rel| !is_engine_dir(rel))
+                    .unwrap_or(true)
+            });
+
+        let mut pruned: usize = 0;
+        for entry in walker.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path == dest_root || !entry.file_type().is_dir() {
+                continue;
+            }
+            let source_dir = match path.strip_prefix(dest_root) {
+                Ok(rel) => source_root.join(rel),
+                Err(_) => continue,
+            };
+            if !source_dir.is_dir() || !is_empty_dir(path) {
+                continue;
+            }
+            if keep_empty_source_dirs && is_empty_dir(&source_dir) {
+                continue;
+            }
+            if std::fs::remove_dir(path).is_ok() {
+                pruned += 1;
+            }
+        }
+
+        if pruned > 0 {
+            eprintln!(
+                "[Sync] Pruned {} empty directories from the destination",
+                pruned
+            );
+        }
+    }
+
+    /// Sets mirrored directories' mtimes to the source's, deepest first. Best
+    /// effort: a directory that can't be stamped keeps the time of the copy.
+    fn restore_directory_mtimes(dest_root: &Path, dirs: &[(&Path, chrono::DateTime<chrono::Utc>)]) {
+        let mut ordered = dirs.to_vec();
+        ordered.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+
+        let mut failed: usize = 0;
+        for (relative, modified) in ordered {
+            let mtime = filetime::FileTime::from_unix_time(
+                modified.timestamp(),
+                modified.timestamp_subsec_nanos(),
+            );
+            let dest = extended_length_path(&dest_root.join(relative));
+            // Pruned, or on a spanned drive that never received this directory
+            if dest.is_dir() && filetime::set_file_mtime(&dest, mtime).is_err() {
+                failed += 1;
+            }
+        }
+
+        if failed > 0 {
+            eprintln!(
+                "[Sync] Could not restore the mtime of {} directories",
+                failed
+            );
+        }
+    }
+
+    /// Gives mirrored directories the source's file flags, after their mtimes
+    /// since an immutable directory takes no more changes. Returns a warning
+    /// per directory whose flags couldn't be set.
+    fn restore_directory_flags(dest_root: &Path, dirs: &[(&Path, u32)]) -> Vec<String> {
+        dirs.iter()
+            .filter_map(|(relative, flags)| {
+                let dest = extended_length_path(&dest_root.join(relative));
+                if !dest.is_dir() {
+                    return None;
+                }
+                let error = apply_file_flags(&dest, *flags).err()?;
+                Some(format!(
+                    "Could not set file flags on {}: {}",
+                    dest.display(),
+                    error
+                ))
+            })
+            .collect()
+    }
+
+    /// Spawns a background task that polls destination free space and pauses the
+    /// transfer through its `TransferControl` when space drops below `threshold`.
+    /// Emits a `low-disk-space` event so the user can free space and resume.
+    fn spawn_disk_space_monitor(
+        transfer_id: String,
+        dest_root: PathBuf,
+        threshold: u64,
+        control: Arc<TransferControl>,
+        state_manager: Arc<TransferStateManager>,
+        app_handle: Option<AppHandle>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                DISK_SPACE_CHECK_INTERVAL_SECS,
+            ));
+
+            while !control.is_cancelled() {
+                interval.tick().await;
+
+                if control.is_paused() {
+                    continue;
+                }
 
-        // Set up parallel processing
-        let max_concurrent = options.max_concurrent_files.clamp(1, 8);
-        let semaphore = Arc::new(Semaphore::new(max_concurrent));
+                let available = match get_disk_space(&dest_root) {
+                    Ok((available, _)) => available,
+                    Err(_) => continue,
+                };
 
-        let files_copied = Arc::new(AtomicUsize::new(0));
-        let files_failed = Arc::new(AtomicUsize::new(0));
-        let bytes_copied_atomic = Arc::new(AtomicUsize::new(0));
+                if available >= threshold {
+                    continue;
+                }
 
-        let mut handles = Vec::new();
+                control.pause();
+                if let Ok(state_arc) = state_manager.get_transfer(&transfer_id) {
+                    let mut state = state_arc.write();
+                    state.status = TransferStatus::Paused;
+                    state.error = Some(format!(
+                        "Paused: destination has {} bytes free (threshold: {} bytes)",
+                        available, threshold
+                    ));
+                    let reason = state.error.clone();
+                    state.record_event(TransferEventKind::Paused, None, reason);
+                    let _ = state_manager.save_state(&state);
+                }
 
-        for file in files_to_transfer {
-            if control.is_cancelled() {
-                break;
+                eprintln!(
+                    "[DiskSpace] Paused transfer {}: {} bytes free, threshold {} bytes",
+                    transfer_id, available, threshold
+                );
+
+                if let Some(handle) = app_handle.as_ref() {
+                    let _ = handle.emit(
+                        "low-disk-space",
+                        serde_json::json!({
+                            "transferId": &transfer_id,
+                            "destination": dest_root.display().to_string(),
+                            "availableBytes": available,
+                            "thresholdBytes": threshold,
+                        }),
+                    );
+                }
             }
+        })
+    }
 
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
-            let src_path = source_path.join(&file.path);
-            let dst_path = dest_path.join(&file.path);
-            let control_clone = control.clone();
-            let state_arc_clone = state_arc.clone();
-            let state_manager = self.state_manager.clone();
-            let files_copied = files_copied.clone();
-            let files_failed = files_failed.clone();
-            let bytes_copied = bytes_copied_atomic.clone();
-            let app_handle = self.app_handle.clone();
-            let transfer_id_owned = transfer_id.to_string();
-            let bandwidth_limit = options.bandwidth_limit;
+    /// Reports the transfer once no copy has made progress for `timeout_secs`,
+    /// and again each time it stalls after recovering. With `fail` set, the
+    /// transfer is stopped and ends as Failed with `SyncError::Timeout`.
+    fn spawn_stall_watchdog(
+        transfer_id: String,
+        timeout_secs: u64,
+        fail: bool,
+        control: Arc<TransferControl>,
+        abort_error: Arc<parking_lot::Mutex<Option<SyncError>>>,
+        state_manager: Arc<TransferStateManager>,
+        app_handle: Option<AppHandle>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let timeout = std::time::Duration::from_secs(timeout_secs.max(1));
+            let mut interval = tokio::time::interval(
+                timeout.min(std::time::Duration::from_secs(STALL_CHECK_INTERVAL_SECS)),
+            );
+            // The scan before the copy loop isn't part of the stall window
+            control.note_progress();
+            let mut reported = false;
 
-            let handle = tokio::spawn(async move {
-                let _permit = permit;
+            while !control.is_cancelled() {
+                interval.tick().await;
 
-                // Handle pause
-                while control_clone.is_paused() && !control_clone.is_cancelled() {
-                    control_clone.wait_for_resume().await;
+                let stalled_for = control.since_last_progress();
+                if control.is_paused() || stalled_for < timeout {
+                    reported = false;
+                    continue;
                 }
-
-                if control_clone.is_cancelled() {
-                    return;
+                if reported {
+                    continue;
                 }
-
-                // Create parent directory if needed
-                if let Some(parent) = dst_path.parent() {
-                    let _ = std::fs::create_dir_all(parent);
+                reported = true;
+
+                let current_file = state_manager
+                    .get_transfer(&transfer_id)
+                    .ok()
+                    .and_then(|state_arc| state_arc.read().current_file.clone());
+                eprintln!(
+                    "[Watchdog] Transfer {} made no progress for {}s",
+                    transfer_id,
+                    stalled_for.as_secs()
+                );
+
+                if let Some(handle) = app_handle.as_ref() {
+                    let _ = handle.emit(
+                        "transfer-stalled",
+                        serde_json::json!({
+                            "transferId": &transfer_id,
+                            "stalledSecs": stalled_for.as_secs(),
+                            "currentFile": current_file.as_ref().map(|p| p.display().to_string()),
+                            "failed": fail,
+                        }),
+                    );
                 }
 
-                // Get resume offset from state
-                let resume_offset = {
-                    let state = state_arc_clone.read();
-                    state.files.get(&src_path).map(|f| f.get_resume_offset()).unwrap_or(0)
-                };
-
-                let copy_options = CopyOptions {
-                    preserve_metadata: true,
-                    buffer_size: 256 * 1024,
-                    verify_integrity: false, // Resume uses block-level verification
-                    resume_offset,
-                    bandwidth_limit,
-                    pre_copy_source_hash: None,
-                    source_mtime_before_copy: None,
-                };
-
-                match copy_file_with_progress(&src_path, &dst_path, &copy_options, |copied, _total| {
-                    if let Some(handle) = &app_handle {
-                        let _ = handle.emit("transfer_progress", serde_json::json!({
-                            "transfer_id": &transfer_id_owned,
-                            "file": src_path.display().to_string(),
-                            "bytes_copied": copied,
-                        }));
-                    }
-                    true // Continue the transfer
-                }) {
-                    Ok(bytes) => {
-                        files_copied.fetch_add(1, Ordering::Relaxed);
-                        bytes_copied.fetch_add(bytes as usize, Ordering::Relaxed);
-                        
-                        let mut state = state_arc_clone.write();
-                        state.complete_file(&src_path);
+                if fail {
+                    let error = SyncError::Timeout(format!(
+                        "Transfer made no progress for {}s",
+                        stalled_for.as_secs()
+                    ));
+                    // A copy wedged in I/O may never return, so mark the
+                    // state now rather than when the copy loop unwinds
+                    if let Ok(state_arc) = state_manager.get_transfer(&transfer_id) {
+                        let mut state = state_arc.write();
+                        state.status = TransferStatus::Failed;
+                        state.error = Some(error.to_string());
                         let _ = state_manager.save_state(&state);
                     }
-                    Err(e) => {
-                        files_failed.fetch_add(1, Ordering::Relaxed);
-                        
-                        let mut state = state_arc_clone.write();
-                        state.fail_file(&src_path, e.to_string());
-                        let _ = state_manager.save_state(&state);
+                    let mut first = abort_error.lock();
+                    if first.is_none() {
+                        *first = Some(error);
                     }
+                    control.cancel();
+                    break;
                 }
-            });
+            }
+        })
+    }
 
-            handles.push(handle);
+    /// Compiles every exclude layer that applies to a sync. `source_root` is
+    /// where the `.rsyncignore` is looked up; sources without a local root
+    /// only get the global and per-sync patterns.
+    fn exclude_matcher(
+        options: &SyncOptions,
+        source_root: Option<&Path>,
+    ) -> Option<ExcludeMatcher> {
+        let matcher = ExcludeMatcher::compile(layered_exclude_rules(
+            &options.global_exclude_patterns,
+            &options.exclude_patterns,
+            source_root,
+        ));
+        match &options.subdirs {
+            Some(subdirs) => Some(ExcludeMatcher::restricted_to(matcher, subdirs.clone())),
+            None => matcher,
         }
+    }
 
-        // Wait for all file transfers to complete
-        for handle in handles {
-            let _ = handle.await;
+    /// Checks `options.subdirs` against the source and makes each of them
+    /// relative to it. A subdirectory has to be inside the source, and an empty
+    /// list would sync nothing, so both are rejected.
+    fn validate_subdirs(source: &Path, options: &mut SyncOptions) -> SyncResult<()> {
+        let Some(subdirs) = options.subdirs.as_mut() else {
+            return Ok(());
+        };
+        if subdirs.is_empty() {
+            return Err(SyncError::InvalidPath(
+                "No source subdirectories to sync".into(),
+            ));
         }
-
-        // Update final state
-        {
-            let mut state = state_arc.write();
-            if control.is_cancelled() {
-                state.status = TransferStatus::Cancelled;
-                state.error = Some("Transfer cancelled by user".to_string());
-            } else if state.files_failed > 0 {
-                state.status = TransferStatus::Failed;
-                state.error = Some(format!("{} files failed to transfer", state.files_failed));
+        for subdir in subdirs.iter_mut() {
+            let relative = if subdir.is_absolute() {
+                subdir.strip_prefix(source).ok().map(Path::to_path_buf)
             } else {
-                state.status = TransferStatus::Completed;
+                Some(subdir.clone())
+            };
+            match relative {
+                Some(relative)
+                    if relative.components().next().is_some()
+                        && relative
+                            .components()
+                            .all(|c| matches!(c, std::path::Component::Normal(_))) =>
+                {
+                    *subdir = relative;
+                }
+                _ => {
+                    return Err(SyncError::InvalidPath(format!(
+                        "{} is not a subdirectory of the source {}",
+                        subdir.display(),
+                        source.display()
+                    )));
+                }
             }
-            self.state_manager.save_state(&state)?;
         }
+        Ok(())
+    }
 
-        // Clean up controls
-        {
-            let mut controls = self.controls.write();
-            controls.remove(transfer_id);
+    /// Measures the clock skew of each existing destination root and widens
+    /// `mtime_tolerance_seconds` to cover the largest. Returns a warning per
+    /// root whose skew needed it or that couldn't be measured.
+    fn widen_for_clock_skew(dest_path: &Path, options: &mut SyncOptions) -> Vec<String> {
+        let roots: Vec<PathBuf> = std::iter::once(dest_path.to_path_buf())
+            .chain(options.spanning_destinations.iter().cloned())
+            .filter(|root| root.is_dir())
+            .collect();
+        let mut warnings = Vec::new();
+        for root in roots {
+            match measure_clock_skew(&root) {
+                Ok(skew) if skew.tolerance_seconds > 0 => {
+                    let tolerance = options.mtime_tolerance_seconds.unwrap_or(0);
+                    options.mtime_tolerance_seconds = Some(tolerance.max(skew.tolerance_seconds));
+                    let warning =
+                        format!(
+                        "{} stamps files {:.1}s {} the local clock; mtimes up to {}s apart are \
+                         treated as equal",
+                        root.display(),
+                        skew.skew_ms.unsigned_abs() as f64 / 1000.0,
+                        if skew.skew_ms > 0 { "ahead of" } else { "behind" },
+                        skew.tolerance_seconds
+                    );
+                    eprintln!("[Sync] {}", warning);
+                    warnings.push(warning);
+                }
+                Ok(_) => {}
+                Err(e) => warnings.push(format!(
+                    "Could not measure clock skew on {}: {}",
+                    root.display(),
+                    e
+                )),
+            }
         }
+        warnings
+    }
 
-        Ok(())
+    /// Estimates how many bytes a sync would actually write by skipping files
+    /// that are already unchanged at the destination, along with the size of
+    /// the destination files those writes replace.
+    fn estimate_bytes_to_copy(
+        files: &[&FileInfo],
+        dest_root: &Path,
+        modify_window: chrono::Duration,
+    ) -> (u64, u64) {
+        files
+            .iter()
+            .filter(|file| {
+                detect_delta_within(file, dest_root, modify_window)
+                    .map(|delta| delta.status != DeltaStatus::Unchanged)
+                    .unwrap_or(true)
+            })
+            .fold((0, 0), |(written, replaced), file| {
+                let existing =
+                    std::fs::symlink_metadata(extended_length_path(&dest_root.join(&file.path)))
+                        .ok()
+                        .filter(|metadata| metadata.is_file())
+                        .map_or(0, |metadata| metadata.len());
+                (written + file.size, replaced + existing)
+            })
     }
 
-    /// Scan a directory while tracking any errors encountered.
-    /// This allows us to determine if the scan was complete for safe orphan deletion.
-    fn scan_directory_with_error_tracking(
-        &self,
-        path: &Path,
-        follow_symlinks: bool,
-    ) -> SyncResult<ScanResult> {
-        if !path.exists() {
-            return Err(SyncError::SourceNotFound(path.display().to_string()));
+    /// Checks the policy limits for total bytes written and destination fill level.
+    /// These are user-configured safety valves (e.g. SSD endurance), separate
+    /// from the physical disk-full check done per file. The fill level counts
+    /// the space of the `bytes_replaced` overwritten files as freed, unless
+    /// rollback backups keep them. `whole_source` marks estimates a streaming
+    /// sync made from the whole source.
+    fn check_write_limits(
+        options: &SyncOptions,
+        dest_root: &Path,
+        bytes_to_copy: u64,
+        bytes_replaced: u64,
+        whole_source: bool,
+    ) -> SyncResult<()> {
+        if let Some(limit_bytes) = options.max_write_bytes {
+            if bytes_to_copy > limit_bytes {
+                return Err(SyncError::WriteLimitExceeded {
+                    required_bytes: bytes_to_copy,
+                    limit_bytes,
+                    whole_source,
+                });
+            }
         }
 
-        if !path.is_dir() {
-            return Err(SyncError::InvalidPath(format!(
-                "{} is not a directory",
-                path.display()
-            )));
+        if let Some(limit_percent) = options.max_dest_fill_percent {
+            let (available, total) = get_disk_space(dest_root)?;
+            if total > 0 {
+                let freed = if options.keep_rollback_backups {
+                    0
+                } else {
+                    bytes_replaced
+                };
+                let used_after = total
+                    .saturating_sub(available)
+                    .saturating_add(bytes_to_copy)
+                    .saturating_sub(freed);
+                let projected_percent = used_after as f64 / total as f64 * 100.0;
+                if projected_percent > limit_percent {
+                    return Err(SyncError::DestinationFillLimitExceeded {
+                        path: dest_root.to_path_buf(),
+                        projected_percent,
+                        limit_percent,
+                        whole_source,
+                    });
+                }
+            }
         }
 
-        let mut total_size: u64 = 0;
-        let mut file_count: usize = 0;
-        let mut dir_count: usize = 0;
-        let mut files = Vec::new();
-        let mut scan_errors = Vec::new();
+        Ok(())
+    }
 
-        for entry in WalkDir::new(path)
-            .follow_links(follow_symlinks)
-            .into_iter()
-        {
-            match entry {
-                Ok(e) => {
-                    let entry_path = e.path();
-                    if entry_path == path {
-                        continue;
-                    }
+    /// Rejects a destination that is the source or nested inside it: the sync
+    /// would copy its own output back into itself. Both paths are resolved
+    /// through symlinks first; a destination that doesn't exist yet is
+    /// resolved through its nearest existing ancestor.
+    fn check_source_dest_overlap(source: &Path, dest: &Path) -> SyncResult<()> {
+        let source_real = source
+            .canonicalize()
+            .unwrap_or_else(|_| source.to_path_buf());
+        let dest_real = dest
+            .ancestors()
+            .find_map(|ancestor| {
+                let real = ancestor.canonicalize().ok()?;
+                let rest = dest.strip_prefix(ancestor).ok()?;
+                Some(real.join(rest))
+            })
+            .unwrap_or_else(|| dest.to_path_buf());
 
-                    match crate::file_ops::get_file_info(entry_path, path) {
-                        Ok(info) => {
-                            if info.is_dir {
-                                dir_count += 1;
-                            } else {
-                                file_count += 1;
-                                total_size += info.size;
+        if dest_real.starts_with(&source_real) {
+            return Err(SyncError::SourceDestOverlap {
+                source_path: source.to_path_buf(),
+                destination: dest.to_path_buf(),
+            });
+        }
+        Ok(())
+    }
+
+    /// With `follow_symlinks`, a destination symlink resolving into the source
+    /// (or a source symlink resolving into a destination) makes the sync read
+    /// its own writes, or loop. Returns each such link, relative to its tree,
+    /// with a warning naming it; the copy and orphan cleanup don't go through them.
+    fn looping_symlinks(
+        options: &SyncOptions,
+        source_root: &Path,
+        dest_roots: &[PathBuf],
+    ) -> Vec<(PathBuf, String)> {
+        if !options.follow_symlinks {
+            return Vec::new();
+        }
+        let mut links: Vec<(PathBuf, String)> = Vec::new();
+        for dest_root in dest_roots {
+            for link in symlinks_resolving_into(dest_root, source_root) {
+                let warning = format!(
+                    "Not following {}: it is a destination symlink that resolves into the source",
+                    dest_root.join(&link).display()
+                );
+                links.push((link, warning));
+            }
+            for link in symlinks_resolving_into(source_root, dest_root) {
+                let warning = format!(
+                    "Not following {}: it is a source symlink that resolves into the destination",
+                    source_root.join(&link).display()
+                );
+                links.push((link, warning));
+            }
+        }
+        for (_, warning) in &links {
+            eprintln!("[Sync] {}", warning);
+        }
+        links
+    }
+
+    /// Drives `adaptive_concurrency`: every window, compares the transfer's
+    /// throughput with the previous window's and adds a permit while the last
+    /// one raised throughput by `ADAPTIVE_MIN_GAIN`. Once another copy stops
+    /// helping the device is saturated; a level that made things slower is
+    /// given back, and the concurrency is then left alone.
+    fn spawn_concurrency_controller(
+        transfer_id: String,
+        max_concurrent: usize,
+        semaphore: Arc<Semaphore>,
+        chosen: Arc<AtomicUsize>,
+        control: Arc<TransferControl>,
+        state_manager: Arc<TransferStateManager>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let window = std::time::Duration::from_secs(ADAPTIVE_WINDOW_SECS);
+            let bytes_transferred = || {
+                state_manager
+                    .get_transfer(&transfer_id)
+                    .map(|state_arc| state_arc.read().bytes_transferred)
+                    .unwrap_or(0)
+            };
+            let mut interval = tokio::time::interval(window);
+            interval.tick().await;
+            let mut last_bytes = bytes_transferred();
+            let mut last_rate: Option<f64> = None;
+            let mut current = 1;
+
+            while !control.is_cancelled() && current < max_concurrent {
+                interval.tick().await;
+                let bytes = bytes_transferred();
+                let delta = bytes.saturating_sub(last_bytes);
+                last_bytes = bytes;
+                // Nothing to learn from a paused window
+                if control.is_paused() || delta == 0 {
+                    continue;
+                }
+
+                let rate = delta as f64 / window.as_secs_f64();
+                match last_rate {
+                    Some(previous) if rate < previous * (1.0 + ADAPTIVE_MIN_GAIN) => {
+                        if rate < previous {
+                            // Wait out a copy in flight if every permit is taken
+                            if semaphore.forget_permits(1) == 0 {
+                                if let Ok(permit) = semaphore.clone().acquire_owned().await {
+                                    permit.forget();
+                                }
                             }
-                            files.push(info);
-                        }
-                        Err(e) => {
-                            scan_errors.push(format!(
-                                "Failed to get info for '{}': {}",
-                                entry_path.display(),
-                                e
-                            ));
+                            current -= 1;
+                            chosen.store(current, Ordering::Relaxed);
                         }
+                        eprintln!(
+                            "[Adaptive] Throughput levelled off at {:.1} MB/s; settled on {} files at a time",
+                            previous.max(rate) / 1_000_000.0,
+                            current
+                        );
+                        return;
+                    }
+                    _ => {
+                        semaphore.add_permits(1);
+                        current += 1;
+                        chosen.store(current, Ordering::Relaxed);
+                        last_rate = Some(rate);
+                        eprintln!(
+                            "[Adaptive] {:.1} MB/s, trying {} files at a time",
+                            rate / 1_000_000.0,
+                            current
+                        );
                     }
-                }
-                Err(e) => {
-                    scan_errors.push(format!("Scan error: {}", e));
                 }
             }
-        }
-
-        let info = DirectoryInfo {
-            path: path.to_path_buf(),
-            total_size,
-            file_count,
-            dir_count,
-            files,
-        };
-
-        Ok(ScanResult::with_errors(info, scan_errors))
+        })
     }
 
-    /// Clean up orphaned files in the destination that don't exist in the source.
-    /// SAFETY: This will refuse to delete files if the source scan was incomplete
-    /// to prevent accidental data loss.
-    fn cleanup_orphans(
-        &self,
-        source_info: &DirectoryInfo,
-        dest_root: &Path,
-        scan_complete: bool,
-        scan_errors: &[String],
-    ) -> SyncResult<()> {
-        // CRITICAL SAFETY CHECK: Do not delete orphans if the scan was incomplete
-        // This prevents data loss if we couldn't fully scan the source
-        if !scan_complete {
-            let error_count = scan_errors.len();
-            let error_preview: String = scan_errors
-                .iter()
-                .take(3)
-                .cloned()
-                .collect::<Vec<_>>()
-                .join("; ");
-            
-            return Err(SyncError::IncompleteScan(format!(
-                "Orphan deletion skipped: source scan was incomplete ({} errors). \
-                 First errors: {}. \
-                 Re-run sync after resolving scan issues to safely delete orphans.",
-                error_count,
-                error_preview
-            )));
+    /// Rejects spanning setups that can't work: options that need a single
+    /// destination tree, and roots that share a drive (their free space would
+    /// be counted twice when packing).
+    fn validate_spanning(dest_root: &Path, options: &SyncOptions) -> SyncResult<()> {
+        if options.atomic_publish {
+            return Err(SyncError::InvalidSpanning(
+                "atomic publish needs a single destination".into(),
+            ));
         }
-
-        let mut source_paths: HashSet<String> = HashSet::new();
-        for entry in &source_info.files {
-            source_paths.insert(entry.path.to_string_lossy().to_string());
+        if options.streaming {
+            return Err(SyncError::InvalidSpanning(
+                "files can only be packed across drives after a full scan, not with streaming"
+                    .into(),
+            ));
         }
 
-        for entry in WalkDir::new(dest_root).contents_first(true).into_iter().filter_map(|e| e.ok()) {
-            let path = entry.path();
-            if path == dest_root {
-                continue;
+        let mut roots: Vec<&Path> = vec![dest_root];
+        for root in &options.spanning_destinations {
+            if let Some(other) = roots
+                .iter()
+                .find(|other| **other == root.as_path() || is_same_device(other, root))
+            {
+                return Err(SyncError::InvalidSpanning(format!(
+                    "{} is on the same drive as {}",
+                    root.display(),
+                    other.display()
+                )));
             }
-            let relative = match path.strip_prefix(dest_root) {
-                Ok(rel) => rel.to_string_lossy().to_string(),
-                Err(_) => continue,
-            };
-            if source_paths.contains(&relative) {
-                continue;
+            roots.push(root);
+        }
+        Ok(())
+    }
+
+    /// Picks a destination root for each regular file, keyed by relative path.
+    /// A file already present under a root stays there if it still fits; the
+    /// rest are packed largest first onto the first root with room, so each
+    /// drive fills before the next is used. Fails before anything is copied if
+    /// some file fits on no drive.
+    fn plan_spanning(
+        files: &[&FileInfo],
+        roots: &[PathBuf],
+        modify_window: chrono::Duration,
+    ) -> SyncResult<HashMap<PathBuf, usize>> {
+        let mut free = roots
+            .iter()
+            .map(|root| {
+                get_disk_space(root)
+                    .map(|(available, _)| available.saturating_sub(SPANNING_RESERVE_BYTES))
+            })
+            .collect::<SyncResult<Vec<u64>>>()?;
+        let mut placement = HashMap::with_capacity(files.len());
+        let mut unplaced = Vec::new();
+
+        for file in files {
+            let existing = roots
+                .iter()
+                .position(|root| root.join(&file.path).symlink_metadata().is_ok());
+            if let Some(index) = existing {
+                let unchanged = detect_delta_within(file, &roots[index], modify_window)
+                    .is_ok_and(|delta| delta.status == DeltaStatus::Unchanged);
+                // An overwrite is staged in a temp file next to the old copy
+                let needed = if unchanged {
+                    0
+                } else {
+                    file.size.saturating_add(SPANNING_FILE_OVERHEAD_BYTES)
+                };
+                if needed <= free[index] {
+                    free[index] -= needed;
+                    placement.insert(file.path.clone(), index);
+                    continue;
+                }
             }
-            if entry.file_type().is_dir() {
-                let _ = std::fs::remove_dir(path);
-            } else {
-                let _ = std::fs::remove_file(path);
+            unplaced.push(*file);
+        }
+
+        unplaced.sort_by_key(|file| std::cmp::Reverse(file.size));
+        for file in unplaced {
+            let needed = file.size.saturating_add(SPANNING_FILE_OVERHEAD_BYTES);
+            match free.iter().position(|available| *available >= needed) {
+                Some(index) => {
+                    free[index] -= needed;
+                    placement.insert(file.path.clone(), index);
+                }
+                None => {
+                    return Err(SyncError::SpanningCapacityExceeded {
+                        path: file.path.clone(),
+                        required_bytes: needed,
+                        largest_free_bytes: free.iter().copied().max().unwrap_or(0),
+                    });
+                }
             }
         }
 
-        Ok(())
+        Ok(placement)
     }
 
     fn get_control(&self, transfer_id: &str) -> SyncResult<Arc<TransferControl>> {
@@ -1066,6 +5706,16 @@ impl SyncEngine {
         status: TransferStatus,
         error: Option<String>,
     ) -> SyncResult<()> {
+        if matches!(
+            status,
+            TransferStatus::Completed | TransferStatus::Failed | TransferStatus::Cancelled
+        ) {
+            // Only transfers still running, waiting or queued keep a control
+            self.controls.write().remove(transfer_id);
+            self.untrack_transfer_volumes(transfer_id);
+            self.volume_paused.write().remove(transfer_id);
+        }
+
         let state_arc = self.state_manager.get_transfer(transfer_id)?;
         let mut state = state_arc.write();
         state.status = status;
@@ -1073,6 +5723,106 @@ impl SyncEngine {
         self.state_manager.save_state(&state)
     }
 
+    /// Temp and partial files of unfinished files in transfers that have not
+    /// finished or can be resumed, including interrupted ones loaded from disk.
+    pub fn resumable_partial_paths(&self) -> HashSet<PathBuf> {
+        let mut paths = HashSet::new();
+        let transfers = self.state_manager.get_active_transfers();
+        for transfer in transfers
+            .into_iter()
+            .chain(self.state_manager.get_interrupted_transfers())
+        {
+            for file_state in transfer.files.values() {
+                if file_state.status != TransferStatus::Completed {
+                    paths.insert(get_temp_path(&file_state.dest_path));
+                    paths.insert(get_partial_path(&file_state.dest_path));
+                }
+            }
+        }
+        paths
+    }
+
+    /// Notes in the transfer state that the copy about to be made overwrites
+    /// the destination file, or goes beside it as `actual_dest`, for
+    /// `rollback_transfer`. With `keep_backup` the destination file is copied
+    /// aside first; if that fails the file fails rather than losing it.
+    fn record_replacement(
+        transfer_id: &str,
+        state_manager: &TransferStateManager,
+        keep_backup: bool,
+        source_path: &Path,
+        dest_root: &Path,
+        relative: &Path,
+        actual_dest: &Path,
+    ) -> SyncResult<()> {
+        let dest_path = dest_root.join(relative);
+        let state_arc = state_manager.get_transfer(transfer_id)?;
+        if actual_dest != dest_path {
+            let mut state = state_arc.write();
+            state.record_written_to(source_path, actual_dest.to_path_buf());
+            return state_manager.save_state(&state);
+        }
+        // Already recorded by the run that started this copy
+        if state_arc
+            .read()
+            .files
+            .get(source_path)
+            .is_some_and(|f| f.replaced)
+        {
+            return Ok(());
+        }
+
+        let backup = if keep_backup {
+            let backup = rollback_backup_path(dest_root, transfer_id, relative);
+            keep_rollback_backup(&dest_path, &backup).map_err(|e| {
+                SyncError::Internal(format!(
+                    "Could not back up {} for rollback: {}",
+                    dest_path.display(),
+                    e
+                ))
+            })?;
+            Some(backup)
+        } else {
+            None
+        };
+        let mut state = state_arc.write();
+        state.record_replaced(source_path, backup);
+        state_manager.save_state(&state)
+    }
+
+    /// True if both files hash the same. Any read error counts as a mismatch,
+    /// so the file is copied rather than wrongly skipped.
+    fn contents_match(source: &Path, dest: &Path, state_manager: &TransferStateManager) -> bool {
+        let cache = state_manager.hash_cache();
+        match (cache.hash_file(source), cache.hash_file(dest)) {
+            (Ok(source_hash), Ok(dest_hash)) => source_hash == dest_hash,
+            _ => false,
+        }
+    }
+
+    /// Compares a scanned file with the source as it is now. Symlinks are
+    /// left alone: their scanned metadata describes the link, not its target.
+    fn restat_source(source_path: &Path, file: &FileInfo) -> SourceChange {
+        if file.is_symlink {
+            return SourceChange::Unchanged;
+        }
+        let metadata = match std::fs::symlink_metadata(source_path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return SourceChange::Removed,
+            // Let the copy itself report anything else
+            Err(_) => return SourceChange::Unchanged,
+        };
+        let modified = crate::file_ops::metadata_to_datetime(&metadata).unwrap_or(file.modified);
+        if metadata.len() == file.size && modified == file.modified {
+            return SourceChange::Unchanged;
+        }
+        SourceChange::Modified(FileInfo {
+            size: metadata.len(),
+            modified,
+            ..file.clone()
+        })
+    }
+
     fn create_directory(&self, dest_root: &Path, file: &FileInfo) -> SyncResult<()> {
         let dest_path = dest_root.join(&file.path);
         std::fs::create_dir_all(&dest_path)?;
@@ -1093,7 +5843,130 @@ impl SyncEngine {
         let source_path = source_root.join(&file.path);
         let dest_path = dest_root.join(&file.path);
 
-        let delta = detect_delta_detailed(file, dest_root)?;
+        // The source may have changed since the scan; decide and copy based on
+        // what is on disk now, and keep the transfer totals in step
+        let refreshed;
+        let file = match Self::restat_source(&source_path, file) {
+            SourceChange::Unchanged => file,
+            SourceChange::Removed => {
+                eprintln!(
+                    "[Sync] Source removed since scan, skipping: {}",
+                    file.path.display()
+                );
+                let state_arc = state_manager.get_transfer(transfer_id)?;
+                let mut state = state_arc.write();
+                state.rescan_file(&source_path, 0, file.modified);
+                state.skip_file(&source_path);
+                state_manager.save_state(&state)?;
+                return Ok(0);
+            }
+            SourceChange::Modified(current) => {
+                eprintln!(
+                    "[Sync] Source changed since scan ({} -> {} bytes): {}",
+                    file.size,
+                    current.size,
+                    file.path.display()
+                );
+                let state_arc = state_manager.get_transfer(transfer_id)?;
+                {
+                    let mut state = state_arc.write();
+                    state.rescan_file(&source_path, current.size, current.modified);
+                    state_manager.save_state(&state)?;
+                }
+                refreshed = current;
+                &refreshed
+            }
+        };
+
+        // A copy that finished before the transfer was interrupted but was cut
+        // off while being verified; carry on verifying it instead of copying
+        // it again
+        if options.verify_integrity && !options.dry_run {
+            let resumed = {
+                let _active_copy = ActiveCopy::enter(control);
+                let (transfer_id, state_manager, control) = (
+                    transfer_id.to_string(),
+                    state_manager.clone(),
+                    control.clone(),
+                );
+                let (source, dest, file) = (source_path.clone(), dest_path.clone(), file.clone());
+                tokio::task::spawn_blocking(move || {
+                    Self::resume_verification(
+                        &transfer_id,
+                        &source,
+                        &dest,
+                        &file,
+                        &control,
+                        &state_manager,
+                    )
+                })
+                .await
+                .map_err(|e| SyncError::Internal(e.to_string()))?
+            };
+            match resumed {
+                Some(Ok(written)) => {
+                    Self::record_finished_copy(
+                        transfer_id,
+                        &source_path,
+                        &written,
+                        file,
+                        options,
+                        state_manager,
+                        None,
+                    )?;
+                    return Self::remove_moved_source(options, source_path, 0);
+                }
+                Some(Err(e @ SyncError::TransferCancelled(_))) => return Err(e),
+                Some(Err(e)) => {
+                    eprintln!(
+                        "[Resume] Copy of {} failed verification, copying it again: {}",
+                        source_path.display(),
+                        e
+                    );
+                    cleanup_temp_files(&dest_path);
+                    let state_arc = state_manager.get_transfer(transfer_id)?;
+                    let mut state = state_arc.write();
+                    state.rescan_file(&source_path, file.size, file.modified);
+                    state_manager.save_state(&state)?;
+                }
+                None => {}
+            }
+        }
+
+        // Part way through when the transfer was interrupted: the copy carries
+        // on where it was going, past the overwrite checks made back then
+        let resumed_dest = state_manager
+            .get_transfer(transfer_id)
+            .ok()
+            .and_then(|state_arc| {
+                let state = state_arc.read();
+                state
+                    .files
+                    .get(&source_path)
+                    .filter(|f| f.status != TransferStatus::Completed && f.bytes_transferred > 0)
+                    .map(|f| f.written_to.clone().unwrap_or_else(|| dest_path.clone()))
+            });
+
+        // Reading xattrs costs two calls per file, so only when they are synced
+        let delta = if options.preserve_xattr {
+            detect_delta_with_xattrs(file, &source_path, dest_root, options.modify_window())?
+        } else {
+            detect_delta_within(file, dest_root, options.modify_window())?
+        };
+        let conflict_resolution = options.conflict_resolution_for(&file.path);
+        // Recovered in place, without consulting the overwrite settings
+        let zero_byte_dest = options.recopy_zero_byte_dest
+            && delta.status == DeltaStatus::Modified
+            && file.size > 0
+            && std::fs::metadata(extended_length_path(&dest_path))
+                .is_ok_and(|metadata| metadata.is_file() && metadata.len() == 0);
+        let interrupted_copy = resumed_dest.is_some() || zero_byte_dest;
+        if zero_byte_dest && resumed_dest.is_none() {
+            eprintln!(
+                "[Sync] Re-copying over empty destination file: {}",
+                file.path.display()
+            );
+        }
 
         // Handle unchanged files - always skip
         if delta.status == DeltaStatus::Unchanged {
@@ -1104,8 +5977,21 @@ impl SyncEngine {
             return Ok(0);
         }
 
+        // Same contents, different xattrs: update the attributes in place
+        // rather than copying identical bytes again
+        if delta.xattrs_differ && !interrupted_copy {
+            if !options.dry_run {
+                copy_xattrs(&source_path, &dest_path)?;
+            }
+            let state_arc = state_manager.get_transfer(transfer_id)?;
+            let mut state = state_arc.write();
+            state.complete_file(&source_path);
+            state_manager.save_state(&state)?;
+            return Ok(0);
+        }
+
         // Handle existing files based on overwrite options
-        if delta.status == DeltaStatus::Modified {
+        if delta.status == DeltaStatus::Modified && !interrupted_copy {
             // If skip_existing is set, skip all existing files
             if options.skip_existing {
                 let state_arc = state_manager.get_transfer(transfer_id)?;
@@ -1123,13 +6009,28 @@ impl SyncEngine {
             } else if options.overwrite_older {
                 delta.source_older
             } else {
-                match options.conflict_resolution {
-                    ConflictResolution::Skip | ConflictResolution::Ask => false,
-                    ConflictResolution::Overwrite | ConflictResolution::Rename => true,
-                }
+                conflict_resolution.replaces_destination(&delta)
             };
 
             if !should_overwrite {
+                let state_arc = state_manager.get_transfer(transfer_id)?;
+                let mut state = state_arc.write();
+                state.record_event(
+                    TransferEventKind::Conflict,
+                    Some(&source_path),
+                    Some("Kept the existing destination file".to_string()),
+                );
+                state.skip_file(&source_path);
+                state_manager.save_state(&state)?;
+                return Ok(0);
+            }
+
+            // Only files whose contents change get touched; rewriting identical
+            // bytes would bump the mtime for nothing
+            if options.timestamp_mode == TimestampMode::PreserveSourceButTouchOnChange
+                && !delta.size_differs
+                && Self::contents_match(&source_path, &dest_path, state_manager)
+            {
                 let state_arc = state_manager.get_transfer(transfer_id)?;
                 let mut state = state_arc.write();
                 state.skip_file(&source_path);
@@ -1139,16 +6040,41 @@ impl SyncEngine {
         }
 
         // Determine actual destination
-        let actual_dest = if delta.status == DeltaStatus::Modified 
-            && options.conflict_resolution == ConflictResolution::Rename 
-            && !options.overwrite_newer 
-            && !options.overwrite_older 
+        let actual_dest = if let Some(resumed_dest) = resumed_dest {
+            resumed_dest
+        } else if delta.status == DeltaStatus::Modified
+            && conflict_resolution == ConflictResolution::Rename
+            && !options.overwrite_newer
+            && !options.overwrite_older
+            && !interrupted_copy
         {
-            generate_conflict_name(&dest_path)
+            let renamed =
+                generate_conflict_name(&dest_path, options.conflict_name_template.as_deref());
+            if let Ok(state_arc) = state_manager.get_transfer(transfer_id) {
+                state_arc.write().record_event(
+                    TransferEventKind::Conflict,
+                    Some(&source_path),
+                    Some(format!("Kept both; copying to {}", renamed.display())),
+                );
+            }
+            renamed
         } else {
             dest_path.clone()
         };
 
+        #[cfg(debug_assertions)]
+        if options.simulate {
+            return Self::simulate_file_copy(
+                transfer_id,
+                &source_path,
+                file,
+                control,
+                state_manager,
+                app_handle,
+            )
+            .await;
+        }
+
         // In dry-run mode, just report what would be copied
         if options.dry_run {
             let state_arc = state_manager.get_transfer(transfer_id)?;
@@ -1160,11 +6086,63 @@ impl SyncEngine {
             return Ok(file.size);
         }
 
-        // Get resume offset
+        // A placeholder never replaces a file that is already there, since
+        // that would throw its contents away
+        if options.structure_only {
+            let state_arc = state_manager.get_transfer(transfer_id)?;
+            if delta.status == DeltaStatus::New {
+                Self::create_placeholder(&source_path, &actual_dest)?;
+                let mut state = state_arc.write();
+                state.complete_file(&source_path);
+                state_manager.save_state(&state)?;
+            } else {
+                let mut state = state_arc.write();
+                state.skip_file(&source_path);
+                state_manager.save_state(&state)?;
+            }
+            return Ok(0);
+        }
+
+        if delta.status == DeltaStatus::Modified && !interrupted_copy {
+            if options.preserve_metadata && actual_dest == dest_path {
+                if let Err(e) = unlock_copied_flags(&dest_path, file.flags) {
+                    eprintln!("[Sync] Could not unlock {}: {}", dest_path.display(), e);
+                }
+            }
+            Self::record_replacement(
+                transfer_id,
+                state_manager,
+                options.keep_rollback_backups,
+                &source_path,
+                dest_root,
+                &file.path,
+                &actual_dest,
+            )?;
+        }
+
+        // Get resume offset, restarting the file if what was written of it has
+        // changed since. The copy was being staged in the temp file.
         let resume_offset = {
             if let Ok(state_arc) = state_manager.get_transfer(transfer_id) {
-                let state = state_arc.read();
-                state.files.get(&source_path).map(|f| f.get_resume_offset()).unwrap_or(0)
+                let mut state = state_arc.write();
+                let (offset, intact) = match state.files.get(&source_path) {
+                    Some(f) if f.get_resume_offset() > 0 => (
+                        f.get_resume_offset(),
+                        f.last_block_intact(&get_temp_path(&actual_dest)),
+                    ),
+                    _ => (0, true),
+                };
+                if intact {
+                    offset
+                } else {
+                    eprintln!(
+                        "[Resume] Last written block of {} no longer matches, copying it again",
+                        source_path.display()
+                    );
+                    state.rescan_file(&source_path, file.size, file.modified);
+                    let _ = state_manager.save_state(&state);
+                    0
+                }
             } else {
                 0
             }
@@ -1172,33 +6150,55 @@ impl SyncEngine {
 
         // BULLETPROOF VERIFICATION: Capture source state BEFORE copy begins
         // This prevents race conditions where source changes during/after copy
-        let (pre_copy_source_hash, source_mtime_before_copy) = if options.verify_integrity && resume_offset == 0 {
-            // Only compute pre-copy hash for fresh copies (not resumes)
-            // For resumes, we rely on block-level verification instead
-            let mtime = std::fs::metadata(&source_path)
+        let (pre_copy_source_hash, source_mtime_before_copy) =
+            if options.verify_integrity && resume_offset == 0 {
+                // Only compute pre-copy hash for fresh copies (not resumes)
+                // For resumes, we rely on block-level verification instead
+                let mtime = std::fs::metadata(&source_path)
+                    .ok()
+                    .and_then(|m| m.modified().ok());
+                let (hash_manager, hash_source) = (state_manager.clone(), source_path.clone());
+                let hash_limiter = control.hash_limiter.clone();
+                let hash = tokio::task::spawn_blocking(move || {
+                    let _permit = hash_limiter.acquire();
+                    hash_manager.hash_cache().hash_file(&hash_source).ok()
+                })
+                .await
                 .ok()
-                .and_then(|m| m.modified().ok());
-            let hash = crate::file_ops::compute_file_hash(&source_path).ok();
-            (hash, mtime)
-        } else {
-            (None, None)
-        };
+                .flatten();
+                (hash, mtime)
+            } else {
+                (None, None)
+            };
 
+        // Only offsets the copy has synced to disk become the resume point
+        let durable_offset_for_cb = Arc::new(AtomicU64::new(resume_offset));
         let copy_options = CopyOptions {
             buffer_size: options.buffer_size.unwrap_or(8 * 1024 * 1024),
             preserve_metadata: options.preserve_metadata,
             verify_integrity: options.verify_integrity,
             resume_offset,
             bandwidth_limit: options.bandwidth_limit,
+            shared_bandwidth_limit: Some(control.bandwidth_limit.clone()),
             pre_copy_source_hash,
             source_mtime_before_copy,
+            force_overwrite_locked: options.force_overwrite_locked,
+            preserve_acl: options.preserve_acl,
+            preserve_xattr: options.preserve_xattr,
+            timestamp_mode: options.timestamp_mode,
+            flush_requested: Some(control.flush_requested.clone()),
+            durable_offset: Some(durable_offset_for_cb.clone()),
+            pipelined_copy: options.pipelined_copy,
+            hash_limiter: Some(control.hash_limiter.clone()),
+            exclude_temp_from_indexing: options.exclude_temp_from_indexing,
+            verify_during_copy: options.verify_during_copy,
         };
 
         // Log throttling configuration if enabled
-        if options.bandwidth_limit > 0 {
+        if control.bandwidth_limit() > 0 {
             eprintln!(
                 "[Throttle] Bandwidth limit active: {} bytes/sec for file: {}",
-                options.bandwidth_limit,
+                control.bandwidth_limit(),
                 file.path.display()
             );
         }
@@ -1216,21 +6216,24 @@ impl SyncEngine {
 
         let (progress_tx, mut progress_rx) = tokio::sync::mpsc::channel::<ProgressEvent>(64);
         let app_handle_owned = app_handle.cloned();
+        let progress_ndjson = options.progress_ndjson;
         
         let emit_task = tauri::async_runtime::spawn(async move {
             if let Some(handle) = app_handle_owned {
                 while let Some(event) = progress_rx.recv().await {
                     let _ = handle.emit("sync-progress", event);
                 }
+            } else if progress_ndjson {
+                while let Some(event) = progress_rx.recv().await {
+                    print_ndjson("sync-progress", &event);
+                }
             } else {
                 while let Some(_) = progress_rx.recv().await {}
             }
         });
 
-        // Run the blocking file copy in a separate thread
-        // Use atomic copy for new files (no resume), regular copy for resumes
-        let use_atomic = resume_offset == 0;
-        
+        // Run the blocking file copy in a separate thread; a resume carries on
+        // in the staged temp file and is moved into place the same way
         // Clone values needed by the progress callback
         let source_path_for_cb = source_path_clone.clone();
         let current_file_for_cb = current_file.clone();
@@ -1245,16 +6248,15 @@ impl SyncEngine {
             let transfer_id = transfer_id_for_cb2.clone();
             let state_manager = state_manager_for_cb.clone();
             let progress_tx = progress_tx.clone();
+            let durable_offset = durable_offset_for_cb.clone();
+            // Each block reported ends where the one before it did
+            let block_start = AtomicU64::new(resume_offset);
             
             move |copied: u64, hash: Option<u64>| {
-                if control.is_cancelled() {
+                if control.is_cancelled() || control.is_skip_requested(&source_path) {
                     return false;
                 }
 
-                while control.is_paused() {
-                    std::thread::sleep(std::time::Duration::from_millis(50));
-                }
-
                 let elapsed = start_time.elapsed().as_secs_f64();
                 let speed = if elapsed > 0.0 {
                     (copied.saturating_sub(resume_offset)) as f64 / elapsed
@@ -1269,11 +6271,22 @@ impl SyncEngine {
                     None
                 };
 
+                control.note_progress();
+
                 if let Ok(state_arc) = state_manager.get_transfer(&transfer_id) {
                     let mut state = state_arc.write();
-                    state.status = TransferStatus::Running;
+                    if !control.is_paused() {
+                        state.status = TransferStatus::Running;
+                    }
                     state.current_file = Some(source_path.clone());
-                    state.update_file_progress(&source_path, copied, hash);
+                    let offset = block_start.swap(copied, Ordering::SeqCst);
+                    state.update_file_progress(
+                        &source_path,
+                        copied,
+                        hash.map(|hash| (offset, hash)),
+                    );
+                    state
+                        .record_durable_offset(&source_path, durable_offset.load(Ordering::SeqCst));
                     state.speed_bytes_per_sec = speed;
                     let _ = state_manager.save_state(&state);
 
@@ -1298,48 +6311,456 @@ impl SyncEngine {
                     let _ = progress_tx.blocking_send(event);
                 }
 
-                true
+                // Park after persisting progress, so a checkpoint sees the
+                // flushed offset on disk and in the saved state
+                if control.is_paused() {
+                    control.parked_copies.fetch_add(1, Ordering::SeqCst);
+                    while control.is_paused() && !control.is_cancelled() {
+                        std::thread::sleep(std::time::Duration::from_millis(50));
+                    }
+                    control.parked_copies.fetch_sub(1, Ordering::SeqCst);
+                }
+
+                !control.is_cancelled()
             }
         };
         
+        let active_copy = ActiveCopy::enter(control);
+        let low_priority = options.low_priority;
         let bytes_copied = tokio::task::spawn_blocking(move || {
-            // Choose atomic or regular copy based on whether we're resuming
-            let callback = make_progress_callback();
-            if use_atomic {
-                copy_file_atomic(
-                    &source_path_for_task,
-                    &actual_dest_for_task,
-                    &copy_options,
-                    callback,
-                )
-            } else {
-                // Resume mode - use regular copy with same progress callback
-                let resume_callback = make_progress_callback();
-                copy_file_with_progress(
-                    &source_path_for_task,
-                    &actual_dest_for_task,
-                    &copy_options,
-                    resume_callback,
-                )
-            }
+            // Held for the copy only: the pooled thread runs other work afterwards
+            let _io_priority = low_priority.then(LowIoPriority::enter);
+            copy_file_atomic(
+                &source_path_for_task,
+                &actual_dest_for_task,
+                &copy_options,
+                make_progress_callback(),
+            )
         })
-        .await
-        .map_err(|e| SyncError::Internal(e.to_string()))??;
+        .await;
+        drop(active_copy);
+        let bytes_copied = bytes_copied.map_err(|e| SyncError::Internal(e.to_string()))?;
+
+        // A skip aborts the copy like a cancel; report it as a skip instead.
+        // A skip that raced with the last block leaves a finished copy alone.
+        if control.take_skip(&source_path) && bytes_copied.is_err() {
+            let _ = emit_task.await;
+            let state_arc = state_manager.get_transfer(&transfer_id_string)?;
+            let mut state = state_arc.write();
+            state.skip_file_for_retry(&source_path);
+            state_manager.save_state(&state)?;
+            return Err(SyncError::SkippedByUser { path: source_path });
+        }
+        let bytes_copied = bytes_copied?;
 
         let _ = emit_task.await;
 
-        let state_arc = state_manager.get_transfer(&transfer_id_string)?;
-        {
-            let mut state = state_arc.write();
-            state.complete_file(&source_path);
-            state_manager.save_state(&state)?;
+        Self::record_finished_copy(
+            &transfer_id_string,
+            &source_path,
+            &actual_dest,
+            file,
+            options,
+            state_manager,
+            pre_copy_source_hash,
+        )?;
+        Self::remove_moved_source(
+            options,
+            source_path,
+            bytes_copied.saturating_sub(resume_offset),
+        )
+    }
+
+    /// For a file whose copy finished before the transfer was interrupted but
+    /// whose verification was cut off, carries on verifying the copy from its
+    /// checkpoint and moves it into place, returning where it now is. `None`
+    /// when there is no such copy; an error other than a cancel means the
+    /// file has to be copied again.
+    fn resume_verification(
+        transfer_id: &str,
+        source_path: &Path,
+        dest_path: &Path,
+        file: &FileInfo,
+        control: &TransferControl,
+        state_manager: &TransferStateManager,
+    ) -> Option<SyncResult<PathBuf>> {
+        let state_arc = state_manager.get_transfer(transfer_id).ok()?;
+        let (checkpoint, already_verified, written) = {
+            let state = state_arc.read();
+            let file_state = state.files.get(source_path)?;
+            let copy_finished = file_state.status != TransferStatus::Completed
+                && file.size > 0
+                && file_state.is_complete()
+                && file_state.total_bytes == file.size
+                && file_state.source_mtime == file.modified;
+            if !copy_finished {
+                return None;
+            }
+            let written = file_state
+                .written_to
+                .clone()
+                .unwrap_or_else(|| dest_path.to_path_buf());
+            (file_state.verify_checkpoint, file_state.verified, written)
+        };
+
+        let temp_path = get_temp_path(&written);
+        let staged = if temp_path.is_file() {
+            temp_path
+        } else {
+            written.clone()
+        };
+        if !staged.is_file() {
+            return None;
         }
+        let verified = if already_verified {
+            Ok(())
+        } else {
+            Self::verify_resumed_file(
+                source_path,
+                &staged,
+                checkpoint,
+                &state_arc,
+                state_manager,
+                control,
+            )
+        };
+        Some(verified.and_then(|_| {
+            if staged != written {
+                std::fs::rename(&staged, &written)?;
+                sync_parent_directory(&written)?;
+            }
+            Ok(written)
+        }))
+    }
+
+    /// Records the copy of `file` now in place at `dest` as done, marking it
+    /// verified and giving it its hash attribute and file flags as the
+    /// options ask.
+    fn record_finished_copy(
+        transfer_id: &str,
+        source_path: &Path,
+        dest: &Path,
+        file: &FileInfo,
+        options: &SyncOptions,
+        state_manager: &TransferStateManager,
+        source_hash: Option<u64>,
+    ) -> SyncResult<()> {
+        let state_arc = state_manager.get_transfer(transfer_id)?;
+        let mut state = state_arc.write();
+        if options.verify_integrity {
+            state.mark_verified(source_path);
+            if options.store_hash_xattr {
+                // The destination matched this hash when it was verified
+                let hash =
+                    source_hash.or_else(|| state_manager.hash_cache().hash_file(source_path).ok());
+                if let Some(hash) = hash {
+                    if let Err(e) = write_hash_xattr(dest, hash) {
+                        state.record_warning(Self::hash_xattr_warning(dest, &e));
+                    }
+                }
+            }
+        }
+        // Last, since an immutable file takes no more changes
+        if options.preserve_metadata {
+            Self::apply_flags(&mut state, dest, file.flags);
+        }
+        state.complete_file(source_path);
+        state_manager.save_state(&state)
+    }
 
+    /// Removes the source of a file a move has copied, returning `bytes`,
+    /// what was copied of it.
+    fn remove_moved_source(
+        options: &SyncOptions,
+        source_path: PathBuf,
+        bytes: u64,
+    ) -> SyncResult<u64> {
         if options.mode == SyncMode::Move {
-            std::fs::remove_file(&source_path)?;
+            if let Err(e) = std::fs::remove_file(&source_path) {
+                return Err(SyncError::SourceRemovalFailed {
+                    path: source_path,
+                    bytes_copied: bytes,
+                    reason: e.to_string(),
+                });
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Gives `dest` the source's file flags, with a warning for those that
+    /// couldn't be set, such as system flags without root.
+    fn apply_flags(state: &mut TransferState, dest: &Path, flags: u32) {
+        if let Err(e) = apply_file_flags(dest, flags) {
+            state.record_warning(format!(
+                "Could not set file flags on {}: {}",
+                dest.display(),
+                e
+            ));
+        }
+    }
+
+    /// Warning for a hash that couldn't be stored on `dest`. Filesystems
+    /// without xattr support get one warning for the whole transfer.
+    fn hash_xattr_warning(dest: &Path, error: &std::io::Error) -> String {
+        if is_xattr_unsupported(error) {
+            "Destination doesn't support extended attributes; file hashes were not stored"
+                .to_string()
+        } else {
+            format!("Could not store hash on {}: {}", dest.display(), error)
+        }
+    }
+
+    /// Creates the empty stand-in for `source` under `structure_only`, with the
+    /// source's mtime so the tree looks like a real mirror.
+    fn create_placeholder(source: &Path, dest: &Path) -> SyncResult<()> {
+        let dest = extended_length_path(dest);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        std::fs::File::create(&dest)?;
+        let metadata = std::fs::metadata(extended_length_path(source))?;
+        let _ = std::fs::set_permissions(&dest, metadata.permissions());
+        filetime::set_file_mtime(
+            &dest,
+            filetime::FileTime::from_last_modification_time(&metadata),
+        )?;
+        Ok(())
+    }
+
+    /// Fakes a file copy for `SyncOptions::simulate`: sleeps in proportion to
+    /// the file size and reports progress like a real copy, honouring
+    /// pause and cancel, without reading or writing any data.
+    #[cfg(debug_assertions)]
+    async fn simulate_file_copy(
+        transfer_id: &str,
+        source_path: &Path,
+        file: &FileInfo,
+        control: &Arc<TransferControl>,
+        state_manager: &Arc<TransferStateManager>,
+        app_handle: Option<&AppHandle>,
+    ) -> SyncResult<u64> {
+        let start_time = std::time::Instant::now();
+        let chunk_delay = std::time::Duration::from_secs_f64(
+            SIMULATED_CHUNK_SIZE as f64 / SIMULATED_BYTES_PER_SEC as f64,
+        );
+        let mut copied: u64 = 0;
+
+        loop {
+            if control.is_cancelled() {
+                return Err(SyncError::TransferCancelled(
+                    "Transfer cancelled by user".into(),
+                ));
+            }
+            control.wait_for_resume().await;
+
+            let step = SIMULATED_CHUNK_SIZE.min(file.size - copied);
+            tokio::time::sleep(chunk_delay.mul_f64(step as f64 / SIMULATED_CHUNK_SIZE as f64))
+                .await;
+            copied += step;
+
+            let state_arc = state_manager.get_transfer(transfer_id)?;
+            let event = {
+                let mut state = state_arc.write();
+                if !control.is_paused() {
+                    state.status = TransferStatus::Running;
+                }
+                state.current_file = Some(source_path.to_path_buf());
+                state.update_file_progress(source_path, copied, None);
+
+                let elapsed = start_time.elapsed().as_secs_f64();
+                let speed = if elapsed > 0.0 {
+                    copied as f64 / elapsed
+                } else {
+                    0.0
+                };
+                state.speed_bytes_per_sec = speed;
+
+                ProgressEvent {
+                    transfer_id: transfer_id.to_string(),
+                    current_file: file.path.display().to_string(),
+                    current_file_progress: if file.size > 0 {
+                        copied as f64 / file.size as f64
+                    } else {
+                        1.0
+                    },
+                    overall_progress: if state.total_bytes > 0 {
+                        state.bytes_transferred as f64 / state.total_bytes as f64
+                    } else {
+                        0.0
+                    },
+                    bytes_copied: state.bytes_transferred,
+                    bytes_total: state.total_bytes,
+                    files_completed: state.files_completed,
+                    files_total: state.total_files,
+                    speed_bytes_per_sec: speed,
+                    eta_seconds: if speed > 0.0 {
+                        Some((file.size - copied) as f64 / speed)
+                    } else {
+                        None
+                    },
+                }
+            };
+            if let Some(handle) = app_handle {
+                let _ = handle.emit("sync-progress", &event);
+            }
+
+            if copied >= file.size {
+                break;
+            }
+        }
+
+        let state_arc = state_manager.get_transfer(transfer_id)?;
+        let mut state = state_arc.write();
+        state.complete_file(source_path);
+        state_manager.save_state(&state)?;
+        Ok(file.size)
+    }
+
+    /// Number of files to copy at once. Parallel copies on one spinning disk
+    /// make the heads seek between files and end up slower than copying one
+    /// at a time, so that case runs sequentially unless `force_parallel` is set.
+    /// Returns the concurrency and, when it was lowered, the reason.
+    fn effective_concurrency(
+        options: &SyncOptions,
+        source: &Path,
+        dest: &Path,
+    ) -> (usize, Option<String>) {
+        if options.deterministic_order {
+            return (
+                1,
+                Some("deterministic_order copies one file at a time in path order".to_string()),
+            );
+        }
+
+        let requested = options.max_concurrent_files.clamp(1, 8);
+        if requested == 1 || options.force_parallel || !is_same_device(source, dest) {
+            return (requested, None);
+        }
+
+        let is_hdd = crate::file_ops::get_volume_info(source)
+            .map(|info| info.drive_type == "HDD")
+            .unwrap_or(false);
+        if !is_hdd {
+            return (requested, None);
+        }
+
+        let reason =
+            "Source and destination are on the same hard disk; copying one file at a time \
+                      avoids seek thrashing (set force_parallel to override)"
+                .to_string();
+        eprintln!("[Sync] {}", reason);
+        (1, Some(reason))
+    }
+
+    /// Shrinks `options.buffer_size`, then `concurrency`, until the copy buffers
+    /// of every parallel file fit in `max_memory_bytes`. A single file always
+    /// runs, with its buffer cut to what the budget allows but no lower than 64 KiB.
+    fn fit_memory_budget(
+        options: &mut SyncOptions,
+        concurrency: usize,
+        limited_reason: Option<String>,
+    ) -> (usize, Option<String>) {
+        let Some(budget) = options.max_memory_bytes else {
+            return (concurrency, limited_reason);
+        };
+        let requested_buffer = options.buffer_size.unwrap_or(COPY_BUFFER_SIZE);
+        // Read-ahead blocks plus the writer's buffer
+        let buffers_per_file = if options.pipelined_copy {
+            READ_AHEAD_BUFFERS as u64 + 1
+        } else {
+            COPY_BUFFERS_PER_FILE
+        };
+        let buffer_budget = |files: usize| (budget / (buffers_per_file * files as u64)) as usize;
+
+        let (files, buffer) = if buffer_budget(concurrency) >= requested_buffer {
+            (concurrency, requested_buffer)
+        } else if buffer_budget(concurrency) >= MIN_BUDGET_BUFFER_SIZE {
+            (concurrency, buffer_budget(concurrency))
+        } else {
+            let files = budget / (buffers_per_file * MIN_BUDGET_BUFFER_SIZE as u64);
+            let files = (files as usize).clamp(1, concurrency);
+            (
+                files,
+                buffer_budget(files).clamp(64 * 1024, MIN_BUDGET_BUFFER_SIZE),
+            )
+        };
+        // Page-align so reads stay block-sized
+        let buffer = (buffer / 4096 * 4096).max(4096);
+        options.buffer_size = Some(buffer);
+
+        eprintln!(
+            "[Sync] Memory budget of {} bytes: {} of {} files at a time, {} byte buffers (requested {})",
+            budget, files, concurrency, buffer, requested_buffer
+        );
+        if files < concurrency {
+            let reason = format!(
+                "max_memory_bytes of {} bytes allows {} files at a time with {} KiB buffers",
+                budget,
+                files,
+                buffer / 1024
+            );
+            return (files, Some(reason));
+        }
+        (files, limited_reason)
+    }
 
-        Ok(bytes_copied.saturating_sub(resume_offset))
+    /// FAT variant of the volume `dest_path` is on, if it is a FAT-family volume.
+    fn destination_fat_variant(dest_path: &Path) -> Option<FatVariant> {
+        get_volume_for_path(dest_path)?
+            .fs_type
+            .as_deref()
+            .and_then(FatVariant::from_fs_type)
+    }
+
+    /// With `check_drive_health`, checks the SMART health of the drive
+    /// `dest_root` is on and, if it reports problems, warns the UI with a
+    /// `drive-health-warning` event and returns a warning for the result.
+    fn drive_health_warning(
+        &self,
+        transfer_id: &str,
+        options: &SyncOptions,
+        dest_root: &Path,
+    ) -> Option<String> {
+        if !options.check_drive_health {
+            return None;
+        }
+        let health = check_drive_health(dest_root);
+        if !health.has_problems() {
+            return None;
+        }
+        let warning = format!(
+            "Destination drive {}: {}",
+            dest_root.display(),
+            health.message
+        );
+        eprintln!("[DriveHealth] {}", warning);
+        if let Some(handle) = self.app_handle.as_ref() {
+            let _ = handle.emit(
+                "drive-health-warning",
+                serde_json::json!({
+                    "transferId": transfer_id,
+                    "path": dest_root,
+                    "health": health,
+                }),
+            );
+        }
+        Some(warning)
+    }
+
+    /// Warns the UI about files the destination filesystem can't store.
+    fn emit_incompatible_files(&self, transfer_id: &str, files: &[IncompatibleFile]) {
+        if files.is_empty() {
+            return;
+        }
+        if let Some(handle) = self.app_handle.as_ref() {
+            let _ = handle.emit(
+                "incompatible-files",
+                serde_json::json!({
+                    "transferId": transfer_id,
+                    "files": files,
+                }),
+            );
+        }
     }
 
     fn emit_initial_progress(&self, transfer_id: &str, source_info: &DirectoryInfo) {
@@ -1361,3 +6782,270 @@ impl SyncEngine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn test_engine(root: &Path) -> SyncEngine {
+        let state_manager = TransferStateManager::in_dir(root.join("state")).unwrap();
+        SyncEngine::with_state_manager(None, state_manager)
+    }
+
+    fn test_options(source: &Path, dest: &Path, mode: &str) -> SyncOptions {
+        serde_json::from_value(serde_json::json!({
+            "source": source,
+            "destination": dest,
+            "mode": mode,
+            "conflict_resolution": "skip",
+            "verify_integrity": true,
+            "preserve_metadata": true,
+            "delete_orphans": false,
+            "buffer_size": null,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_move_survives_a_reconnect() {
+        let root = temp_root("reconnect-move");
+        let (source, dest) = (root.join("source"), root.join("dest"));
+        std::fs::create_dir_all(source.join("docs")).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(source.join("a.txt"), b"first").unwrap();
+        std::fs::write(source.join("docs/b.txt"), b"second").unwrap();
+
+        let engine = test_engine(&root);
+        let mut options = test_options(&source, &dest, "move");
+        options.reconnect_timeout_secs = Some(5);
+        let (transfer_id, control) = engine.begin_transfer(&source, &dest, &options).unwrap();
+        let slot = engine
+            .wait_for_transfer_slot(&transfer_id, 0, &control)
+            .await
+            .unwrap();
+        let disconnect = SyncError::DriveDisconnected {
+            path: dest.clone(),
+            device_name: None,
+        };
+
+        let result = engine
+            .wait_for_reconnect_and_resume(
+                &transfer_id,
+                disconnect,
+                5,
+                &options,
+                slot,
+                std::time::Instant::now(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.files_copied, 2);
+        assert_eq!(std::fs::read(dest.join("docs/b.txt")).unwrap(), b"second");
+        assert!(!source.join("a.txt").exists());
+        assert!(!source.join("docs/b.txt").exists());
+        let state = engine.get_transfer_state(&transfer_id).unwrap();
+        assert_eq!(state.status, TransferStatus::Completed);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_resume_carries_on_in_the_staged_file() {
+        let root = temp_root("staged-resume");
+        let (source, dest) = (root.join("source"), root.join("dest"));
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+        let contents: Vec<u8> = (0..3u32 << 20).map(|i| (i % 251) as u8).collect();
+        std::fs::write(source.join("big.bin"), &contents).unwrap();
+        let (dest_file, staged) = (dest.join("big.bin"), get_temp_path(&dest.join("big.bin")));
+        let written = 2usize << 20;
+        let engine = test_engine(&root);
+
+        // An intact staged copy with nothing at the destination yet resumes a
+        // block rewind before what was synced. A staged copy cut short of that,
+        // next to an older and shorter destination file, is copied again.
+        for (staged_len, old_dest, bytes_copied) in [
+            (written, None, 2 << 20),
+            (written / 2, Some(1 << 20), 3 << 20),
+        ] {
+            let _ = std::fs::remove_file(&dest_file);
+            if let Some(len) = old_dest {
+                std::fs::write(&dest_file, vec![0xEE; len]).unwrap();
+            }
+            std::fs::write(&staged, &contents[..staged_len]).unwrap();
+
+            let mut options = test_options(&source, &dest, "copy");
+            let (transfer_id, _) = engine.begin_transfer(&source, &dest, &options).unwrap();
+            {
+                let state_arc = engine.state_manager.get_transfer(&transfer_id).unwrap();
+                let mut state = state_arc.write();
+                let block_start = written - (64 << 10);
+                let mut file_state = FileTransferState::new(
+                    source.join("big.bin"),
+                    dest_file.clone(),
+                    contents.len() as u64,
+                    chrono::Utc::now(),
+                );
+                file_state.bytes_transferred = written as u64;
+                file_state.last_verified_offset = written as u64;
+                file_state.last_block_offset = block_start as u64;
+                file_state.last_block_hash = Some(crate::file_ops::compute_hash(
+                    &contents[block_start..written],
+                ));
+                state
+                    .files
+                    .insert(file_state.source_path.clone(), file_state);
+            }
+            options.resume_of = Some(transfer_id);
+
+            let result = engine
+                .run_sync(source.clone(), dest.clone(), options)
+                .await
+                .unwrap();
+            assert_eq!(result.bytes_copied, bytes_copied);
+            assert!(std::fs::read(&dest_file).unwrap() == contents);
+            assert!(!staged.exists());
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_transfer_refuses_rollback() {
+        let root = temp_root("streaming-rollback");
+        let (source, dest) = (root.join("source"), root.join("dest"));
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(source.join("a.txt"), b"streamed").unwrap();
+
+        let engine = test_engine(&root);
+        let mut options = test_options(&source, &dest, "copy");
+        options.streaming = true;
+        let (transfer_id, _) = engine.begin_transfer(&source, &dest, &options).unwrap();
+        options.resume_of = Some(transfer_id.clone());
+        engine
+            .run_sync(source.clone(), dest.clone(), options)
+            .await
+            .unwrap();
+
+        let rollback = engine.rollback_transfer(&transfer_id).await;
+        assert!(
+            matches!(rollback, Err(SyncError::RollbackUnavailable(_))),
+            "{:?}",
+            rollback.map(|_| ())
+        );
+        assert_eq!(std::fs::read(dest.join("a.txt")).unwrap(), b"streamed");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_write_limit_says_it_used_the_whole_source() {
+        let root = temp_root("streaming-limit");
+        let (source, dest) = (root.join("source"), root.join("dest"));
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(source.join("same.txt"), b"unchanged").unwrap();
+        std::fs::write(dest.join("same.txt"), b"unchanged").unwrap();
+        let mtime = filetime::FileTime::from_last_modification_time(
+            &std::fs::metadata(source.join("same.txt")).unwrap(),
+        );
+        filetime::set_file_mtime(dest.join("same.txt"), mtime).unwrap();
+        assert_eq!(counterpart_bytes(&source, &dest, false), 9);
+
+        let engine = test_engine(&root);
+        for streaming in [false, true] {
+            let mut options = test_options(&source, &dest, "copy");
+            options.streaming = streaming;
+            options.max_write_bytes = Some(0);
+            match engine
+                .sync_files(source.clone(), dest.clone(), options)
+                .await
+            {
+                Ok(_) => assert!(!streaming, "the unchanged file writes nothing"),
+                Err(
+                    e @ SyncError::WriteLimitExceeded {
+                        whole_source: true, ..
+                    },
+                ) => {
+                    assert!(streaming);
+                    assert!(e.to_string().contains("streaming sync"));
+                }
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_rerooted_resume_keeps_the_sync_options() {
+        let root = temp_root("reroot-options");
+        let (source, first, second) =
+            (root.join("source"), root.join("first"), root.join("second"));
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        std::fs::write(source.join("a.txt"), b"kept").unwrap();
+        std::fs::write(second.join("stale.txt"), b"orphan").unwrap();
+
+        let engine = test_engine(&root);
+        let mut options = test_options(&source, &first, "copy");
+        options.delete_orphans = true;
+        let (transfer_id, _) = engine.begin_transfer(&source, &first, &options).unwrap();
+        options.resume_of = Some(transfer_id.clone());
+        engine
+            .run_sync(source.clone(), first.clone(), options)
+            .await
+            .unwrap();
+        engine
+            .state_manager
+            .get_transfer(&transfer_id)
+            .unwrap()
+            .write()
+            .status = TransferStatus::Failed;
+
+        engine
+            .resume_interrupted_transfer_to(&transfer_id, second.clone())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(second.join("a.txt")).unwrap(), b"kept");
+        assert!(!second.join("stale.txt").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_plan_spanning_rejects_a_file_too_big_for_every_drive() {
+        let root = std::env::temp_dir().join(format!("spanning-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let file = FileInfo {
+            path: PathBuf::from("huge.bin"),
+            size: u64::MAX / 2,
+            modified: chrono::Utc::now(),
+            is_dir: false,
+            is_symlink: false,
+            file_type: SpecialFileType::None,
+            is_dataless: false,
+            flags: 0,
+        };
+
+        let roots = std::slice::from_ref(&root);
+        let result = SyncEngine::plan_spanning(&[&file], roots, chrono::Duration::zero());
+        match result {
+            Err(SyncError::SpanningCapacityExceeded {
+                path,
+                required_bytes,
+                ..
+            }) => {
+                assert_eq!(path, PathBuf::from("huge.bin"));
+                assert!(required_bytes > file.size);
+            }
+            other => panic!(
+                "expected SpanningCapacityExceeded, got {:?}",
+                other.map(|p| p.len())
+            ),
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,235 @@
+//! Named sync profiles.
+//!
+//! A profile is a saved source/destination pair plus the full `SyncOptions`
+//! used for it. Profiles are persisted as a single JSON file in the app data
+//! directory and can be exported and imported to share them across machines.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::errors::{SyncError, SyncResult};
+use crate::sync_engine::SyncOptions;
+
+/// File name of the profile store inside the app data directory.
+const PROFILES_FILENAME: &str = "profiles.json";
+
+/// Serializes read-modify-write cycles on the profile store.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A named, saved sync configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProfile {
+    pub name: String,
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub options: SyncOptions,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Returns the path to the profile store.
+fn get_profiles_path() -> SyncResult<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| SyncError::Internal("Could not determine app data directory".into()))?;
+    Ok(data_dir.join("rsync-app").join(PROFILES_FILENAME))
+}
+
+fn read_store() -> SyncResult<BTreeMap<String, SyncProfile>> {
+    let path = get_profiles_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let data = std::fs::read(&path)?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+fn write_store(profiles: &BTreeMap<String, SyncProfile>) -> SyncResult<()> {
+    let path = get_profiles_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write-then-rename so a crash never leaves a half-written store
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, serde_json::to_vec_pretty(profiles)?)?;
+    std::fs::rename(&temp_path, &path)?;
+    Ok(())
+}
+
+/// Rejects profile paths that point into protected system directories.
+/// Paths are checked lexically because an imported profile may reference
+/// drives that aren't mounted on this machine.
+fn validate_profile_path(path: &Path) -> SyncResult<()> {
+    if !path.is_absolute() {
+        return Err(SyncError::InvalidPath(format!(
+            "Profile paths must be absolute: {}",
+            path.display()
+        )));
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(SyncError::PermissionDenied(
+            "Path traversal sequences (..) are not allowed".to_string(),
+        ));
+    }
+    for blocked in crate::BLOCKED_PATHS {
+        if path.starts_with(blocked) {
+            return Err(SyncError::PermissionDenied(format!(
+                "Access to system directory '{}' is not allowed for security reasons",
+                blocked
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validates a profile's name and paths.
+fn validate_profile(profile: &SyncProfile) -> SyncResult<()> {
+    if profile.name.trim().is_empty() {
+        return Err(SyncError::Internal("Profile name cannot be empty".into()));
+    }
+    validate_profile_path(&profile.source)?;
+    validate_profile_path(&profile.destination)
+}
+
+/// Builds a profile, keeping the options' own source/destination in step
+/// with the profile paths.
+fn make_profile(
+    name: &str,
+    source: PathBuf,
+    destination: PathBuf,
+    mut options: SyncOptions,
+) -> SyncProfile {
+    options.source = source.clone();
+    options.destination = destination.clone();
+    SyncProfile {
+        name: name.trim().to_string(),
+        source,
+        destination,
+        options,
+        updated_at: Utc::now(),
+    }
+}
+
+/// Saves (or replaces) a named profile.
+pub fn save_profile(
+    name: &str,
+    source: PathBuf,
+    destination: PathBuf,
+    options: SyncOptions,
+) -> SyncResult<SyncProfile> {
+    let profile = make_profile(name, source, destination, options);
+    validate_profile(&profile)?;
+
+    let _guard = STORE_LOCK.lock();
+    let mut profiles = read_store()?;
+    profiles.insert(profile.name.clone(), profile.clone());
+    write_store(&profiles)?;
+    Ok(profile)
+}
+
+/// Loads a profile by name.
+pub fn load_profile(name: &str) -> SyncResult<SyncProfile> {
+    let _guard = STORE_LOCK.lock();
+    read_store()?
+        .remove(name.trim())
+        .ok_or_else(|| SyncError::ProfileNotFound(name.to_string()))
+}
+
+/// Lists all saved profiles, sorted by name.
+pub fn list_profiles() -> SyncResult<Vec<SyncProfile>> {
+    let _guard = STORE_LOCK.lock();
+    Ok(read_store()?.into_values().collect())
+}
+
+/// Deletes a profile by name.
+pub fn delete_profile(name: &str) -> SyncResult<()> {
+    let _guard = STORE_LOCK.lock();
+    let mut profiles = read_store()?;
+    if profiles.remove(name.trim()).is_none() {
+        return Err(SyncError::ProfileNotFound(name.to_string()));
+    }
+    write_store(&profiles)
+}
+
+/// Serializes all saved profiles to a JSON document for sharing.
+pub fn export_profiles() -> SyncResult<String> {
+    let profiles = list_profiles()?;
+    Ok(serde_json::to_string_pretty(&profiles)?)
+}
+
+/// Parses and validates an exported profile document.
+/// The whole import is rejected if any profile fails validation.
+fn parse_import(json: &str) -> SyncResult<Vec<SyncProfile>> {
+    let profiles: Vec<SyncProfile> = serde_json::from_str(json)?;
+    profiles
+        .into_iter()
+        .map(|p| {
+            let profile = make_profile(&p.name, p.source, p.destination, p.options);
+            validate_profile(&profile).map(|_| profile)
+        })
+        .collect()
+}
+
+/// Imports profiles from an exported JSON document, replacing any existing
+/// profiles with the same names. Returns the number of profiles imported.
+pub fn import_profiles(json: &str) -> SyncResult<usize> {
+    let imported = parse_import(json)?;
+
+    let _guard = STORE_LOCK.lock();
+    let mut profiles = read_store()?;
+    let count = imported.len();
+    for profile in imported {
+        profiles.insert(profile.name.clone(), profile);
+    }
+    write_store(&profiles)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export_json(source: &str, destination: &str) -> String {
+        serde_json::json!([{
+            "name": "photos",
+            "source": source,
+            "destination": destination,
+            "updatedAt": "2024-01-01T00:00:00Z",
+            "options": {
+                "source": "/ignored",
+                "destination": "/ignored",
+                "mode": "copy",
+                "conflict_resolution": "skip",
+                "verify_integrity": true,
+                "preserve_metadata": true,
+                "delete_orphans": false,
+                "buffer_size": null
+            }
+        }])
+        .to_string()
+    }
+
+    #[test]
+    fn test_import_syncs_option_paths() {
+        let profiles = parse_import(&export_json("/Users/me/Pictures", "/Volumes/Backup")).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(
+            profiles[0].options.source,
+            PathBuf::from("/Users/me/Pictures")
+        );
+        assert_eq!(
+            profiles[0].options.destination,
+            PathBuf::from("/Volumes/Backup")
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_blocked_paths() {
+        assert!(parse_import(&export_json("/System/Library", "/Volumes/Backup")).is_err());
+        assert!(parse_import(&export_json("/Users/me", "/usr/local")).is_err());
+        assert!(parse_import(&export_json("/Users/me/../../etc", "/Volumes/Backup")).is_err());
+        assert!(parse_import(&export_json("relative/path", "/Volumes/Backup")).is_err());
+    }
+}
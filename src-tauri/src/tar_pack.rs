@@ -0,0 +1,497 @@
+//! Batches of small files packed into a tar (ustar) stream and unpacked at
+//! the destination.
+//!
+//! Copying a tree of tiny files one by one is bound by per-file overhead: a
+//! task, a temp file, a state save and a progress event for every few
+//! kilobytes. A batch is packed on one thread and streamed through a pipe to
+//! the destination side, which writes the files out back to back, with the
+//! transfer state saved once per batch. The bytes unpacked for every file are
+//! hashed as they're written and must match the source bytes packed for it
+//! before the file is renamed into place.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use crate::errors::{SyncError, SyncResult};
+use crate::file_ops::{compute_hash, get_temp_path, sync_parent_directory, Throttle};
+
+/// Files below this size are packed rather than copied one at a time.
+pub const PACK_FILE_THRESHOLD: u64 = 64 * 1024;
+
+/// Most file bytes packed into one batch. Only the pipe's buffers and the
+/// file being packed are in memory at once, whatever the batch size.
+pub const PACK_BATCH_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Most files packed into one batch.
+pub const PACK_BATCH_FILES: usize = 2_000;
+
+/// Size of a tar header and of the blocks file data is padded to.
+const BLOCK_SIZE: usize = 512;
+
+/// Longest `name` field of a ustar header.
+const NAME_LEN: usize = 100;
+
+/// Longest `prefix` field of a ustar header.
+const PREFIX_LEN: usize = 155;
+
+/// A file as it went into a batch.
+#[derive(Debug, Clone)]
+pub struct PackedFile {
+    /// Relative to the source root, and so to the destination root
+    pub path: PathBuf,
+    pub size: u64,
+    /// Hash of the bytes packed, which the unpacked file has to match
+    pub hash: u64,
+    pub modified: SystemTime,
+}
+
+/// One file's outcome in `transfer_batch`.
+#[derive(Debug)]
+pub struct BatchEntry {
+    pub path: PathBuf,
+    /// The packed file, or why it couldn't be packed or unpacked
+    pub result: SyncResult<PackedFile>,
+}
+
+/// Whether a relative path can be stored in a ustar header: UTF-8, plain
+/// components only, and short enough for `name` alone or `prefix` and `name`.
+pub fn fits_header(path: &Path) -> bool {
+    tar_name(path).is_some_and(|name| split_name(&name).is_some())
+}
+
+fn tar_name(path: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Splits a name into the header's `prefix` and `name` fields.
+fn split_name(name: &str) -> Option<(&str, &str)> {
+    if name.len() <= NAME_LEN {
+        return Some(("", name));
+    }
+    name.match_indices('/')
+        .map(|(index, _)| (&name[..index], &name[index + 1..]))
+        .find(|(prefix, rest)| {
+            prefix.len() <= PREFIX_LEN && rest.len() <= NAME_LEN && !rest.is_empty()
+        })
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let text = format!("{:0width$o}", value, width = digits);
+    field[..digits].copy_from_slice(&text.as_bytes()[text.len() - digits..]);
+    field[digits] = 0;
+}
+
+fn read_octal(field: &[u8]) -> Option<u64> {
+    let text = std::str::from_utf8(field).ok()?;
+    let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+fn checksum(header: &[u8; BLOCK_SIZE]) -> u64 {
+    header
+        .iter()
+        .enumerate()
+        .map(|(index, &byte)| {
+            if (148..156).contains(&index) {
+                b' ' as u64
+            } else {
+                byte as u64
+            }
+        })
+        .sum()
+}
+
+fn header(name: &str, size: u64, mode: u32, mtime: u64) -> Option<[u8; BLOCK_SIZE]> {
+    let (prefix, name) = split_name(name)?;
+    let mut header = [0u8; BLOCK_SIZE];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], mode as u64);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size);
+    write_octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+    let sum = checksum(&header);
+    write_octal(&mut header[148..155], sum);
+    header[155] = b' ';
+    Some(header)
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
+/// Appends each file under `source_root` to `out` as a tar entry, followed
+/// by the end-of-archive marker. A file that can't be read is reported and
+/// left out of the stream.
+pub fn pack<W: Write>(
+    source_root: &Path,
+    files: &[PathBuf],
+    out: &mut W,
+) -> SyncResult<Vec<BatchEntry>> {
+    let mut entries = Vec::with_capacity(files.len());
+    for relative in files {
+        let path = source_root.join(relative);
+        let packed = fs::metadata(&path)
+            .and_then(|metadata| Ok((fs::read(&path)?, metadata)))
+            .map_err(SyncError::from)
+            .and_then(|(data, metadata)| {
+                let modified = metadata.modified()?;
+                let mtime = modified
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map_or(0, |since| since.as_secs());
+                let header = tar_name(relative)
+                    .and_then(|name| header(&name, data.len() as u64, file_mode(&metadata), mtime))
+                    .ok_or_else(|| {
+                        SyncError::InvalidPath(format!(
+                            "{} doesn't fit a tar header",
+                            relative.display()
+                        ))
+                    })?;
+                out.write_all(&header)?;
+                out.write_all(&data)?;
+                let padding = (BLOCK_SIZE - data.len() % BLOCK_SIZE) % BLOCK_SIZE;
+                out.write_all(&[0u8; BLOCK_SIZE][..padding])?;
+                Ok(PackedFile {
+                    path: relative.clone(),
+                    size: data.len() as u64,
+                    hash: compute_hash(&data),
+                    modified,
+                })
+            });
+        entries.push(BatchEntry {
+            path: relative.clone(),
+            result: packed,
+        });
+    }
+    out.write_all(&[0u8; BLOCK_SIZE * 2])?;
+    Ok(entries)
+}
+
+/// Passes writes through to `inner`, held to a transfer's live bandwidth limit.
+struct Paced<'a, W> {
+    inner: W,
+    bandwidth_limit: &'a AtomicU64,
+    throttle: Throttle,
+}
+
+impl<W: Write> Write for Paced<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.throttle
+            .pace(written as u64, self.bandwidth_limit.load(Ordering::Relaxed));
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A file unpacked by `unpack`.
+#[derive(Debug)]
+pub struct UnpackedFile {
+    pub path: PathBuf,
+    /// Written next to its destination, not yet renamed into place
+    pub staged: PathBuf,
+    pub mode: u32,
+    /// Hash of the bytes written to `staged`
+    pub hash: u64,
+}
+
+/// Copies the next `len` bytes of `input` to `out`, returning their hash.
+fn copy_exact<R: Read, W: Write>(input: &mut R, out: &mut W, len: u64) -> SyncResult<u64> {
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    let mut buf = [0u8; BLOCK_SIZE * 16];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = &mut buf[..remaining.min((BLOCK_SIZE * 16) as u64) as usize];
+        input.read_exact(chunk)?;
+        hasher.update(chunk);
+        out.write_all(chunk)?;
+        remaining -= chunk.len() as u64;
+    }
+    Ok(hasher.digest())
+}
+
+/// Reads tar entries from `input` and writes each regular file's data next to
+/// its path under `dest_root`, leaving the rename to the caller. Entries with
+/// absolute paths or `..` components are rejected rather than written
+/// outside `dest_root`. On error every file staged so far is removed.
+pub fn unpack<R: Read>(input: &mut R, dest_root: &Path) -> SyncResult<Vec<UnpackedFile>> {
+    let mut unpacked = Vec::new();
+    match unpack_into(input, dest_root, &mut unpacked) {
+        Ok(()) => Ok(unpacked),
+        Err(e) => {
+            remove_staged(unpacked);
+            Err(e)
+        }
+    }
+}
+
+fn remove_staged(files: impl IntoIterator<Item = UnpackedFile>) {
+    for file in files {
+        let _ = fs::remove_file(&file.staged);
+    }
+}
+
+fn unpack_into<R: Read>(
+    input: &mut R,
+    dest_root: &Path,
+    unpacked: &mut Vec<UnpackedFile>,
+) -> SyncResult<()> {
+    let mut header = [0u8; BLOCK_SIZE];
+    loop {
+        input.read_exact(&mut header)?;
+        if header.iter().all(|&byte| byte == 0) {
+            return Ok(());
+        }
+        let stored = read_octal(&header[148..156]);
+        if stored != Some(checksum(&header)) {
+            return Err(SyncError::Internal("tar header checksum mismatch".into()));
+        }
+        let field = |range: std::ops::Range<usize>| {
+            let bytes = &header[range];
+            let end = bytes
+                .iter()
+                .position(|&byte| byte == 0)
+                .unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).into_owned()
+        };
+        let (prefix, name) = (field(345..500), field(0..100));
+        let name = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let size = read_octal(&header[124..136])
+            .ok_or_else(|| SyncError::Internal(format!("bad tar size for {}", name)))?;
+        let mode = read_octal(&header[100..108]).unwrap_or(0o644) as u32;
+
+        let relative = PathBuf::from(&name);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(SyncError::InvalidPath(format!(
+                "tar entry outside the destination: {}",
+                name
+            )));
+        }
+        let padding = size.div_ceil(BLOCK_SIZE as u64) * BLOCK_SIZE as u64 - size;
+        // Only regular files are ever packed
+        if !matches!(header[156], b'0' | 0) {
+            copy_exact(input, &mut std::io::sink(), size + padding)?;
+            continue;
+        }
+
+        let dest = dest_root.join(&relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let staged = get_temp_path(&dest);
+        let file = fs::File::create(&staged)?;
+        // Listed before its data is written, so a failed write is cleaned up too
+        unpacked.push(UnpackedFile {
+            path: relative,
+            staged,
+            mode,
+            hash: 0,
+        });
+        let mut out = BufWriter::new(file);
+        let hash = copy_exact(input, &mut out, size)?;
+        out.flush()?;
+        // On disk before the rename puts it in place as a verified copy
+        out.get_ref().sync_all()?;
+        if let Some(last) = unpacked.last_mut() {
+            last.hash = hash;
+        }
+        copy_exact(input, &mut std::io::sink(), padding)?;
+    }
+}
+
+/// Packs `files` from `source_root`, streams the tar through a pipe into
+/// `dest_root` and checks every unpacked file against the hash of the bytes
+/// packed for it. Only files that match are renamed into place; the rest are
+/// reported so they can be copied on their own, and nothing staged is left
+/// behind. The stream is held to `bandwidth_limit`, read as it goes. With
+/// `preserve_mtime` each file gets its source's modification time at full
+/// precision, not the whole seconds the tar header holds.
+pub fn transfer_batch(
+    source_root: &Path,
+    dest_root: &Path,
+    files: &[PathBuf],
+    preserve_mtime: bool,
+    bandwidth_limit: &AtomicU64,
+) -> SyncResult<Vec<BatchEntry>> {
+    let (reader, writer) = std::io::pipe()?;
+    let (packed, unpacked) = std::thread::scope(|scope| {
+        let packer = scope.spawn(move || {
+            let paced = Paced {
+                inner: writer,
+                bandwidth_limit,
+                throttle: Throttle::default(),
+            };
+            let mut out = BufWriter::new(paced);
+            let entries = pack(source_root, files, &mut out)?;
+            out.flush()?;
+            Ok(entries)
+        });
+        // The reader is dropped once this returns, so a packer still writing
+        // after a failed unpack stops instead of blocking on a full pipe
+        let unpacked = unpack(&mut BufReader::new(reader), dest_root);
+        let packed = packer
+            .join()
+            .unwrap_or_else(|_| Err(SyncError::Internal("tar packing thread panicked".into())));
+        (packed, unpacked)
+    });
+    let (mut entries, unpacked) = match (packed, unpacked) {
+        (Ok(entries), Ok(unpacked)) => (entries, unpacked),
+        (Err(e), unpacked) => {
+            remove_staged(unpacked.unwrap_or_default());
+            return Err(e);
+        }
+        (Ok(_), Err(e)) => return Err(e),
+    };
+    let mut staged: HashMap<PathBuf, UnpackedFile> = unpacked
+        .into_iter()
+        .map(|file| (file.path.clone(), file))
+        .collect();
+
+    for entry in &mut entries {
+        let Ok(packed) = &entry.result else {
+            continue;
+        };
+        let Some(file) = staged.remove(&entry.path) else {
+            entry.result = Err(SyncError::Internal(
+                "missing from the unpacked stream".into(),
+            ));
+            continue;
+        };
+        let dest = dest_root.join(&entry.path);
+        let placed = (|| {
+            if file.hash != packed.hash {
+                return Err(SyncError::HashMismatch(entry.path.display().to_string()));
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&file.staged, fs::Permissions::from_mode(file.mode))?;
+            }
+            if preserve_mtime {
+                filetime::set_file_mtime(
+                    &file.staged,
+                    filetime::FileTime::from_system_time(packed.modified),
+                )?;
+            }
+            fs::rename(&file.staged, &dest)?;
+            Ok(())
+        })();
+        if let Err(e) = placed {
+            let _ = fs::remove_file(&file.staged);
+            entry.result = Err(e);
+        }
+    }
+    // Unpacked files no packed entry accounts for
+    remove_staged(staged.into_values());
+    // Renames are made durable once per directory rather than once per file
+    let mut synced = std::collections::HashSet::new();
+    for entry in entries.iter().filter(|entry| entry.result.is_ok()) {
+        let dest = dest_root.join(&entry.path);
+        if synced.insert(dest.parent().map(Path::to_path_buf)) {
+            let _ = sync_parent_directory(&dest);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_names_are_split_into_prefix_and_name() {
+        let deep = format!("{}/{}", "d".repeat(120), "f".repeat(90));
+        assert!(fits_header(Path::new(&deep)));
+        assert!(!fits_header(Path::new(&"f".repeat(101))));
+        assert!(!fits_header(Path::new("../escape.txt")));
+    }
+
+    #[test]
+    fn batch_round_trips_through_the_stream() {
+        let root = std::env::temp_dir().join(format!("tar-pack-{}", uuid::Uuid::new_v4()));
+        let (source, dest) = (root.join("src"), root.join("dst"));
+        fs::create_dir_all(source.join("a/b")).unwrap();
+        fs::write(source.join("one.txt"), b"hello").unwrap();
+        fs::write(source.join("a/b/two.bin"), vec![7u8; 1500]).unwrap();
+        fs::write(source.join("empty"), b"").unwrap();
+
+        let files: Vec<PathBuf> = ["one.txt", "a/b/two.bin", "empty", "missing"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let entries = transfer_batch(&source, &dest, &files, true, &AtomicU64::new(0)).unwrap();
+
+        assert!(entries[..3].iter().all(|entry| entry.result.is_ok()));
+        assert!(entries[3].result.is_err());
+        assert_eq!(fs::read(dest.join("one.txt")).unwrap(), b"hello");
+        assert_eq!(fs::read(dest.join("a/b/two.bin")).unwrap(), vec![7u8; 1500]);
+        assert_eq!(fs::read(dest.join("empty")).unwrap(), b"");
+        assert_eq!(
+            fs::metadata(dest.join("one.txt"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            fs::metadata(source.join("one.txt"))
+                .unwrap()
+                .modified()
+                .unwrap()
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn truncated_stream_leaves_nothing_staged() {
+        let root = std::env::temp_dir().join(format!("tar-pack-{}", uuid::Uuid::new_v4()));
+        let (source, dest) = (root.join("src"), root.join("dst"));
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("one.txt"), vec![1u8; 2000]).unwrap();
+
+        let mut stream = Vec::new();
+        pack(&source, &[PathBuf::from("one.txt")], &mut stream).unwrap();
+        stream.truncate(BLOCK_SIZE * 2);
+
+        assert!(unpack(&mut stream.as_slice(), &dest).is_err());
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
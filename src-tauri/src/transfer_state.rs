@@ -3,13 +3,16 @@
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::errors::{SyncError, SyncResult};
-use crate::file_ops::sync_parent_directory;
+use crate::file_ops::{compute_hash, sync_parent_directory, HashCheckpoint};
+use crate::hash_cache::HashCache;
+use crate::sync_engine::SyncOptions;
 
 /// Size of each block for partial file verification: 256 KiB.
 /// Used when resuming interrupted transfers to verify file integrity
@@ -27,6 +30,11 @@ pub enum TransferStatus {
     Pending,
     Running,
     Paused,
+    /// Source or destination drive disconnected; waiting for it to be reconnected
+    WaitingForDevice,
+    /// Every file is copied and the orphans in `TransferState::orphan_deletion`
+    /// are being deleted; a resume goes straight to finishing that
+    DeletingOrphans,
     Completed,
     Failed,
     Cancelled,
@@ -39,10 +47,33 @@ pub struct FileTransferState {
     pub total_bytes: u64,
     pub bytes_transferred: u64,
     pub last_block_hash: Option<u64>,
+    /// Where the block `last_block_hash` was taken over starts; it ends at
+    /// `bytes_transferred`
+    #[serde(default)]
+    pub last_block_offset: u64,
     pub last_verified_offset: u64,
     pub source_mtime: DateTime<Utc>,
     pub status: TransferStatus,
     pub error: Option<String>,
+    /// The destination was hashed and found to match the source
+    #[serde(default)]
+    pub verified: bool,
+    /// How far an interrupted verification got, so a resume carries on from there
+    #[serde(default)]
+    pub verify_checkpoint: Option<HashCheckpoint>,
+    /// Completed without being copied, because the destination was up to date
+    /// or a conflict resolution kept it
+    #[serde(default)]
+    pub skipped: bool,
+    /// The copy overwrote a destination file that was there before the transfer
+    #[serde(default)]
+    pub replaced: bool,
+    /// Copy of the overwritten destination file, kept for `rollback_transfer`
+    #[serde(default)]
+    pub rollback_backup: Option<PathBuf>,
+    /// Where the copy was written when not `dest_path`, like a conflict's renamed copy
+    #[serde(default)]
+    pub written_to: Option<PathBuf>,
 }
 
 impl FileTransferState {
@@ -53,10 +84,17 @@ impl FileTransferState {
             total_bytes,
             bytes_transferred: 0,
             last_block_hash: None,
+            last_block_offset: 0,
             last_verified_offset: 0,
             source_mtime: mtime,
             status: TransferStatus::Pending,
             error: None,
+            verified: false,
+            verify_checkpoint: None,
+            skipped: false,
+            replaced: false,
+            rollback_backup: None,
+            written_to: None,
         }
     }
 
@@ -71,6 +109,86 @@ impl FileTransferState {
             0
         }
     }
+
+    /// Whether the file an interrupted copy was writing, `staged`, still holds
+    /// what was written to it: everything synced to disk, and the last block
+    /// with the hash recorded for it. A last block beyond what was synced may
+    /// not have reached the disk and is left to the resume rewind. A missing
+    /// or shorter file, or a read error, counts as a mismatch.
+    pub fn last_block_intact(&self, staged: &Path) -> bool {
+        let end = self.bytes_transferred;
+        let len = end.saturating_sub(self.last_block_offset);
+        let read_back = || -> std::io::Result<bool> {
+            let mut file = std::fs::File::open(staged)?;
+            let file_len = file.metadata()?.len();
+            if file_len < self.last_verified_offset {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            let Some(expected) = self.last_block_hash.filter(|_| len > 0 && file_len >= end) else {
+                return Ok(true);
+            };
+            file.seek(SeekFrom::Start(self.last_block_offset))?;
+            let mut block = vec![0u8; len as usize];
+            file.read_exact(&mut block)?;
+            Ok(compute_hash(&block) == expected)
+        };
+        read_back().unwrap_or(false)
+    }
+}
+
+/// File format of `TransferStateManager::export_report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// One file's outcome in a transfer report.
+#[derive(Debug, Clone, Serialize)]
+struct ReportRow {
+    /// Relative to the transfer's source
+    path: PathBuf,
+    size: u64,
+    status: &'static str,
+    error: Option<String>,
+    verified: bool,
+    /// xxh3 of the source as 16 hex digits, for verified files whose hash is still cached
+    hash: Option<String>,
+}
+
+impl ReportRow {
+    fn status_name(file: &FileTransferState) -> &'static str {
+        match file.status {
+            TransferStatus::Completed if file.skipped => "skipped",
+            TransferStatus::Completed => "completed",
+            TransferStatus::Failed => "failed",
+            TransferStatus::Cancelled => "cancelled",
+            _ => "pending",
+        }
+    }
+
+    fn csv_line(&self) -> String {
+        let fields = [
+            self.path.to_string_lossy().into_owned(),
+            self.size.to_string(),
+            self.status.to_string(),
+            self.error.clone().unwrap_or_default(),
+            self.verified.to_string(),
+            self.hash.clone().unwrap_or_default(),
+        ];
+        let escaped: Vec<String> = fields
+            .iter()
+            .map(|field| {
+                if field.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.clone()
+                }
+            })
+            .collect();
+        escaped.join(",") + "\n"
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +213,63 @@ pub struct TransferState {
     pub current_file: Option<PathBuf>,
     pub speed_bytes_per_sec: f64,
     pub error: Option<String>,
+    /// Copies files one at a time in path order; kept so a resume does the same
+    #[serde(default)]
+    pub deterministic_order: bool,
+    /// Hashes every copied file; kept so a resume verifies what it copies too
+    #[serde(default)]
+    pub verify_integrity: bool,
+    /// Further destination roots in spanning mode. Each file's `dest_path`
+    /// records which of the roots (including `dest_path`) it was placed on
+    #[serde(default)]
+    pub spanning_destinations: Vec<PathBuf>,
+    /// Source subdirectories the sync was restricted to; kept so a resume
+    /// doesn't pick up the rest of the source
+    #[serde(default)]
+    pub subdirs: Option<Vec<PathBuf>>,
+    /// `conflict_name_template` of the sync, for copies kept when a conflict
+    /// raised during it is resolved by keeping both
+    #[serde(default)]
+    pub conflict_name_template: Option<String>,
+    /// `keep_rollback_backups` of the sync, so a resume keeps backing up
+    #[serde(default)]
+    pub keep_rollback_backups: bool,
+    /// `mtime_tolerance_seconds` of the sync, widened by any clock skew found
+    #[serde(default)]
+    pub mtime_tolerance_seconds: Option<u64>,
+    /// `exclude_patterns` and `global_exclude_patterns` of the sync, so a
+    /// resume doesn't copy what the sync left out
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    #[serde(default)]
+    pub global_exclude_patterns: Vec<String>,
+    /// Everything the sync was started with, so a resume runs it the same
+    /// way. `None` in states saved before the options were kept
+    #[serde(default)]
+    pub options: Option<SyncOptions>,
+    /// Orphans found after the copy phase that are still to be deleted, one
+    /// entry per destination root; empty once deletion has finished
+    #[serde(default)]
+    pub orphan_deletion: Vec<OrphanDeletion>,
+    /// Problems that didn't fail any file, each recorded once
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// The latest `MAX_TRANSFER_EVENTS` events, oldest first. Kept in memory
+    /// only, so a transfer loaded from disk starts with none
+    #[serde(skip)]
+    events: VecDeque<TransferEvent>,
+    #[serde(skip)]
+    last_event_seq: u64,
+}
+
+/// Destination entries under one root that have no source counterpart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanDeletion {
+    pub dest_root: PathBuf,
+    /// Relative to `dest_root`, each directory after its contents
+    pub orphans: Vec<PathBuf>,
+    /// How many of `orphans` have been dealt with
+    pub next: usize,
 }
 
 impl TransferState {
@@ -120,6 +295,20 @@ impl TransferState {
             current_file: None,
             speed_bytes_per_sec: 0.0,
             error: None,
+            deterministic_order: false,
+            verify_integrity: false,
+            spanning_destinations: Vec::new(),
+            subdirs: None,
+            conflict_name_template: None,
+            keep_rollback_backups: false,
+            mtime_tolerance_seconds: None,
+            exclude_patterns: Vec::new(),
+            global_exclude_patterns: Vec::new(),
+            options: None,
+            orphan_deletion: Vec::new(),
+            warnings: Vec::new(),
+            events: VecDeque::new(),
+            last_event_seq: 0,
         }
     }
 
@@ -129,17 +318,73 @@ impl TransferState {
         self.files.insert(file_state.source_path.clone(), file_state);
     }
 
-    pub fn update_file_progress(&mut self, source_path: &Path, bytes_transferred: u64, last_block_hash: Option<u64>) {
+    /// `last_block` is the offset and hash of the block that ends at `bytes_transferred`.
+    pub fn update_file_progress(
+        &mut self,
+        source_path: &Path,
+        bytes_transferred: u64,
+        last_block: Option<(u64, u64)>,
+    ) {
         if let Some(file_state) = self.files.get_mut(source_path) {
             let delta = bytes_transferred.saturating_sub(file_state.bytes_transferred);
             file_state.bytes_transferred = bytes_transferred;
-            file_state.last_verified_offset = bytes_transferred;
-            file_state.last_block_hash = last_block_hash;
+            // Written bytes may still be in a buffer; only `record_durable_offset`
+            // moves the resume point forward
+            file_state.last_verified_offset =
+                file_state.last_verified_offset.min(bytes_transferred);
+            file_state.last_block_offset = last_block.map_or(0, |(offset, _)| offset);
+            file_state.last_block_hash = last_block.map(|(_, hash)| hash);
+            // New bytes on disk invalidate any earlier verification
+            file_state.verified = false;
+            file_state.verify_checkpoint = None;
             self.bytes_transferred += delta;
             self.updated_at = Utc::now();
         }
     }
 
+    /// Advances the resume point of a file to an offset its copy has flushed
+    /// and synced to disk.
+    pub fn record_durable_offset(&mut self, source_path: &Path, offset: u64) {
+        if let Some(file_state) = self.files.get_mut(source_path) {
+            file_state.last_verified_offset = offset.min(file_state.bytes_transferred);
+        }
+    }
+
+    pub fn record_warning(&mut self, warning: String) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    pub fn record_event(
+        &mut self,
+        kind: TransferEventKind,
+        path: Option<&Path>,
+        message: Option<String>,
+    ) {
+        if self.events.len() == MAX_TRANSFER_EVENTS {
+            self.events.pop_front();
+        }
+        self.last_event_seq += 1;
+        self.events.push_back(TransferEvent {
+            seq: self.last_event_seq,
+            timestamp: Utc::now(),
+            kind,
+            path: path.map(Path::to_path_buf),
+            message,
+        });
+    }
+
+    /// Events recorded after the one numbered `since`, or all that are kept.
+    pub fn events_since(&self, since: Option<u64>) -> Vec<TransferEvent> {
+        let since = since.unwrap_or(0);
+        self.events
+            .iter()
+            .filter(|event| event.seq > since)
+            .cloned()
+            .collect()
+    }
+
     pub fn complete_file(&mut self, source_path: &Path) {
         if let Some(file_state) = self.files.get_mut(source_path) {
             let remaining = file_state.total_bytes.saturating_sub(file_state.bytes_transferred);
@@ -148,26 +393,196 @@ impl TransferState {
             file_state.status = TransferStatus::Completed;
             self.files_completed += 1;
             self.updated_at = Utc::now();
+            self.record_event(TransferEventKind::FileCompleted, Some(source_path), None);
+        }
+    }
+
+    /// Updates a file whose source changed after the scan, keeping the
+    /// transfer totals in line with what will actually be copied. Progress
+    /// already recorded for it is dropped, since it was for the old contents.
+    pub fn rescan_file(&mut self, source_path: &Path, total_bytes: u64, mtime: DateTime<Utc>) {
+        if let Some(file_state) = self.files.get_mut(source_path) {
+            self.total_bytes =
+                self.total_bytes.saturating_sub(file_state.total_bytes) + total_bytes;
+            self.bytes_transferred = self
+                .bytes_transferred
+                .saturating_sub(file_state.bytes_transferred);
+            file_state.total_bytes = total_bytes;
+            file_state.source_mtime = mtime;
+            file_state.bytes_transferred = 0;
+            file_state.last_block_hash = None;
+            file_state.last_block_offset = 0;
+            file_state.last_verified_offset = 0;
+            file_state.verified = false;
+            file_state.verify_checkpoint = None;
+            self.updated_at = Utc::now();
+        }
+    }
+
+    /// Records how far a resumable verification of the file has got.
+    pub fn checkpoint_verification(&mut self, source_path: &Path, checkpoint: HashCheckpoint) {
+        if let Some(file_state) = self.files.get_mut(source_path) {
+            file_state.verify_checkpoint = Some(checkpoint);
+            self.updated_at = Utc::now();
+        }
+    }
+
+    /// Marks the file's destination as verified against its source.
+    pub fn mark_verified(&mut self, source_path: &Path) {
+        if let Some(file_state) = self.files.get_mut(source_path) {
+            file_state.verified = true;
+            file_state.verify_checkpoint = None;
+            self.updated_at = Utc::now();
         }
     }
 
     pub fn fail_file(&mut self, source_path: &Path, error: String) {
         if let Some(file_state) = self.files.get_mut(source_path) {
             file_state.status = TransferStatus::Failed;
-            file_state.error = Some(error);
+            file_state.error = Some(error.clone());
             self.files_failed += 1;
             self.updated_at = Utc::now();
+            self.record_event(
+                TransferEventKind::FileFailed,
+                Some(source_path),
+                Some(error),
+            );
         }
     }
 
     pub fn skip_file(&mut self, source_path: &Path) {
         if let Some(file_state) = self.files.get_mut(source_path) {
             file_state.status = TransferStatus::Completed;
+            file_state.skipped = true;
             self.files_skipped += 1;
             self.updated_at = Utc::now();
         }
     }
 
+    /// Sets failed files back to pending, for a resume that tries them again.
+    pub fn retry_failed_files(&mut self) {
+        for file_state in self.files.values_mut() {
+            if file_state.status == TransferStatus::Failed {
+                file_state.status = TransferStatus::Pending;
+                file_state.error = None;
+            }
+        }
+        self.files_failed = 0;
+    }
+
+    /// Records a file the user skipped mid-copy. Unlike `skip_file` it is not
+    /// marked completed, so resuming the transfer copies it again.
+    pub fn skip_file_for_retry(&mut self, source_path: &Path) {
+        if let Some(file_state) = self.files.get_mut(source_path) {
+            file_state.status = TransferStatus::Cancelled;
+            file_state.error = Some("Skipped by user".to_string());
+            self.files_skipped += 1;
+            self.updated_at = Utc::now();
+            self.record_event(TransferEventKind::FileSkipped, Some(source_path), None);
+        }
+    }
+
+    /// Records that the file's copy overwrites the destination file already
+    /// there, and where a backup of it was kept. Only the first call counts,
+    /// so a resumed copy isn't taken for the original.
+    pub fn record_replaced(&mut self, source_path: &Path, backup: Option<PathBuf>) {
+        if let Some(file_state) = self.files.get_mut(source_path) {
+            if !file_state.replaced {
+                file_state.replaced = true;
+                file_state.rollback_backup = backup;
+                self.updated_at = Utc::now();
+            }
+        }
+    }
+
+    /// Records that the file's copy goes to `written_to` instead of its
+    /// destination, which is left as it was.
+    pub fn record_written_to(&mut self, source_path: &Path, written_to: PathBuf) {
+        if let Some(file_state) = self.files.get_mut(source_path) {
+            file_state.written_to = Some(written_to);
+            self.updated_at = Utc::now();
+        }
+    }
+
+    /// Resets a file `rollback_transfer` undid, so resuming the transfer
+    /// copies it again.
+    pub fn roll_back_file(&mut self, source_path: &Path) {
+        if let Some(file_state) = self.files.get_mut(source_path) {
+            match file_state.status {
+                TransferStatus::Completed => {
+                    self.files_completed = self.files_completed.saturating_sub(1)
+                }
+                TransferStatus::Failed => self.files_failed = self.files_failed.saturating_sub(1),
+                TransferStatus::Cancelled => {
+                    self.files_skipped = self.files_skipped.saturating_sub(1)
+                }
+                _ => {}
+            }
+            self.bytes_transferred = self
+                .bytes_transferred
+                .saturating_sub(file_state.bytes_transferred);
+            file_state.bytes_transferred = 0;
+            file_state.last_block_hash = None;
+            file_state.last_block_offset = 0;
+            file_state.last_verified_offset = 0;
+            file_state.status = TransferStatus::Pending;
+            file_state.error = None;
+            file_state.verified = false;
+            file_state.verify_checkpoint = None;
+            file_state.replaced = false;
+            file_state.rollback_backup = None;
+            file_state.written_to = None;
+            self.updated_at = Utc::now();
+        }
+    }
+
+    /// Moves the transfer onto a new destination root.
+    /// Each file's destination is re-rooted under `new_dest` and all progress is
+    /// reset, because nothing has been written to the new destination yet.
+    /// A spanned transfer is collapsed onto the single new root.
+    pub fn reroot_destination(&mut self, new_dest: PathBuf) {
+        for file_state in self.files.values_mut() {
+            let relative = file_state
+                .dest_path
+                .strip_prefix(&self.dest_path)
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|_| {
+                    file_state
+                        .source_path
+                        .strip_prefix(&self.source_path)
+                        .map(|p| p.to_path_buf())
+                        .unwrap_or_default()
+                });
+            file_state.dest_path = new_dest.join(relative);
+            file_state.bytes_transferred = 0;
+            file_state.last_block_hash = None;
+            file_state.last_block_offset = 0;
+            file_state.last_verified_offset = 0;
+            file_state.status = TransferStatus::Pending;
+            file_state.error = None;
+            file_state.verified = false;
+            file_state.verify_checkpoint = None;
+            file_state.replaced = false;
+            file_state.rollback_backup = None;
+            file_state.written_to = None;
+        }
+
+        if let Some(options) = self.options.as_mut() {
+            options.destination = new_dest.clone();
+            options.spanning_destinations.clear();
+        }
+        self.dest_path = new_dest;
+        self.spanning_destinations.clear();
+        self.orphan_deletion.clear();
+        self.bytes_transferred = 0;
+        self.files_completed = 0;
+        self.files_failed = 0;
+        self.files_skipped = 0;
+        self.current_file = None;
+        self.error = None;
+        self.updated_at = Utc::now();
+    }
+
     pub fn is_finished(&self) -> bool {
         matches!(
             self.status,
@@ -181,20 +596,95 @@ impl TransferState {
         }
         (self.bytes_transferred as f64 / self.total_bytes as f64) * 100.0
     }
+
+    /// Work left for a resume, from the recorded file states alone
+    pub fn resume_estimate(&self) -> ResumeEstimate {
+        let remaining = self
+            .files
+            .values()
+            .filter(|file_state| file_state.status != TransferStatus::Completed);
+        let (files_remaining, bytes_remaining) =
+            remaining.fold((0, 0), |(files, bytes), file_state| {
+                (
+                    files + 1,
+                    bytes
+                        + file_state
+                            .total_bytes
+                            .saturating_sub(file_state.bytes_transferred),
+                )
+            });
+        ResumeEstimate {
+            transfer_id: self.id.clone(),
+            files_remaining,
+            bytes_remaining,
+            total_files: self.files.len(),
+            total_bytes: self
+                .files
+                .values()
+                .map(|file_state| file_state.total_bytes)
+                .sum(),
+        }
+    }
+}
+
+/// Events kept per transfer for the activity log; older ones are dropped.
+const MAX_TRANSFER_EVENTS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferEventKind {
+    FileCompleted,
+    FileFailed,
+    /// Skipped by the user mid-copy
+    FileSkipped,
+    Conflict,
+    Paused,
+    Resumed,
+    VolumeDisconnected,
+}
+
+/// Something significant that happened during a transfer, for the activity log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferEvent {
+    /// Counts up from 1 within the transfer, for fetching only newer events
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub kind: TransferEventKind,
+    pub path: Option<PathBuf>,
+    pub message: Option<String>,
+}
+
+/// How much of an interrupted transfer is still to be copied.
+/// Files that appeared in the source since it was interrupted aren't counted.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeEstimate {
+    pub transfer_id: String,
+    pub files_remaining: usize,
+    pub bytes_remaining: u64,
+    pub total_files: usize,
+    pub total_bytes: u64,
 }
 
 pub struct TransferStateManager {
     states: RwLock<HashMap<String, Arc<RwLock<TransferState>>>>,
     state_dir: PathBuf,
+    hash_cache: HashCache,
 }
 
 impl TransferStateManager {
     pub fn new() -> SyncResult<Self> {
-        let state_dir = Self::get_state_directory()?;
+        Self::in_dir(Self::get_state_directory()?)
+    }
+
+    /// A manager keeping its state files and hash cache in `state_dir`.
+    pub fn in_dir(state_dir: PathBuf) -> SyncResult<Self> {
         std::fs::create_dir_all(&state_dir)?;
 
         let manager = Self {
             states: RwLock::new(HashMap::new()),
+            hash_cache: HashCache::open(&state_dir),
             state_dir,
         };
 
@@ -204,6 +694,11 @@ impl TransferStateManager {
         Ok(manager)
     }
 
+    /// Source hashes remembered across syncs, keyed by path, size and mtime.
+    pub fn hash_cache(&self) -> &HashCache {
+        &self.hash_cache
+    }
+
     fn get_state_directory() -> SyncResult<PathBuf> {
         let data_dir = dirs::data_local_dir()
             .ok_or_else(|| SyncError::Internal("Could not determine app data directory".into()))?;
@@ -296,6 +791,41 @@ impl TransferStateManager {
         Ok(())
     }
 
+    /// Path of the "last successful sync" marker for a source/destination pair.
+    /// Markers live in a subdirectory so they are never mistaken for transfer states.
+    fn get_marker_path(&self, source: &Path, dest: &Path) -> PathBuf {
+        let key = format!("{}\0{}", source.display(), dest.display());
+        let hash = xxhash_rust::xxh3::xxh3_64(key.as_bytes());
+        self.state_dir
+            .join("markers")
+            .join(format!("{:016x}", hash))
+    }
+
+    /// Returns when the last successful sync of this source/destination pair started.
+    pub fn load_sync_marker(&self, source: &Path, dest: &Path) -> Option<DateTime<Utc>> {
+        let content = std::fs::read_to_string(self.get_marker_path(source, dest)).ok()?;
+        DateTime::parse_from_rfc3339(content.trim())
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    /// Records the start time of a successful sync of this source/destination pair.
+    pub fn save_sync_marker(
+        &self,
+        source: &Path,
+        dest: &Path,
+        started_at: DateTime<Utc>,
+    ) -> SyncResult<()> {
+        let marker = self.get_marker_path(source, dest);
+        if let Some(parent) = marker.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp_file = marker.with_extension("tmp");
+        std::fs::write(&temp_file, started_at.to_rfc3339())?;
+        std::fs::rename(&temp_file, &marker)?;
+        Ok(())
+    }
+
     pub fn save_state(&self, state: &TransferState) -> SyncResult<()> {
         self.persist_state(state)?;
         // Note: We don't update the in-memory state here because the caller
@@ -305,6 +835,21 @@ impl TransferStateManager {
         Ok(())
     }
 
+    /// Writes every transfer state to disk, e.g. before the app quits.
+    /// All are attempted; the first error is returned.
+    pub fn save_all(&self) -> SyncResult<()> {
+        let states: Vec<_> = self.states.read().values().cloned().collect();
+        let mut result = Ok(());
+        for state in states {
+            if let Err(e) = self.persist_state(&state.read()) {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
     pub fn get_state(&self, transfer_id: &str) -> SyncResult<TransferState> {
         let states = self.states.read();
         states
@@ -349,8 +894,8 @@ impl TransferStateManager {
     }
 
     /// Gets all interrupted transfers that can be resumed.
-    /// These are transfers with status Paused, Failed, or Running (interrupted by app crash).
-    /// Excludes Pending transfers as those haven't started yet.
+    /// These are transfers with status Paused, Failed, WaitingForDevice,
+    /// DeletingOrphans, or Running (interrupted by app crash). Excludes Pending transfers as those haven't started yet.
     pub fn get_interrupted_transfers(&self) -> Vec<TransferState> {
         let states = self.states.read();
         states
@@ -360,7 +905,10 @@ impl TransferStateManager {
                 // Include paused, failed, or running (interrupted) transfers
                 // that have made some progress
                 match s.status {
-                    TransferStatus::Paused | TransferStatus::Failed => Some(s.clone()),
+                    TransferStatus::Paused
+                    | TransferStatus::Failed
+                    | TransferStatus::WaitingForDevice
+                    | TransferStatus::DeletingOrphans => Some(s.clone()),
                     TransferStatus::Running => {
                         // Running status without active control means it was interrupted
                         // (e.g., app crashed during transfer)
@@ -376,6 +924,66 @@ impl TransferStateManager {
             .collect()
     }
 
+    /// Writes every file of a transfer, sorted by path, with its size, status,
+    /// error and hash to `path` as CSV or JSON. Returns the number of files.
+    /// Hashes come from the hash cache only, so exporting never reads the files.
+    pub fn export_report(
+        &self,
+        transfer_id: &str,
+        path: &Path,
+        format: ReportFormat,
+    ) -> SyncResult<usize> {
+        let state = self.get_state(transfer_id)?;
+        let mut rows: Vec<ReportRow> = state
+            .files
+            .values()
+            .map(|file| ReportRow {
+                path: file
+                    .source_path
+                    .strip_prefix(&state.source_path)
+                    .unwrap_or(&file.source_path)
+                    .to_path_buf(),
+                size: file.total_bytes,
+                status: ReportRow::status_name(file),
+                error: file.error.clone(),
+                verified: file.verified,
+                hash: file
+                    .verified
+                    .then(|| self.hash_cache.cached_hash(&file.source_path))
+                    .flatten()
+                    .map(|hash| format!("{:016x}", hash)),
+            })
+            .collect();
+        rows.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // Written beside the final name and renamed, so a reader never sees half a report
+        let temp_path = PathBuf::from(format!("{}.tmp", path.display()));
+        let written = (|| -> SyncResult<()> {
+            let file = std::fs::File::create(&temp_path)?;
+            let mut writer = std::io::BufWriter::new(&file);
+            match format {
+                ReportFormat::Csv => {
+                    writer.write_all(b"path,size,status,error,verified,hash\n")?;
+                    for row in &rows {
+                        writer.write_all(row.csv_line().as_bytes())?;
+                    }
+                }
+                ReportFormat::Json => serde_json::to_writer_pretty(&mut writer, &rows)?,
+            }
+            writer.flush()?;
+            drop(writer);
+            file.sync_all()?;
+            std::fs::rename(&temp_path, path)?;
+            Ok(())
+        })();
+        if written.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        written?;
+
+        Ok(rows.len())
+    }
+
     pub fn remove_transfer(&self, transfer_id: &str) -> SyncResult<()> {
         {
             let mut states = self.states.write();
@@ -8,7 +8,11 @@
 //! Monitors `/Volumes` directory for changes using FSEvents (via notify crate).
 //!
 //! # Linux  
-//! Monitors `/media`, `/mnt`, and `/run/media/$USER` directories.
+//! Monitors `/media`, `/mnt`, and `/run/media/$USER` directories. Built with
+//! the `udev` feature, block-device add/remove events from libudev also
+//! trigger a rescan, so drives mounted anywhere are picked up straight away,
+//! and removability comes from udev's device properties. Polling stays on as
+//! the fallback either way.
 //!
 //! # Windows
 //! Monitors drive letters and uses the sysinfo crate for disk enumeration.
@@ -25,7 +29,7 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use sysinfo::Disks;
 use tauri::{AppHandle, Emitter};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
 use crate::errors::{SyncError, SyncResult};
 
@@ -75,6 +79,81 @@ pub enum VolumeEvent {
         name: String,
         error: String,
     },
+    /// Mount changes coalesced over one debounce window
+    VolumesChanged {
+        mounted: Vec<VolumeInfo>,
+        unmounted: Vec<UnmountedVolume>,
+    },
+}
+
+/// A volume removed during a batched `VolumesChanged` window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmountedVolume {
+    pub mount_point: PathBuf,
+    pub name: String,
+    /// Transfer IDs affected by this disconnection
+    pub affected_transfers: Vec<String>,
+}
+
+/// Mount/unmount events buffered for the current debounce window.
+#[derive(Debug, Default)]
+struct PendingVolumeChanges {
+    mounted: Vec<VolumeInfo>,
+    unmounted: Vec<UnmountedVolume>,
+}
+
+impl PendingVolumeChanges {
+    /// Buffers a mount change, returning any event that isn't batched.
+    ///
+    /// A volume that mounts and unmounts within the window never reaches the
+    /// frontend; one that unmounts and remounts is reported as both, since
+    /// transfers on it may have been interrupted.
+    fn push(&mut self, event: VolumeEvent) -> Option<VolumeEvent> {
+        match event {
+            VolumeEvent::Mounted { volume } => {
+                if !self
+                    .mounted
+                    .iter()
+                    .any(|v| v.mount_point == volume.mount_point)
+                {
+                    self.mounted.push(volume);
+                }
+                None
+            }
+            VolumeEvent::Unmounted {
+                mount_point,
+                name,
+                affected_transfers,
+            } => {
+                let mounted_before = self.mounted.len();
+                self.mounted.retain(|v| v.mount_point != mount_point);
+                let appeared_in_window = self.mounted.len() != mounted_before;
+                if !appeared_in_window
+                    && !self.unmounted.iter().any(|v| v.mount_point == mount_point)
+                {
+                    self.unmounted.push(UnmountedVolume {
+                        mount_point,
+                        name,
+                        affected_transfers,
+                    });
+                }
+                None
+            }
+            other => Some(other),
+        }
+    }
+
+    /// Drains the buffer into a single batch event, if anything is pending.
+    fn take(&mut self) -> Option<VolumeEvent> {
+        if self.mounted.is_empty() && self.unmounted.is_empty() {
+            return None;
+        }
+        Some(VolumeEvent::VolumesChanged {
+            mounted: std::mem::take(&mut self.mounted),
+            unmounted: std::mem::take(&mut self.unmounted),
+        })
+    }
 }
 
 // ============================================================================
@@ -92,6 +171,9 @@ pub struct VolumeWatcherConfig {
     pub include_system_volumes: bool,
     /// Debounce duration for rapid mount/unmount events
     pub debounce_duration: Duration,
+    /// Coalesce mount/unmount events over `debounce_duration` into a single
+    /// `VolumesChanged` event instead of emitting each one
+    pub batch_events: bool,
 }
 
 impl Default for VolumeWatcherConfig {
@@ -101,6 +183,7 @@ impl Default for VolumeWatcherConfig {
             watch_paths: Self::default_watch_paths(),
             include_system_volumes: false,
             debounce_duration: Duration::from_millis(500),
+            batch_events: true,
         }
     }
 }
@@ -208,6 +291,48 @@ impl TransferVolumeMap {
     }
 }
 
+/// Shared handle to a watcher's transfer tracking, so the sync engine can
+/// register transfers without holding the watcher itself.
+#[derive(Clone)]
+pub struct TransferVolumeRegistry {
+    known_volumes: Arc<RwLock<Vec<VolumeInfo>>>,
+    transfer_map: Arc<RwLock<TransferVolumeMap>>,
+}
+
+impl TransferVolumeRegistry {
+    /// Records which volumes a transfer's source and destination are on.
+    pub fn register_transfer(&self, transfer_id: &str, source: &Path, destination: &Path) {
+        let volumes = self.known_volumes.read().clone();
+        let mut map = self.transfer_map.write();
+        map.register_transfer(
+            transfer_id,
+            &[source.to_path_buf(), destination.to_path_buf()],
+            &volumes,
+        );
+    }
+
+    /// Forgets a finished transfer.
+    pub fn unregister_transfer(&self, transfer_id: &str) {
+        self.transfer_map.write().unregister_transfer(transfer_id);
+    }
+
+    /// Returns the IDs of transfers using the volume that `path` is on.
+    /// `path` may be the mount point itself or any path inside the volume.
+    pub fn transfers_on_volume(&self, path: &Path) -> Vec<String> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let volumes = self.known_volumes.read();
+        let Some(volume) = TransferVolumeMap::find_volume_for_path(&path, &volumes) else {
+            return Vec::new();
+        };
+        let mut transfers = self
+            .transfer_map
+            .read()
+            .get_affected_transfers(&volume.mount_point);
+        transfers.sort();
+        transfers
+    }
+}
+
 // ============================================================================
 // Volume Watcher Implementation
 // ============================================================================
@@ -224,6 +349,9 @@ pub struct VolumeWatcher {
     is_running: Arc<AtomicBool>,
     /// Channel to send stop signal
     stop_tx: Option<mpsc::Sender<()>>,
+    /// Broadcasts newly mounted volumes to in-process listeners (e.g. the sync engine
+    /// waiting for a disconnected drive to come back)
+    mount_tx: broadcast::Sender<VolumeInfo>,
 }
 
 impl VolumeWatcher {
@@ -241,6 +369,7 @@ impl VolumeWatcher {
             transfer_map: Arc::new(RwLock::new(TransferVolumeMap::new())),
             is_running: Arc::new(AtomicBool::new(false)),
             stop_tx: None,
+            mount_tx: broadcast::channel(16).0,
         }
     }
 
@@ -266,6 +395,7 @@ impl VolumeWatcher {
         let is_running = self.is_running.clone();
         let app_handle = self.app_handle.clone();
         let config = self.config.clone();
+        let mount_tx = self.mount_tx.clone();
 
         tokio::spawn(async move {
             Self::watch_loop(
@@ -274,6 +404,7 @@ impl VolumeWatcher {
                 transfer_map,
                 is_running,
                 app_handle,
+                mount_tx,
                 stop_rx,
             )
             .await;
@@ -293,20 +424,34 @@ impl VolumeWatcher {
     /// Registers a transfer with the volume watcher.
     /// Call this when starting a sync operation.
     pub fn register_transfer(&self, transfer_id: &str, source: &Path, destination: &Path) {
-        let volumes = self.known_volumes.read().clone();
-        let mut map = self.transfer_map.write();
-        map.register_transfer(
-            transfer_id,
-            &[source.to_path_buf(), destination.to_path_buf()],
-            &volumes,
-        );
+        self.transfer_registry()
+            .register_transfer(transfer_id, source, destination);
     }
 
     /// Unregisters a transfer from the volume watcher.
     /// Call this when a sync operation completes, fails, or is cancelled.
     pub fn unregister_transfer(&self, transfer_id: &str) {
-        let mut map = self.transfer_map.write();
-        map.unregister_transfer(transfer_id);
+        self.transfer_registry().unregister_transfer(transfer_id);
+    }
+
+    /// Returns a handle for registering transfers and querying which
+    /// transfers use a volume. Shares state with this watcher.
+    pub fn transfer_registry(&self) -> TransferVolumeRegistry {
+        TransferVolumeRegistry {
+            known_volumes: self.known_volumes.clone(),
+            transfer_map: self.transfer_map.clone(),
+        }
+    }
+
+    /// Returns the IDs of transfers using the volume that `path` is on.
+    pub fn transfers_on_volume(&self, path: &Path) -> Vec<String> {
+        self.transfer_registry().transfers_on_volume(path)
+    }
+
+    /// Returns a sender that can be subscribed to for mount notifications.
+    /// Receivers only see volumes mounted after they subscribe.
+    pub fn mount_sender(&self) -> broadcast::Sender<VolumeInfo> {
+        self.mount_tx.clone()
     }
 
     /// Gets the current list of mounted volumes.
@@ -314,6 +459,67 @@ impl VolumeWatcher {
         self.known_volumes.read().clone()
     }
 
+    /// Rescans the mounted volumes now instead of waiting for the next poll,
+    /// and returns the updated list. While the watcher runs, only volumes it
+    /// already knows are updated; drives that appeared or went away are left
+    /// for the poll, so their mount events and transfer pauses still happen.
+    pub fn refresh_volumes(&self) -> Vec<VolumeInfo> {
+        let current = Self::scan_volumes();
+        let mut volumes = self.known_volumes.write();
+        if self.is_running.load(Ordering::SeqCst) {
+            for volume in volumes.iter_mut() {
+                if let Some(fresh) = current.iter().find(|v| v.mount_point == volume.mount_point) {
+                    *volume = fresh.clone();
+                }
+            }
+        } else {
+            *volumes = current;
+        }
+        volumes.clone()
+    }
+
+    /// Updates the space figures of the known volume holding `path` without
+    /// rescanning every drive. Returns `None` if no known volume holds it.
+    pub fn refresh_volume(&self, path: &Path) -> Option<VolumeInfo> {
+        let mount_point = {
+            let volumes = self.known_volumes.read();
+            TransferVolumeMap::find_volume_for_path(path, &volumes)?
+                .mount_point
+                .clone()
+        };
+        // Queried without the lock, since a stalled network mount can block
+        let space = Self::volume_space(&mount_point);
+
+        let mut volumes = self.known_volumes.write();
+        let volume = volumes.iter_mut().find(|v| v.mount_point == mount_point)?;
+        match space {
+            Some((available, total)) => {
+                volume.available_bytes = available;
+                volume.total_bytes = total;
+                volume.is_mounted = true;
+            }
+            None => volume.is_mounted = mount_point.exists(),
+        }
+        Some(volume.clone())
+    }
+
+    /// Available and total bytes of a mounted volume.
+    #[cfg(unix)]
+    fn volume_space(mount_point: &Path) -> Option<(u64, u64)> {
+        if !mount_point.exists() {
+            return None;
+        }
+        crate::file_ops::get_disk_space(mount_point).ok()
+    }
+
+    #[cfg(not(unix))]
+    fn volume_space(mount_point: &Path) -> Option<(u64, u64)> {
+        Self::scan_volumes()
+            .into_iter()
+            .find(|v| v.mount_point == mount_point)
+            .map(|v| (v.available_bytes, v.total_bytes))
+    }
+
     /// Checks if a specific path's volume is still mounted and accessible.
     pub fn is_volume_accessible(&self, path: &Path) -> bool {
         let volumes = self.known_volumes.read();
@@ -368,7 +574,7 @@ impl VolumeWatcher {
                     },
                     total_bytes: disk.total_space(),
                     available_bytes: disk.available_space(),
-                    is_removable: disk.is_removable(),
+                    is_removable: disk_is_removable(disk),
                     fs_type: Some(disk.file_system().to_string_lossy().to_string()),
                     is_mounted: mount_point.exists(),
                 }
@@ -383,6 +589,7 @@ impl VolumeWatcher {
         transfer_map: Arc<RwLock<TransferVolumeMap>>,
         is_running: Arc<AtomicBool>,
         app_handle: Option<AppHandle>,
+        mount_tx: broadcast::Sender<VolumeInfo>,
         mut stop_rx: mpsc::Receiver<()>,
     ) {
         // Set up filesystem watcher for immediate detection
@@ -390,10 +597,24 @@ impl VolumeWatcher {
         
         let _watcher = Self::setup_fs_watcher(&config, fs_tx);
 
+        // Block-device hotplug events, when built with udev support
+        #[cfg(all(target_os = "linux", feature = "udev"))]
+        let mut block_rx = udev_events::spawn_monitor(is_running.clone());
+        #[cfg(not(all(target_os = "linux", feature = "udev")))]
+        let mut block_rx: Option<mpsc::Receiver<BlockEvent>> = None;
+        // A drive is mounted a moment after its device appears, so look again shortly
+        let mut repoll_at: Option<tokio::time::Instant> = None;
+
         let mut poll_interval = tokio::time::interval(config.poll_interval);
         let mut last_event_time = Instant::now();
 
+        // Mount changes waiting to be emitted as one batch, and when that batch is due
+        let mut pending = PendingVolumeChanges::default();
+        let mut flush_at: Option<tokio::time::Instant> = None;
+
         while is_running.load(Ordering::SeqCst) {
+            let mut events = Vec::new();
+
             tokio::select! {
                 // Stop signal received
                 _ = stop_rx.recv() => {
@@ -412,20 +633,84 @@ impl VolumeWatcher {
                         event,
                         &known_volumes,
                         &transfer_map,
-                        app_handle.as_ref(),
+                        &mount_tx,
+                        &mut events,
                     ).await;
                 }
                 
+                // Block device added or removed anywhere on the system
+                Some(event) = async { block_rx.as_mut().unwrap().recv().await }, if block_rx.is_some() => {
+                    if let BlockEvent::Removed { devnode } = &event {
+                        // The mount can outlive a pulled drive, so don't wait for it to go
+                        let mount_point = known_volumes
+                            .read()
+                            .iter()
+                            .find(|volume| Path::new(&volume.name) == devnode)
+                            .map(|volume| volume.mount_point.clone());
+                        if let Some(mount_point) = mount_point {
+                            Self::handle_potential_unmount(
+                                &mount_point,
+                                &known_volumes,
+                                &transfer_map,
+                                &mut events,
+                            ).await;
+                        }
+                    }
+                    Self::poll_volumes(
+                        &known_volumes,
+                        &transfer_map,
+                        &mount_tx,
+                        &mut events,
+                    ).await;
+                    if matches!(event, BlockEvent::Added) {
+                        repoll_at = Some(tokio::time::Instant::now() + BLOCK_EVENT_REPOLL_DELAY);
+                    }
+                }
+
+                _ = async { tokio::time::sleep_until(repoll_at.unwrap()).await }, if repoll_at.is_some() => {
+                    repoll_at = None;
+                    Self::poll_volumes(
+                        &known_volumes,
+                        &transfer_map,
+                        &mount_tx,
+                        &mut events,
+                    ).await;
+                }
+
                 // Periodic poll (fallback, catches events we might miss)
                 _ = poll_interval.tick() => {
                     Self::poll_volumes(
                         &known_volumes,
                         &transfer_map,
-                        app_handle.as_ref(),
+                        &mount_tx,
+                        &mut events,
                     ).await;
                 }
+
+                // Debounce window over: emit the batch
+                _ = async { tokio::time::sleep_until(flush_at.unwrap()).await }, if flush_at.is_some() => {
+                    flush_at = None;
+                    if let Some(batch) = pending.take() {
+                        Self::emit_event(app_handle.as_ref(), &batch);
+                    }
+                }
+            }
+
+            for event in events {
+                if !config.batch_events {
+                    Self::emit_event(app_handle.as_ref(), &event);
+                } else if let Some(unbatched) = pending.push(event) {
+                    Self::emit_event(app_handle.as_ref(), &unbatched);
+                } else if flush_at.is_none() {
+                    flush_at = Some(tokio::time::Instant::now() + config.debounce_duration);
+                }
             }
         }
+
+        // Don't drop changes that were still waiting on the debounce window
+        if let Some(batch) = pending.take() {
+            Self::emit_event(app_handle.as_ref(), &batch);
+        }
     }
 
     /// Sets up the filesystem watcher for the configured paths.
@@ -470,22 +755,18 @@ impl VolumeWatcher {
         event: Event,
         known_volumes: &Arc<RwLock<Vec<VolumeInfo>>>,
         transfer_map: &Arc<RwLock<TransferVolumeMap>>,
-        app_handle: Option<&AppHandle>,
+        mount_tx: &broadcast::Sender<VolumeInfo>,
+        events: &mut Vec<VolumeEvent>,
     ) {
         match event.kind {
             EventKind::Create(_) => {
                 // A new mount point appeared - rescan
-                Self::poll_volumes(known_volumes, transfer_map, app_handle).await;
+                Self::poll_volumes(known_volumes, transfer_map, mount_tx, events).await;
             }
             EventKind::Remove(_) => {
                 // A mount point was removed - check what's gone
                 for path in &event.paths {
-                    Self::handle_potential_unmount(
-                        path,
-                        known_volumes,
-                        transfer_map,
-                        app_handle,
-                    ).await;
+                    Self::handle_potential_unmount(path, known_volumes, transfer_map, events).await;
                 }
             }
             _ => {}
@@ -497,7 +778,7 @@ impl VolumeWatcher {
         path: &Path,
         known_volumes: &Arc<RwLock<Vec<VolumeInfo>>>,
         transfer_map: &Arc<RwLock<TransferVolumeMap>>,
-        app_handle: Option<&AppHandle>,
+        events: &mut Vec<VolumeEvent>,
     ) {
         let (removed_volume, affected_transfers) = {
             let volumes = known_volumes.read();
@@ -526,7 +807,7 @@ impl VolumeWatcher {
                 affected_transfers: affected_transfers.clone(),
             };
 
-            Self::emit_event(app_handle, &event);
+            events.push(event);
 
             // Log for debugging
             if !affected_transfers.is_empty() {
@@ -542,7 +823,8 @@ impl VolumeWatcher {
     async fn poll_volumes(
         known_volumes: &Arc<RwLock<Vec<VolumeInfo>>>,
         transfer_map: &Arc<RwLock<TransferVolumeMap>>,
-        app_handle: Option<&AppHandle>,
+        mount_tx: &broadcast::Sender<VolumeInfo>,
+        events: &mut Vec<VolumeEvent>,
     ) {
         let current_volumes = Self::scan_volumes();
         
@@ -572,8 +854,10 @@ impl VolumeWatcher {
 
         // Emit events for new volumes
         for volume in new_volumes {
+            // No receivers is the common case - ignore the send error
+            let _ = mount_tx.send(volume.clone());
             let event = VolumeEvent::Mounted { volume };
-            Self::emit_event(app_handle, &event);
+            events.push(event);
         }
 
         // Emit events for removed volumes
@@ -588,7 +872,7 @@ impl VolumeWatcher {
                 name: volume.name.clone(),
                 affected_transfers: affected_transfers.clone(),
             };
-            Self::emit_event(app_handle, &event);
+            events.push(event);
 
             if !affected_transfers.is_empty() {
                 eprintln!(
@@ -599,14 +883,14 @@ impl VolumeWatcher {
         }
 
         // Also check if existing volumes became inaccessible
-        Self::check_volume_accessibility(known_volumes, transfer_map, app_handle).await;
+        Self::check_volume_accessibility(known_volumes, transfer_map, events).await;
     }
 
     /// Checks if known volumes are still accessible (catches I/O errors).
     async fn check_volume_accessibility(
         known_volumes: &Arc<RwLock<Vec<VolumeInfo>>>,
         transfer_map: &Arc<RwLock<TransferVolumeMap>>,
-        app_handle: Option<&AppHandle>,
+        events: &mut Vec<VolumeEvent>,
     ) {
         let volumes_to_check = {
             let volumes = known_volumes.read();
@@ -632,7 +916,7 @@ impl VolumeWatcher {
                     name: volume.name.clone(),
                     error: "Volume is not accessible (possible disconnection or I/O error)".to_string(),
                 };
-                Self::emit_event(app_handle, &event);
+                events.push(event);
 
                 eprintln!(
                     "[VolumeWatcher] Volume '{}' became inaccessible. Affected transfers: {:?}",
@@ -652,6 +936,149 @@ impl VolumeWatcher {
     }
 }
 
+// ============================================================================
+// udev Block Device Monitoring (Linux)
+// ============================================================================
+
+/// How long after a block device appears to rescan for its mount.
+const BLOCK_EVENT_REPOLL_DELAY: Duration = Duration::from_millis(1500);
+
+/// A block device change reported by udev.
+#[cfg_attr(not(all(target_os = "linux", feature = "udev")), allow(dead_code))]
+enum BlockEvent {
+    /// A disk or partition appeared, or its media changed (card readers)
+    Added,
+    /// A disk or partition went away
+    Removed { devnode: PathBuf },
+}
+
+/// Whether `disk` is on a removable drive. With udev this is decided from the
+/// block device's bus and `removable` attribute, since sysinfo's heuristic is
+/// often wrong on Linux; otherwise it is sysinfo's answer.
+fn disk_is_removable(disk: &sysinfo::Disk) -> bool {
+    #[cfg(all(target_os = "linux", feature = "udev"))]
+    if let Some(removable) = udev_events::is_removable(Path::new(disk.name())) {
+        return removable;
+    }
+    disk.is_removable()
+}
+
+#[cfg(all(target_os = "linux", feature = "udev"))]
+mod udev_events {
+    use super::BlockEvent;
+    use std::ffi::OsStr;
+    use std::os::fd::AsRawFd;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    /// Buses whose drives are plugged in and out by the user. USB hard disks
+    /// report `removable=0`, so the bus is checked first.
+    const REMOVABLE_BUSES: &[&str] = &["usb", "ieee1394"];
+
+    /// How often the monitor thread wakes to notice the watcher stopping.
+    const MONITOR_POLL_TIMEOUT_MS: libc::c_int = 1000;
+
+    /// Looks up the block device behind `devnode` (such as `/dev/sdb1`).
+    /// `None` if udev doesn't know it, e.g. for network or virtual filesystems.
+    pub(super) fn is_removable(devnode: &Path) -> Option<bool> {
+        let sysname = devnode.strip_prefix("/dev").ok()?.to_str()?;
+        let device =
+            udev::Device::from_subsystem_sysname("block".to_string(), sysname.to_string()).ok()?;
+
+        let bus = device.property_value("ID_BUS").and_then(OsStr::to_str);
+        if bus.is_some_and(|bus| REMOVABLE_BUSES.contains(&bus)) {
+            return Some(true);
+        }
+
+        // `removable` is an attribute of the whole disk, not its partitions
+        let disk = if device
+            .devtype()
+            .is_some_and(|devtype| devtype == "partition")
+        {
+            device
+                .parent_with_subsystem_devtype("block", "disk")
+                .ok()??
+        } else {
+            device
+        };
+        Some(
+            disk.attribute_value("removable")
+                .is_some_and(|value| value == "1"),
+        )
+    }
+
+    /// Starts a thread forwarding block-device events from a udev monitor.
+    /// The thread exits when `is_running` is cleared or the receiver is
+    /// dropped; if udev can't be reached the channel just closes.
+    pub(super) fn spawn_monitor(is_running: Arc<AtomicBool>) -> Option<mpsc::Receiver<BlockEvent>> {
+        let (tx, rx) = mpsc::channel(32);
+        let spawned = std::thread::Builder::new()
+            .name("udev-monitor".to_string())
+            .spawn(move || {
+                // Created on this thread, since udev handles can't move between threads
+                let socket = match udev::MonitorBuilder::new()
+                    .and_then(|builder| builder.match_subsystem("block"))
+                    .and_then(|builder| builder.listen())
+                {
+                    Ok(socket) => socket,
+                    Err(e) => {
+                        eprintln!(
+                            "[VolumeWatcher] udev monitor unavailable, polling only: {}",
+                            e
+                        );
+                        return;
+                    }
+                };
+                let mut poll_fd = libc::pollfd {
+                    fd: socket.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+
+                while is_running.load(Ordering::SeqCst) {
+                    let ready = unsafe { libc::poll(&mut poll_fd, 1, MONITOR_POLL_TIMEOUT_MS) };
+                    if ready < 0 {
+                        let error = std::io::Error::last_os_error();
+                        if error.kind() == std::io::ErrorKind::Interrupted {
+                            continue;
+                        }
+                        eprintln!(
+                            "[VolumeWatcher] udev monitor failed, polling only: {}",
+                            error
+                        );
+                        return;
+                    }
+
+                    for event in socket.iter() {
+                        let block_event = match event.event_type() {
+                            udev::EventType::Add | udev::EventType::Change => BlockEvent::Added,
+                            udev::EventType::Remove => match event.devnode() {
+                                Some(devnode) => BlockEvent::Removed {
+                                    devnode: devnode.to_path_buf(),
+                                },
+                                None => continue,
+                            },
+                            _ => continue,
+                        };
+                        if tx.blocking_send(block_event).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+
+        match spawned {
+            Ok(_) => Some(rx),
+            Err(e) => {
+                eprintln!("[VolumeWatcher] Failed to start udev monitor thread: {}", e);
+                None
+            }
+        }
+    }
+}
+
 // ============================================================================
 // Helper Functions for Sync Integration
 // ============================================================================
@@ -663,7 +1090,7 @@ pub fn is_on_removable_volume(path: &Path) -> bool {
     
     for disk in disks.iter() {
         if path.starts_with(disk.mount_point()) {
-            return disk.is_removable();
+            return disk_is_removable(disk);
         }
     }
     
@@ -709,6 +1136,94 @@ pub fn get_volume_for_path(path: &Path) -> Option<VolumeInfo> {
         })
 }
 
+/// Filesystem types of network mounts, as reported for macOS and Linux mounts.
+const NETWORK_FS_TYPES: &[&str] = &[
+    "smbfs",
+    "afpfs",
+    "nfs",
+    "nfs4",
+    "webdav",
+    "cifs",
+    "smb3",
+    "fuse.sshfs",
+];
+
+/// Whether a filesystem type is a network mount.
+pub fn is_network_fs(fs_type: &str) -> bool {
+    NETWORK_FS_TYPES
+        .iter()
+        .any(|network| fs_type.eq_ignore_ascii_case(network))
+}
+
+/// Writes a test file on `destination` when it is on a network mount. Shares
+/// are often mounted read-only, or only check credentials on the first write,
+/// so they pass an existence check and then fail deep into a transfer.
+/// Local destinations are left to fail, if at all, on their first copy.
+pub fn probe_network_destination(destination: &Path) -> SyncResult<()> {
+    let Some(volume) = get_volume_for_path(destination) else {
+        return Ok(());
+    };
+    if !volume.fs_type.as_deref().is_some_and(is_network_fs) {
+        return Ok(());
+    }
+    if crate::permissions::check_write_access(destination) {
+        Ok(())
+    } else {
+        Err(SyncError::DestinationNotWritable(format!(
+            "{} (network mount {} refused a test write)",
+            destination.display(),
+            volume.mount_point.display()
+        )))
+    }
+}
+
+/// Skew plus probe uncertainty below this is within the one- to two-second
+/// mtime resolution of most filesystems, and needs no tolerance.
+const CLOCK_SKEW_IGNORED_MS: u64 = 1000;
+
+/// How far a destination's clock is from the local one, from `measure_clock_skew`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSkew {
+    /// The mtime the destination gave a new file minus the local time it was
+    /// written; positive when the destination is ahead
+    pub skew_ms: i64,
+    /// Half the time the probe write took, which bounds how exact `skew_ms` is
+    pub uncertainty_ms: u64,
+    /// `mtime_tolerance_seconds` that covers the skew, or 0 if none is needed
+    pub tolerance_seconds: u64,
+}
+
+/// Writes a probe file on `destination` and compares the mtime it gets with
+/// the local clock. Network shares stamp new files with the server's clock,
+/// so this is how far apart the two are; a FAT drive's two-second mtimes
+/// show up as skew too. The probe is removed again.
+pub fn measure_clock_skew(destination: &Path) -> SyncResult<ClockSkew> {
+    let probe = destination.join(format!(".rsync-clock-probe-{}", uuid::Uuid::new_v4()));
+    let before = chrono::Utc::now();
+    let written = std::fs::write(&probe, b"")
+        .and_then(|()| std::fs::metadata(&probe).and_then(|metadata| metadata.modified()));
+    let after = chrono::Utc::now();
+    let _ = std::fs::remove_file(&probe);
+    let stamped = chrono::DateTime::<chrono::Utc>::from(written?);
+
+    let elapsed = after - before;
+    let written_at = before + elapsed / 2;
+    let skew_ms = (stamped - written_at).num_milliseconds();
+    let uncertainty_ms = (elapsed.num_milliseconds() / 2).unsigned_abs();
+    let spread_ms = skew_ms.unsigned_abs() + uncertainty_ms;
+    let tolerance_seconds = if spread_ms < CLOCK_SKEW_IGNORED_MS {
+        0
+    } else {
+        spread_ms.div_ceil(1000)
+    };
+    Ok(ClockSkew {
+        skew_ms,
+        uncertainty_ms,
+        tolerance_seconds,
+    })
+}
+
 /// Validates that both source and destination volumes are accessible before sync.
 /// Returns Ok(()) if both are accessible, or an appropriate SyncError.
 pub fn validate_volumes_for_sync(source: &Path, destination: &Path) -> SyncResult<()> {
@@ -788,4 +1303,152 @@ mod tests {
         let affected = map.get_affected_transfers(&PathBuf::from("/Volumes/External"));
         assert!(affected.is_empty());
     }
+
+    #[test]
+    fn test_transfers_on_volume_resolves_paths() {
+        let watcher = VolumeWatcher::new(None);
+        *watcher.known_volumes.write() = vec![
+            VolumeInfo {
+                mount_point: PathBuf::from("/"),
+                name: "Macintosh HD".to_string(),
+                total_bytes: 1000,
+                available_bytes: 500,
+                is_removable: false,
+                fs_type: Some("apfs".to_string()),
+                is_mounted: true,
+            },
+            VolumeInfo {
+                mount_point: PathBuf::from("/Volumes/External"),
+                name: "External".to_string(),
+                total_bytes: 1000,
+                available_bytes: 500,
+                is_removable: true,
+                fs_type: Some("apfs".to_string()),
+                is_mounted: true,
+            },
+        ];
+
+        watcher.register_transfer(
+            "transfer-2",
+            Path::new("/Users/me/Documents"),
+            Path::new("/Volumes/External/backup"),
+        );
+        watcher.register_transfer(
+            "transfer-1",
+            Path::new("/Users/me/Pictures"),
+            Path::new("/Volumes/External/photos"),
+        );
+
+        let registry = watcher.transfer_registry();
+        assert_eq!(
+            registry.transfers_on_volume(Path::new("/Volumes/External")),
+            vec!["transfer-1", "transfer-2"]
+        );
+        assert_eq!(
+            registry.transfers_on_volume(Path::new("/Volumes/External/photos/2024")),
+            vec!["transfer-1", "transfer-2"]
+        );
+
+        registry.unregister_transfer("transfer-2");
+        assert_eq!(
+            watcher.transfers_on_volume(Path::new("/Volumes/External")),
+            vec!["transfer-1"]
+        );
+    }
+
+    #[test]
+    fn test_pending_volume_changes_coalesce() {
+        let volume = |name: &str| VolumeInfo {
+            mount_point: PathBuf::from(format!("/Volumes/{}", name)),
+            name: name.to_string(),
+            total_bytes: 1000,
+            available_bytes: 500,
+            is_removable: true,
+            fs_type: Some("apfs".to_string()),
+            is_mounted: true,
+        };
+        let unmounted = |name: &str| VolumeEvent::Unmounted {
+            mount_point: PathBuf::from(format!("/Volumes/{}", name)),
+            name: name.to_string(),
+            affected_transfers: Vec::new(),
+        };
+
+        let mut pending = PendingVolumeChanges::default();
+        // Partition burst, including a duplicate
+        assert!(pending
+            .push(VolumeEvent::Mounted {
+                volume: volume("A")
+            })
+            .is_none());
+        assert!(pending
+            .push(VolumeEvent::Mounted {
+                volume: volume("B")
+            })
+            .is_none());
+        assert!(pending
+            .push(VolumeEvent::Mounted {
+                volume: volume("B")
+            })
+            .is_none());
+        // C mounts and vanishes within the window; D blinks out and back
+        assert!(pending
+            .push(VolumeEvent::Mounted {
+                volume: volume("C")
+            })
+            .is_none());
+        assert!(pending.push(unmounted("C")).is_none());
+        assert!(pending.push(unmounted("D")).is_none());
+        assert!(pending
+            .push(VolumeEvent::Mounted {
+                volume: volume("D")
+            })
+            .is_none());
+
+        match pending.take() {
+            Some(VolumeEvent::VolumesChanged { mounted, unmounted }) => {
+                let names: Vec<_> = mounted.iter().map(|v| v.name.as_str()).collect();
+                assert_eq!(names, vec!["A", "B", "D"]);
+                assert_eq!(unmounted.len(), 1);
+                assert_eq!(unmounted[0].name, "D");
+            }
+            other => panic!("expected a VolumesChanged batch, got {:?}", other),
+        }
+        assert!(pending.take().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_refresh_volume_updates_space() {
+        let watcher = VolumeWatcher::new(None);
+        assert!(watcher.refresh_volume(Path::new("/tmp")).is_none());
+
+        *watcher.known_volumes.write() = vec![VolumeInfo {
+            mount_point: PathBuf::from("/"),
+            name: "Root".to_string(),
+            total_bytes: 0,
+            available_bytes: 0,
+            is_removable: false,
+            fs_type: None,
+            is_mounted: false,
+        }];
+        let volume = watcher.refresh_volume(Path::new("/tmp")).unwrap();
+        assert_eq!(volume.mount_point, PathBuf::from("/"));
+        assert!(volume.total_bytes > 0);
+        assert!(volume.is_mounted);
+        assert_eq!(watcher.get_volumes()[0].total_bytes, volume.total_bytes);
+    }
+
+    #[test]
+    fn test_local_clock_skew_needs_no_tolerance() {
+        let dir = std::env::temp_dir().join(format!("clock-skew-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let skew = measure_clock_skew(&dir).unwrap();
+        assert_eq!(skew.tolerance_seconds, 0);
+        assert!(skew.skew_ms.abs() < 1000);
+        // The probe is gone again
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -12,6 +12,9 @@ import { ProgressBar } from '../ui/ProgressBar';
 import { Modal } from '../ui/Modal';
 import type { ActiveFileTransfer } from '../../types';
 
+/** Extensions listed on the completion screen */
+const EXTENSION_BREAKDOWN_LIMIT = 5;
+
 export function TransferProgress() {
   const { t } = useTranslation();
   const [showConfirmModal, setShowConfirmModal] = useState(false);
@@ -297,6 +300,20 @@ export function TransferProgress() {
             />
           </div>
         )}
+
+        {/* What dominated the copy, largest extensions first */}
+        {syncState === 'completed' && transferStats.extensionBreakdown.length > 0 && (
+          <div className="mt-4 flex flex-wrap items-center justify-center gap-x-6 gap-y-2 text-[13px]">
+            <span className="text-text-tertiary">{t('progress.byExtension')}</span>
+            {transferStats.extensionBreakdown.slice(0, EXTENSION_BREAKDOWN_LIMIT).map((entry) => (
+              <StatItem
+                key={entry.extension}
+                label={entry.extension ? `.${entry.extension}` : t('progress.noExtension')}
+                value={formatBytes(entry.bytes)}
+              />
+            ))}
+          </div>
+        )}
       </div>
     </div>
   );
@@ -1106,6 +1106,8 @@ export function useSync() {
         bytes_copied: number;
         duration_ms: number;
         errors: string[];
+        by_extension: Record<string, { files: number; bytes: number }>;
+        cancelled: boolean;
       }>('sync_files', {
         source: resolvedSource,
         destination: destPath,
@@ -1114,6 +1116,21 @@ export function useSync() {
 
       logger.log('[Sync] Result:', result);
 
+      // cancelSync already recorded the history entry; show what actually finished
+      if (result.cancelled) {
+        updateTransferStats({
+          completedFiles: result.files_copied,
+          totalFiles: result.files_total,
+          totalBytes: result.bytes_total,
+          transferredBytes: result.bytes_copied,
+          currentSpeed: 0,
+          estimatedTimeRemaining: 0,
+          currentFile: null,
+        });
+        setSyncState('cancelled');
+        return;
+      }
+
       // Perform post-transfer verification if enabled
       let verificationResult = { totalVerified: 0, totalErrors: 0, errors: [] as Array<unknown> };
       const shouldVerifyAfter = syncOptions.verifyChecksum === 'after' || syncOptions.verifyChecksum === 'both';
@@ -1163,6 +1180,9 @@ export function useSync() {
         averageSpeed: result.bytes_copied / Math.max(result.duration_ms / 1000, 1),
         estimatedTimeRemaining: 0,
         currentFile: null,
+        extensionBreakdown: Object.entries(result.by_extension ?? {})
+          .map(([extension, totals]) => ({ extension, ...totals }))
+          .sort((a, b) => b.bytes - a.bytes),
       });
 
       addHistoryItem({
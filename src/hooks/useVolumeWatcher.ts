@@ -163,41 +163,55 @@ export function useVolumeWatcher(options: UseVolumeWatcherOptions = {}): UseVolu
       // Load initial volumes
       await refreshVolumes();
 
+      const handleMounted = (volume: WatchedVolumeInfo) => {
+        setVolumes(prev => {
+          // Add if not already present
+          const exists = prev.some(v => v.mountPoint === volume.mountPoint);
+          if (exists) return prev;
+          return [...prev, volume];
+        });
+        callbacksRef.current.onVolumeMounted?.(volume);
+      };
+
+      const handleUnmounted = (mountPoint: string, name: string, affectedTransfers: string[]) => {
+        setVolumes(prev => 
+          prev.filter(v => v.mountPoint !== mountPoint)
+        );
+        
+        // Set disconnected volume state if there were affected transfers
+        if (affectedTransfers.length > 0) {
+          setDisconnectedVolume({
+            mountPoint,
+            name,
+            affectedTransfers,
+            detectedAt: new Date(),
+          });
+        }
+        
+        callbacksRef.current.onVolumeUnmounted?.(mountPoint, name, affectedTransfers);
+      };
+
       // Listen for volume events from the backend
       unlistenFn = await listen<VolumeEvent>('volume-event', (event) => {
         const payload = event.payload;
         
         switch (payload.type) {
           case 'mounted':
-            setVolumes(prev => {
-              // Add if not already present
-              const exists = prev.some(v => v.mountPoint === payload.volume.mountPoint);
-              if (exists) return prev;
-              return [...prev, payload.volume];
-            });
-            callbacksRef.current.onVolumeMounted?.(payload.volume);
+            handleMounted(payload.volume);
             break;
 
           case 'unmounted':
-            setVolumes(prev => 
-              prev.filter(v => v.mountPoint !== payload.mountPoint)
-            );
-            
-            // Set disconnected volume state if there were affected transfers
-            if (payload.affectedTransfers.length > 0) {
-              setDisconnectedVolume({
-                mountPoint: payload.mountPoint,
-                name: payload.name,
-                affectedTransfers: payload.affectedTransfers,
-                detectedAt: new Date(),
-              });
+            handleUnmounted(payload.mountPoint, payload.name, payload.affectedTransfers);
+            break;
+
+          case 'volumesChanged':
+            // Unmounts first: a volume that came back within the window ends up mounted
+            for (const volume of payload.unmounted) {
+              handleUnmounted(volume.mountPoint, volume.name, volume.affectedTransfers);
+            }
+            for (const volume of payload.mounted) {
+              handleMounted(volume);
             }
-            
-            callbacksRef.current.onVolumeUnmounted?.(
-              payload.mountPoint, 
-              payload.name, 
-              payload.affectedTransfers
-            );
             break;
 
           case 'inaccessible':
@@ -273,6 +273,7 @@ const defaultTransferStats: TransferStats = {
   currentFile: null,
   currentFiles: [],
   activeTransfers: [],
+  extensionBreakdown: [],
 };
 
 export const useSyncStore = create<SyncStore>((set, get) => ({
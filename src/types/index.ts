@@ -149,6 +149,13 @@ export interface ActiveFileTransfer {
   speed: number; // bytes per second
 }
 
+/** Files and bytes copied with one extension, from the sync result */
+export interface ExtensionBreakdown {
+  extension: string; // lowercased, '' for files without one
+  files: number;
+  bytes: number;
+}
+
 export interface TransferStats {
   totalFiles: number;
   completedFiles: number;
@@ -161,6 +168,7 @@ export interface TransferStats {
   currentFile: string | null;
   currentFiles: string[]; // For parallel transfers - multiple files being copied
   activeTransfers: ActiveFileTransfer[]; // Individual file progress for parallel transfers
+  extensionBreakdown: ExtensionBreakdown[]; // Largest first, filled when a sync completes
 }
 
 export interface ConflictInfo {
@@ -318,7 +326,7 @@ export interface WatchedVolumeInfo {
  * Volume event types emitted by the backend volume watcher.
  * Subscribe to these via Tauri's event listener.
  */
-export type VolumeEventType = 'mounted' | 'unmounted' | 'unmountPending' | 'inaccessible';
+export type VolumeEventType = 'mounted' | 'unmounted' | 'unmountPending' | 'inaccessible' | 'volumesChanged';
 
 /**
  * Volume mounted event payload.
@@ -358,6 +366,25 @@ export interface VolumeEventInaccessible {
   error: string;
 }
 
+/**
+ * A volume unmounted within a batched `volumesChanged` window.
+ */
+export interface UnmountedVolume {
+  mountPoint: string;
+  name: string;
+  /** Transfer IDs affected by this disconnection */
+  affectedTransfers: string[];
+}
+
+/**
+ * Mount and unmount events coalesced over one debounce window.
+ */
+export interface VolumeEventVolumesChanged {
+  type: 'volumesChanged';
+  mounted: WatchedVolumeInfo[];
+  unmounted: UnmountedVolume[];
+}
+
 /**
  * Union type for all volume events.
  */
@@ -365,7 +392,8 @@ export type VolumeEvent =
   | VolumeEventMounted 
   | VolumeEventUnmounted 
   | VolumeEventUnmountPending 
-  | VolumeEventInaccessible;
+  | VolumeEventInaccessible
+  | VolumeEventVolumesChanged;
 
 // Navigation
 export type NavigationPage = 'sync' | 'history' | 'settings';
@@ -412,6 +440,7 @@ export type TransferStatus =
   | 'pending'
   | 'running'
   | 'paused'
+  | 'deleting_orphans'
   | 'completed'
   | 'failed'
   | 'cancelled';